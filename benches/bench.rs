@@ -6,7 +6,7 @@
 use ark_bls12_381::{Bls12_381, Fr as BlsFr};
 use ark_crypto_primitives::snark::SNARK;
 use ark_ff::{PrimeField, UniformRand};
-use ark_groth16::Groth16;
+use ark_groth16::{generator::GeneratorConfig, prover::ProverScratch, Groth16, NoZkSNARK};
 use ark_mnt4_298::{Fr as MNT4Fr, MNT4_298};
 use ark_relations::{
     gr1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
@@ -15,8 +15,17 @@ use ark_relations::{
 
 const NUM_PROVE_REPETITIONS: usize = 1;
 const NUM_VERIFY_REPETITIONS: usize = 50;
+const NUM_BATCH_PROOFS: usize = 64;
+const NUM_BATCH_CONSTRAINTS: usize = 100;
 const NUM_CONSTRAINTS: usize = (1 << 20) - 100;
 const NUM_VARIABLES: usize = (1 << 20) - 100;
+const NUM_PREPARED_PROVE_REPETITIONS: usize = 10;
+const NUM_PREPARED_CONSTRAINTS: usize = (1 << 16) - 100;
+const NUM_PREPARED_VARIABLES: usize = (1 << 16) - 100;
+const NUM_BATCH_PROVE_PROOFS: usize = 64;
+const NUM_SETUP_CONSTRAINTS: usize = (1 << 16) - 100;
+const NUM_SETUP_VARIABLES: usize = (1 << 16) - 100;
+const SETUP_WINDOW_SIZES: [Option<usize>; 4] = [None, Some(8), Some(12), Some(16)];
 
 #[derive(Copy)]
 struct DummyCircuit<F: PrimeField> {
@@ -90,6 +99,116 @@ macro_rules! groth16_prove_bench {
             stringify!($bench_pairing_engine),
             start.elapsed().as_secs_f64() / NUM_PROVE_REPETITIONS as f64
         );
+
+        let start = ark_std::time::Instant::now();
+        for _ in 0..NUM_PROVE_REPETITIONS {
+            let _ = Groth16::<$bench_pairing_engine>::prove_no_zk(&pk, c.clone()).unwrap();
+        }
+        println!(
+            "wall-clock proving time without zero knowledge for {}: {} s",
+            stringify!($bench_pairing_engine),
+            start.elapsed().as_secs_f64() / NUM_PROVE_REPETITIONS as f64
+        );
+    };
+}
+
+macro_rules! groth16_prepared_prove_bench {
+    ($bench_name:ident, $bench_field:ty, $bench_pairing_engine:ty) => {
+        let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(0u64);
+        let c = DummyCircuit::<$bench_field> {
+            a: Some(<$bench_field>::rand(rng)),
+            b: Some(<$bench_field>::rand(rng)),
+            num_variables: NUM_PREPARED_VARIABLES,
+            num_constraints: NUM_PREPARED_CONSTRAINTS,
+        };
+
+        let (pk, _) = Groth16::<$bench_pairing_engine>::circuit_specific_setup(c, rng).unwrap();
+
+        let start = ark_std::time::Instant::now();
+        let prepared_pk = pk.prepare();
+        println!(
+            "proving key preparation time for {}: {} s",
+            stringify!($bench_pairing_engine),
+            start.elapsed().as_secs_f64()
+        );
+
+        let start = ark_std::time::Instant::now();
+        for _ in 0..NUM_PREPARED_PROVE_REPETITIONS {
+            let _ = Groth16::<$bench_pairing_engine>::prove(&pk, c.clone(), rng).unwrap();
+        }
+        println!(
+            "repeated proving time for {}: {} s/proof",
+            stringify!($bench_pairing_engine),
+            start.elapsed().as_secs_f64() / NUM_PREPARED_PROVE_REPETITIONS as f64
+        );
+
+        let start = ark_std::time::Instant::now();
+        for _ in 0..NUM_PREPARED_PROVE_REPETITIONS {
+            let _ = Groth16::<$bench_pairing_engine>::create_proof_with_prepared_pk(
+                &prepared_pk,
+                c.clone(),
+                rng,
+            )
+            .unwrap();
+        }
+        println!(
+            "repeated proving time with a prepared key for {}: {} s/proof",
+            stringify!($bench_pairing_engine),
+            start.elapsed().as_secs_f64() / NUM_PREPARED_PROVE_REPETITIONS as f64
+        );
+
+        let mut scratch = ProverScratch::<$bench_pairing_engine>::new();
+        let start = ark_std::time::Instant::now();
+        for _ in 0..NUM_PREPARED_PROVE_REPETITIONS {
+            let _ = Groth16::<$bench_pairing_engine>::create_proof_with_scratch(
+                &pk,
+                c.clone(),
+                rng,
+                &mut scratch,
+            )
+            .unwrap();
+        }
+        println!(
+            "repeated proving time with a scratch buffer for {}: {} s/proof, reusing {} bytes per proof",
+            stringify!($bench_pairing_engine),
+            start.elapsed().as_secs_f64() / NUM_PREPARED_PROVE_REPETITIONS as f64,
+            scratch.reserved_bytes()
+        );
+    };
+}
+
+macro_rules! groth16_prove_batch_bench {
+    ($bench_name:ident, $bench_field:ty, $bench_pairing_engine:ty) => {
+        let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(0u64);
+        let c = DummyCircuit::<$bench_field> {
+            a: Some(<$bench_field>::rand(rng)),
+            b: Some(<$bench_field>::rand(rng)),
+            num_variables: NUM_PREPARED_VARIABLES,
+            num_constraints: NUM_PREPARED_CONSTRAINTS,
+        };
+
+        let (pk, _) = Groth16::<$bench_pairing_engine>::circuit_specific_setup(c, rng).unwrap();
+
+        let start = ark_std::time::Instant::now();
+        for _ in 0..NUM_BATCH_PROVE_PROOFS {
+            let _ = Groth16::<$bench_pairing_engine>::prove(&pk, c.clone(), rng).unwrap();
+        }
+        println!(
+            "independent proving time for {} proofs for {}: {} s/proof",
+            NUM_BATCH_PROVE_PROOFS,
+            stringify!($bench_pairing_engine),
+            start.elapsed().as_secs_f64() / NUM_BATCH_PROVE_PROOFS as f64
+        );
+
+        let start = ark_std::time::Instant::now();
+        let circuits = (0..NUM_BATCH_PROVE_PROOFS).map(|_| c.clone());
+        let _ = Groth16::<$bench_pairing_engine>::prove_batch(&pk, circuits, rng).unwrap();
+        println!(
+            "batch proving time for {} proofs for {}: {} s/proof",
+            NUM_BATCH_PROVE_PROOFS,
+            stringify!($bench_pairing_engine),
+            start.elapsed().as_secs_f64() / NUM_BATCH_PROVE_PROOFS as f64
+        );
     };
 }
 
@@ -122,19 +241,125 @@ macro_rules! groth16_verify_bench {
     };
 }
 
+macro_rules! groth16_verify_same_vk_bench {
+    ($bench_name:ident, $bench_field:ty, $bench_pairing_engine:ty) => {
+        let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(0u64);
+        // Verification does not depend on the size of the circuit, so a small
+        // one keeps proving the batch cheap.
+        let c = DummyCircuit::<$bench_field> {
+            a: None,
+            b: None,
+            num_variables: 10,
+            num_constraints: NUM_BATCH_CONSTRAINTS,
+        };
+        let (pk, vk) = Groth16::<$bench_pairing_engine>::circuit_specific_setup(c, rng).unwrap();
+        let pvk = Groth16::<$bench_pairing_engine>::process_vk(&vk).unwrap();
+
+        let proofs_and_inputs = (0..NUM_BATCH_PROOFS)
+            .map(|_| {
+                let a = <$bench_field>::rand(rng);
+                let b = <$bench_field>::rand(rng);
+                let c = DummyCircuit {
+                    a: Some(a),
+                    b: Some(b),
+                    ..c
+                };
+                let proof = Groth16::<$bench_pairing_engine>::prove(&pk, c, rng).unwrap();
+                (proof, vec![a * b])
+            })
+            .collect::<Vec<_>>();
+
+        let start = ark_std::time::Instant::now();
+        for (proof, inputs) in &proofs_and_inputs {
+            assert!(Groth16::<$bench_pairing_engine>::verify_proof(&pvk, proof, inputs).unwrap());
+        }
+        let independent = start.elapsed();
+
+        let start = ark_std::time::Instant::now();
+        assert!(Groth16::<$bench_pairing_engine>::verify_proofs_same_vk(
+            &pvk,
+            &proofs_and_inputs,
+            rng
+        )
+        .unwrap());
+        let batched = start.elapsed();
+
+        println!(
+            "verifying {} proofs for {}: {} ns independently, {} ns batched ({:.1}x)",
+            NUM_BATCH_PROOFS,
+            stringify!($bench_pairing_engine),
+            independent.as_nanos(),
+            batched.as_nanos(),
+            independent.as_secs_f64() / batched.as_secs_f64()
+        );
+    };
+}
+
+macro_rules! groth16_setup_bench {
+    ($bench_name:ident, $bench_field:ty, $bench_pairing_engine:ty) => {
+        let rng = &mut ark_std::rand::rngs::StdRng::seed_from_u64(0u64);
+        let c = DummyCircuit::<$bench_field> {
+            a: None,
+            b: None,
+            num_variables: NUM_SETUP_VARIABLES,
+            num_constraints: NUM_SETUP_CONSTRAINTS,
+        };
+
+        for window_size in SETUP_WINDOW_SIZES {
+            let config = GeneratorConfig { window_size };
+            let start = ark_std::time::Instant::now();
+            let _ = Groth16::<$bench_pairing_engine>::generate_random_parameters_with_config(
+                c.clone(),
+                &config,
+                rng,
+            )
+            .unwrap();
+            println!(
+                "setup time for {} with window size {:?}: {} s",
+                stringify!($bench_pairing_engine),
+                window_size,
+                start.elapsed().as_secs_f64()
+            );
+        }
+    };
+}
+
+fn bench_setup() {
+    use ark_std::rand::SeedableRng;
+    groth16_setup_bench!(bls, BlsFr, Bls12_381);
+    groth16_setup_bench!(mnt4, MNT4Fr, MNT4_298);
+}
+
 fn bench_prove() {
     use ark_std::rand::SeedableRng;
     groth16_prove_bench!(bls, BlsFr, Bls12_381);
     groth16_prove_bench!(mnt4, MNT4Fr, MNT4_298);
 }
 
+fn bench_prepared_prove() {
+    use ark_std::rand::SeedableRng;
+    groth16_prepared_prove_bench!(bls, BlsFr, Bls12_381);
+    groth16_prepared_prove_bench!(mnt4, MNT4Fr, MNT4_298);
+}
+
+fn bench_prove_batch() {
+    use ark_std::rand::SeedableRng;
+    groth16_prove_batch_bench!(bls, BlsFr, Bls12_381);
+    groth16_prove_batch_bench!(mnt4, MNT4Fr, MNT4_298);
+}
+
 fn bench_verify() {
     use ark_std::rand::SeedableRng;
     groth16_verify_bench!(bls, BlsFr, Bls12_381);
     groth16_verify_bench!(mnt4, MNT4Fr, MNT4_298);
+    groth16_verify_same_vk_bench!(bls, BlsFr, Bls12_381);
+    groth16_verify_same_vk_bench!(mnt4, MNT4Fr, MNT4_298);
 }
 
 fn main() {
+    bench_setup();
     bench_prove();
+    bench_prepared_prove();
+    bench_prove_batch();
     bench_verify();
 }
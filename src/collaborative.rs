@@ -0,0 +1,217 @@
+use crate::{r1cs_to_qap::R1CSToQAP, Groth16, Proof, ProvingKey};
+use ark_ec::{pairing::Pairing, CurveGroup, VariableBaseMSM};
+use ark_ff::{One, UniformRand, Zero};
+use ark_poly::GeneralEvaluationDomain;
+use ark_relations::gr1cs::{Matrix, Result as R1CSResult, SynthesisError};
+use ark_std::{rand::Rng, vec, vec::Vec};
+
+type D<F> = GeneralEvaluationDomain<F>;
+
+/// A proof whose witness is contributed in parts, created by
+/// [`Groth16::begin_proof`].
+///
+/// The state holds running sums of the multi-scalar multiplications that are
+/// linear in the witness, and the running values of the `A`, `B` and `C`
+/// sides of every constraint, but never the witness itself.
+pub struct PartialProof<'a, E: Pairing> {
+    pk: &'a ProvingKey<E>,
+    /// The instance assignment, including the leading constant `1`.
+    inputs: Vec<E::ScalarField>,
+    /// For every witness variable, its nonzero coefficients as
+    /// `(matrix, constraint, coefficient)`.
+    columns: Vec<Vec<(usize, usize, E::ScalarField)>>,
+    /// `evaluations[j][i]` is the part of `<matrices[j][i], z>` contributed so
+    /// far.
+    evaluations: [Vec<E::ScalarField>; 3],
+    assigned: Vec<bool>,
+    a_acc: E::G1,
+    b_g1_acc: E::G1,
+    b_g2_acc: E::G2,
+    l_acc: E::G1,
+}
+
+impl<E: Pairing> PartialProof<'_, E> {
+    /// Returns the indices of the witness variables that have not been
+    /// contributed yet.
+    pub fn missing_witness(&self) -> impl Iterator<Item = usize> + '_ {
+        self.assigned
+            .iter()
+            .enumerate()
+            .filter_map(|(i, assigned)| (!assigned).then_some(i))
+    }
+}
+
+impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
+    /// Starts a proof whose witness is contributed in parts with
+    /// [`Self::contribute_witness`], e.g. by parties that each hold part of
+    /// it, and is then completed with [`Self::finalize_proof`].
+    ///
+    /// As in [`Self::prove_from_assignment`], `matrices` are the R1CS
+    /// matrices `[A, B, C]` of the circuit that `pk` was generated for and
+    /// `public` holds the instance assignment without the leading constant
+    /// `1`. Returns [`SynthesisError::Unsatisfiable`] if they do not match
+    /// `pk`.
+    pub fn begin_proof<'a>(
+        pk: &'a ProvingKey<E>,
+        matrices: &[Matrix<E::ScalarField>],
+        public: &[E::ScalarField],
+    ) -> R1CSResult<PartialProof<'a, E>> {
+        let num_inputs = public.len() + 1;
+        let num_witness = pk.l_query.len();
+        let num_variables = num_inputs + num_witness;
+        if matrices.len() != 3
+            || matrices[1].len() != matrices[0].len()
+            || matrices[2].len() != matrices[0].len()
+            || num_inputs != pk.vk.gamma_abc_g1.len()
+            || pk.a_query.len() != num_variables
+            || pk.b_g1_query.len() != num_variables
+            || pk.b_g2_query.len() != num_variables
+        {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        let inputs = [&[E::ScalarField::one()][..], public].concat();
+        let num_constraints = matrices[0].len();
+        let mut columns = vec![Vec::new(); num_witness];
+        let mut evaluations = [
+            vec![E::ScalarField::zero(); num_constraints],
+            vec![E::ScalarField::zero(); num_constraints],
+            vec![E::ScalarField::zero(); num_constraints],
+        ];
+        for (j, matrix) in matrices.iter().enumerate() {
+            for (i, row) in matrix.iter().enumerate() {
+                for &(coeff, index) in row {
+                    if index < num_inputs {
+                        evaluations[j][i] += coeff * inputs[index];
+                    } else if index < num_variables {
+                        columns[index - num_inputs].push((j, i, coeff));
+                    } else {
+                        return Err(SynthesisError::Unsatisfiable);
+                    }
+                }
+            }
+        }
+
+        Ok(PartialProof {
+            pk,
+            a_acc: E::G1::msm_unchecked(&pk.a_query[..num_inputs], &inputs),
+            b_g1_acc: E::G1::msm_unchecked(&pk.b_g1_query[..num_inputs], &inputs),
+            b_g2_acc: E::G2::msm_unchecked(&pk.b_g2_query[..num_inputs], &inputs),
+            l_acc: E::G1::zero(),
+            inputs,
+            columns,
+            evaluations,
+            assigned: vec![false; num_witness],
+        })
+    }
+
+    /// Adds the witness variables at `indices` (counted from the first
+    /// witness variable) with the given `values` to `state`.
+    ///
+    /// Every witness variable must be contributed exactly once over all
+    /// calls. Returns [`SynthesisError::Unsatisfiable`], leaving `state`
+    /// unchanged, if `indices` and `values` have different lengths or an
+    /// index is out of range or was already contributed.
+    pub fn contribute_witness(
+        state: &mut PartialProof<'_, E>,
+        indices: &[usize],
+        values: &[E::ScalarField],
+    ) -> R1CSResult<()> {
+        if indices.len() != values.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        for (k, &index) in indices.iter().enumerate() {
+            if state.assigned.get(index) != Some(&false) {
+                for &index in &indices[..k] {
+                    state.assigned[index] = false;
+                }
+                return Err(SynthesisError::Unsatisfiable);
+            }
+            state.assigned[index] = true;
+        }
+
+        let pk = state.pk;
+        let num_inputs = state.inputs.len();
+        let gather = |query: &[E::G1Affine], offset: usize| {
+            indices
+                .iter()
+                .map(|index| query[offset + index])
+                .collect::<Vec<_>>()
+        };
+        state.a_acc += E::G1::msm_unchecked(&gather(&pk.a_query, num_inputs), values);
+        state.b_g1_acc += E::G1::msm_unchecked(&gather(&pk.b_g1_query, num_inputs), values);
+        state.b_g2_acc += E::G2::msm_unchecked(
+            &indices
+                .iter()
+                .map(|index| pk.b_g2_query[num_inputs + index])
+                .collect::<Vec<_>>(),
+            values,
+        );
+        state.l_acc += E::G1::msm_unchecked(&gather(&pk.l_query, 0), values);
+
+        for (&index, value) in indices.iter().zip(values) {
+            for &(j, i, coeff) in &state.columns[index] {
+                state.evaluations[j][i] += coeff * value;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Completes a zero-knowledge proof once every witness variable has been
+    /// contributed to `state`, sampling the blinding factors from `rng`.
+    ///
+    /// The proof is identical to the one [`Self::prove_from_assignment`]
+    /// produces for the same assignment and randomness. Returns
+    /// [`SynthesisError::AssignmentMissing`] if part of the witness is
+    /// missing.
+    pub fn finalize_proof(state: PartialProof<'_, E>, rng: &mut impl Rng) -> R1CSResult<Proof<E>> {
+        if state.missing_witness().next().is_some() {
+            return Err(SynthesisError::AssignmentMissing);
+        }
+
+        // `h` depends on the assignment only through the constraint
+        // evaluations and the instance, so it is computed from an equivalent
+        // system in which every side of every constraint selects its value
+        // from an assignment made of the instance and those evaluations.
+        let num_inputs = state.inputs.len();
+        let num_constraints = state.evaluations[0].len();
+        let one = E::ScalarField::one();
+        let matrices = (0..3)
+            .map(|j| {
+                (0..num_constraints)
+                    .map(|i| vec![(one, num_inputs + j * num_constraints + i)])
+                    .collect::<Matrix<_>>()
+            })
+            .collect::<Vec<_>>();
+        let [a, b, c] = state.evaluations;
+        let assignment = [state.inputs, a, b, c].concat();
+        let h = QAP::witness_map_from_matrices::<E::ScalarField, D<E::ScalarField>>(
+            &matrices,
+            num_inputs,
+            num_constraints,
+            &assignment,
+        )?;
+        drop(assignment);
+
+        let pk = state.pk;
+        if h.len() > pk.h_query.len() + 1 {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let h_acc = E::G1::msm_unchecked(&pk.h_query, &h);
+
+        let r = E::ScalarField::rand(rng);
+        let s = E::ScalarField::rand(rng);
+
+        let g_a = state.a_acc + pk.vk.alpha_g1 + pk.delta_g1 * r;
+        let g1_b = state.b_g1_acc + pk.beta_g1 + pk.delta_g1 * s;
+        let g2_b = state.b_g2_acc + pk.vk.beta_g2 + pk.vk.delta_g2 * s;
+        let g_c = g_a * s + g1_b * r - pk.delta_g1 * (r * s) + state.l_acc + h_acc;
+
+        Ok(Proof {
+            a: g_a.into_affine(),
+            b: g2_b.into_affine(),
+            c: g_c.into_affine(),
+        })
+    }
+}
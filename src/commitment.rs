@@ -0,0 +1,257 @@
+//! In this variant of Groth16, as in LegoSNARK, the last public inputs of a
+//! circuit are not revealed to the verifier. Instead, the proof carries a
+//! hiding commitment `D` to them. The elements of `gamma_abc_g1` for these
+//! inputs are moved out of the verifying key into a [`CommittedVerifyingKey`]'s
+//! `commitment_key`, together with a blinding base `eta / gamma` under a
+//! separate trapdoor `eta`, and
+//!
+//! `D = sum_i x_i * commitment_key_i + o * (eta / gamma)`
+//!
+//! for the committed inputs `x_i` and a random `o`. The proof satisfies the
+//! Groth16 equation with `D` added to the accumulated public input, because
+//! the prover subtracts `o * (eta / delta)` from `C`. A verifier checks the
+//! [`Opening`] `(x, o)` against `D` along with the proof.
+//!
+//! `commitment_key` and the opening, as its values followed by its
+//! randomness, are the inputs that the `Groth16CommitmentVerifierGadget`
+//! checks `D` against.
+
+use crate::{
+    check_input_len,
+    generator::{wipe, GeneratorConfig},
+    msm::CpuBackend,
+    prepare_verifying_key,
+    r1cs_to_qap::R1CSToQAP,
+    Groth16, Groth16Error, PreparedVerifyingKey, Proof, ProvingKey, VerifyingKey,
+};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, UniformRand};
+use ark_relations::gr1cs::{ConstraintSynthesizer, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{rand::Rng, vec::Vec};
+
+/// The proving key of a circuit whose last public inputs are committed.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CommittedProvingKey<E: Pairing> {
+    /// The proving key of the circuit, in which the committed inputs are
+    /// ordinary public inputs. Its `vk` must not be used for verification.
+    pub pk: ProvingKey<E>,
+    /// The commitment key, as in [`CommittedVerifyingKey`].
+    pub commitment_key: Vec<E::G1Affine>,
+    /// `eta / delta` in `G1`, by whose multiple the prover offsets `C`.
+    pub eta_delta_inv_g1: E::G1Affine,
+}
+
+/// The verifying key of a circuit whose last public inputs are committed.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CommittedVerifyingKey<E: Pairing> {
+    /// The verifying key for the revealed public inputs.
+    pub vk: VerifyingKey<E>,
+    /// The elements of `gamma_abc_g1` of the committed inputs, followed by
+    /// the blinding base `eta / gamma` in `G1`.
+    pub commitment_key: Vec<E::G1Affine>,
+}
+
+/// A Groth16 proof together with the commitment `D` to the committed inputs.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CommittedProof<E: Pairing> {
+    /// The Groth16 proof, whose `C` accounts for the blinding of `d`.
+    pub proof: Proof<E>,
+    /// The commitment `D` in `G1` to the committed inputs.
+    pub d: E::G1Affine,
+}
+
+/// The opening of the commitment `D` of a [`CommittedProof`].
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Opening<F: Field> {
+    /// The committed inputs, in the order of the circuit's public inputs.
+    pub values: Vec<F>,
+    /// The randomness `o` that blinds the commitment.
+    pub randomness: F,
+}
+
+impl<E: Pairing> CommittedVerifyingKey<E> {
+    /// Returns the number of committed inputs.
+    pub fn num_committed(&self) -> usize {
+        self.commitment_key.len().saturating_sub(1)
+    }
+
+    /// Returns whether `opening` opens `d` under `self.commitment_key`.
+    pub fn check_opening(&self, d: &E::G1Affine, opening: &Opening<E::ScalarField>) -> bool {
+        if opening.values.len() != self.num_committed() {
+            return false;
+        }
+        let scalars = [&opening.values[..], &[opening.randomness]].concat();
+        E::G1::msm_unchecked(&self.commitment_key, &scalars) == d.into_group()
+    }
+}
+
+impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
+    /// Generates random parameters for `circuit` in which its last
+    /// `num_committed` public inputs are committed instead of revealed.
+    ///
+    /// The parameters for the other inputs are distributed as those of
+    /// [`Self::generate_random_parameters_with_reduction`]. Returns
+    /// [`SynthesisError::Unsatisfiable`] if the circuit has fewer than
+    /// `num_committed` public inputs.
+    pub fn generate_random_parameters_with_commitment<C>(
+        circuit: C,
+        num_committed: usize,
+        rng: &mut impl Rng,
+    ) -> Result<(CommittedProvingKey<E>, CommittedVerifyingKey<E>), Groth16Error>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let cs = Self::synthesize_for_setup(circuit)?;
+        let num_revealed = cs
+            .num_instance_variables()
+            .checked_sub(num_committed)
+            .filter(|&n| n > 0)
+            .ok_or(SynthesisError::Unsatisfiable)?;
+
+        let mut alpha = E::ScalarField::rand(rng);
+        let mut beta = E::ScalarField::rand(rng);
+        let mut gamma = E::ScalarField::rand(rng);
+        let mut delta = E::ScalarField::rand(rng);
+        let mut eta = E::ScalarField::rand(rng);
+
+        let g1_generator = E::G1::rand(rng);
+        let g2_generator = E::G2::rand(rng);
+
+        let pk = Self::generate_parameters_with_qap_and_cs(
+            cs,
+            alpha,
+            beta,
+            gamma,
+            delta,
+            g1_generator,
+            g2_generator,
+            &GeneratorConfig::default(),
+            rng,
+        );
+
+        let mut eta_gamma_inv = eta * gamma.inverse().unwrap();
+        let mut eta_delta_inv = eta * delta.inverse().unwrap();
+        let eta_gamma_inv_g1 = (g1_generator * eta_gamma_inv).into_affine();
+        let eta_delta_inv_g1 = (g1_generator * eta_delta_inv).into_affine();
+
+        wipe(&mut alpha);
+        wipe(&mut beta);
+        wipe(&mut gamma);
+        wipe(&mut delta);
+        wipe(&mut eta);
+        wipe(&mut eta_gamma_inv);
+        wipe(&mut eta_delta_inv);
+
+        let pk = pk?;
+        let mut vk = pk.vk.clone();
+        let mut commitment_key = vk.gamma_abc_g1.split_off(num_revealed);
+        commitment_key.push(eta_gamma_inv_g1);
+
+        Ok((
+            CommittedProvingKey {
+                pk,
+                commitment_key: commitment_key.clone(),
+                eta_delta_inv_g1,
+            },
+            CommittedVerifyingKey { vk, commitment_key },
+        ))
+    }
+
+    /// Creates a zero-knowledge proof for `circuit` that commits to its
+    /// committed public inputs, and returns it with the opening of the
+    /// commitment.
+    ///
+    /// The committed inputs are taken from the circuit's assignment; the
+    /// revealed ones are passed to the verifier as usual.
+    pub fn prove_with_commitment<C>(
+        pk: &CommittedProvingKey<E>,
+        circuit: C,
+        rng: &mut impl Rng,
+    ) -> Result<(CommittedProof<E>, Opening<E::ScalarField>), Groth16Error>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let (h, input_assignment, aux_assignment) = Self::synthesize_witness(circuit, &mut None)?;
+        let num_revealed = (input_assignment.len() + 1)
+            .checked_sub(pk.commitment_key.len())
+            .ok_or(SynthesisError::Unsatisfiable)?;
+
+        let r = E::ScalarField::rand(rng);
+        let s = E::ScalarField::rand(rng);
+        let mut proof = Self::create_proof_with_assignment(
+            &pk.pk,
+            &CpuBackend,
+            r,
+            s,
+            &h,
+            &input_assignment,
+            &aux_assignment,
+            &mut Vec::new(),
+            &mut None,
+        )?;
+
+        let opening = Opening {
+            values: input_assignment[num_revealed..].to_vec(),
+            randomness: E::ScalarField::rand(rng),
+        };
+        let scalars = [&opening.values[..], &[opening.randomness]].concat();
+        let d = E::G1::msm_unchecked(&pk.commitment_key, &scalars).into_affine();
+        proof.c = (proof.c.into_group() - pk.eta_delta_inv_g1 * opening.randomness).into_affine();
+
+        Ok((CommittedProof { proof, d }, opening))
+    }
+
+    /// Checks the Groth16 equation for `proof` against the prepared verifying
+    /// key `pvk` of the revealed public inputs `public_inputs`, with
+    /// `proof.d` added to their accumulator.
+    ///
+    /// This alone does not bind the proof to the revealed inputs: nothing
+    /// stops a prover from choosing a `d` that also cancels or shifts their
+    /// terms. It is only sound together with a check that `d` opens under
+    /// the commitment key, as done by [`Self::verify_with_opening`] or, on
+    /// the caller's side, by [`CommittedVerifyingKey::check_opening`] or a
+    /// proof of knowledge of the opening. `pvk` is the preparation of
+    /// [`CommittedVerifyingKey::vk`]. Returns
+    /// [`Groth16Error::InputLengthMismatch`] if the number of inputs does not
+    /// match `pvk`.
+    pub fn verify_committed(
+        pvk: &PreparedVerifyingKey<E>,
+        public_inputs: &[E::ScalarField],
+        proof: &CommittedProof<E>,
+    ) -> Result<bool, Groth16Error> {
+        check_input_len(&pvk.vk.gamma_abc_g1, public_inputs)?;
+        let prepared_inputs = Self::prepare_inputs(pvk, public_inputs)?;
+        Ok(Self::verify_proof_with_prepared_inputs(
+            pvk,
+            &proof.proof,
+            &(prepared_inputs + proof.d),
+        )?)
+    }
+
+    /// Verifies `proof` against `vk` and the revealed public inputs
+    /// `public_inputs`, and checks that `opening` opens its commitment.
+    ///
+    /// Returns [`Groth16Error::InputLengthMismatch`] if `public_inputs` or
+    /// `opening` do not have the number of inputs that `vk` expects.
+    pub fn verify_with_opening(
+        vk: &CommittedVerifyingKey<E>,
+        public_inputs: &[E::ScalarField],
+        proof: &CommittedProof<E>,
+        opening: &Opening<E::ScalarField>,
+    ) -> Result<bool, Groth16Error> {
+        let expected = vk.num_committed();
+        if opening.values.len() != expected {
+            return Err(Groth16Error::InputLengthMismatch {
+                expected,
+                got: opening.values.len(),
+            });
+        }
+        check_input_len(&vk.vk.gamma_abc_g1, public_inputs)?;
+        if !vk.check_opening(&proof.d, opening) {
+            return Ok(false);
+        }
+        let pvk = prepare_verifying_key(&vk.vk);
+        Self::verify_committed(&pvk, public_inputs, proof)
+    }
+}
@@ -1,5 +1,7 @@
 use crate::{
+    interop::point_from_coordinates,
     r1cs_to_qap::{LibsnarkReduction, R1CSToQAP},
+    verifier::{batch_sponge_config, input_chunk_len},
     Groth16, PreparedVerifyingKey, Proof, VerifyingKey,
 };
 use ark_crypto_primitives::{
@@ -7,22 +9,37 @@ use ark_crypto_primitives::{
         constraints::{CircuitSpecificSetupSNARKGadget, SNARKGadget},
         BooleanInputVar, SNARK,
     },
-    sponge::constraints::AbsorbGadget,
+    sponge::{
+        constraints::{AbsorbGadget, CryptographicSpongeVar},
+        poseidon::constraints::PoseidonSpongeVar,
+        CryptographicSponge,
+    },
+};
+use ark_ec::{
+    pairing::Pairing,
+    short_weierstrass::{Projective as SWProjective, SWCurveConfig},
+    AffineRepr, CurveGroup,
 };
-use ark_ec::{pairing::Pairing, AffineRepr};
-use ark_ff::Field;
+use ark_ff::{BigInteger, Field, PrimeField, ToConstraintField};
 use ark_r1cs_std::{
     alloc::{AllocVar, AllocationMode},
     boolean::Boolean,
-    convert::{ToBitsGadget, ToBytesGadget},
+    convert::{ToBitsGadget, ToBytesGadget, ToConstraintFieldGadget},
     eq::EqGadget,
-    fields::fp::FpVar,
-    groups::CurveVar,
+    fields::{emulated_fp::EmulatedFpVar, fp::FpVar, FieldVar},
+    groups::{
+        curves::short_weierstrass::{NonZeroAffineVar, ProjectiveVar},
+        CurveVar,
+    },
     pairing::PairingVar,
+    select::CondSelectGadget,
     uint8::UInt8,
+    R1CSVar,
+};
+use ark_relations::gr1cs::{
+    ConstraintSystem, ConstraintSystemRef, Namespace, SynthesisError, SynthesisMode,
 };
-use ark_relations::gr1cs::{Namespace, SynthesisError};
-use ark_std::{borrow::Borrow, marker::PhantomData, vec::Vec};
+use ark_std::{borrow::Borrow, marker::PhantomData, ops::Deref, vec::Vec};
 
 type BasePrimeField<E> = <<E as Pairing>::BaseField as Field>::BasePrimeField;
 
@@ -58,11 +75,34 @@ pub struct VerifyingKeyVar<E: Pairing, P: PairingVar<E>> {
 
 impl<E: Pairing, P: PairingVar<E>> VerifyingKeyVar<E, P> {
     /// Prepare `self` for use in proof verification.
+    ///
+    /// This does not build the fixed-base tables of
+    /// [`Groth16VerifierGadget::verify_with_precomputed_tables`]. Unless
+    /// `gamma_abc_g1` is constant, building them costs constraints of their
+    /// own, which only pay off if the prepared key is used for several
+    /// verifications, so they are only built on request, by
+    /// [`Self::prepare_with_tables`].
     pub fn prepare(&self) -> Result<PreparedVerifyingKeyVar<E, P>, SynthesisError> {
         let alpha_g1_pc = P::prepare_g1(&self.alpha_g1)?;
         let beta_g2_pc = P::prepare_g2(&self.beta_g2)?;
 
         let alpha_g1_beta_g2 = P::pairing(alpha_g1_pc, beta_g2_pc)?;
+        self.prepare_with_alpha_beta(alpha_g1_beta_g2)
+    }
+
+    /// Prepare `self` for use in proof verification, taking
+    /// `e(alpha_g1, beta_g2)` as `alpha_g1_beta_g2` instead of computing it
+    /// in the circuit.
+    ///
+    /// This saves a full pairing when the value is known in advance, e.g. as
+    /// a constant allocated from [`PreparedVerifyingKey::alpha_g1_beta_g2`].
+    /// It is not checked against `alpha_g1` and `beta_g2`: passing a value
+    /// that does not match them makes every proof fail to verify, or, if it
+    /// is chosen by the prover, lets them forge proofs.
+    pub fn prepare_with_alpha_beta(
+        &self,
+        alpha_g1_beta_g2: P::GTVar,
+    ) -> Result<PreparedVerifyingKeyVar<E, P>, SynthesisError> {
         let gamma_g2_neg_pc = P::prepare_g2(&self.gamma_g2.negate()?)?;
         let delta_g2_neg_pc = P::prepare_g2(&self.delta_g2.negate()?)?;
 
@@ -71,8 +111,18 @@ impl<E: Pairing, P: PairingVar<E>> VerifyingKeyVar<E, P> {
             gamma_g2_neg_pc,
             delta_g2_neg_pc,
             gamma_abc_g1: self.gamma_abc_g1.clone(),
+            gamma_abc_g1_tables: None,
         })
     }
+
+    /// Prepare `self` for use in proof verification, additionally
+    /// precomputing the fixed-base tables used by
+    /// [`Groth16VerifierGadget::verify_with_precomputed_tables`].
+    pub fn prepare_with_tables(&self) -> Result<PreparedVerifyingKeyVar<E, P>, SynthesisError> {
+        let mut pvk = self.prepare()?;
+        pvk.compute_input_tables();
+        Ok(pvk)
+    }
 }
 
 impl<E, P> AbsorbGadget<E::BaseField> for VerifyingKeyVar<E, P>
@@ -109,6 +159,30 @@ where
     }
 }
 
+impl<E, P> AbsorbGadget<E::BaseField> for ProofVar<E, P>
+where
+    E: Pairing,
+    P: PairingVar<E>,
+    P::G1Var: AbsorbGadget<E::BaseField>,
+    P::G2Var: AbsorbGadget<E::BaseField>,
+{
+    fn to_sponge_bytes(&self) -> Result<Vec<UInt8<<E as Pairing>::BaseField>>, SynthesisError> {
+        let mut bytes = self.a.to_sponge_bytes()?;
+        bytes.extend(self.b.to_sponge_bytes()?);
+        bytes.extend(self.c.to_sponge_bytes()?);
+        Ok(bytes)
+    }
+
+    fn to_sponge_field_elements(
+        &self,
+    ) -> Result<Vec<FpVar<<E as Pairing>::BaseField>>, SynthesisError> {
+        let mut field_elements = self.a.to_sponge_field_elements()?;
+        field_elements.extend(self.b.to_sponge_field_elements()?);
+        field_elements.extend(self.c.to_sponge_field_elements()?);
+        Ok(field_elements)
+    }
+}
+
 /// Preprocessed verification key parameters variable for the Groth16
 /// construction
 #[derive(educe::Educe)]
@@ -125,422 +199,3421 @@ pub struct PreparedVerifyingKeyVar<E: Pairing, P: PairingVar<E>> {
     pub delta_g2_neg_pc: P::G2PreparedVar,
     #[doc(hidden)]
     pub gamma_abc_g1: Vec<P::G1Var>,
+    /// For every input base `gamma_abc_g1[i + 1]`, one table per
+    /// `INPUT_TABLE_WINDOW`-bit window of the scalar holding the multiples
+    /// `k * 2^(INPUT_TABLE_WINDOW * j) * gamma_abc_g1[i + 1]`.
+    #[doc(hidden)]
+    pub gamma_abc_g1_tables: Option<Vec<Vec<Vec<P::G1Var>>>>,
 }
 
-/// Constraints for the verifier of the SNARK of [[Groth16]](https://eprint.iacr.org/2016/260.pdf).
-pub struct Groth16VerifierGadget<E, P, QAP = LibsnarkReduction>
+impl<E, P> CondSelectGadget<BasePrimeField<E>> for PreparedVerifyingKeyVar<E, P>
 where
     E: Pairing,
     P: PairingVar<E>,
-    QAP: R1CSToQAP,
+    P::G2PreparedVar: CondSelectGadget<BasePrimeField<E>>,
 {
-    _pairing_engine: PhantomData<E>,
-    _pairing_gadget: PhantomData<P>,
-    _qap: PhantomData<QAP>,
+    /// Selects between two prepared verifying keys element-wise.
+    ///
+    /// Returns an error if the keys have a different number of public inputs.
+    /// The fixed-base input tables are only kept if both keys have them.
+    #[tracing::instrument(target = "r1cs", skip(cond, true_value, false_value))]
+    fn conditionally_select(
+        cond: &Boolean<BasePrimeField<E>>,
+        true_value: &Self,
+        false_value: &Self,
+    ) -> Result<Self, SynthesisError> {
+        if true_value.gamma_abc_g1.len() != false_value.gamma_abc_g1.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let gamma_abc_g1 = true_value
+            .gamma_abc_g1
+            .iter()
+            .zip(&false_value.gamma_abc_g1)
+            .map(|(t, f)| P::G1Var::conditionally_select(cond, t, f))
+            .collect::<Result<Vec<_>, _>>()?;
+        let gamma_abc_g1_tables = match (
+            &true_value.gamma_abc_g1_tables,
+            &false_value.gamma_abc_g1_tables,
+        ) {
+            (Some(t), Some(f)) => Some(
+                t.iter()
+                    .zip(f)
+                    .map(|(t, f)| {
+                        t.iter()
+                            .zip(f)
+                            .map(|(t, f)| {
+                                t.iter()
+                                    .zip(f)
+                                    .map(|(t, f)| P::G1Var::conditionally_select(cond, t, f))
+                                    .collect::<Result<Vec<_>, _>>()
+                            })
+                            .collect::<Result<Vec<_>, _>>()
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            _ => None,
+        };
+
+        Ok(Self {
+            alpha_g1_beta_g2: P::GTVar::conditionally_select(
+                cond,
+                &true_value.alpha_g1_beta_g2,
+                &false_value.alpha_g1_beta_g2,
+            )?,
+            gamma_g2_neg_pc: P::G2PreparedVar::conditionally_select(
+                cond,
+                &true_value.gamma_g2_neg_pc,
+                &false_value.gamma_g2_neg_pc,
+            )?,
+            delta_g2_neg_pc: P::G2PreparedVar::conditionally_select(
+                cond,
+                &true_value.delta_g2_neg_pc,
+                &false_value.delta_g2_neg_pc,
+            )?,
+            gamma_abc_g1,
+            gamma_abc_g1_tables,
+        })
+    }
 }
 
-impl<E, QAP, P> SNARKGadget<E::ScalarField, BasePrimeField<E>, Groth16<E, QAP>>
-    for Groth16VerifierGadget<E, P, QAP>
+impl<E, P> AbsorbGadget<BasePrimeField<E>> for PreparedVerifyingKeyVar<E, P>
 where
     E: Pairing,
-    QAP: R1CSToQAP,
     P: PairingVar<E>,
 {
-    type ProcessedVerifyingKeyVar = PreparedVerifyingKeyVar<E, P>;
-    type VerifyingKeyVar = VerifyingKeyVar<E, P>;
-    type InputVar = BooleanInputVar<E::ScalarField, BasePrimeField<E>>;
-    type ProofVar = ProofVar<E, P>;
-
-    type VerifierSize = usize;
-
-    fn verifier_size(
-        circuit_vk: &<Groth16<E> as SNARK<E::ScalarField>>::VerifyingKey,
-    ) -> Self::VerifierSize {
-        circuit_vk.gamma_abc_g1.len()
+    /// Absorbs the byte encoding produced by [`ToBytesGadget::to_bytes_le`].
+    fn to_sponge_bytes(&self) -> Result<Vec<UInt8<BasePrimeField<E>>>, SynthesisError> {
+        self.to_bytes_le()
     }
 
-    /// Allocates `N::Proof` in `cs` without performing
-    /// subgroup checks.
-    #[tracing::instrument(target = "r1cs", skip(cs, f))]
-    fn new_proof_unchecked<T: Borrow<Proof<E>>>(
-        cs: impl Into<Namespace<BasePrimeField<E>>>,
-        f: impl FnOnce() -> Result<T, SynthesisError>,
-        mode: AllocationMode,
-    ) -> Result<Self::ProofVar, SynthesisError> {
-        let ns = cs.into();
-        let cs = ns.cs();
-        f().and_then(|proof| {
-            let proof = proof.borrow();
-            let a = CurveVar::new_variable_omit_prime_order_check(
-                ark_relations::ns!(cs, "Proof.a"),
-                || Ok(proof.a.into_group()),
-                mode,
-            )?;
-            let b = CurveVar::new_variable_omit_prime_order_check(
-                ark_relations::ns!(cs, "Proof.b"),
-                || Ok(proof.b.into_group()),
-                mode,
-            )?;
-            let c = CurveVar::new_variable_omit_prime_order_check(
-                ark_relations::ns!(cs, "Proof.c"),
-                || Ok(proof.c.into_group()),
-                mode,
-            )?;
-            Ok(ProofVar { a, b, c })
-        })
+    fn to_sponge_field_elements(&self) -> Result<Vec<FpVar<BasePrimeField<E>>>, SynthesisError> {
+        self.to_sponge_bytes()?.to_constraint_field()
     }
+}
 
-    /// Allocates `N::Proof` in `cs` without performing
-    /// subgroup checks.
-    #[tracing::instrument(target = "r1cs", skip(cs, f))]
-    fn new_verification_key_unchecked<T: Borrow<VerifyingKey<E>>>(
-        cs: impl Into<Namespace<BasePrimeField<E>>>,
-        f: impl FnOnce() -> Result<T, SynthesisError>,
-        mode: AllocationMode,
-    ) -> Result<Self::VerifyingKeyVar, SynthesisError> {
-        let ns = cs.into();
-        let cs = ns.cs();
-        f().and_then(|vk| {
-            let vk = vk.borrow();
-            let alpha_g1 = P::G1Var::new_variable_omit_prime_order_check(
-                ark_relations::ns!(cs, "alpha_g1"),
-                || Ok(vk.alpha_g1.into_group()),
-                mode,
-            )?;
-            let beta_g2 = P::G2Var::new_variable_omit_prime_order_check(
-                ark_relations::ns!(cs, "beta_g2"),
-                || Ok(vk.beta_g2.into_group()),
-                mode,
-            )?;
-            let gamma_g2 = P::G2Var::new_variable_omit_prime_order_check(
-                ark_relations::ns!(cs, "gamma_g2"),
-                || Ok(vk.gamma_g2.into_group()),
-                mode,
-            )?;
-            let delta_g2 = P::G2Var::new_variable_omit_prime_order_check(
-                ark_relations::ns!(cs, "delta_g2"),
-                || Ok(vk.delta_g2.into_group()),
-                mode,
-            )?;
-            let gamma_abc_g1 = vk
-                .gamma_abc_g1
-                .iter()
-                .map(|g| {
-                    P::G1Var::new_variable_omit_prime_order_check(
-                        ark_relations::ns!(cs, "gamma_abc_g1"),
-                        || Ok(g.into_group()),
-                        mode,
-                    )
-                })
-                .collect::<Result<Vec<_>, _>>()?;
+/// The number of scalar bits handled by each lookup in
+/// [`Groth16VerifierGadget::verify_with_precomputed_tables`].
+const INPUT_TABLE_WINDOW: usize = 3;
 
-            Ok(VerifyingKeyVar {
-                alpha_g1,
-                beta_g2,
-                gamma_g2,
-                delta_g2,
-                gamma_abc_g1,
+impl<E: Pairing, P: PairingVar<E>> PreparedVerifyingKeyVar<E, P> {
+    /// Precomputes fixed-base tables for the public input bases of `self`, so
+    /// that the multiples of each base are computed once rather than on every
+    /// verification.
+    pub fn compute_input_tables(&mut self) {
+        let num_bits = E::ScalarField::MODULUS_BIT_SIZE as usize;
+        let num_windows = num_bits.div_ceil(INPUT_TABLE_WINDOW);
+        let tables = self
+            .gamma_abc_g1
+            .iter()
+            .skip(1)
+            .map(|base| {
+                let mut base = base.clone();
+                (0..num_windows)
+                    .map(|_| {
+                        let mut window = Vec::with_capacity(1 << INPUT_TABLE_WINDOW);
+                        let mut multiple = P::G1Var::zero();
+                        for _ in 0..(1 << INPUT_TABLE_WINDOW) {
+                            window.push(multiple.clone());
+                            multiple += &base;
+                        }
+                        base = multiple;
+                        window
+                    })
+                    .collect()
             })
-        })
+            .collect();
+        self.gamma_abc_g1_tables = Some(tables);
     }
 
-    #[tracing::instrument(target = "r1cs", skip(circuit_pvk, x, proof))]
-    fn verify_with_processed_vk(
-        circuit_pvk: &Self::ProcessedVerifyingKeyVar,
-        x: &Self::InputVar,
-        proof: &Self::ProofVar,
-    ) -> Result<Boolean<BasePrimeField<E>>, SynthesisError> {
-        let circuit_pvk = circuit_pvk.clone();
-
-        let g_ic = {
-            let mut g_ic: P::G1Var = circuit_pvk.gamma_abc_g1[0].clone();
-            let mut input_len = 1;
-            let mut public_inputs = x.clone().into_iter();
-            for (input, b) in public_inputs
-                .by_ref()
-                .zip(circuit_pvk.gamma_abc_g1.iter().skip(1))
-            {
-                let encoded_input_i: P::G1Var = b.scalar_mul_le(input.to_bits_le()?.iter())?;
-                g_ic += encoded_input_i;
-                input_len += 1;
-            }
-            // Check that the input and the query in the verification are of the
-            // same length.
-            assert!(input_len == circuit_pvk.gamma_abc_g1.len() && public_inputs.next().is_none());
-            g_ic
-        };
+    /// Computes the accumulated public input
+    /// `g_ic = gamma_abc_g1[0] + sum_i x_i * gamma_abc_g1[i + 1]`, the point
+    /// that the verification equation pairs with `-gamma`.
+    ///
+    /// Returns an error if `self` has no `gamma_abc_g1` elements or if the
+    /// number of inputs does not match it.
+    pub fn accumulate_inputs(
+        &self,
+        x: &BooleanInputVar<E::ScalarField, BasePrimeField<E>>,
+    ) -> Result<P::G1Var, SynthesisError> {
+        let mut g_ic: P::G1Var = self
+            .gamma_abc_g1
+            .first()
+            .cloned()
+            .ok_or(SynthesisError::MalformedVerifyingKey)?;
+        let mut input_len = 1;
+        let mut public_inputs = x.clone().into_iter();
+        for (input, b) in public_inputs.by_ref().zip(self.gamma_abc_g1.iter().skip(1)) {
+            let encoded_input_i: P::G1Var = b.scalar_mul_le(input.to_bits_le()?.iter())?;
+            g_ic += encoded_input_i;
+            input_len += 1;
+        }
+        // Check that the input and the query in the verification are of the
+        // same length.
+        if input_len != self.gamma_abc_g1.len() || public_inputs.next().is_some() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        Ok(g_ic)
+    }
 
-        let test_exp = {
-            let proof_a_prep = P::prepare_g1(&proof.a)?;
-            let proof_b_prep = P::prepare_g2(&proof.b)?;
-            let proof_c_prep = P::prepare_g1(&proof.c)?;
+    /// Returns `gamma_abc_g1[i]` for the index `i` given by the little-endian
+    /// `index_bits`, which may be a witness.
+    ///
+    /// The element is selected by a multiplexer tree of depth
+    /// `ceil(log2(gamma_abc_g1.len()))`, over the elements padded to a power
+    /// of two. Indices that are out of range are constrained out, i.e. make the
+    /// circuit unsatisfiable. Returns an error if `self` has no
+    /// `gamma_abc_g1` elements.
+    pub fn select_gamma_abc(
+        &self,
+        index_bits: &[Boolean<BasePrimeField<E>>],
+    ) -> Result<P::G1Var, SynthesisError> {
+        let len = self.gamma_abc_g1.len();
+        if len == 0 {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+        let depth = (usize::BITS - (len - 1).leading_zeros()) as usize;
 
-            let g_ic_prep = P::prepare_g1(&g_ic)?;
+        let mut bits = index_bits.to_vec();
+        if bits.len() < depth {
+            bits.resize(depth, Boolean::constant(false));
+        }
+        // This also enforces that the bits above `depth` are zero.
+        Boolean::enforce_smaller_or_equal_than_le(&bits, [(len - 1) as u64])?;
 
-            P::miller_loop(
-                &[proof_a_prep, g_ic_prep, proof_c_prep],
-                &[
-                    proof_b_prep,
-                    circuit_pvk.gamma_g2_neg_pc.clone(),
-                    circuit_pvk.delta_g2_neg_pc.clone(),
-                ],
-            )?
-        };
+        let mut values = self.gamma_abc_g1.clone();
+        values.resize(1 << depth, self.gamma_abc_g1[0].clone());
+        // `conditionally_select_power_of_two_vector` expects the position in
+        // big-endian order.
+        let position = bits[..depth].iter().rev().cloned().collect::<Vec<_>>();
+        P::G1Var::conditionally_select_power_of_two_vector(&position, &values)
+    }
+}
 
-        let test = P::final_exponentiation(&test_exp)?;
-        test.is_eq(&circuit_pvk.alpha_g1_beta_g2)
+impl<E: Pairing, P: PairingVar<E>> VerifyingKeyVar<E, P> {
+    /// Allocates a verifying key with `num_gamma_abc_g1` elements in
+    /// `gamma_abc_g1` from its byte encoding, as emitted by
+    /// [`ToBytesGadget::to_bytes_le`].
+    ///
+    /// The key is allocated as a witness (including subgroup checks) and its
+    /// encoding is enforced to equal `bytes`. Returns an error if the length
+    /// of `bytes` does not match `num_gamma_abc_g1`.
+    #[tracing::instrument(target = "r1cs", skip(cs, bytes))]
+    pub fn from_bytes_le(
+        cs: impl Into<Namespace<BasePrimeField<E>>>,
+        bytes: &[UInt8<BasePrimeField<E>>],
+        num_gamma_abc_g1: usize,
+    ) -> Result<Self, SynthesisError> {
+        let g1_len = P::G1Var::zero().to_bytes_le()?.len();
+        let g2_len = P::G2Var::zero().to_bytes_le()?.len();
+        let fixed_len = g1_len + 3 * g2_len;
+        if num_gamma_abc_g1 == 0
+            || bytes.len() < fixed_len
+            || (bytes.len() - fixed_len) % g1_len != 0
+            || (bytes.len() - fixed_len) / g1_len != num_gamma_abc_g1
+        {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+
+        let vk = Self::new_witness(cs, || {
+            let bytes = bytes.value()?;
+            let (alpha_g1, rest) = bytes.split_at(g1_len);
+            let (beta_g2, rest) = rest.split_at(g2_len);
+            let (gamma_g2, rest) = rest.split_at(g2_len);
+            let (delta_g2, rest) = rest.split_at(g2_len);
+            Ok(VerifyingKey::<E> {
+                alpha_g1: decode_point(alpha_g1)?,
+                beta_g2: decode_point(beta_g2)?,
+                gamma_g2: decode_point(gamma_g2)?,
+                delta_g2: decode_point(delta_g2)?,
+                gamma_abc_g1: rest
+                    .chunks(g1_len)
+                    .map(decode_point)
+                    .collect::<Result<_, _>>()?,
+            })
+        })?;
+        vk.to_bytes_le()?[..].enforce_equal(bytes)?;
+        Ok(vk)
     }
+}
 
-    #[tracing::instrument(target = "r1cs", skip(circuit_vk, x, proof))]
-    fn verify(
-        circuit_vk: &Self::VerifyingKeyVar,
-        x: &Self::InputVar,
-        proof: &Self::ProofVar,
-    ) -> Result<Boolean<BasePrimeField<E>>, SynthesisError> {
-        let pvk = circuit_vk.prepare()?;
-        Self::verify_with_processed_vk(&pvk, x, proof)
+/// Natively decodes a point from the byte layout emitted by the short
+/// Weierstrass curve gadgets: the little-endian affine coordinates, with each
+/// base prime field element padded to a whole number of limbs, followed by
+/// an infinity flag byte.
+fn decode_point<G: AffineRepr>(bytes: &[u8]) -> Result<G, SynthesisError> {
+    let (coordinates, infinity) = bytes
+        .split_last()
+        .map(|(infinity, coordinates)| (coordinates, *infinity))
+        .ok_or(SynthesisError::MalformedVerifyingKey)?;
+    if infinity != 0 {
+        return Ok(G::zero());
+    }
+
+    let element_len =
+        <<G::BaseField as Field>::BasePrimeField as PrimeField>::BigInt::NUM_LIMBS * 8;
+    let degree = G::BaseField::extension_degree() as usize;
+    let elements = coordinates
+        .chunks(element_len)
+        .map(<<G::BaseField as Field>::BasePrimeField as PrimeField>::from_le_bytes_mod_order)
+        .collect::<Vec<_>>();
+    if elements.len() != 2 * degree {
+        return Err(SynthesisError::MalformedVerifyingKey);
     }
+    let x = G::BaseField::from_base_prime_field_elems(elements[..degree].iter().copied())
+        .ok_or(SynthesisError::MalformedVerifyingKey)?;
+    let y = G::BaseField::from_base_prime_field_elems(elements[degree..].iter().copied())
+        .ok_or(SynthesisError::MalformedVerifyingKey)?;
+
+    point_from_coordinates(x, y).ok_or(SynthesisError::MalformedVerifyingKey)
 }
 
-impl<E, P, QAP: R1CSToQAP>
-    CircuitSpecificSetupSNARKGadget<E::ScalarField, BasePrimeField<E>, Groth16<E, QAP>>
-    for Groth16VerifierGadget<E, P, QAP>
+impl<E, P> VerifyingKeyVar<E, P>
 where
     E: Pairing,
+    E::BaseField: PrimeField,
     P: PairingVar<E>,
-    QAP: R1CSToQAP,
+    P::G1Var: AbsorbGadget<E::BaseField>,
+    P::G2Var: AbsorbGadget<E::BaseField>,
 {
+    /// Hashes `self` to a single field element by absorbing it into `sponge`.
+    ///
+    /// This matches the native `VerifyingKey::hash` when both use the same
+    /// sponge parameters.
+    #[tracing::instrument(target = "r1cs", skip(self, sponge))]
+    pub fn hash<S: CryptographicSponge>(
+        &self,
+        sponge: &mut impl CryptographicSpongeVar<E::BaseField, S>,
+    ) -> Result<FpVar<E::BaseField>, SynthesisError> {
+        sponge.absorb(self)?;
+        Ok(sponge.squeeze_field_elements(1)?.remove(0))
+    }
 }
 
-impl<E, P> AllocVar<PreparedVerifyingKey<E>, BasePrimeField<E>> for PreparedVerifyingKeyVar<E, P>
-where
-    E: Pairing,
-    P: PairingVar<E>,
-{
-    #[tracing::instrument(target = "r1cs", skip(cs, f))]
-    fn new_variable<T: Borrow<PreparedVerifyingKey<E>>>(
-        cs: impl Into<Namespace<BasePrimeField<E>>>,
+/// Public inputs for the Groth16 verifier gadget, represented as emulated
+/// `F` elements in a circuit over `CF`.
+///
+/// This is an alternative to [`BooleanInputVar`] for circuits in which the
+/// public inputs are already available as field elements.
+#[derive(Clone)]
+pub struct FieldInputVar<F: PrimeField, CF: PrimeField> {
+    val: Vec<EmulatedFpVar<F, CF>>,
+}
+
+impl<F: PrimeField, CF: PrimeField> FieldInputVar<F, CF> {
+    /// Wraps already allocated emulated field elements.
+    pub fn new(val: Vec<EmulatedFpVar<F, CF>>) -> Self {
+        Self { val }
+    }
+}
+
+impl<F: PrimeField, CF: PrimeField> Deref for FieldInputVar<F, CF> {
+    type Target = [EmulatedFpVar<F, CF>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.val
+    }
+}
+
+impl<F: PrimeField, CF: PrimeField> AllocVar<Vec<F>, CF> for FieldInputVar<F, CF> {
+    fn new_variable<T: Borrow<Vec<F>>>(
+        cs: impl Into<Namespace<CF>>,
         f: impl FnOnce() -> Result<T, SynthesisError>,
         mode: AllocationMode,
     ) -> Result<Self, SynthesisError> {
-        let ns = cs.into();
-        let cs = ns.cs();
+        let val = Vec::<EmulatedFpVar<F, CF>>::new_variable(
+            cs,
+            || f().map(|v| v.borrow().clone()),
+            mode,
+        )?;
+        Ok(Self { val })
+    }
+}
 
-        f().and_then(|pvk| {
-            let pvk = pvk.borrow();
-            let alpha_g1_beta_g2 = P::GTVar::new_variable(
-                ark_relations::ns!(cs, "alpha_g1_beta_g2"),
-                || Ok(pvk.alpha_g1_beta_g2.clone()),
-                mode,
-            )?;
+/// Public inputs for the Groth16 verifier gadget given as bytes in a circuit
+/// over `CF`, which are packed into `F` elements as by
+/// [`pack_bytes_to_inputs`](crate::pack_bytes_to_inputs).
+#[derive(Clone)]
+pub struct BytesInputVar<F: PrimeField, CF: PrimeField> {
+    val: Vec<UInt8<CF>>,
+    _field: PhantomData<F>,
+}
 
-            let gamma_g2_neg_pc = P::G2PreparedVar::new_variable(
-                ark_relations::ns!(cs, "gamma_g2_neg_pc"),
-                || Ok(pvk.gamma_g2_neg_pc.clone()),
-                mode,
-            )?;
+impl<F: PrimeField, CF: PrimeField> BytesInputVar<F, CF> {
+    /// Wraps already allocated bytes.
+    pub fn new(val: Vec<UInt8<CF>>) -> Self {
+        Self {
+            val,
+            _field: PhantomData,
+        }
+    }
 
-            let delta_g2_neg_pc = P::G2PreparedVar::new_variable(
-                ark_relations::ns!(cs, "delta_g2_neg_pc"),
-                || Ok(pvk.delta_g2_neg_pc.clone()),
-                mode,
-            )?;
+    /// Packs the bytes into public inputs.
+    ///
+    /// Every chunk of bytes is shorter than the modulus of `F`, so its bits
+    /// are already the canonical encoding of the input and no range check is
+    /// needed.
+    pub fn to_boolean_input(&self) -> Result<BooleanInputVar<F, CF>, SynthesisError> {
+        let inputs = self
+            .val
+            .chunks(input_chunk_len::<F>())
+            .map(|chunk| chunk.to_bits_le())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(BooleanInputVar::new(inputs))
+    }
+}
 
-            let gamma_abc_g1 = Vec::new_variable(
-                ark_relations::ns!(cs, "gamma_abc_g1"),
-                || Ok(pvk.vk.gamma_abc_g1.clone()),
-                mode,
-            )?;
+impl<F: PrimeField, CF: PrimeField> Deref for BytesInputVar<F, CF> {
+    type Target = [UInt8<CF>];
 
-            Ok(Self {
-                alpha_g1_beta_g2,
-                gamma_g2_neg_pc,
-                delta_g2_neg_pc,
-                gamma_abc_g1,
-            })
-        })
+    fn deref(&self) -> &Self::Target {
+        &self.val
     }
 }
 
-impl<E, P> AllocVar<VerifyingKey<E>, BasePrimeField<E>> for VerifyingKeyVar<E, P>
+impl<F: PrimeField, CF: PrimeField> AllocVar<Vec<u8>, CF> for BytesInputVar<F, CF> {
+    fn new_variable<T: Borrow<Vec<u8>>>(
+        cs: impl Into<Namespace<CF>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let val = Vec::<UInt8<CF>>::new_variable(cs, || f().map(|v| v.borrow().clone()), mode)?;
+        Ok(Self::new(val))
+    }
+}
+
+/// Constraints for the verifier of the SNARK of [[Groth16]](https://eprint.iacr.org/2016/260.pdf).
+///
+/// The verifier circuit is defined over `BasePrimeField<E>`, the prime field
+/// underlying the base field of `E`, so `P` must implement the pairing of `E`
+/// with native arithmetic over that field. The public inputs live in
+/// `E::ScalarField`, which is always a different field: they are encoded
+/// either as bits ([`BooleanInputVar`]) or as emulated field elements
+/// ([`FieldInputVar`]), and in both cases enter the circuit only through
+/// scalar multiplications of `gamma_abc_g1`.
+///
+/// Verifying proofs over a curve whose base field is itself emulated (e.g. a
+/// BN254 proof in a BLS12-377 circuit) is not supported, since `PairingVar`
+/// gadgets require native base field arithmetic.
+///
+/// Verification does not depend on `QAP`: the R1CS-to-QAP reduction only
+/// determines how the proving key is built and used, while the verifying key
+/// and the verification equation are the same for every reduction. The
+/// parameter is kept so that the gadget implements [`SNARKGadget`] for
+/// exactly one SNARK, `Groth16<E, QAP>`. Code that is generic over a SNARK
+/// `S` and a gadget `G: SNARKGadget<_, _, S>` thus fails to compile if the
+/// gadget's reduction differs from the one `S` uses for setup:
+///
+/// ```compile_fail
+/// use ark_crypto_primitives::snark::{constraints::SNARKGadget, SNARK};
+/// use ark_groth16::{
+///     constraints::Groth16VerifierGadget, r1cs_to_qap::GrothPaperReduction, Groth16,
+/// };
+/// use ark_mnt4_298::{constraints::PairingVar, Fq, Fr, MNT4_298};
+///
+/// fn gadget_for<S: SNARK<Fr>, G: SNARKGadget<Fr, Fq, S>>() {}
+///
+/// // The gadget defaults to `LibsnarkReduction`.
+/// gadget_for::<
+///     Groth16<MNT4_298, GrothPaperReduction>,
+///     Groth16VerifierGadget<MNT4_298, PairingVar>,
+/// >();
+/// ```
+pub struct Groth16VerifierGadget<E, P, QAP = LibsnarkReduction>
 where
     E: Pairing,
     P: PairingVar<E>,
+    QAP: R1CSToQAP,
 {
-    #[tracing::instrument(target = "r1cs", skip(cs, f))]
-    fn new_variable<T: Borrow<VerifyingKey<E>>>(
-        cs: impl Into<Namespace<BasePrimeField<E>>>,
-        f: impl FnOnce() -> Result<T, SynthesisError>,
-        mode: AllocationMode,
-    ) -> Result<Self, SynthesisError> {
-        let ns = cs.into();
-        let cs = ns.cs();
+    _pairing_engine: PhantomData<E>,
+    _pairing_gadget: PhantomData<P>,
+    _qap: PhantomData<QAP>,
+}
 
+/// The number of bits in each challenge used to combine proofs in
+/// [`Groth16VerifierGadget::verify_batch`].
+const BATCH_CHALLENGE_BITS: usize = 128;
+
+/// Packs little-endian bits into bytes, padding the final byte with zeros.
+fn bits_to_bytes<F: PrimeField>(bits: &[Boolean<F>]) -> Vec<UInt8<F>> {
+    bits.chunks(8)
+        .map(|chunk| {
+            let mut byte = chunk.to_vec();
+            byte.resize(8, Boolean::constant(false));
+            UInt8::from_bits_le(&byte)
+        })
+        .collect()
+}
+
+impl<E, P, QAP> Groth16VerifierGadget<E, P, QAP>
+where
+    E: Pairing,
+    P: PairingVar<E>,
+    QAP: R1CSToQAP,
+{
+    /// Allocates a proof in `cs`, enforcing that every element lies in the
+    /// prime-order subgroup.
+    ///
+    /// Unlike [`SNARKGadget::new_proof_unchecked`], this is safe to use with
+    /// proofs from untrusted provers on curves whose groups have a non-trivial
+    /// cofactor.
+    #[tracing::instrument(target = "r1cs", skip(cs, f))]
+    pub fn new_proof_checked<T: Borrow<Proof<E>>>(
+        cs: impl Into<Namespace<BasePrimeField<E>>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<ProofVar<E, P>, SynthesisError> {
+        ProofVar::new_variable(cs, f, mode)
+    }
+
+    /// Allocates a verifying key in `cs`, enforcing that every element lies in
+    /// the prime-order subgroup.
+    ///
+    /// This is the checked counterpart of
+    /// [`SNARKGadget::new_verification_key_unchecked`].
+    #[tracing::instrument(target = "r1cs", skip(cs, f))]
+    pub fn new_verification_key_checked<T: Borrow<VerifyingKey<E>>>(
+        cs: impl Into<Namespace<BasePrimeField<E>>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<VerifyingKeyVar<E, P>, SynthesisError> {
+        VerifyingKeyVar::new_variable(cs, f, mode)
+    }
+
+    /// Computes `gamma_abc_g1[0] + sum_i x_i * gamma_abc_g1[i + 1]`, which is
+    /// just `gamma_abc_g1[0]` for a circuit without public inputs.
+    ///
+    /// The result can be passed to [`Self::verify_with_prepared_input_point`]
+    /// for every proof that must share the public input `x`. This is the same
+    /// as [`PreparedVerifyingKeyVar::accumulate_inputs`].
+    pub fn accumulate_inputs(
+        circuit_pvk: &PreparedVerifyingKeyVar<E, P>,
+        x: &BooleanInputVar<E::ScalarField, BasePrimeField<E>>,
+    ) -> Result<P::G1Var, SynthesisError> {
+        circuit_pvk.accumulate_inputs(x)
+    }
+
+    /// Returns `gamma_abc_g1[0]`, the accumulated input of the empty
+    /// instance, or an error if `circuit_pvk` has no `gamma_abc_g1` elements
+    /// at all.
+    fn input_base(circuit_pvk: &PreparedVerifyingKeyVar<E, P>) -> Result<P::G1Var, SynthesisError> {
+        circuit_pvk
+            .gamma_abc_g1
+            .first()
+            .cloned()
+            .ok_or(SynthesisError::MalformedVerifyingKey)
+    }
+
+    /// Computes the left-hand side of the Groth16 verification equation,
+    /// `e(A, B) * e(g_ic, -gamma) * e(C, -delta)`, for `proof` against
+    /// `circuit_pvk` and the public inputs `x`.
+    ///
+    /// No equality check is performed: the proof is valid iff the returned
+    /// element equals `circuit_pvk.alpha_g1_beta_g2`, and callers are
+    /// responsible for enforcing that (possibly after folding several such
+    /// results together).
+    #[tracing::instrument(target = "r1cs", skip(circuit_pvk, x, proof))]
+    pub fn verify_with_processed_vk_raw(
+        circuit_pvk: &PreparedVerifyingKeyVar<E, P>,
+        x: &BooleanInputVar<E::ScalarField, BasePrimeField<E>>,
+        proof: &ProofVar<E, P>,
+    ) -> Result<P::GTVar, SynthesisError> {
+        let g_ic = Self::accumulate_inputs(circuit_pvk, x)?;
+
+        Self::pairing_check_exp(circuit_pvk, &g_ic, proof)
+    }
+
+    /// Verifies `proof` against `circuit_pvk` and the public input whose
+    /// accumulated input point `g_ic` was computed by
+    /// [`Self::accumulate_inputs`].
+    ///
+    /// Verifying several proofs against the same `g_ic` enforces that they
+    /// share that public input, and pays for the input scalar
+    /// multiplications only once.
+    #[tracing::instrument(target = "r1cs", skip(circuit_pvk, g_ic, proof))]
+    pub fn verify_with_prepared_input_point(
+        circuit_pvk: &PreparedVerifyingKeyVar<E, P>,
+        g_ic: &P::G1Var,
+        proof: &ProofVar<E, P>,
+    ) -> Result<Boolean<BasePrimeField<E>>, SynthesisError> {
+        let test = Self::pairing_check_exp(circuit_pvk, g_ic, proof)?;
+        test.is_eq(&circuit_pvk.alpha_g1_beta_g2)
+    }
+
+    /// Verifies `proof` against `circuit_pvk` and the public inputs `x`, and
+    /// returns a commitment to `x` squeezed from `sponge`, which binds the
+    /// result to the inputs it was checked against.
+    ///
+    /// The commitment is computed from the same bits of `x` that are
+    /// multiplied into `gamma_abc_g1`: the bits of each input are packed into
+    /// little-endian bytes, the last one padded with zeros, and each input's
+    /// bytes are absorbed in turn before one field element is squeezed.
+    /// Natively, this is absorbing the first
+    /// `ceil(E::ScalarField::MODULUS_BIT_SIZE / 8)` bytes of
+    /// `x_i.into_bigint().to_bytes_le()` for each input `x_i`.
+    #[tracing::instrument(target = "r1cs", skip(circuit_pvk, x, proof, sponge))]
+    pub fn verify_and_commit_inputs<S: CryptographicSponge>(
+        circuit_pvk: &PreparedVerifyingKeyVar<E, P>,
+        x: &BooleanInputVar<E::ScalarField, BasePrimeField<E>>,
+        proof: &ProofVar<E, P>,
+        sponge: &mut impl CryptographicSpongeVar<BasePrimeField<E>, S>,
+    ) -> Result<(Boolean<BasePrimeField<E>>, FpVar<BasePrimeField<E>>), SynthesisError> {
+        let g_ic = Self::accumulate_inputs(circuit_pvk, x)?;
+        let valid = Self::verify_with_prepared_input_point(circuit_pvk, &g_ic, proof)?;
+
+        for input in x.clone() {
+            sponge.absorb(&bits_to_bytes(&input))?;
+        }
+        let commitment = sponge.squeeze_field_elements(1)?.remove(0);
+
+        Ok((valid, commitment))
+    }
+
+    /// Returns a `Boolean` that is true iff `proof` is valid for `circuit_pvk`
+    /// and `x`, or `fallback` is true, e.g. to let an administrator override
+    /// the proof check. Enforce it to be true to require one of the two.
+    ///
+    /// An R1CS circuit has the same constraints for every assignment, so the
+    /// full verification is always paid for, even when `fallback` is set:
+    /// this costs as much as [`SNARKGadget::verify_with_processed_vk`] plus
+    /// the selections below and one OR. When `fallback` is set, `proof` is
+    /// replaced by a fixed dummy proof before it is verified, so that the
+    /// circuit remains satisfiable whatever points the prover allocated for
+    /// `proof`, e.g. the identity.
+    #[tracing::instrument(target = "r1cs", skip(circuit_pvk, x, proof, fallback))]
+    pub fn verify_or(
+        circuit_pvk: &PreparedVerifyingKeyVar<E, P>,
+        x: &BooleanInputVar<E::ScalarField, BasePrimeField<E>>,
+        proof: &ProofVar<E, P>,
+        fallback: &Boolean<BasePrimeField<E>>,
+    ) -> Result<Boolean<BasePrimeField<E>>, SynthesisError> {
+        let dummy = ProofVar {
+            a: P::G1Var::constant(E::G1Affine::generator().into_group()),
+            b: P::G2Var::constant(E::G2Affine::generator().into_group()),
+            c: P::G1Var::constant(E::G1Affine::generator().into_group()),
+        };
+        let proof = fallback.select(&dummy, proof)?;
+        let test = Self::verify_with_processed_vk_raw(circuit_pvk, x, &proof)?;
+        let valid = test.is_eq(&circuit_pvk.alpha_g1_beta_g2)?;
+        Boolean::kary_or(&[fallback.clone(), valid])
+    }
+
+    /// Verifies `proof` against `circuit_pvk` with public inputs given as
+    /// emulated scalar field elements.
+    ///
+    /// This avoids allocating the inputs as bits when they are already
+    /// available as field elements in the circuit. Returns an error if the
+    /// number of inputs does not match the verifying key.
+    #[tracing::instrument(target = "r1cs", skip(circuit_pvk, x, proof))]
+    pub fn verify_with_field_inputs(
+        circuit_pvk: &PreparedVerifyingKeyVar<E, P>,
+        x: &FieldInputVar<E::ScalarField, BasePrimeField<E>>,
+        proof: &ProofVar<E, P>,
+    ) -> Result<Boolean<BasePrimeField<E>>, SynthesisError> {
+        if x.len() + 1 != circuit_pvk.gamma_abc_g1.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let mut g_ic: P::G1Var = Self::input_base(circuit_pvk)?;
+        for (input, b) in x.iter().zip(circuit_pvk.gamma_abc_g1.iter().skip(1)) {
+            g_ic += b.scalar_mul_le(input.to_bits_le()?.iter())?;
+        }
+
+        let test = Self::pairing_check_exp(circuit_pvk, &g_ic, proof)?;
+        test.is_eq(&circuit_pvk.alpha_g1_beta_g2)
+    }
+
+    /// Estimates the number of constraints that [`SNARKGadget::verify`] adds
+    /// for a verifying key of the same size as `vk`, e.g. to budget an outer
+    /// circuit before synthesizing it.
+    ///
+    /// The verifier costs a fixed amount for preparing the key and for the
+    /// pairing check, plus one scalar multiplication and one addition per
+    /// public input. Since `P` does not publish the costs of its operations,
+    /// both are measured by synthesizing the verifier for zero and one public
+    /// inputs in setup mode, so this takes about as long as two verifier
+    /// syntheses without witness generation. Allocating the key, proof, and
+    /// inputs is not counted: its cost depends on the allocation mode.
+    pub fn estimated_constraints(vk: &VerifyingKey<E>) -> usize {
+        let num_inputs = vk.gamma_abc_g1.len().saturating_sub(1);
+        let base = Self::measure_verify(0);
+        let per_input = Self::measure_verify(1) - base;
+        base + per_input * num_inputs
+    }
+
+    /// Returns the number of constraints of [`SNARKGadget::verify`] for a key
+    /// with `num_inputs` public inputs, allocated as witnesses.
+    fn measure_verify(num_inputs: usize) -> usize {
+        let g1 = E::G1Affine::generator();
+        let g2 = E::G2Affine::generator();
+        let vk = VerifyingKey::<E> {
+            alpha_g1: g1,
+            beta_g2: g2,
+            gamma_g2: g2,
+            delta_g2: g2,
+            gamma_abc_g1: vec![g1; num_inputs + 1],
+        };
+        let proof = Proof::<E> {
+            a: g1,
+            b: g2,
+            c: g1,
+        };
+
+        let cs = ConstraintSystem::<BasePrimeField<E>>::new_ref();
+        cs.set_mode(SynthesisMode::Setup);
+        let synthesize = || -> Result<usize, SynthesisError> {
+            let vk = VerifyingKeyVar::<E, P>::new_witness(cs.clone(), || Ok(vk))?;
+            let proof = ProofVar::<E, P>::new_witness(cs.clone(), || Ok(proof))?;
+            let x = BooleanInputVar::new_witness(cs.clone(), || {
+                Ok(vec![E::ScalarField::default(); num_inputs])
+            })?;
+            let before = cs.num_constraints();
+            <Self as SNARKGadget<E::ScalarField, BasePrimeField<E>, Groth16<E, QAP>>>::verify(
+                &vk, &x, &proof,
+            )?;
+            Ok(cs.num_constraints() - before)
+        };
+        synthesize().expect("synthesizing the verifier in setup mode does not fail")
+    }
+
+    /// Verifies `proof` against `circuit_pvk` with public inputs given as
+    /// bytes, which are packed into scalar field elements as by
+    /// [`pack_bytes_to_inputs`](crate::pack_bytes_to_inputs).
+    ///
+    /// Returns an error if the number of packed inputs does not match the
+    /// verifying key.
+    #[tracing::instrument(target = "r1cs", skip(circuit_pvk, x, proof))]
+    pub fn verify_with_byte_inputs(
+        circuit_pvk: &PreparedVerifyingKeyVar<E, P>,
+        x: &BytesInputVar<E::ScalarField, BasePrimeField<E>>,
+        proof: &ProofVar<E, P>,
+    ) -> Result<Boolean<BasePrimeField<E>>, SynthesisError> {
+        let g_ic = circuit_pvk.accumulate_inputs(&x.to_boolean_input()?)?;
+        Self::verify_with_prepared_input_point(circuit_pvk, &g_ic, proof)
+    }
+
+    /// Verifies `proof` against `circuit_pvk` using the fixed-base tables
+    /// computed by [`PreparedVerifyingKeyVar::compute_input_tables`] for the
+    /// public input multiplications.
+    ///
+    /// Returns [`SynthesisError::MalformedVerifyingKey`] if `circuit_pvk` has
+    /// no tables, i.e. if it was built by [`VerifyingKeyVar::prepare`] rather
+    /// than [`VerifyingKeyVar::prepare_with_tables`] or
+    /// [`PreparedVerifyingKeyVar::compute_input_tables`], and an error if the
+    /// number of inputs does not match the verifying key.
+    #[tracing::instrument(target = "r1cs", skip(circuit_pvk, x, proof))]
+    pub fn verify_with_precomputed_tables(
+        circuit_pvk: &PreparedVerifyingKeyVar<E, P>,
+        x: &BooleanInputVar<E::ScalarField, BasePrimeField<E>>,
+        proof: &ProofVar<E, P>,
+    ) -> Result<Boolean<BasePrimeField<E>>, SynthesisError> {
+        let tables = circuit_pvk
+            .gamma_abc_g1_tables
+            .as_ref()
+            .ok_or(SynthesisError::MalformedVerifyingKey)?;
+
+        let mut g_ic: P::G1Var = Self::input_base(circuit_pvk)?;
+        let mut input_len = 1;
+        let mut public_inputs = x.clone().into_iter();
+        for (input, table) in public_inputs.by_ref().zip(tables) {
+            let num_table_bits = table.len() * INPUT_TABLE_WINDOW;
+            let mut bits = input.to_bits_le()?;
+            // Bits beyond the tables must be zero for the input to be a
+            // canonical scalar.
+            for bit in bits.iter().skip(num_table_bits) {
+                bit.enforce_equal(&Boolean::constant(false))?;
+            }
+            bits.resize(num_table_bits, Boolean::constant(false));
+
+            for (window_bits, window) in bits.chunks(INPUT_TABLE_WINDOW).zip(table) {
+                // `conditionally_select_power_of_two_vector` expects the
+                // position in big-endian order.
+                let position = window_bits.iter().rev().cloned().collect::<Vec<_>>();
+                g_ic += P::G1Var::conditionally_select_power_of_two_vector(&position, window)?;
+            }
+            input_len += 1;
+        }
+        if input_len != circuit_pvk.gamma_abc_g1.len() || public_inputs.next().is_some() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        let test = Self::pairing_check_exp(circuit_pvk, &g_ic, proof)?;
+        test.is_eq(&circuit_pvk.alpha_g1_beta_g2)
+    }
+
+    /// Computes `e(A, B) * e(g_ic, -gamma) * e(C, -delta)` given the
+    /// accumulated public input `g_ic`.
+    fn pairing_check_exp(
+        circuit_pvk: &PreparedVerifyingKeyVar<E, P>,
+        g_ic: &P::G1Var,
+        proof: &ProofVar<E, P>,
+    ) -> Result<P::GTVar, SynthesisError> {
+        let test_exp = {
+            let proof_a_prep = P::prepare_g1(&proof.a)?;
+            let proof_b_prep = P::prepare_g2(&proof.b)?;
+            let proof_c_prep = P::prepare_g1(&proof.c)?;
+
+            let g_ic_prep = P::prepare_g1(g_ic)?;
+
+            P::miller_loop(
+                &[proof_a_prep, g_ic_prep, proof_c_prep],
+                &[
+                    proof_b_prep,
+                    circuit_pvk.gamma_g2_neg_pc.clone(),
+                    circuit_pvk.delta_g2_neg_pc.clone(),
+                ],
+            )?
+        };
+
+        P::final_exponentiation(&test_exp)
+    }
+
+    /// Verifies a batch of proofs against the same prepared verifying key
+    /// using a single Miller loop and final exponentiation.
+    ///
+    /// The verification equations of the individual proofs are combined in a
+    /// random linear combination. The combining challenges are squeezed from a
+    /// Poseidon sponge that has absorbed every proof and public input in the
+    /// batch, as well as `circuit_pvk` unless it is a constant of the circuit,
+    /// so a prover cannot choose proofs or a key after seeing them.
+    ///
+    /// Returns an error if `inputs` and `proofs` have different lengths.
+    #[tracing::instrument(target = "r1cs", skip(circuit_pvk, inputs, proofs))]
+    pub fn verify_batch(
+        circuit_pvk: &PreparedVerifyingKeyVar<E, P>,
+        inputs: &[BooleanInputVar<E::ScalarField, BasePrimeField<E>>],
+        proofs: &[ProofVar<E, P>],
+    ) -> Result<Boolean<BasePrimeField<E>>, SynthesisError> {
+        if inputs.len() != proofs.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        if proofs.is_empty() {
+            return Ok(Boolean::constant(true));
+        }
+
+        let cs = proofs
+            .iter()
+            .fold(circuit_pvk.alpha_g1_beta_g2.cs(), |cs, proof| {
+                cs.or(proof.a.cs()).or(proof.b.cs()).or(proof.c.cs())
+            });
+        let mut sponge = PoseidonSpongeVar::new(cs, &batch_sponge_config());
+        // As in `verify_many`, a constant key is fixed when the circuit is
+        // built, so only a key allocated as a variable needs to be absorbed.
+        let pvk_bytes = circuit_pvk.to_bytes_le()?;
+        if pvk_bytes.iter().any(|b| !b.is_constant()) {
+            sponge.absorb(&pvk_bytes)?;
+        }
+        for (x, proof) in inputs.iter().zip(proofs) {
+            sponge.absorb(&proof.a.to_bytes_le()?)?;
+            sponge.absorb(&proof.b.to_bytes_le()?)?;
+            sponge.absorb(&proof.c.to_bytes_le()?)?;
+            for input in x.clone() {
+                sponge.absorb(&bits_to_bytes(&input))?;
+            }
+        }
+
+        let mut g1_prep = Vec::with_capacity(proofs.len() + 2);
+        let mut g2_prep = Vec::with_capacity(proofs.len() + 2);
+        let mut g_ic_acc = P::G1Var::zero();
+        let mut c_acc = P::G1Var::zero();
+        let mut alpha_g1_beta_g2_acc = P::GTVar::one();
+        for (i, (x, proof)) in inputs.iter().zip(proofs).enumerate() {
+            let g_ic = Self::accumulate_inputs(circuit_pvk, x)?;
+            // The first equation is left unscaled; this keeps the combination
+            // sound while saving one round of scalar multiplications.
+            if i == 0 {
+                g_ic_acc += g_ic;
+                c_acc += proof.c.clone();
+                g1_prep.push(P::prepare_g1(&proof.a)?);
+                alpha_g1_beta_g2_acc = circuit_pvk.alpha_g1_beta_g2.clone();
+            } else {
+                let challenge = sponge.squeeze_bits(BATCH_CHALLENGE_BITS)?;
+                g_ic_acc += g_ic.scalar_mul_le(challenge.iter())?;
+                c_acc += proof.c.scalar_mul_le(challenge.iter())?;
+                g1_prep.push(P::prepare_g1(&proof.a.scalar_mul_le(challenge.iter())?)?);
+                alpha_g1_beta_g2_acc *= circuit_pvk.alpha_g1_beta_g2.pow_le(&challenge)?;
+            }
+            g2_prep.push(P::prepare_g2(&proof.b)?);
+        }
+        g1_prep.push(P::prepare_g1(&g_ic_acc)?);
+        g2_prep.push(circuit_pvk.gamma_g2_neg_pc.clone());
+        g1_prep.push(P::prepare_g1(&c_acc)?);
+        g2_prep.push(circuit_pvk.delta_g2_neg_pc.clone());
+
+        let test_exp = P::miller_loop(&g1_prep, &g2_prep)?;
+        let test = P::final_exponentiation(&test_exp)?;
+        test.is_eq(&alpha_g1_beta_g2_acc)
+    }
+
+    /// Verifies `proofs[i]` against the prepared verifying key `pvks[i]` and
+    /// the public inputs `inputs[i]`, using a single Miller loop and final
+    /// exponentiation for all of them.
+    ///
+    /// The keys may differ, so unlike [`Self::verify_batch`] no pairing
+    /// terms are shared: every proof contributes its own `A`, `g_ic` and `C`
+    /// pairings, and the accumulated input `g_ic` is computed against its own
+    /// key. The equations are combined in a random linear combination with
+    /// challenges squeezed from a Poseidon sponge that has absorbed every
+    /// proof and public input, as well as every key that is not a constant of
+    /// the circuit. This saves one final exponentiation and one `GT`
+    /// comparison per proof over separate calls to `verify`.
+    ///
+    /// Returns an error if `pvks`, `inputs` and `proofs` have different
+    /// lengths, or if the number of inputs does not match a key.
+    #[tracing::instrument(target = "r1cs", skip(pvks, inputs, proofs))]
+    pub fn verify_many(
+        pvks: &[PreparedVerifyingKeyVar<E, P>],
+        inputs: &[BooleanInputVar<E::ScalarField, BasePrimeField<E>>],
+        proofs: &[ProofVar<E, P>],
+    ) -> Result<Boolean<BasePrimeField<E>>, SynthesisError> {
+        if pvks.len() != proofs.len() || inputs.len() != proofs.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        if proofs.is_empty() {
+            return Ok(Boolean::constant(true));
+        }
+
+        let cs = pvks
+            .iter()
+            .zip(proofs)
+            .fold(ConstraintSystemRef::None, |cs, (pvk, proof)| {
+                cs.or(pvk.alpha_g1_beta_g2.cs())
+                    .or(proof.a.cs())
+                    .or(proof.b.cs())
+                    .or(proof.c.cs())
+            });
+        let mut sponge = PoseidonSpongeVar::new(cs, &batch_sponge_config());
+        for ((pvk, x), proof) in pvks.iter().zip(inputs).zip(proofs) {
+            // A constant key is fixed when the circuit is built, so the
+            // prover cannot choose it after seeing the challenges.
+            let pvk_bytes = pvk.to_bytes_le()?;
+            if pvk_bytes.iter().any(|b| !b.is_constant()) {
+                sponge.absorb(&pvk_bytes)?;
+            }
+            sponge.absorb(&proof.a.to_bytes_le()?)?;
+            sponge.absorb(&proof.b.to_bytes_le()?)?;
+            sponge.absorb(&proof.c.to_bytes_le()?)?;
+            for input in x.clone() {
+                sponge.absorb(&bits_to_bytes(&input))?;
+            }
+        }
+
+        let mut g1_prep = Vec::with_capacity(3 * proofs.len());
+        let mut g2_prep = Vec::with_capacity(3 * proofs.len());
+        let mut alpha_g1_beta_g2_acc = P::GTVar::one();
+        for (i, ((pvk, x), proof)) in pvks.iter().zip(inputs).zip(proofs).enumerate() {
+            let g_ic = Self::accumulate_inputs(pvk, x)?;
+            // As in `verify_batch`, the first equation is left unscaled.
+            let (a, g_ic, c) = if i == 0 {
+                alpha_g1_beta_g2_acc = pvk.alpha_g1_beta_g2.clone();
+                (proof.a.clone(), g_ic, proof.c.clone())
+            } else {
+                let challenge = sponge.squeeze_bits(BATCH_CHALLENGE_BITS)?;
+                alpha_g1_beta_g2_acc *= pvk.alpha_g1_beta_g2.pow_le(&challenge)?;
+                (
+                    proof.a.scalar_mul_le(challenge.iter())?,
+                    g_ic.scalar_mul_le(challenge.iter())?,
+                    proof.c.scalar_mul_le(challenge.iter())?,
+                )
+            };
+            g1_prep.push(P::prepare_g1(&a)?);
+            g2_prep.push(P::prepare_g2(&proof.b)?);
+            g1_prep.push(P::prepare_g1(&g_ic)?);
+            g2_prep.push(pvk.gamma_g2_neg_pc.clone());
+            g1_prep.push(P::prepare_g1(&c)?);
+            g2_prep.push(pvk.delta_g2_neg_pc.clone());
+        }
+
+        let test_exp = P::miller_loop(&g1_prep, &g2_prep)?;
+        let test = P::final_exponentiation(&test_exp)?;
+        test.is_eq(&alpha_g1_beta_g2_acc)
+    }
+}
+
+impl<E, P, QAP, C> Groth16VerifierGadget<E, P, QAP>
+where
+    E: Pairing<G1 = SWProjective<C>>,
+    C: SWCurveConfig<BaseField = BasePrimeField<E>>,
+    P: PairingVar<E, G1Var = ProjectiveVar<C, FpVar<BasePrimeField<E>>>>,
+    QAP: R1CSToQAP,
+{
+    /// Verifies `proof` against `circuit_pvk` like
+    /// [`SNARKGadget::verify_with_processed_vk`], but accumulates the public
+    /// inputs with incomplete affine additions, which costs fewer constraints
+    /// than the complete projective formulas.
+    ///
+    /// The input accumulator starts at `gamma_abc_g1[0]`, and each bit of
+    /// each input conditionally adds the corresponding multiple
+    /// `2^j * gamma_abc_g1[i + 1]`. Incomplete addition requires the two
+    /// summands to have distinct `x`-coordinates, which is enforced for every
+    /// addition, whether or not that bit is set, by witnessing the inverse of
+    /// the difference of the `x`-coordinates; every element of
+    /// `gamma_abc_g1` must be nonzero as well. With these checks each sum is
+    /// the correct one, so a false statement is never accepted.
+    ///
+    /// The price is completeness: if a partial sum of the accumulator is
+    /// equal to, or the negation of, the multiple added next, the circuit is
+    /// unsatisfiable, or, if both are constants, this returns an error. For
+    /// honestly generated keys that only happens for inputs that reveal a
+    /// discrete-logarithm relation between the elements of `gamma_abc_g1`.
+    #[tracing::instrument(target = "r1cs", skip(circuit_pvk, x, proof))]
+    pub fn verify_with_processed_vk_affine(
+        circuit_pvk: &PreparedVerifyingKeyVar<E, P>,
+        x: &BooleanInputVar<E::ScalarField, BasePrimeField<E>>,
+        proof: &ProofVar<E, P>,
+    ) -> Result<Boolean<BasePrimeField<E>>, SynthesisError> {
+        let to_non_zero = |p: &P::G1Var| {
+            let p = p.to_affine()?;
+            p.infinity.enforce_equal(&Boolean::constant(false))?;
+            Ok::<_, SynthesisError>(NonZeroAffineVar::new(p.x, p.y))
+        };
+
+        let mut g_ic = to_non_zero(&Self::input_base(circuit_pvk)?)?;
+        let mut input_len = 1;
+        let mut public_inputs = x.clone().into_iter();
+        for (input, b) in public_inputs
+            .by_ref()
+            .zip(circuit_pvk.gamma_abc_g1.iter().skip(1))
+        {
+            let mut multiple = to_non_zero(b)?;
+            for bit in input.to_bits_le()? {
+                // With equal `x`-coordinates the slope of the incomplete
+                // addition would be unconstrained.
+                (&multiple.x - &g_ic.x).inverse()?;
+                let sum = g_ic.add_unchecked(&multiple)?;
+                g_ic = bit.select(&sum, &g_ic)?;
+                multiple.double_in_place()?;
+            }
+            input_len += 1;
+        }
+        if input_len != circuit_pvk.gamma_abc_g1.len() || public_inputs.next().is_some() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        let test = Self::pairing_check_exp(circuit_pvk, &g_ic.into_projective(), proof)?;
+        test.is_eq(&circuit_pvk.alpha_g1_beta_g2)
+    }
+}
+
+impl<E, QAP, P> SNARKGadget<E::ScalarField, BasePrimeField<E>, Groth16<E, QAP>>
+    for Groth16VerifierGadget<E, P, QAP>
+where
+    E: Pairing,
+    QAP: R1CSToQAP,
+    P: PairingVar<E>,
+{
+    type ProcessedVerifyingKeyVar = PreparedVerifyingKeyVar<E, P>;
+    type VerifyingKeyVar = VerifyingKeyVar<E, P>;
+    type InputVar = BooleanInputVar<E::ScalarField, BasePrimeField<E>>;
+    type ProofVar = ProofVar<E, P>;
+
+    type VerifierSize = usize;
+
+    fn verifier_size(
+        circuit_vk: &<Groth16<E> as SNARK<E::ScalarField>>::VerifyingKey,
+    ) -> Self::VerifierSize {
+        circuit_vk.gamma_abc_g1.len()
+    }
+
+    /// Allocates `N::Proof` in `cs` without performing
+    /// subgroup checks.
+    #[tracing::instrument(target = "r1cs", skip(cs, f))]
+    fn new_proof_unchecked<T: Borrow<Proof<E>>>(
+        cs: impl Into<Namespace<BasePrimeField<E>>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self::ProofVar, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
+        f().and_then(|proof| {
+            let proof = proof.borrow();
+            let a = CurveVar::new_variable_omit_prime_order_check(
+                ark_relations::ns!(cs, "Proof.a"),
+                || Ok(proof.a.into_group()),
+                mode,
+            )?;
+            let b = CurveVar::new_variable_omit_prime_order_check(
+                ark_relations::ns!(cs, "Proof.b"),
+                || Ok(proof.b.into_group()),
+                mode,
+            )?;
+            let c = CurveVar::new_variable_omit_prime_order_check(
+                ark_relations::ns!(cs, "Proof.c"),
+                || Ok(proof.c.into_group()),
+                mode,
+            )?;
+            Ok(ProofVar { a, b, c })
+        })
+    }
+
+    /// Allocates `N::Proof` in `cs` without performing
+    /// subgroup checks.
+    #[tracing::instrument(target = "r1cs", skip(cs, f))]
+    fn new_verification_key_unchecked<T: Borrow<VerifyingKey<E>>>(
+        cs: impl Into<Namespace<BasePrimeField<E>>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self::VerifyingKeyVar, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
         f().and_then(|vk| {
-            let VerifyingKey {
+            let vk = vk.borrow();
+            let alpha_g1 = P::G1Var::new_variable_omit_prime_order_check(
+                ark_relations::ns!(cs, "alpha_g1"),
+                || Ok(vk.alpha_g1.into_group()),
+                mode,
+            )?;
+            let beta_g2 = P::G2Var::new_variable_omit_prime_order_check(
+                ark_relations::ns!(cs, "beta_g2"),
+                || Ok(vk.beta_g2.into_group()),
+                mode,
+            )?;
+            let gamma_g2 = P::G2Var::new_variable_omit_prime_order_check(
+                ark_relations::ns!(cs, "gamma_g2"),
+                || Ok(vk.gamma_g2.into_group()),
+                mode,
+            )?;
+            let delta_g2 = P::G2Var::new_variable_omit_prime_order_check(
+                ark_relations::ns!(cs, "delta_g2"),
+                || Ok(vk.delta_g2.into_group()),
+                mode,
+            )?;
+            let gamma_abc_g1 = vk
+                .gamma_abc_g1
+                .iter()
+                .map(|g| {
+                    P::G1Var::new_variable_omit_prime_order_check(
+                        ark_relations::ns!(cs, "gamma_abc_g1"),
+                        || Ok(g.into_group()),
+                        mode,
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(VerifyingKeyVar {
                 alpha_g1,
                 beta_g2,
                 gamma_g2,
                 delta_g2,
                 gamma_abc_g1,
-            } = vk.borrow().clone();
-            let alpha_g1 =
-                P::G1Var::new_variable(ark_relations::ns!(cs, "alpha_g1"), || Ok(alpha_g1), mode)?;
-            let beta_g2 =
-                P::G2Var::new_variable(ark_relations::ns!(cs, "beta_g2"), || Ok(beta_g2), mode)?;
-            let gamma_g2 =
-                P::G2Var::new_variable(ark_relations::ns!(cs, "gamma_g2"), || Ok(gamma_g2), mode)?;
-            let delta_g2 =
-                P::G2Var::new_variable(ark_relations::ns!(cs, "delta_g2"), || Ok(delta_g2), mode)?;
+            })
+        })
+    }
+
+    #[tracing::instrument(target = "r1cs", skip(circuit_pvk, x, proof))]
+    fn verify_with_processed_vk(
+        circuit_pvk: &Self::ProcessedVerifyingKeyVar,
+        x: &Self::InputVar,
+        proof: &Self::ProofVar,
+    ) -> Result<Boolean<BasePrimeField<E>>, SynthesisError> {
+        let g_ic = circuit_pvk.accumulate_inputs(x)?;
+        Self::verify_with_prepared_input_point(circuit_pvk, &g_ic, proof)
+    }
+
+    #[tracing::instrument(target = "r1cs", skip(circuit_vk, x, proof))]
+    fn verify(
+        circuit_vk: &Self::VerifyingKeyVar,
+        x: &Self::InputVar,
+        proof: &Self::ProofVar,
+    ) -> Result<Boolean<BasePrimeField<E>>, SynthesisError> {
+        let pvk = circuit_vk.prepare()?;
+        Self::verify_with_processed_vk(&pvk, x, proof)
+    }
+}
+
+/// A Groth16 proof that additionally carries a commitment `D` to part of the
+/// witness, as in the LegoSNARK variant of Groth16.
+#[derive(educe::Educe)]
+#[educe(Clone(bound = "P::G1Var: Clone, P::G2Var: Clone"))]
+pub struct ProofWithCommitmentVar<E: Pairing, P: PairingVar<E>> {
+    /// The underlying Groth16 proof.
+    pub proof: ProofVar<E, P>,
+    /// The commitment `D` in `G1` to the committed witness values.
+    pub d: P::G1Var,
+}
+
+/// Constraints for the verifier of Groth16 proofs with a committed witness.
+pub struct Groth16CommitmentVerifierGadget<E, P, QAP = LibsnarkReduction>
+where
+    E: Pairing,
+    P: PairingVar<E>,
+    QAP: R1CSToQAP,
+{
+    _pairing_engine: PhantomData<E>,
+    _pairing_gadget: PhantomData<P>,
+    _qap: PhantomData<QAP>,
+}
+
+impl<E, P, QAP> Groth16CommitmentVerifierGadget<E, P, QAP>
+where
+    E: Pairing,
+    P: PairingVar<E>,
+    QAP: R1CSToQAP,
+{
+    /// Verifies `proof` against `circuit_pvk` and the public inputs `x`, and
+    /// checks that `proof.d` opens to `opening` under `commitment_key`.
+    ///
+    /// The proof is checked against the Groth16 equation with `D` added to
+    /// the accumulated public input, i.e.
+    /// `e(A, B) = e(alpha, beta) * e(g_ic + D, gamma) * e(C, delta)`, and the
+    /// opening against `D = sum_i opening_i * commitment_key_i`. Returns an
+    /// error if `opening` and `commitment_key` have different lengths.
+    #[tracing::instrument(target = "r1cs", skip(circuit_pvk, x, proof, commitment_key, opening))]
+    pub fn verify_with_commitment(
+        circuit_pvk: &PreparedVerifyingKeyVar<E, P>,
+        x: &BooleanInputVar<E::ScalarField, BasePrimeField<E>>,
+        proof: &ProofWithCommitmentVar<E, P>,
+        commitment_key: &[P::G1Var],
+        opening: &FieldInputVar<E::ScalarField, BasePrimeField<E>>,
+    ) -> Result<Boolean<BasePrimeField<E>>, SynthesisError> {
+        if opening.len() != commitment_key.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        let mut commitment = P::G1Var::zero();
+        for (value, base) in opening.iter().zip(commitment_key) {
+            commitment += base.scalar_mul_le(value.to_bits_le()?.iter())?;
+        }
+        let opening_ok = commitment.is_eq(&proof.d)?;
+
+        let g_ic = Groth16VerifierGadget::<E, P, QAP>::accumulate_inputs(circuit_pvk, x)?;
+        let test = Groth16VerifierGadget::<E, P, QAP>::pairing_check_exp(
+            circuit_pvk,
+            &(g_ic + &proof.d),
+            &proof.proof,
+        )?;
+        let proof_ok = test.is_eq(&circuit_pvk.alpha_g1_beta_g2)?;
+
+        Boolean::kary_and(&[opening_ok, proof_ok])
+    }
+}
+
+impl<E, P, QAP: R1CSToQAP>
+    CircuitSpecificSetupSNARKGadget<E::ScalarField, BasePrimeField<E>, Groth16<E, QAP>>
+    for Groth16VerifierGadget<E, P, QAP>
+where
+    E: Pairing,
+    P: PairingVar<E>,
+    QAP: R1CSToQAP,
+{
+}
+
+impl<E, P> AllocVar<PreparedVerifyingKey<E>, BasePrimeField<E>> for PreparedVerifyingKeyVar<E, P>
+where
+    E: Pairing,
+    P: PairingVar<E>,
+{
+    #[tracing::instrument(target = "r1cs", skip(cs, f))]
+    fn new_variable<T: Borrow<PreparedVerifyingKey<E>>>(
+        cs: impl Into<Namespace<BasePrimeField<E>>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
+
+        f().and_then(|pvk| {
+            let pvk = pvk.borrow();
+            let mut pvk_var = Self::alloc_pairing_terms(cs.clone(), pvk, mode)?;
+            pvk_var.gamma_abc_g1 = Vec::new_variable(
+                ark_relations::ns!(cs, "gamma_abc_g1"),
+                || Ok(pvk.vk.gamma_abc_g1.clone()),
+                mode,
+            )?;
+            Ok(pvk_var)
+        })
+    }
+}
+
+/// A verifying key that is fixed when the circuit is written, e.g. of the
+/// circuit verified at every step of a recursion.
+///
+/// The key is prepared natively once, so the circuit of
+/// [`Self::prepared_var`] contains `e(alpha_g1, beta_g2)`, the prepared
+/// `-gamma_g2` and `-delta_g2`, and `gamma_abc_g1` as constants: preparing
+/// it costs no constraints, and the public inputs are multiplied with
+/// constant bases. This is the same as allocating the
+/// [`PreparedVerifyingKey`] with [`AllocVar::new_constant`], which is the
+/// only sound mode for a key that is trusted rather than checked.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConstVerifyingKeyGadget<E: Pairing> {
+    pvk: PreparedVerifyingKey<E>,
+}
+
+impl<E: Pairing> ConstVerifyingKeyGadget<E> {
+    /// Prepares `vk` natively.
+    pub fn new(vk: &VerifyingKey<E>) -> Self {
+        Self {
+            pvk: crate::prepare_verifying_key(vk),
+        }
+    }
+
+    /// Returns the natively prepared key.
+    pub fn pvk(&self) -> &PreparedVerifyingKey<E> {
+        &self.pvk
+    }
+
+    /// Returns the key as a constant in `cs`, which adds no constraints.
+    pub fn prepared_var<P: PairingVar<E>>(
+        &self,
+        cs: impl Into<Namespace<BasePrimeField<E>>>,
+    ) -> Result<PreparedVerifyingKeyVar<E, P>, SynthesisError> {
+        PreparedVerifyingKeyVar::new_constant(cs, &self.pvk)
+    }
+}
+
+impl<E: Pairing, P: PairingVar<E>> PreparedVerifyingKeyVar<E, P> {
+    /// Prepares `vk` for use in proof verification by allocating the
+    /// pairing-related elements that [`VerifyingKeyVar::prepare`] would
+    /// compute from those of `pvk`, the native preparation of the same key.
+    ///
+    /// `e(alpha_g1, beta_g2)` and the prepared `-gamma_g2` and `-delta_g2`
+    /// are allocated in `mode` as computed natively, which saves the pairing,
+    /// the negations and the line coefficient computations in the circuit,
+    /// while `gamma_abc_g1` is taken from `vk`. Unlike
+    /// [`VerifyingKeyVar::prepare`], nothing ties the allocated elements to
+    /// `vk`: they are trusted to match it, so correctness rests on how they
+    /// are allocated. As constants or public inputs they are fixed by the
+    /// verifier, but as witnesses a prover that is free to choose them can
+    /// forge proofs. The [`AllocVar`] implementation for
+    /// `PreparedVerifyingKeyVar` trusts `pvk` in the same way, but also
+    /// allocates `gamma_abc_g1` from it.
+    ///
+    /// Returns [`SynthesisError::MalformedVerifyingKey`] if `vk` and `pvk`
+    /// have different numbers of `gamma_abc_g1` elements.
+    #[tracing::instrument(target = "r1cs", skip(cs, vk, pvk))]
+    pub fn from_native_prepared(
+        cs: impl Into<Namespace<BasePrimeField<E>>>,
+        vk: &VerifyingKeyVar<E, P>,
+        pvk: &PreparedVerifyingKey<E>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        if vk.gamma_abc_g1.len() != pvk.vk.gamma_abc_g1.len() {
+            return Err(SynthesisError::MalformedVerifyingKey);
+        }
+        let ns = cs.into();
+        let mut pvk_var = Self::alloc_pairing_terms(ns.cs(), pvk, mode)?;
+        pvk_var.gamma_abc_g1 = vk.gamma_abc_g1.clone();
+        Ok(pvk_var)
+    }
+
+    /// Allocates the elements of `pvk` that enter the pairings, leaving
+    /// `gamma_abc_g1` empty.
+    fn alloc_pairing_terms(
+        cs: ConstraintSystemRef<BasePrimeField<E>>,
+        pvk: &PreparedVerifyingKey<E>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let alpha_g1_beta_g2 = P::GTVar::new_variable(
+            ark_relations::ns!(cs, "alpha_g1_beta_g2"),
+            || Ok(pvk.alpha_g1_beta_g2.clone()),
+            mode,
+        )?;
+
+        let gamma_g2_neg_pc = P::G2PreparedVar::new_variable(
+            ark_relations::ns!(cs, "gamma_g2_neg_pc"),
+            || Ok(pvk.gamma_g2_neg_pc.clone()),
+            mode,
+        )?;
+
+        let delta_g2_neg_pc = P::G2PreparedVar::new_variable(
+            ark_relations::ns!(cs, "delta_g2_neg_pc"),
+            || Ok(pvk.delta_g2_neg_pc.clone()),
+            mode,
+        )?;
+
+        Ok(Self {
+            alpha_g1_beta_g2,
+            gamma_g2_neg_pc,
+            delta_g2_neg_pc,
+            gamma_abc_g1: Vec::new(),
+            gamma_abc_g1_tables: None,
+        })
+    }
+}
+
+impl<E, P> AllocVar<VerifyingKey<E>, BasePrimeField<E>> for VerifyingKeyVar<E, P>
+where
+    E: Pairing,
+    P: PairingVar<E>,
+{
+    #[tracing::instrument(target = "r1cs", skip(cs, f))]
+    fn new_variable<T: Borrow<VerifyingKey<E>>>(
+        cs: impl Into<Namespace<BasePrimeField<E>>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
+
+        f().and_then(|vk| {
+            let VerifyingKey {
+                alpha_g1,
+                beta_g2,
+                gamma_g2,
+                delta_g2,
+                gamma_abc_g1,
+            } = vk.borrow().clone();
+            let alpha_g1 =
+                P::G1Var::new_variable(ark_relations::ns!(cs, "alpha_g1"), || Ok(alpha_g1), mode)?;
+            let beta_g2 =
+                P::G2Var::new_variable(ark_relations::ns!(cs, "beta_g2"), || Ok(beta_g2), mode)?;
+            let gamma_g2 =
+                P::G2Var::new_variable(ark_relations::ns!(cs, "gamma_g2"), || Ok(gamma_g2), mode)?;
+            let delta_g2 =
+                P::G2Var::new_variable(ark_relations::ns!(cs, "delta_g2"), || Ok(delta_g2), mode)?;
+
+            let gamma_abc_g1 = Vec::new_variable(cs.clone(), || Ok(gamma_abc_g1), mode)?;
+            Ok(Self {
+                alpha_g1,
+                beta_g2,
+                gamma_g2,
+                delta_g2,
+                gamma_abc_g1,
+            })
+        })
+    }
+}
+
+impl<E, P> AllocVar<Proof<E>, BasePrimeField<E>> for ProofVar<E, P>
+where
+    E: Pairing,
+    P: PairingVar<E>,
+{
+    #[tracing::instrument(target = "r1cs", skip(cs, f))]
+    fn new_variable<T: Borrow<Proof<E>>>(
+        cs: impl Into<Namespace<BasePrimeField<E>>>,
+        f: impl FnOnce() -> Result<T, SynthesisError>,
+        mode: AllocationMode,
+    ) -> Result<Self, SynthesisError> {
+        let ns = cs.into();
+        let cs = ns.cs();
+
+        f().and_then(|proof| {
+            let Proof { a, b, c } = proof.borrow().clone();
+            let a = P::G1Var::new_variable(ark_relations::ns!(cs, "a"), || Ok(a), mode)?;
+            let b = P::G2Var::new_variable(ark_relations::ns!(cs, "b"), || Ok(b), mode)?;
+            let c = P::G1Var::new_variable(ark_relations::ns!(cs, "c"), || Ok(c), mode)?;
+            Ok(Self { a, b, c })
+        })
+    }
+}
+
+impl<E, P> ToBytesGadget<BasePrimeField<E>> for VerifyingKeyVar<E, P>
+where
+    E: Pairing,
+    P: PairingVar<E>,
+{
+    #[inline]
+    #[tracing::instrument(target = "r1cs", skip(self))]
+    fn to_bytes_le(&self) -> Result<Vec<UInt8<BasePrimeField<E>>>, SynthesisError> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.alpha_g1.to_bytes_le()?);
+        bytes.extend_from_slice(&self.beta_g2.to_bytes_le()?);
+        bytes.extend_from_slice(&self.gamma_g2.to_bytes_le()?);
+        bytes.extend_from_slice(&self.delta_g2.to_bytes_le()?);
+        for g in &self.gamma_abc_g1 {
+            bytes.extend_from_slice(&g.to_bytes_le()?);
+        }
+        Ok(bytes)
+    }
+}
+
+impl<E: Pairing, P: PairingVar<E>> ProofVar<E, P> {
+    /// Rerandomizes `self` with the factors whose little-endian bits are `r1`
+    /// and `r2`, like [`Proof::rerandomize`]:
+    ///
+    /// `A' = (1/r1) A`, `B' = r1 B + r1 r2 delta_g2`, and `C' = C + r2 A`.
+    ///
+    /// `A'` is allocated as a witness and constrained by `r1 A' = A`, which
+    /// cannot be satisfied for `r1 = 0` unless `A` is the identity. The
+    /// result verifies under `vk` for the same public inputs as `self`.
+    #[tracing::instrument(target = "r1cs", skip(self, vk, r1, r2))]
+    pub fn rerandomize(
+        &self,
+        vk: &VerifyingKeyVar<E, P>,
+        r1: &[Boolean<BasePrimeField<E>>],
+        r2: &[Boolean<BasePrimeField<E>>],
+    ) -> Result<Self, SynthesisError> {
+        let cs = r1
+            .iter()
+            .chain(r2)
+            .fold(self.cs().or(vk.delta_g2.cs()), |cs, bit| cs.or(bit.cs()));
+        let mode = if cs.is_none() {
+            AllocationMode::Constant
+        } else {
+            AllocationMode::Witness
+        };
+
+        let a = P::G1Var::new_variable(
+            ark_relations::ns!(cs, "rerandomized a"),
+            || {
+                let r1 = scalar_from_bits::<E::ScalarField, _>(r1)?;
+                let r1_inverse = r1.inverse().ok_or(SynthesisError::Unsatisfiable)?;
+                Ok(self.a.value()? * r1_inverse)
+            },
+            mode,
+        )?;
+        a.scalar_mul_le(r1.iter())?.enforce_equal(&self.a)?;
+
+        let b = (vk.delta_g2.scalar_mul_le(r2.iter())? + &self.b).scalar_mul_le(r1.iter())?;
+        let c = self.a.scalar_mul_le(r2.iter())? + &self.c;
+
+        Ok(Self { a, b, c })
+    }
+}
+
+/// Returns the scalar whose little-endian bits are the values of `bits`,
+/// reduced modulo the characteristic of `F`.
+fn scalar_from_bits<F: PrimeField, CF: PrimeField>(
+    bits: &[Boolean<CF>],
+) -> Result<F, SynthesisError> {
+    bits.iter().rev().try_fold(F::zero(), |acc, bit| {
+        let acc = acc.double();
+        Ok(if bit.value()? { acc + F::one() } else { acc })
+    })
+}
+
+impl<E, P> R1CSVar<BasePrimeField<E>> for ProofVar<E, P>
+where
+    E: Pairing,
+    P: PairingVar<E>,
+{
+    type Value = Proof<E>;
+
+    fn cs(&self) -> ConstraintSystemRef<BasePrimeField<E>> {
+        self.a.cs().or(self.b.cs()).or(self.c.cs())
+    }
+
+    fn value(&self) -> Result<Self::Value, SynthesisError> {
+        Ok(Proof {
+            a: self.a.value()?.into_affine(),
+            b: self.b.value()?.into_affine(),
+            c: self.c.value()?.into_affine(),
+        })
+    }
+}
+
+impl<E, P> EqGadget<BasePrimeField<E>> for ProofVar<E, P>
+where
+    E: Pairing,
+    P: PairingVar<E>,
+{
+    #[tracing::instrument(target = "r1cs", skip(self, other))]
+    fn is_eq(&self, other: &Self) -> Result<Boolean<BasePrimeField<E>>, SynthesisError> {
+        let a = self.a.is_eq(&other.a)?;
+        let b = self.b.is_eq(&other.b)?;
+        let c = self.c.is_eq(&other.c)?;
+        Boolean::kary_and(&[a, b, c])
+    }
+
+    #[tracing::instrument(target = "r1cs", skip(self, other))]
+    fn conditional_enforce_equal(
+        &self,
+        other: &Self,
+        should_enforce: &Boolean<BasePrimeField<E>>,
+    ) -> Result<(), SynthesisError> {
+        self.a.conditional_enforce_equal(&other.a, should_enforce)?;
+        self.b.conditional_enforce_equal(&other.b, should_enforce)?;
+        self.c.conditional_enforce_equal(&other.c, should_enforce)
+    }
+}
+
+impl<E, P> CondSelectGadget<BasePrimeField<E>> for ProofVar<E, P>
+where
+    E: Pairing,
+    P: PairingVar<E>,
+{
+    #[tracing::instrument(target = "r1cs", skip(cond, true_value, false_value))]
+    fn conditionally_select(
+        cond: &Boolean<BasePrimeField<E>>,
+        true_value: &Self,
+        false_value: &Self,
+    ) -> Result<Self, SynthesisError> {
+        Ok(Self {
+            a: P::G1Var::conditionally_select(cond, &true_value.a, &false_value.a)?,
+            b: P::G2Var::conditionally_select(cond, &true_value.b, &false_value.b)?,
+            c: P::G1Var::conditionally_select(cond, &true_value.c, &false_value.c)?,
+        })
+    }
+}
+
+impl<E, P> ToBytesGadget<BasePrimeField<E>> for ProofVar<E, P>
+where
+    E: Pairing,
+    P: PairingVar<E>,
+{
+    /// Emits `a`, `b` and `c` in order, each encoded by its curve gadget as
+    /// the little-endian affine coordinates followed by an infinity flag byte.
+    #[inline]
+    #[tracing::instrument(target = "r1cs", skip(self))]
+    fn to_bytes_le(&self) -> Result<Vec<UInt8<BasePrimeField<E>>>, SynthesisError> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.a.to_bytes_le()?);
+        bytes.extend_from_slice(&self.b.to_bytes_le()?);
+        bytes.extend_from_slice(&self.c.to_bytes_le()?);
+        Ok(bytes)
+    }
+}
+
+/// Returns the elements of `BasePrimeField<E>` that [`ProofVar`]'s
+/// [`ToConstraintFieldGadget`] implementation outputs for `proof`.
+///
+/// For each of `a`, `b` and `c` in order, this emits the affine `x` and `y`
+/// coordinates followed by the infinity flag as `0` or `1`. The coordinates
+/// of `b` lie in an extension of `BasePrimeField<E>` and are each written as
+/// their coefficients over it, lowest degree first, i.e. `c0, c1` for a
+/// quadratic extension. The point at infinity has no affine coordinates, and
+/// native points and curve gadgets disagree on the placeholders they use for
+/// it, so its coordinates are written as zeros.
+///
+/// This lets a proof be absorbed into a sponge over the field of an outer
+/// circuit that verifies it, natively and in-circuit alike.
+pub fn proof_to_constraint_field<E>(proof: &Proof<E>) -> Vec<BasePrimeField<E>>
+where
+    E: Pairing,
+    E::G1Affine: ToConstraintField<BasePrimeField<E>>,
+    E::G2Affine: ToConstraintField<BasePrimeField<E>>,
+{
+    // Affine points in short Weierstrass form always have a representation.
+    let mut elements = clear_identity(proof.a.to_field_elements().unwrap());
+    elements.extend(clear_identity(proof.b.to_field_elements().unwrap()));
+    elements.extend(clear_identity(proof.c.to_field_elements().unwrap()));
+    elements
+}
+
+/// Zeroes the coordinates of a point given as `coordinates || infinity` if
+/// its infinity flag is set.
+fn clear_identity<F: Field>(mut elements: Vec<F>) -> Vec<F> {
+    if let Some((infinity, coordinates)) = elements.split_last_mut() {
+        if infinity.is_one() {
+            coordinates.iter_mut().for_each(|c| *c = F::zero());
+        }
+    }
+    elements
+}
+
+/// The in-circuit counterpart of [`clear_identity`].
+fn clear_identity_var<F: PrimeField>(mut elements: Vec<FpVar<F>>) -> Vec<FpVar<F>> {
+    if let Some((infinity, coordinates)) = elements.split_last_mut() {
+        let finite = FpVar::one() - &*infinity;
+        coordinates.iter_mut().for_each(|c| *c = &*c * &finite);
+    }
+    elements
+}
+
+impl<E, P> ToConstraintFieldGadget<BasePrimeField<E>> for ProofVar<E, P>
+where
+    E: Pairing,
+    P: PairingVar<E>,
+    P::G1Var: ToConstraintFieldGadget<BasePrimeField<E>>,
+    P::G2Var: ToConstraintFieldGadget<BasePrimeField<E>>,
+{
+    /// Emits `a`, `b` and `c` in order, in the layout described at
+    /// [`proof_to_constraint_field`].
+    #[tracing::instrument(target = "r1cs", skip(self))]
+    fn to_constraint_field(&self) -> Result<Vec<FpVar<BasePrimeField<E>>>, SynthesisError> {
+        let mut elements = clear_identity_var(self.a.to_constraint_field()?);
+        elements.extend(clear_identity_var(self.b.to_constraint_field()?));
+        elements.extend(clear_identity_var(self.c.to_constraint_field()?));
+        Ok(elements)
+    }
+}
+
+impl<E, P> ToBytesGadget<BasePrimeField<E>> for PreparedVerifyingKeyVar<E, P>
+where
+    E: Pairing,
+    P: PairingVar<E>,
+{
+    #[inline]
+    #[tracing::instrument(target = "r1cs", skip(self))]
+    fn to_bytes_le(&self) -> Result<Vec<UInt8<BasePrimeField<E>>>, SynthesisError> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.alpha_g1_beta_g2.to_bytes_le()?);
+        bytes.extend_from_slice(&self.gamma_g2_neg_pc.to_bytes_le()?);
+        bytes.extend_from_slice(&self.delta_g2_neg_pc.to_bytes_le()?);
+        for g in &self.gamma_abc_g1 {
+            bytes.extend_from_slice(&g.to_bytes_le()?);
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        constraints::{
+            batch_sponge_config, proof_to_constraint_field, BytesInputVar, ConstVerifyingKeyGadget,
+            FieldInputVar, Groth16CommitmentVerifierGadget, Groth16VerifierGadget,
+            PreparedVerifyingKeyVar, ProofVar, ProofWithCommitmentVar, VerifyingKeyVar,
+        },
+        pack_bytes_to_inputs, Groth16, Proof,
+    };
+    use ark_crypto_primitives::{
+        snark::{constraints::SNARKGadget, SNARK},
+        sponge::{
+            constraints::CryptographicSpongeVar,
+            poseidon::{constraints::PoseidonSpongeVar, PoseidonSponge},
+            CryptographicSponge,
+        },
+    };
+    use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+    use ark_ff::{BigInteger, Field, PrimeField, UniformRand};
+    use ark_mnt4_298::{
+        constraints::{G1Var as MNT4G1Var, G2Var as MNT4G2Var, PairingVar as MNT4PairingVar},
+        Fq as MNT6Fr, Fq2 as MNT4Fq2, Fr as MNT4Fr, G2Affine as MNT4G2Affine, MNT4_298 as MNT4,
+    };
+    use ark_r1cs_std::{
+        alloc::{AllocVar, AllocationMode},
+        boolean::Boolean,
+        convert::{ToBytesGadget, ToConstraintFieldGadget},
+        eq::EqGadget,
+        groups::CurveVar,
+        pairing::PairingVar,
+        select::CondSelectGadget,
+        uint8::UInt8,
+        R1CSVar,
+    };
+    use ark_relations::{
+        gr1cs::{ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError},
+        lc, ns,
+    };
+    use ark_std::{
+        ops::MulAssign,
+        rand::{RngCore, SeedableRng},
+        test_rng,
+    };
+
+    #[derive(Copy, Clone)]
+    struct Circuit<F: Field> {
+        a: Option<F>,
+        b: Option<F>,
+        num_constraints: usize,
+        num_variables: usize,
+    }
+
+    impl<ConstraintF: Field> ConstraintSynthesizer<ConstraintF> for Circuit<ConstraintF> {
+        fn generate_constraints(
+            self,
+            cs: ConstraintSystemRef<ConstraintF>,
+        ) -> Result<(), SynthesisError> {
+            let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+            let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+            let c = cs.new_input_variable(|| {
+                let mut a = self.a.ok_or(SynthesisError::AssignmentMissing)?;
+                let b = self.b.ok_or(SynthesisError::AssignmentMissing)?;
+
+                a.mul_assign(&b);
+                Ok(a)
+            })?;
+
+            for _ in 0..(self.num_variables - 3) {
+                let _ =
+                    cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+            }
+
+            for _ in 0..self.num_constraints {
+                cs.enforce_r1cs_constraint(|| lc!() + a, || lc!() + b, || lc!() + c)
+                    .unwrap();
+            }
+            Ok(())
+        }
+    }
+
+    /// Proves knowledge of `a` and `b` with `a * b = c`, all as witnesses.
+    #[derive(Copy, Clone)]
+    struct WitnessOnlyCircuit<F: Field> {
+        a: Option<F>,
+        b: Option<F>,
+    }
+
+    impl<ConstraintF: Field> ConstraintSynthesizer<ConstraintF> for WitnessOnlyCircuit<ConstraintF> {
+        fn generate_constraints(
+            self,
+            cs: ConstraintSystemRef<ConstraintF>,
+        ) -> Result<(), SynthesisError> {
+            let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+            let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+            let c = cs.new_witness_variable(|| {
+                let mut a = self.a.ok_or(SynthesisError::AssignmentMissing)?;
+                let b = self.b.ok_or(SynthesisError::AssignmentMissing)?;
+
+                a.mul_assign(&b);
+                Ok(a)
+            })?;
+            cs.enforce_r1cs_constraint(|| lc!() + a, || lc!() + b, || lc!() + c)?;
+            Ok(())
+        }
+    }
+
+    type TestSNARK = Groth16<MNT4>;
+    type TestSNARKGadget = Groth16VerifierGadget<MNT4, MNT4PairingVar>;
+
+    /// Proves `circ` with `S` and verifies the proof for the input `c` with
+    /// the gadget `G`, which only compiles if `G` is the gadget of `S`.
+    fn prove_and_verify_with_gadget<S, G>(circ: Circuit<MNT4Fr>, c: MNT4Fr) -> bool
+    where
+        S: SNARK<MNT4Fr>,
+        G: SNARKGadget<MNT4Fr, MNT6Fr, S>,
+    {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let (pk, vk) = S::circuit_specific_setup(circ, &mut rng).unwrap();
+        let proof = S::prove(&pk, circ, &mut rng).unwrap();
+
+        let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+        let vk = G::VerifyingKeyVar::new_witness(ns!(cs, "alloc_vk"), || Ok(vk)).unwrap();
+        let input = G::InputVar::new_input(ns!(cs, "new_input"), || Ok(vec![c])).unwrap();
+        let proof = G::ProofVar::new_witness(ns!(cs, "alloc_proof"), || Ok(proof)).unwrap();
+        G::verify(&vk, &input, &proof)
+            .unwrap()
+            .enforce_equal(&Boolean::constant(true))
+            .unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn groth16_snark_test() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let a = MNT4Fr::rand(&mut rng);
+        let b = MNT4Fr::rand(&mut rng);
+        let mut c = a;
+        c.mul_assign(&b);
+
+        let circ = Circuit {
+            a: Some(a.clone()),
+            b: Some(b.clone()),
+            num_constraints: 100,
+            num_variables: 25,
+        };
+
+        let (pk, vk) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+
+        let proof = TestSNARK::prove(&pk, circ.clone(), &mut rng).unwrap();
+
+        assert!(
+            TestSNARK::verify(&vk, &vec![c], &proof).unwrap(),
+            "The native verification check fails."
+        );
+
+        let cs_sys = ConstraintSystem::<MNT6Fr>::new();
+        let cs = ConstraintSystemRef::new(cs_sys);
+
+        let input_gadget = <TestSNARKGadget as SNARKGadget<
+            <MNT4 as Pairing>::ScalarField,
+            <MNT4 as Pairing>::BaseField,
+            TestSNARK,
+        >>::InputVar::new_input(ns!(cs, "new_input"), || Ok(vec![c]))
+        .unwrap();
+        let proof_gadget = <TestSNARKGadget as SNARKGadget<
+            <MNT4 as Pairing>::ScalarField,
+            <MNT4 as Pairing>::BaseField,
+            TestSNARK,
+        >>::ProofVar::new_witness(ns!(cs, "alloc_proof"), || Ok(proof))
+        .unwrap();
+        let vk_gadget = <TestSNARKGadget as SNARKGadget<
+            <MNT4 as Pairing>::ScalarField,
+            <MNT4 as Pairing>::BaseField,
+            TestSNARK,
+        >>::VerifyingKeyVar::new_constant(ns!(cs, "alloc_vk"), vk.clone())
+        .unwrap();
+        <TestSNARKGadget as SNARKGadget<
+            <MNT4 as Pairing>::ScalarField,
+            <MNT4 as Pairing>::BaseField,
+            TestSNARK,
+        >>::verify(&vk_gadget, &input_gadget, &proof_gadget)
+        .unwrap()
+        .enforce_equal(&Boolean::constant(true))
+        .unwrap();
+
+        assert!(
+            cs.is_satisfied().unwrap(),
+            "Constraints not satisfied: {}",
+            cs.which_is_unsatisfied().unwrap().unwrap_or_default()
+        );
+
+        let pvk = TestSNARK::process_vk(&vk).unwrap();
+        let pvk_gadget = <TestSNARKGadget as SNARKGadget<
+            <MNT4 as Pairing>::ScalarField,
+            <MNT4 as Pairing>::BaseField,
+            TestSNARK,
+        >>::ProcessedVerifyingKeyVar::new_constant(
+            ns!(cs, "alloc_pvk"), pvk.clone()
+        )
+        .unwrap();
+        TestSNARKGadget::verify_with_processed_vk(&pvk_gadget, &input_gadget, &proof_gadget)
+            .unwrap()
+            .enforce_equal(&Boolean::constant(true))
+            .unwrap();
+
+        assert!(
+            cs.is_satisfied().unwrap(),
+            "Constraints not satisfied: {}",
+            cs.which_is_unsatisfied().unwrap().unwrap_or_default()
+        );
+    }
+
+    #[test]
+    fn groth16_verify_without_inputs_test() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let circ = WitnessOnlyCircuit {
+            a: Some(MNT4Fr::rand(&mut rng)),
+            b: Some(MNT4Fr::rand(&mut rng)),
+        };
+        let (pk, vk) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+        let proof = TestSNARK::prove(&pk, circ, &mut rng).unwrap();
+        assert_eq!(vk.gamma_abc_g1.len(), 1);
+        assert!(TestSNARK::verify(&vk, &[], &proof).unwrap());
+
+        let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+        let input_gadget = <TestSNARKGadget as SNARKGadget<
+            <MNT4 as Pairing>::ScalarField,
+            <MNT4 as Pairing>::BaseField,
+            TestSNARK,
+        >>::InputVar::new_input(ns!(cs, "new_input"), || Ok(vec![]))
+        .unwrap();
+        let proof_gadget = <TestSNARKGadget as SNARKGadget<
+            <MNT4 as Pairing>::ScalarField,
+            <MNT4 as Pairing>::BaseField,
+            TestSNARK,
+        >>::ProofVar::new_witness(ns!(cs, "alloc_proof"), || Ok(&proof))
+        .unwrap();
+        let vk_gadget = <TestSNARKGadget as SNARKGadget<
+            <MNT4 as Pairing>::ScalarField,
+            <MNT4 as Pairing>::BaseField,
+            TestSNARK,
+        >>::VerifyingKeyVar::new_constant(ns!(cs, "alloc_vk"), &vk)
+        .unwrap();
+        <TestSNARKGadget as SNARKGadget<
+            <MNT4 as Pairing>::ScalarField,
+            <MNT4 as Pairing>::BaseField,
+            TestSNARK,
+        >>::verify(&vk_gadget, &input_gadget, &proof_gadget)
+        .unwrap()
+        .enforce_equal(&Boolean::constant(true))
+        .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        // A proof for a different statement must not verify.
+        let mut corrupted = proof.clone();
+        corrupted.c = proof.a;
+        let corrupted_gadget = <TestSNARKGadget as SNARKGadget<
+            <MNT4 as Pairing>::ScalarField,
+            <MNT4 as Pairing>::BaseField,
+            TestSNARK,
+        >>::ProofVar::new_witness(
+            ns!(cs, "alloc_corrupted_proof"), || Ok(corrupted)
+        )
+        .unwrap();
+        assert!(!<TestSNARKGadget as SNARKGadget<
+            <MNT4 as Pairing>::ScalarField,
+            <MNT4 as Pairing>::BaseField,
+            TestSNARK,
+        >>::verify(&vk_gadget, &input_gadget, &corrupted_gadget)
+        .unwrap()
+        .value()
+        .unwrap());
+    }
+
+    #[test]
+    fn groth16_verify_or_test() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let a = MNT4Fr::rand(&mut rng);
+        let b = MNT4Fr::rand(&mut rng);
+        let circ = Circuit {
+            a: Some(a),
+            b: Some(b),
+            num_constraints: 100,
+            num_variables: 25,
+        };
+        let (pk, vk) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+        let proof = TestSNARK::prove(&pk, circ, &mut rng).unwrap();
+        let identity = Proof::<MNT4> {
+            a: AffineRepr::zero(),
+            b: AffineRepr::zero(),
+            c: AffineRepr::zero(),
+        };
+
+        for (proof, c, fallback, expected) in [
+            (&proof, a * b, false, true),
+            (&proof, a * b, true, true),
+            (&proof, a, false, false),
+            (&proof, a, true, true),
+            (&identity, a, true, true),
+        ] {
+            let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+            let input_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::InputVar::new_input(ns!(cs, "new_input"), || Ok(vec![c]))
+                .unwrap();
+            let proof_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::ProofVar::new_witness(ns!(cs, "alloc_proof"), || Ok(proof))
+                .unwrap();
+            let vk_gadget = <TestSNARKGadget as SNARKGadget<
+                <MNT4 as Pairing>::ScalarField,
+                <MNT4 as Pairing>::BaseField,
+                TestSNARK,
+            >>::VerifyingKeyVar::new_constant(ns!(cs, "alloc_vk"), &vk)
+            .unwrap();
+            let fallback_gadget =
+                Boolean::new_witness(ns!(cs, "fallback"), || Ok(fallback)).unwrap();
+
+            let pvk_gadget = vk_gadget.prepare().unwrap();
+            let result = TestSNARKGadget::verify_or(
+                &pvk_gadget,
+                &input_gadget,
+                &proof_gadget,
+                &fallback_gadget,
+            )
+            .unwrap();
+            assert_eq!(result.value().unwrap(), expected);
+            assert!(cs.is_satisfied().unwrap());
+        }
+    }
+
+    #[test]
+    fn groth16_accumulate_inputs_test() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let a = MNT4Fr::rand(&mut rng);
+        let b = MNT4Fr::rand(&mut rng);
+        let circ = Circuit {
+            a: Some(a),
+            b: Some(b),
+            num_constraints: 100,
+            num_variables: 25,
+        };
+        let (_, vk) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+        let pvk = crate::prepare_verifying_key(&vk);
+
+        let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+        let vk_gadget = <TestSNARKGadget as SNARKGadget<
+            <MNT4 as Pairing>::ScalarField,
+            <MNT4 as Pairing>::BaseField,
+            TestSNARK,
+        >>::VerifyingKeyVar::new_constant(ns!(cs, "alloc_vk"), &vk)
+        .unwrap();
+        let pvk_gadget = vk_gadget.prepare().unwrap();
+
+        let input_gadget =
+            <TestSNARKGadget as SNARKGadget<
+                <MNT4 as Pairing>::ScalarField,
+                <MNT4 as Pairing>::BaseField,
+                TestSNARK,
+            >>::InputVar::new_input(ns!(cs, "new_input"), || Ok(vec![a * b]))
+            .unwrap();
+        let g_ic = pvk_gadget.accumulate_inputs(&input_gadget).unwrap();
+        assert_eq!(
+            g_ic.value().unwrap().into_affine(),
+            Groth16::<MNT4>::prepare_inputs(&pvk, &[a * b])
+                .unwrap()
+                .into_affine()
+        );
+        assert!(cs.is_satisfied().unwrap());
+
+        let too_long = <TestSNARKGadget as SNARKGadget<
+            <MNT4 as Pairing>::ScalarField,
+            <MNT4 as Pairing>::BaseField,
+            TestSNARK,
+        >>::InputVar::new_input(ns!(cs, "too_long"), || Ok(vec![a, b]))
+        .unwrap();
+        assert!(matches!(
+            pvk_gadget.accumulate_inputs(&too_long),
+            Err(SynthesisError::Unsatisfiable)
+        ));
+    }
+
+    #[test]
+    fn groth16_verify_with_prepared_input_point_test() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let a = MNT4Fr::rand(&mut rng);
+        let b = MNT4Fr::rand(&mut rng);
+        let circ = Circuit {
+            a: Some(a),
+            b: Some(b),
+            num_constraints: 100,
+            num_variables: 25,
+        };
+        let (pk, vk) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+        let proofs = (0..2)
+            .map(|_| TestSNARK::prove(&pk, circ, &mut rng).unwrap())
+            .collect::<Vec<_>>();
+        let other = TestSNARK::prove(
+            &pk,
+            Circuit {
+                a: Some(a),
+                b: Some(a),
+                ..circ
+            },
+            &mut rng,
+        )
+        .unwrap();
+
+        let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+        let input_gadget =
+            <TestSNARKGadget as SNARKGadget<
+                <MNT4 as Pairing>::ScalarField,
+                <MNT4 as Pairing>::BaseField,
+                TestSNARK,
+            >>::InputVar::new_input(ns!(cs, "new_input"), || Ok(vec![a * b]))
+            .unwrap();
+        let vk_gadget = <TestSNARKGadget as SNARKGadget<
+            <MNT4 as Pairing>::ScalarField,
+            <MNT4 as Pairing>::BaseField,
+            TestSNARK,
+        >>::VerifyingKeyVar::new_constant(ns!(cs, "alloc_vk"), &vk)
+        .unwrap();
+        let pvk_gadget = vk_gadget.prepare().unwrap();
+        let g_ic = TestSNARKGadget::accumulate_inputs(&pvk_gadget, &input_gadget).unwrap();
+
+        for (proof, expected) in [(&proofs[0], true), (&proofs[1], true), (&other, false)] {
+            let proof_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::ProofVar::new_witness(ns!(cs, "alloc_proof"), || Ok(proof))
+                .unwrap();
+            let result = TestSNARKGadget::verify_with_prepared_input_point(
+                &pvk_gadget,
+                &g_ic,
+                &proof_gadget,
+            )
+            .unwrap();
+            assert_eq!(result.value().unwrap(), expected);
+        }
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn groth16_verify_affine_test() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let a = MNT4Fr::rand(&mut rng);
+        let b = MNT4Fr::rand(&mut rng);
+        let circ = Circuit {
+            a: Some(a),
+            b: Some(b),
+            num_constraints: 100,
+            num_variables: 25,
+        };
+        let (pk, vk) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+        let proof = TestSNARK::prove(&pk, circ, &mut rng).unwrap();
+
+        let verify = |c: MNT4Fr, affine: bool| {
+            let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+            let input_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::InputVar::new_input(ns!(cs, "new_input"), || Ok(vec![c]))
+                .unwrap();
+            let proof_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::ProofVar::new_witness(ns!(cs, "alloc_proof"), || Ok(&proof))
+                .unwrap();
+            let vk_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::VerifyingKeyVar::new_witness(ns!(cs, "alloc_vk"), || Ok(&vk))
+                .unwrap();
+            let pvk_gadget = vk_gadget.prepare().unwrap();
+
+            let constraints_before = cs.num_constraints();
+            let result = if affine {
+                TestSNARKGadget::verify_with_processed_vk_affine(
+                    &pvk_gadget,
+                    &input_gadget,
+                    &proof_gadget,
+                )
+            } else {
+                TestSNARKGadget::verify_with_processed_vk(&pvk_gadget, &input_gadget, &proof_gadget)
+            }
+            .unwrap();
+            assert!(cs.is_satisfied().unwrap());
+            (
+                result.value().unwrap(),
+                cs.num_constraints() - constraints_before,
+            )
+        };
+
+        let (valid, projective) = verify(a * b, false);
+        assert!(valid);
+        let (valid, affine) = verify(a * b, true);
+        assert!(valid);
+        assert!(!verify(a, true).0);
+        assert!(
+            affine < projective,
+            "affine accumulation used {} constraints, projective {}",
+            affine,
+            projective
+        );
+    }
+
+    #[test]
+    fn groth16_verify_affine_rejects_colliding_sums() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let circ = Circuit {
+            a: Some(MNT4Fr::rand(&mut rng)),
+            b: Some(MNT4Fr::rand(&mut rng)),
+            num_constraints: 10,
+            num_variables: 5,
+        };
+        let (_, mut vk) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+        let proof = Proof {
+            a: vk.alpha_g1,
+            b: vk.beta_g2,
+            c: vk.alpha_g1,
+        };
+
+        // With `gamma_abc_g1[0] = 3 * M` for `M = gamma_abc_g1[1]`, the input
+        // `5` sets bit 0, which drives the accumulator to `4 * M`, exactly the
+        // multiple that bit 2 adds. The incomplete addition of equal points
+        // would leave its slope, and thus the sum, up to the prover.
+        let m = vk.gamma_abc_g1[1];
+        vk.gamma_abc_g1[0] = (m.into_group() * MNT4Fr::from(3u64)).into_affine();
+
+        let verify = |c: MNT4Fr, mode: AllocationMode| {
+            let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+            let input_gadget = <TestSNARKGadget as SNARKGadget<
+                <MNT4 as Pairing>::ScalarField,
+                <MNT4 as Pairing>::BaseField,
+                TestSNARK,
+            >>::InputVar::new_variable(
+                ns!(cs, "new_input"), || Ok(vec![c]), mode
+            )
+            .unwrap();
+            let proof_gadget = <TestSNARKGadget as SNARKGadget<
+                <MNT4 as Pairing>::ScalarField,
+                <MNT4 as Pairing>::BaseField,
+                TestSNARK,
+            >>::ProofVar::new_variable(
+                ns!(cs, "alloc_proof"), || Ok(&proof), mode
+            )
+            .unwrap();
+            let vk_gadget = <TestSNARKGadget as SNARKGadget<
+                <MNT4 as Pairing>::ScalarField,
+                <MNT4 as Pairing>::BaseField,
+                TestSNARK,
+            >>::VerifyingKeyVar::new_variable(
+                ns!(cs, "alloc_vk"), || Ok(&vk), mode
+            )
+            .unwrap();
+            let pvk_gadget = vk_gadget.prepare().unwrap();
+            TestSNARKGadget::verify_with_processed_vk_affine(
+                &pvk_gadget,
+                &input_gadget,
+                &proof_gadget,
+            )
+            .map(|_| cs.is_satisfied().unwrap())
+        };
+
+        // The collision makes the circuit unsatisfiable, if witness generation
+        // gets that far, and is reported as an error when everything is
+        // constant.
+        assert!(!verify(MNT4Fr::from(5u64), AllocationMode::Witness).unwrap_or(false));
+        assert!(verify(MNT4Fr::from(5u64), AllocationMode::Constant).is_err());
+        // Inputs whose partial sums avoid the multiples are unaffected.
+        assert!(verify(MNT4Fr::from(2u64), AllocationMode::Witness).unwrap());
+    }
+
+    #[test]
+    fn groth16_from_native_prepared_test() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let a = MNT4Fr::rand(&mut rng);
+        let b = MNT4Fr::rand(&mut rng);
+        let circ = Circuit {
+            a: Some(a),
+            b: Some(b),
+            num_constraints: 100,
+            num_variables: 25,
+        };
+        let (pk, vk) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+        let pvk = TestSNARK::process_vk(&vk).unwrap();
+        let proof = TestSNARK::prove(&pk, circ, &mut rng).unwrap();
+
+        let verify = |c: MNT4Fr, native: bool| {
+            let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+            let input_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::InputVar::new_input(ns!(cs, "new_input"), || Ok(vec![c]))
+                .unwrap();
+            let proof_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::ProofVar::new_witness(ns!(cs, "alloc_proof"), || Ok(&proof))
+                .unwrap();
+            let vk_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::VerifyingKeyVar::new_witness(ns!(cs, "alloc_vk"), || Ok(&vk))
+                .unwrap();
+            let constraints_before = cs.num_constraints();
+            let pvk_gadget = if native {
+                PreparedVerifyingKeyVar::from_native_prepared(
+                    ns!(cs, "pvk"),
+                    &vk_gadget,
+                    &pvk,
+                    AllocationMode::Constant,
+                )
+                .unwrap()
+            } else {
+                vk_gadget.prepare().unwrap()
+            };
+            let prepare_constraints = cs.num_constraints() - constraints_before;
+            let result = TestSNARKGadget::verify_with_processed_vk(
+                &pvk_gadget,
+                &input_gadget,
+                &proof_gadget,
+            )
+            .unwrap();
+            assert!(cs.is_satisfied().unwrap());
+            (result.value().unwrap(), prepare_constraints)
+        };
+
+        let (valid, computed) = verify(a * b, false);
+        assert!(valid);
+        let (valid, native) = verify(a * b, true);
+        assert!(valid);
+        assert!(!verify(a, true).0);
+        assert!(
+            native < computed,
+            "allocating the native preparation used {} constraints, computing it {}",
+            native,
+            computed
+        );
+
+        let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+        let mut other_vk = vk.clone();
+        other_vk.gamma_abc_g1.push(other_vk.gamma_abc_g1[0]);
+        let other_vk_gadget =
+            VerifyingKeyVar::<MNT4, MNT4PairingVar>::new_witness(ns!(cs, "alloc_vk"), || {
+                Ok(&other_vk)
+            })
+            .unwrap();
+        assert!(PreparedVerifyingKeyVar::from_native_prepared(
+            ns!(cs, "pvk"),
+            &other_vk_gadget,
+            &pvk,
+            AllocationMode::Constant,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn groth16_prepare_with_alpha_beta_test() {
+        type GTVar = <MNT4PairingVar as PairingVar<MNT4>>::GTVar;
+
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let a = MNT4Fr::rand(&mut rng);
+        let b = MNT4Fr::rand(&mut rng);
+        let circ = Circuit {
+            a: Some(a),
+            b: Some(b),
+            num_constraints: 100,
+            num_variables: 25,
+        };
+        let (pk, vk) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+        let pvk = TestSNARK::process_vk(&vk).unwrap();
+        let proof = TestSNARK::prove(&pk, circ, &mut rng).unwrap();
+
+        let verify = |precomputed: bool| {
+            let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+            let input_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::InputVar::new_input(ns!(cs, "new_input"), || Ok(vec![a * b]))
+                .unwrap();
+            let proof_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::ProofVar::new_witness(ns!(cs, "alloc_proof"), || Ok(&proof))
+                .unwrap();
+            let vk_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::VerifyingKeyVar::new_witness(ns!(cs, "alloc_vk"), || Ok(&vk))
+                .unwrap();
+            let constraints_before = cs.num_constraints();
+            let pvk_gadget = if precomputed {
+                let alpha_g1_beta_g2 =
+                    GTVar::new_constant(ns!(cs, "alpha_g1_beta_g2"), pvk.alpha_g1_beta_g2).unwrap();
+                vk_gadget.prepare_with_alpha_beta(alpha_g1_beta_g2).unwrap()
+            } else {
+                vk_gadget.prepare().unwrap()
+            };
+            let prepare_constraints = cs.num_constraints() - constraints_before;
+            TestSNARKGadget::verify_with_processed_vk(&pvk_gadget, &input_gadget, &proof_gadget)
+                .unwrap()
+                .enforce_equal(&Boolean::constant(true))
+                .unwrap();
+            assert!(cs.is_satisfied().unwrap());
+            prepare_constraints
+        };
+
+        let computed = verify(false);
+        let precomputed = verify(true);
+        assert!(
+            precomputed < computed,
+            "preparing with a precomputed pairing used {} constraints, computing it {}",
+            precomputed,
+            computed
+        );
+    }
+
+    #[test]
+    fn groth16_gadget_qap_test() {
+        use crate::r1cs_to_qap::GrothPaperReduction;
+
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let a = MNT4Fr::rand(&mut rng);
+        let b = MNT4Fr::rand(&mut rng);
+        let circ = Circuit {
+            a: Some(a),
+            b: Some(b),
+            num_constraints: 10,
+            num_variables: 5,
+        };
+
+        // Each reduction is verified by the gadget with the same parameter.
+        assert!(prove_and_verify_with_gadget::<TestSNARK, TestSNARKGadget>(
+            circ,
+            a * b
+        ));
+        assert!(!prove_and_verify_with_gadget::<TestSNARK, TestSNARKGadget>(
+            circ,
+            a + b
+        ));
+        assert!(prove_and_verify_with_gadget::<
+            Groth16<MNT4, GrothPaperReduction>,
+            Groth16VerifierGadget<MNT4, MNT4PairingVar, GrothPaperReduction>,
+        >(circ, a * b));
+    }
+
+    #[test]
+    fn groth16_verify_raw_test() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let a = MNT4Fr::rand(&mut rng);
+        let b = MNT4Fr::rand(&mut rng);
+        let circ = Circuit {
+            a: Some(a),
+            b: Some(b),
+            num_constraints: 10,
+            num_variables: 5,
+        };
+        let (pk, vk) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+        let proof = TestSNARK::prove(&pk, circ, &mut rng).unwrap();
+        let pvk = TestSNARK::process_vk(&vk).unwrap();
+
+        // Returns whether the circuit is satisfied and how many constraints
+        // the verification took.
+        let verify = |c: MNT4Fr, raw: bool| {
+            let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+            let input_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::InputVar::new_input(ns!(cs, "new_input"), || Ok(vec![c]))
+                .unwrap();
+            let proof_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::ProofVar::new_witness(ns!(cs, "alloc_proof"), || Ok(&proof))
+                .unwrap();
+            let pvk_gadget = PreparedVerifyingKeyVar::<MNT4, MNT4PairingVar>::new_witness(
+                ns!(cs, "alloc_pvk"),
+                || Ok(&pvk),
+            )
+            .unwrap();
+            let constraints_before = cs.num_constraints();
+            let valid = if raw {
+                TestSNARKGadget::verify_with_processed_vk_raw(
+                    &pvk_gadget,
+                    &input_gadget,
+                    &proof_gadget,
+                )
+                .unwrap()
+                .is_eq(&pvk_gadget.alpha_g1_beta_g2)
+                .unwrap()
+            } else {
+                TestSNARKGadget::verify_with_processed_vk(&pvk_gadget, &input_gadget, &proof_gadget)
+                    .unwrap()
+            };
+            valid.enforce_equal(&Boolean::constant(true)).unwrap();
+            (
+                cs.is_satisfied().unwrap(),
+                cs.num_constraints() - constraints_before,
+            )
+        };
+
+        // The raw left-hand side, checked against `e(alpha, beta)`, costs
+        // exactly as much as the full verification.
+        let (satisfied, raw) = verify(a * b, true);
+        assert!(satisfied);
+        assert_eq!((true, raw), verify(a * b, false));
+        assert_eq!((false, raw), verify(a + b, true));
+    }
+
+    #[test]
+    fn groth16_const_verifying_key_test() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let a = MNT4Fr::rand(&mut rng);
+        let b = MNT4Fr::rand(&mut rng);
+        let circ = Circuit {
+            a: Some(a),
+            b: Some(b),
+            num_constraints: 10,
+            num_variables: 5,
+        };
+        let (pk, vk) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+        let proof = TestSNARK::prove(&pk, circ, &mut rng).unwrap();
+        let const_vk = ConstVerifyingKeyGadget::new(&vk);
+        assert_eq!(const_vk.pvk(), &TestSNARK::process_vk(&vk).unwrap());
+
+        let verify = |constant: bool| {
+            let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+            let input_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::InputVar::new_input(ns!(cs, "new_input"), || Ok(vec![a * b]))
+                .unwrap();
+            let proof_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::ProofVar::new_witness(ns!(cs, "alloc_proof"), || Ok(&proof))
+                .unwrap();
+            let constraints_before = cs.num_constraints();
+            let pvk_gadget = if constant {
+                let pvk_gadget = const_vk.prepared_var(ns!(cs, "const_vk")).unwrap();
+                assert_eq!(cs.num_constraints(), constraints_before);
+                pvk_gadget
+            } else {
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::VerifyingKeyVar::new_witness(ns!(cs, "alloc_vk"), || Ok(&vk))
+                .unwrap()
+                .prepare()
+                .unwrap()
+            };
+            TestSNARKGadget::verify_with_processed_vk(&pvk_gadget, &input_gadget, &proof_gadget)
+                .unwrap()
+                .enforce_equal(&Boolean::constant(true))
+                .unwrap();
+            assert!(cs.is_satisfied().unwrap());
+            cs.num_constraints() - constraints_before
+        };
+
+        let witness = verify(false);
+        let constant = verify(true);
+        assert!(
+            constant < witness,
+            "verifying with a constant key used {} constraints, with a witness key {}",
+            constant,
+            witness
+        );
+    }
+
+    #[test]
+    fn groth16_verify_batch_test() {
+        const NUM_PROOFS: usize = 4;
+
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let circ = Circuit {
+            a: None,
+            b: None,
+            num_constraints: 100,
+            num_variables: 25,
+        };
+        let (pk, vk) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+        let pvk = TestSNARK::process_vk(&vk).unwrap();
+
+        let mut inputs = Vec::new();
+        let mut proofs = Vec::new();
+        for _ in 0..NUM_PROOFS {
+            let a = MNT4Fr::rand(&mut rng);
+            let b = MNT4Fr::rand(&mut rng);
+            let circ = Circuit {
+                a: Some(a),
+                b: Some(b),
+                num_constraints: 100,
+                num_variables: 25,
+            };
+            proofs.push(TestSNARK::prove(&pk, circ, &mut rng).unwrap());
+            inputs.push(a * b);
+        }
+
+        // Constraint count of verifying a single proof.
+        let single_constraints = {
+            let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+            let input_gadget = <TestSNARKGadget as SNARKGadget<
+                <MNT4 as Pairing>::ScalarField,
+                <MNT4 as Pairing>::BaseField,
+                TestSNARK,
+            >>::InputVar::new_input(ns!(cs, "new_input"), || {
+                Ok(vec![inputs[0]])
+            })
+            .unwrap();
+            let proof_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::ProofVar::new_witness(ns!(cs, "alloc_proof"), || Ok(&proofs[0]))
+                .unwrap();
+            let pvk_gadget = <TestSNARKGadget as SNARKGadget<
+                <MNT4 as Pairing>::ScalarField,
+                <MNT4 as Pairing>::BaseField,
+                TestSNARK,
+            >>::ProcessedVerifyingKeyVar::new_constant(
+                ns!(cs, "alloc_pvk"), pvk.clone()
+            )
+            .unwrap();
+            TestSNARKGadget::verify_with_processed_vk(&pvk_gadget, &input_gadget, &proof_gadget)
+                .unwrap()
+                .enforce_equal(&Boolean::constant(true))
+                .unwrap();
+            assert!(cs.is_satisfied().unwrap());
+            cs.num_constraints()
+        };
+
+        let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+        let input_gadgets = inputs
+            .iter()
+            .map(|input| {
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::InputVar::new_input(ns!(cs, "new_input"), || Ok(vec![*input]))
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let proof_gadgets = proofs
+            .iter()
+            .map(|proof| {
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::ProofVar::new_witness(ns!(cs, "alloc_proof"), || Ok(proof))
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let pvk_gadget = <TestSNARKGadget as SNARKGadget<
+            <MNT4 as Pairing>::ScalarField,
+            <MNT4 as Pairing>::BaseField,
+            TestSNARK,
+        >>::ProcessedVerifyingKeyVar::new_constant(
+            ns!(cs, "alloc_pvk"), pvk.clone()
+        )
+        .unwrap();
+
+        assert!(
+            TestSNARKGadget::verify_batch(&pvk_gadget, &input_gadgets[1..], &proof_gadgets)
+                .is_err()
+        );
+
+        TestSNARKGadget::verify_batch(&pvk_gadget, &input_gadgets, &proof_gadgets)
+            .unwrap()
+            .enforce_equal(&Boolean::constant(true))
+            .unwrap();
+        assert!(
+            cs.is_satisfied().unwrap(),
+            "Constraints not satisfied: {}",
+            cs.which_is_unsatisfied().unwrap().unwrap_or_default()
+        );
+
+        let batch_constraints = cs.num_constraints();
+        assert!(
+            batch_constraints < NUM_PROOFS * single_constraints,
+            "batch verification used {} constraints, {} individual verifications use {}",
+            batch_constraints,
+            NUM_PROOFS,
+            NUM_PROOFS * single_constraints
+        );
+
+        // A key allocated as a witness is absorbed into the challenges, which
+        // must not affect the result for valid proofs.
+        let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+        let input_gadgets = inputs
+            .iter()
+            .map(|input| {
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::InputVar::new_input(ns!(cs, "new_input"), || Ok(vec![*input]))
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let proof_gadgets = proofs
+            .iter()
+            .map(|proof| {
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::ProofVar::new_witness(ns!(cs, "alloc_proof"), || Ok(proof))
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let pvk_gadget = PreparedVerifyingKeyVar::<MNT4, MNT4PairingVar>::new_witness(
+            ns!(cs, "alloc_pvk"),
+            || Ok(&pvk),
+        )
+        .unwrap();
+        TestSNARKGadget::verify_batch(&pvk_gadget, &input_gadgets, &proof_gadgets)
+            .unwrap()
+            .enforce_equal(&Boolean::constant(true))
+            .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        // Swapping the inputs of two proofs must make the batch fail.
+        let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+        let mut swapped = inputs.clone();
+        swapped.swap(0, 1);
+        let input_gadgets = swapped
+            .iter()
+            .map(|input| {
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::InputVar::new_input(ns!(cs, "new_input"), || Ok(vec![*input]))
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let proof_gadgets = proofs
+            .iter()
+            .map(|proof| {
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::ProofVar::new_witness(ns!(cs, "alloc_proof"), || Ok(proof))
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let pvk_gadget =
+            <TestSNARKGadget as SNARKGadget<
+                <MNT4 as Pairing>::ScalarField,
+                <MNT4 as Pairing>::BaseField,
+                TestSNARK,
+            >>::ProcessedVerifyingKeyVar::new_constant(ns!(cs, "alloc_pvk"), pvk)
+            .unwrap();
+        assert!(
+            !TestSNARKGadget::verify_batch(&pvk_gadget, &input_gadgets, &proof_gadgets)
+                .unwrap()
+                .value()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn groth16_verify_many_test() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let circ = Circuit {
+            a: None,
+            b: None,
+            num_constraints: 100,
+            num_variables: 25,
+        };
+        // Two keys for circuits with the same public-input structure; the
+        // first one is used twice.
+        let keys = (0..2)
+            .map(|_| TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap())
+            .collect::<Vec<_>>();
+        let key_indices = [0, 1, 0];
+
+        let mut pvks = Vec::new();
+        let mut inputs = Vec::new();
+        let mut proofs = Vec::new();
+        for &k in &key_indices {
+            let a = MNT4Fr::rand(&mut rng);
+            let b = MNT4Fr::rand(&mut rng);
+            let circ = Circuit {
+                a: Some(a),
+                b: Some(b),
+                num_constraints: 100,
+                num_variables: 25,
+            };
+            proofs.push(TestSNARK::prove(&keys[k].0, circ, &mut rng).unwrap());
+            pvks.push(TestSNARK::process_vk(&keys[k].1).unwrap());
+            inputs.push(a * b);
+        }
+
+        let allocate = |cs: &ConstraintSystemRef<MNT6Fr>, pvks: &[_]| {
+            let pvk_gadgets = pvks
+                .iter()
+                .map(|pvk| {
+                    <TestSNARKGadget as SNARKGadget<
+                        <MNT4 as Pairing>::ScalarField,
+                        <MNT4 as Pairing>::BaseField,
+                        TestSNARK,
+                    >>::ProcessedVerifyingKeyVar::new_constant(
+                        ns!(cs, "alloc_pvk"), pvk
+                    )
+                    .unwrap()
+                })
+                .collect::<Vec<_>>();
+            let input_gadgets = inputs
+                .iter()
+                .map(|input| {
+                    <TestSNARKGadget as SNARKGadget<
+                        <MNT4 as Pairing>::ScalarField,
+                        <MNT4 as Pairing>::BaseField,
+                        TestSNARK,
+                    >>::InputVar::new_input(ns!(cs, "new_input"), || {
+                        Ok(vec![*input])
+                    })
+                    .unwrap()
+                })
+                .collect::<Vec<_>>();
+            let proof_gadgets = proofs
+                .iter()
+                .map(|proof| {
+                    <TestSNARKGadget as SNARKGadget<
+                        <MNT4 as Pairing>::ScalarField,
+                        <MNT4 as Pairing>::BaseField,
+                        TestSNARK,
+                    >>::ProofVar::new_witness(ns!(cs, "alloc_proof"), || {
+                        Ok(proof)
+                    })
+                    .unwrap()
+                })
+                .collect::<Vec<_>>();
+            (pvk_gadgets, input_gadgets, proof_gadgets)
+        };
+
+        // Constraint count of verifying every proof separately.
+        let separate_constraints = {
+            let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+            let (pvk_gadgets, input_gadgets, proof_gadgets) = allocate(&cs, &pvks);
+            for ((pvk, x), proof) in pvk_gadgets.iter().zip(&input_gadgets).zip(&proof_gadgets) {
+                TestSNARKGadget::verify_with_processed_vk(pvk, x, proof)
+                    .unwrap()
+                    .enforce_equal(&Boolean::constant(true))
+                    .unwrap();
+            }
+            assert!(cs.is_satisfied().unwrap());
+            cs.num_constraints()
+        };
+
+        let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+        let (pvk_gadgets, input_gadgets, proof_gadgets) = allocate(&cs, &pvks);
+        assert!(
+            TestSNARKGadget::verify_many(&pvk_gadgets[1..], &input_gadgets, &proof_gadgets)
+                .is_err()
+        );
+        TestSNARKGadget::verify_many(&pvk_gadgets, &input_gadgets, &proof_gadgets)
+            .unwrap()
+            .enforce_equal(&Boolean::constant(true))
+            .unwrap();
+        assert!(
+            cs.is_satisfied().unwrap(),
+            "Constraints not satisfied: {}",
+            cs.which_is_unsatisfied().unwrap().unwrap_or_default()
+        );
+
+        let many_constraints = cs.num_constraints();
+        assert!(
+            many_constraints < separate_constraints,
+            "verify_many used {} constraints, {} separate verifications use {}",
+            many_constraints,
+            key_indices.len(),
+            separate_constraints
+        );
+
+        // Checking a proof against the wrong key must make the batch fail.
+        let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+        let mut swapped = pvks.clone();
+        swapped.swap(0, 1);
+        let (pvk_gadgets, input_gadgets, proof_gadgets) = allocate(&cs, &swapped);
+        assert!(
+            !TestSNARKGadget::verify_many(&pvk_gadgets, &input_gadgets, &proof_gadgets)
+                .unwrap()
+                .value()
+                .unwrap()
+        );
+    }
+
+    /// Native counterpart of the byte layout emitted by the short Weierstrass
+    /// curve gadgets' `to_bytes_le`.
+    fn native_point_bytes<G: AffineRepr>(p: &G) -> Vec<u8> {
+        let (x, y) = p.xy().unwrap_or_default();
+        x.to_base_prime_field_elements()
+            .chain(y.to_base_prime_field_elements())
+            .flat_map(|f| f.into_bigint().to_bytes_le())
+            .chain([p.is_zero() as u8])
+            .collect()
+    }
+
+    #[test]
+    fn groth16_proof_to_bytes_test() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let a = MNT4Fr::rand(&mut rng);
+        let b = MNT4Fr::rand(&mut rng);
+        let circ = Circuit {
+            a: Some(a),
+            b: Some(b),
+            num_constraints: 100,
+            num_variables: 25,
+        };
+        let (pk, _) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+        let proof = TestSNARK::prove(&pk, circ, &mut rng).unwrap();
+
+        let config = batch_sponge_config::<MNT6Fr>();
+
+        let native_bytes = [
+            native_point_bytes(&proof.a),
+            native_point_bytes(&proof.b),
+            native_point_bytes(&proof.c),
+        ]
+        .concat();
+        let mut native_sponge = PoseidonSponge::new(&config);
+        native_sponge.absorb(&native_bytes);
+        let native_digest = native_sponge.squeeze_field_elements::<MNT6Fr>(1)[0];
+
+        let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+        let proof_gadget = <TestSNARKGadget as SNARKGadget<
+            <MNT4 as Pairing>::ScalarField,
+            <MNT4 as Pairing>::BaseField,
+            TestSNARK,
+        >>::ProofVar::new_witness(ns!(cs, "alloc_proof"), || Ok(proof))
+        .unwrap();
+        let bytes = proof_gadget.to_bytes_le().unwrap();
+        assert_eq!(bytes.value().unwrap(), native_bytes);
+
+        let mut sponge = PoseidonSpongeVar::new(cs.clone(), &config);
+        sponge.absorb(&bytes).unwrap();
+        let digest = sponge.squeeze_field_elements(1).unwrap();
+
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(digest[0].value().unwrap(), native_digest);
+    }
+
+    #[test]
+    fn groth16_verify_and_commit_inputs_test() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let a = MNT4Fr::rand(&mut rng);
+        let b = MNT4Fr::rand(&mut rng);
+        let circ = Circuit {
+            a: Some(a),
+            b: Some(b),
+            num_constraints: 10,
+            num_variables: 5,
+        };
+        let (pk, vk) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+        let proof = TestSNARK::prove(&pk, circ, &mut rng).unwrap();
+        let pvk = TestSNARK::process_vk(&vk).unwrap();
+        let config = batch_sponge_config::<MNT6Fr>();
+
+        for (c, valid) in [(a * b, true), (a + b, false)] {
+            let num_bytes = (MNT4Fr::MODULUS_BIT_SIZE as usize).div_ceil(8);
+            let mut native_bytes = c.into_bigint().to_bytes_le();
+            native_bytes.truncate(num_bytes);
+            let mut native_sponge = PoseidonSponge::new(&config);
+            native_sponge.absorb(&native_bytes);
+            let native_commitment = native_sponge.squeeze_field_elements::<MNT6Fr>(1)[0];
+
+            let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+            let input_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::InputVar::new_input(ns!(cs, "new_input"), || Ok(vec![c]))
+                .unwrap();
+            let proof_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::ProofVar::new_witness(ns!(cs, "alloc_proof"), || Ok(&proof))
+                .unwrap();
+            let pvk_gadget = PreparedVerifyingKeyVar::<MNT4, MNT4PairingVar>::new_witness(
+                ns!(cs, "alloc_pvk"),
+                || Ok(&pvk),
+            )
+            .unwrap();
+            let mut sponge = PoseidonSpongeVar::new(cs.clone(), &config);
+            let (result, commitment) = TestSNARKGadget::verify_and_commit_inputs(
+                &pvk_gadget,
+                &input_gadget,
+                &proof_gadget,
+                &mut sponge,
+            )
+            .unwrap();
+
+            assert!(cs.is_satisfied().unwrap());
+            assert_eq!(result.value().unwrap(), valid);
+            assert_eq!(commitment.value().unwrap(), native_commitment);
+        }
+    }
+
+    #[test]
+    fn groth16_proof_select_test() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let circ = Circuit {
+            a: Some(MNT4Fr::rand(&mut rng)),
+            b: Some(MNT4Fr::rand(&mut rng)),
+            num_constraints: 100,
+            num_variables: 25,
+        };
+        let (pk, _) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+        let proof1 = TestSNARK::prove(&pk, circ, &mut rng).unwrap();
+        let proof2 = TestSNARK::prove(&pk, circ, &mut rng).unwrap();
+
+        let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+        let proof1_gadget =
+            <TestSNARKGadget as SNARKGadget<
+                <MNT4 as Pairing>::ScalarField,
+                <MNT4 as Pairing>::BaseField,
+                TestSNARK,
+            >>::ProofVar::new_witness(ns!(cs, "alloc_proof1"), || Ok(proof1))
+            .unwrap();
+        let proof2_gadget =
+            <TestSNARKGadget as SNARKGadget<
+                <MNT4 as Pairing>::ScalarField,
+                <MNT4 as Pairing>::BaseField,
+                TestSNARK,
+            >>::ProofVar::new_witness(ns!(cs, "alloc_proof2"), || Ok(&proof2))
+            .unwrap();
+        let selector = Boolean::new_witness(ns!(cs, "selector"), || Ok(false)).unwrap();
+
+        let selected =
+            CondSelectGadget::conditionally_select(&selector, &proof1_gadget, &proof2_gadget)
+                .unwrap();
+        assert!(!selected.is_eq(&proof1_gadget).unwrap().value().unwrap());
+        selected.enforce_equal(&proof2_gadget).unwrap();
+        assert_eq!(selected.value().unwrap(), proof2);
+
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn groth16_proof_to_constraint_field_test() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let circ = Circuit {
+            a: Some(MNT4Fr::rand(&mut rng)),
+            b: Some(MNT4Fr::rand(&mut rng)),
+            num_constraints: 100,
+            num_variables: 25,
+        };
+        let (pk, _) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+        let proof = TestSNARK::prove(&pk, circ, &mut rng).unwrap();
+        let sponge_config = batch_sponge_config::<MNT6Fr>();
+
+        // The identity has no affine coordinates, so check it as well.
+        for proof in [proof, Proof::default()] {
+            let expected = proof_to_constraint_field(&proof);
+            // Two G1 points with coordinates in `MNT6Fr`, one G2 point with
+            // coordinates in its quadratic extension, and three flags.
+            assert_eq!(expected.len(), 2 * 2 + 2 * 2 + 3);
+
+            let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+            let proof_gadget =
+                ProofVar::<MNT4, MNT4PairingVar>::new_witness(ns!(cs, "alloc_proof"), || {
+                    Ok(&proof)
+                })
+                .unwrap();
+            let elements = proof_gadget.to_constraint_field().unwrap();
+            assert_eq!(elements.value().unwrap(), expected);
+
+            // Absorbing either representation gives the same sponge state.
+            let mut sponge = PoseidonSponge::new(&sponge_config);
+            sponge.absorb(&expected);
+            let mut sponge_var = PoseidonSpongeVar::new(cs.clone(), &sponge_config);
+            sponge_var.absorb(&elements).unwrap();
+            assert_eq!(
+                sponge_var
+                    .squeeze_field_elements(1)
+                    .unwrap()
+                    .value()
+                    .unwrap(),
+                sponge.squeeze_field_elements::<MNT6Fr>(1)
+            );
+            assert!(cs.is_satisfied().unwrap());
+        }
+    }
+
+    #[test]
+    fn groth16_proof_rerandomize_test() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let a = MNT4Fr::rand(&mut rng);
+        let b = MNT4Fr::rand(&mut rng);
+        let circ = Circuit {
+            a: Some(a),
+            b: Some(b),
+            num_constraints: 100,
+            num_variables: 25,
+        };
+        let (pk, vk) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+        let proof = TestSNARK::prove(&pk, circ, &mut rng).unwrap();
+
+        let r1 = MNT4Fr::rand(&mut rng);
+        let r2 = MNT4Fr::rand(&mut rng);
+        let expected = proof.rerandomize(&vk, r1, r2).unwrap();
+
+        let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+        let input_gadget =
+            <TestSNARKGadget as SNARKGadget<
+                <MNT4 as Pairing>::ScalarField,
+                <MNT4 as Pairing>::BaseField,
+                TestSNARK,
+            >>::InputVar::new_input(ns!(cs, "new_input"), || Ok(vec![a * b]))
+            .unwrap();
+        let proof_gadget = <TestSNARKGadget as SNARKGadget<
+            <MNT4 as Pairing>::ScalarField,
+            <MNT4 as Pairing>::BaseField,
+            TestSNARK,
+        >>::ProofVar::new_witness(ns!(cs, "alloc_proof"), || Ok(proof))
+        .unwrap();
+        let vk_gadget = <TestSNARKGadget as SNARKGadget<
+            <MNT4 as Pairing>::ScalarField,
+            <MNT4 as Pairing>::BaseField,
+            TestSNARK,
+        >>::VerifyingKeyVar::new_constant(ns!(cs, "alloc_vk"), vk)
+        .unwrap();
+        let r1_bits = Vec::<Boolean<MNT6Fr>>::new_witness(ns!(cs, "r1"), || {
+            Ok(r1.into_bigint().to_bits_le())
+        })
+        .unwrap();
+        let r2_bits = Vec::<Boolean<MNT6Fr>>::new_witness(ns!(cs, "r2"), || {
+            Ok(r2.into_bigint().to_bits_le())
+        })
+        .unwrap();
+
+        let rerandomized = proof_gadget
+            .rerandomize(&vk_gadget, &r1_bits, &r2_bits)
+            .unwrap();
+        assert_eq!(rerandomized.value().unwrap(), expected);
+        assert!(!rerandomized.is_eq(&proof_gadget).unwrap().value().unwrap());
+
+        <TestSNARKGadget as SNARKGadget<
+            <MNT4 as Pairing>::ScalarField,
+            <MNT4 as Pairing>::BaseField,
+            TestSNARK,
+        >>::verify(&vk_gadget, &input_gadget, &rerandomized)
+        .unwrap()
+        .enforce_equal(&Boolean::constant(true))
+        .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn groth16_checked_proof_allocation_test() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let circ = Circuit {
+            a: Some(MNT4Fr::rand(&mut rng)),
+            b: Some(MNT4Fr::rand(&mut rng)),
+            num_constraints: 100,
+            num_variables: 25,
+        };
+        let (pk, _) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+        let mut proof = TestSNARK::prove(&pk, circ, &mut rng).unwrap();
+
+        // Find a point on the G2 curve outside the prime-order subgroup.
+        proof.b = loop {
+            let x = MNT4Fq2::rand(&mut rng);
+            if let Some(p) = MNT4G2Affine::get_point_from_x_unchecked(x, false) {
+                if !p.is_in_correct_subgroup_assuming_on_curve() {
+                    break p;
+                }
+            }
+        };
+
+        let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+        let proof_gadget = <TestSNARKGadget as SNARKGadget<
+            <MNT4 as Pairing>::ScalarField,
+            <MNT4 as Pairing>::BaseField,
+            TestSNARK,
+        >>::new_proof_unchecked(
+            ns!(cs, "alloc_proof"),
+            || Ok(&proof),
+            AllocationMode::Witness,
+        )
+        .unwrap();
+        let b = MNT4G2Var::constant(proof.b.into_group());
+        proof_gadget.b.enforce_equal(&b).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+        let proof_gadget = TestSNARKGadget::new_proof_checked(
+            ns!(cs, "alloc_proof"),
+            || Ok(&proof),
+            AllocationMode::Witness,
+        )
+        .unwrap();
+        let b = MNT4G2Var::constant(proof.b.into_group());
+        proof_gadget.b.enforce_equal(&b).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn groth16_verify_with_field_inputs_test() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let a = MNT4Fr::rand(&mut rng);
+        let b = MNT4Fr::rand(&mut rng);
+        let circ = Circuit {
+            a: Some(a),
+            b: Some(b),
+            num_constraints: 100,
+            num_variables: 25,
+        };
+        let (pk, vk) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+        let pvk = TestSNARK::process_vk(&vk).unwrap();
+        let proof = TestSNARK::prove(&pk, circ, &mut rng).unwrap();
+
+        let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+        let input_gadget =
+            FieldInputVar::new_input(ns!(cs, "new_input"), || Ok(vec![a * b])).unwrap();
+        let proof_gadget = <TestSNARKGadget as SNARKGadget<
+            <MNT4 as Pairing>::ScalarField,
+            <MNT4 as Pairing>::BaseField,
+            TestSNARK,
+        >>::ProofVar::new_witness(ns!(cs, "alloc_proof"), || Ok(proof))
+        .unwrap();
+        let pvk_gadget =
+            <TestSNARKGadget as SNARKGadget<
+                <MNT4 as Pairing>::ScalarField,
+                <MNT4 as Pairing>::BaseField,
+                TestSNARK,
+            >>::ProcessedVerifyingKeyVar::new_constant(ns!(cs, "alloc_pvk"), pvk)
+            .unwrap();
 
-            let gamma_abc_g1 = Vec::new_variable(cs.clone(), || Ok(gamma_abc_g1), mode)?;
-            Ok(Self {
-                alpha_g1,
-                beta_g2,
-                gamma_g2,
-                delta_g2,
-                gamma_abc_g1,
-            })
-        })
+        TestSNARKGadget::verify_with_field_inputs(&pvk_gadget, &input_gadget, &proof_gadget)
+            .unwrap()
+            .enforce_equal(&Boolean::constant(true))
+            .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        let too_many_inputs =
+            FieldInputVar::new_input(ns!(cs, "too_many_inputs"), || Ok(vec![a * b, a])).unwrap();
+        assert!(TestSNARKGadget::verify_with_field_inputs(
+            &pvk_gadget,
+            &too_many_inputs,
+            &proof_gadget
+        )
+        .is_err());
     }
-}
 
-impl<E, P> AllocVar<Proof<E>, BasePrimeField<E>> for ProofVar<E, P>
-where
-    E: Pairing,
-    P: PairingVar<E>,
-{
-    #[tracing::instrument(target = "r1cs", skip(cs, f))]
-    fn new_variable<T: Borrow<Proof<E>>>(
-        cs: impl Into<Namespace<BasePrimeField<E>>>,
-        f: impl FnOnce() -> Result<T, SynthesisError>,
-        mode: AllocationMode,
-    ) -> Result<Self, SynthesisError> {
-        let ns = cs.into();
-        let cs = ns.cs();
+    #[test]
+    fn groth16_verify_with_byte_inputs_test() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
 
-        f().and_then(|proof| {
-            let Proof { a, b, c } = proof.borrow().clone();
-            let a = P::G1Var::new_variable(ark_relations::ns!(cs, "a"), || Ok(a), mode)?;
-            let b = P::G2Var::new_variable(ark_relations::ns!(cs, "b"), || Ok(b), mode)?;
-            let c = P::G1Var::new_variable(ark_relations::ns!(cs, "c"), || Ok(c), mode)?;
-            Ok(Self { a, b, c })
-        })
-    }
-}
+        // Several chunks, the last one shorter, are packed like natively.
+        let bytes = (0..100).map(|_| rng.next_u32() as u8).collect::<Vec<_>>();
+        let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+        let packed =
+            BytesInputVar::<MNT4Fr, MNT6Fr>::new_input(ns!(cs, "bytes"), || Ok(bytes.clone()))
+                .unwrap()
+                .to_boolean_input()
+                .unwrap()
+                .into_iter()
+                .map(|bits| {
+                    let bits = bits.value().unwrap();
+                    MNT4Fr::from_bigint(<MNT4Fr as PrimeField>::BigInt::from_bits_le(&bits))
+                        .unwrap()
+                })
+                .collect::<Vec<_>>();
+        assert_eq!(packed, pack_bytes_to_inputs::<MNT4Fr>(&bytes));
+        assert_eq!(packed.len(), 3);
 
-impl<E, P> ToBytesGadget<BasePrimeField<E>> for VerifyingKeyVar<E, P>
-where
-    E: Pairing,
-    P: PairingVar<E>,
-{
-    #[inline]
-    #[tracing::instrument(target = "r1cs", skip(self))]
-    fn to_bytes_le(&self) -> Result<Vec<UInt8<BasePrimeField<E>>>, SynthesisError> {
-        let mut bytes = Vec::new();
-        bytes.extend_from_slice(&self.alpha_g1.to_bytes_le()?);
-        bytes.extend_from_slice(&self.beta_g2.to_bytes_le()?);
-        bytes.extend_from_slice(&self.gamma_g2.to_bytes_le()?);
-        bytes.extend_from_slice(&self.delta_g2.to_bytes_le()?);
-        for g in &self.gamma_abc_g1 {
-            bytes.extend_from_slice(&g.to_bytes_le()?);
+        // A proof whose public input is a packed byte string.
+        let bytes = bytes[..20].to_vec();
+        let a = pack_bytes_to_inputs::<MNT4Fr>(&bytes)[0];
+        let circ = Circuit {
+            a: Some(a),
+            b: Some(MNT4Fr::from(1u64)),
+            num_constraints: 100,
+            num_variables: 25,
+        };
+        let (pk, vk) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+        let pvk = TestSNARK::process_vk(&vk).unwrap();
+        let proof = TestSNARK::prove(&pk, circ, &mut rng).unwrap();
+
+        for (bytes, valid) in [(bytes.clone(), true), ([&bytes[1..], &[0]].concat(), false)] {
+            let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+            let input_gadget =
+                BytesInputVar::new_input(ns!(cs, "new_input"), || Ok(bytes)).unwrap();
+            let proof_gadget = <TestSNARKGadget as SNARKGadget<
+                <MNT4 as Pairing>::ScalarField,
+                <MNT4 as Pairing>::BaseField,
+                TestSNARK,
+            >>::ProofVar::new_witness(
+                ns!(cs, "alloc_proof"), || Ok(proof.clone())
+            )
+            .unwrap();
+            let pvk_gadget = <TestSNARKGadget as SNARKGadget<
+                <MNT4 as Pairing>::ScalarField,
+                <MNT4 as Pairing>::BaseField,
+                TestSNARK,
+            >>::ProcessedVerifyingKeyVar::new_constant(
+                ns!(cs, "alloc_pvk"), pvk.clone()
+            )
+            .unwrap();
+
+            TestSNARKGadget::verify_with_byte_inputs(&pvk_gadget, &input_gadget, &proof_gadget)
+                .unwrap()
+                .enforce_equal(&Boolean::constant(true))
+                .unwrap();
+            assert_eq!(cs.is_satisfied().unwrap(), valid);
         }
-        Ok(bytes)
     }
-}
 
-#[cfg(test)]
-mod test {
-    use crate::{constraints::Groth16VerifierGadget, Groth16};
-    use ark_crypto_primitives::snark::{constraints::SNARKGadget, SNARK};
-    use ark_ec::pairing::Pairing;
-    use ark_ff::{Field, UniformRand};
-    use ark_mnt4_298::{
-        constraints::PairingVar as MNT4PairingVar, Fq as MNT6Fr, Fr as MNT4Fr, MNT4_298 as MNT4,
-    };
-    use ark_r1cs_std::{alloc::AllocVar, boolean::Boolean, eq::EqGadget};
-    use ark_relations::{
-        gr1cs::{ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError},
-        lc, ns,
-    };
-    use ark_std::{
-        ops::MulAssign,
-        rand::{RngCore, SeedableRng},
-        test_rng,
-    };
+    #[test]
+    fn groth16_estimated_constraints_test() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let a = MNT4Fr::rand(&mut rng);
+        let b = MNT4Fr::rand(&mut rng);
+        let circ = Circuit {
+            a: Some(a),
+            b: Some(b),
+            num_constraints: 10,
+            num_variables: 5,
+        };
+        let (pk, mut vk) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+        let proof = TestSNARK::prove(&pk, circ, &mut rng).unwrap();
 
-    #[derive(Copy, Clone)]
-    struct Circuit<F: Field> {
-        a: Option<F>,
-        b: Option<F>,
-        num_constraints: usize,
-        num_variables: usize,
-    }
+        // The count does not depend on whether the key and inputs match, so
+        // larger keys can be padded with arbitrary points.
+        for num_inputs in [1, 4] {
+            vk.gamma_abc_g1.resize(num_inputs + 1, vk.gamma_abc_g1[1]);
+            let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+            let input_gadget = <TestSNARKGadget as SNARKGadget<
+                <MNT4 as Pairing>::ScalarField,
+                <MNT4 as Pairing>::BaseField,
+                TestSNARK,
+            >>::InputVar::new_witness(ns!(cs, "new_input"), || {
+                Ok(vec![a * b; num_inputs])
+            })
+            .unwrap();
+            let proof_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::ProofVar::new_witness(ns!(cs, "alloc_proof"), || Ok(&proof))
+                .unwrap();
+            let vk_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::VerifyingKeyVar::new_witness(ns!(cs, "alloc_vk"), || Ok(&vk))
+                .unwrap();
 
-    impl<ConstraintF: Field> ConstraintSynthesizer<ConstraintF> for Circuit<ConstraintF> {
-        fn generate_constraints(
-            self,
-            cs: ConstraintSystemRef<ConstraintF>,
-        ) -> Result<(), SynthesisError> {
-            let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
-            let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
-            let c = cs.new_input_variable(|| {
-                let mut a = self.a.ok_or(SynthesisError::AssignmentMissing)?;
-                let b = self.b.ok_or(SynthesisError::AssignmentMissing)?;
+            let constraints_before = cs.num_constraints();
+            TestSNARKGadget::verify(&vk_gadget, &input_gadget, &proof_gadget).unwrap();
+            let actual = cs.num_constraints() - constraints_before;
 
-                a.mul_assign(&b);
-                Ok(a)
-            })?;
+            let estimate = TestSNARKGadget::estimated_constraints(&vk);
+            assert!(
+                estimate.abs_diff(actual) * 100 <= actual,
+                "estimated {} constraints, but verification took {}",
+                estimate,
+                actual
+            );
+        }
+    }
 
-            for _ in 0..(self.num_variables - 3) {
-                let _ =
-                    cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
-            }
+    #[test]
+    fn groth16_select_gamma_abc_test() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let circ = Circuit::<MNT4Fr> {
+            a: None,
+            b: None,
+            num_constraints: 10,
+            num_variables: 5,
+        };
+        let (_, mut vk) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+        // Five elements, which is not a power of two.
+        for _ in 0..3 {
+            let g = <MNT4 as Pairing>::G1::rand(&mut rng).into_affine();
+            vk.gamma_abc_g1.push(g);
+        }
+        let pvk = TestSNARK::process_vk(&vk).unwrap();
 
-            for _ in 0..self.num_constraints {
-                cs.enforce_r1cs_constraint(|| lc!() + a, || lc!() + b, || lc!() + c)
-                    .unwrap();
+        for index in 0..9usize {
+            let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+            let pvk_gadget = <TestSNARKGadget as SNARKGadget<
+                <MNT4 as Pairing>::ScalarField,
+                <MNT4 as Pairing>::BaseField,
+                TestSNARK,
+            >>::ProcessedVerifyingKeyVar::new_witness(
+                ns!(cs, "alloc_pvk"), || Ok(pvk.clone())
+            )
+            .unwrap();
+            let index_bits = Vec::<Boolean<MNT6Fr>>::new_witness(ns!(cs, "index"), || {
+                Ok((0..4).map(|j| (index >> j) & 1 == 1).collect::<Vec<_>>())
+            })
+            .unwrap();
+
+            let selected = pvk_gadget.select_gamma_abc(&index_bits).unwrap();
+            if index < vk.gamma_abc_g1.len() {
+                assert!(cs.is_satisfied().unwrap());
+                assert_eq!(
+                    selected.value().unwrap().into_affine(),
+                    vk.gamma_abc_g1[index]
+                );
+            } else {
+                assert!(!cs.is_satisfied().unwrap());
             }
-            Ok(())
         }
     }
 
-    type TestSNARK = Groth16<MNT4>;
-    type TestSNARKGadget = Groth16VerifierGadget<MNT4, MNT4PairingVar>;
-
     #[test]
-    fn groth16_snark_test() {
+    fn groth16_wrong_input_count_test() {
         let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
         let a = MNT4Fr::rand(&mut rng);
         let b = MNT4Fr::rand(&mut rng);
-        let mut c = a;
-        c.mul_assign(&b);
-
         let circ = Circuit {
-            a: Some(a.clone()),
-            b: Some(b.clone()),
+            a: Some(a),
+            b: Some(b),
             num_constraints: 100,
             num_variables: 25,
         };
-
         let (pk, vk) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+        let proof = TestSNARK::prove(&pk, circ, &mut rng).unwrap();
 
-        let proof = TestSNARK::prove(&pk, circ.clone(), &mut rng).unwrap();
-
-        assert!(
-            TestSNARK::verify(&vk, &vec![c], &proof).unwrap(),
-            "The native verification check fails."
-        );
-
-        let cs_sys = ConstraintSystem::<MNT6Fr>::new();
-        let cs = ConstraintSystemRef::new(cs_sys);
-
-        let input_gadget = <TestSNARKGadget as SNARKGadget<
-            <MNT4 as Pairing>::ScalarField,
-            <MNT4 as Pairing>::BaseField,
-            TestSNARK,
-        >>::InputVar::new_input(ns!(cs, "new_input"), || Ok(vec![c]))
-        .unwrap();
+        let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+        let input_gadget =
+            <TestSNARKGadget as SNARKGadget<
+                <MNT4 as Pairing>::ScalarField,
+                <MNT4 as Pairing>::BaseField,
+                TestSNARK,
+            >>::InputVar::new_input(ns!(cs, "new_input"), || Ok(vec![a * b, a]))
+            .unwrap();
         let proof_gadget = <TestSNARKGadget as SNARKGadget<
             <MNT4 as Pairing>::ScalarField,
             <MNT4 as Pairing>::BaseField,
@@ -551,41 +3624,277 @@ mod test {
             <MNT4 as Pairing>::ScalarField,
             <MNT4 as Pairing>::BaseField,
             TestSNARK,
-        >>::VerifyingKeyVar::new_constant(ns!(cs, "alloc_vk"), vk.clone())
+        >>::VerifyingKeyVar::new_constant(ns!(cs, "alloc_vk"), vk)
         .unwrap();
-        <TestSNARKGadget as SNARKGadget<
+
+        assert!(<TestSNARKGadget as SNARKGadget<
             <MNT4 as Pairing>::ScalarField,
             <MNT4 as Pairing>::BaseField,
             TestSNARK,
         >>::verify(&vk_gadget, &input_gadget, &proof_gadget)
-        .unwrap()
-        .enforce_equal(&Boolean::constant(true))
-        .unwrap();
+        .is_err());
+    }
 
-        assert!(
-            cs.is_satisfied().unwrap(),
-            "Constraints not satisfied: {}",
-            cs.which_is_unsatisfied().unwrap().unwrap_or_default()
-        );
+    #[test]
+    fn groth16_verify_with_precomputed_tables_test() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let a = MNT4Fr::rand(&mut rng);
+        let b = MNT4Fr::rand(&mut rng);
+        let circ = Circuit {
+            a: Some(a),
+            b: Some(b),
+            num_constraints: 100,
+            num_variables: 25,
+        };
+        let (pk, vk) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+        let proof = TestSNARK::prove(&pk, circ, &mut rng).unwrap();
 
-        let pvk = TestSNARK::process_vk(&vk).unwrap();
-        let pvk_gadget = <TestSNARKGadget as SNARKGadget<
+        for (c, expected) in [(a * b, true), (a, false)] {
+            let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+            let input_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::InputVar::new_input(ns!(cs, "new_input"), || Ok(vec![c]))
+                .unwrap();
+            let proof_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::ProofVar::new_witness(ns!(cs, "alloc_proof"), || Ok(&proof))
+                .unwrap();
+            let vk_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::VerifyingKeyVar::new_witness(ns!(cs, "alloc_vk"), || Ok(&vk))
+                .unwrap();
+
+            let pvk_gadget = vk_gadget.prepare().unwrap();
+            assert!(TestSNARKGadget::verify_with_precomputed_tables(
+                &pvk_gadget,
+                &input_gadget,
+                &proof_gadget
+            )
+            .is_err());
+
+            let pvk_gadget = vk_gadget.prepare_with_tables().unwrap();
+            let with_tables = TestSNARKGadget::verify_with_precomputed_tables(
+                &pvk_gadget,
+                &input_gadget,
+                &proof_gadget,
+            )
+            .unwrap();
+            let without_tables = TestSNARKGadget::verify_with_processed_vk(
+                &pvk_gadget,
+                &input_gadget,
+                &proof_gadget,
+            )
+            .unwrap();
+
+            assert_eq!(with_tables.value().unwrap(), expected);
+            assert_eq!(without_tables.value().unwrap(), expected);
+            assert!(cs.is_satisfied().unwrap());
+        }
+    }
+
+    #[test]
+    fn groth16_vk_hash_test() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let circ = Circuit {
+            a: Some(MNT4Fr::rand(&mut rng)),
+            b: Some(MNT4Fr::rand(&mut rng)),
+            num_constraints: 100,
+            num_variables: 25,
+        };
+        let (_, vk) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+
+        let config = batch_sponge_config::<MNT6Fr>();
+        let native_hash: MNT6Fr = vk.hash(&mut PoseidonSponge::new(&config));
+
+        let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+        let vk_gadget = <TestSNARKGadget as SNARKGadget<
             <MNT4 as Pairing>::ScalarField,
             <MNT4 as Pairing>::BaseField,
             TestSNARK,
-        >>::ProcessedVerifyingKeyVar::new_constant(
-            ns!(cs, "alloc_pvk"), pvk.clone()
-        )
+        >>::VerifyingKeyVar::new_witness(ns!(cs, "alloc_vk"), || Ok(&vk))
         .unwrap();
-        TestSNARKGadget::verify_with_processed_vk(&pvk_gadget, &input_gadget, &proof_gadget)
+        let hash = vk_gadget
+            .hash(&mut PoseidonSpongeVar::new(cs.clone(), &config))
+            .unwrap();
+
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(hash.value().unwrap(), native_hash);
+    }
+
+    /// Verifies a BLS12-377 proof in a circuit over its base field, where the
+    /// 253-bit public inputs are emulated in the 377-bit constraint field.
+    #[test]
+    fn groth16_emulated_inputs_bls12_377_test() {
+        use ark_bls12_377::{
+            constraints::PairingVar as Bls12_377PairingVar, Bls12_377, Fq as Bls12_377Fq,
+            Fr as Bls12_377Fr,
+        };
+
+        type Snark = Groth16<Bls12_377>;
+        type Gadget = Groth16VerifierGadget<Bls12_377, Bls12_377PairingVar>;
+
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let a = Bls12_377Fr::rand(&mut rng);
+        let b = Bls12_377Fr::rand(&mut rng);
+        let circ = Circuit {
+            a: Some(a),
+            b: Some(b),
+            num_constraints: 10,
+            num_variables: 5,
+        };
+        let (pk, vk) = Snark::circuit_specific_setup(circ, &mut rng).unwrap();
+        let pvk = Snark::process_vk(&vk).unwrap();
+        let proof = Snark::prove(&pk, circ, &mut rng).unwrap();
+
+        let cs = ConstraintSystem::<Bls12_377Fq>::new_ref();
+        let input_gadget =
+            FieldInputVar::new_input(ns!(cs, "new_input"), || Ok(vec![a * b])).unwrap();
+        let proof_gadget =
+            <Gadget as SNARKGadget<Bls12_377Fr, Bls12_377Fq, Snark>>::ProofVar::new_witness(
+                ns!(cs, "alloc_proof"),
+                || Ok(proof),
+            )
+            .unwrap();
+        let pvk_gadget =
+            <Gadget as SNARKGadget<Bls12_377Fr, Bls12_377Fq, Snark>>::ProcessedVerifyingKeyVar::new_constant(
+                ns!(cs, "alloc_pvk"),
+                pvk,
+            )
+            .unwrap();
+
+        Gadget::verify_with_field_inputs(&pvk_gadget, &input_gadget, &proof_gadget)
             .unwrap()
             .enforce_equal(&Boolean::constant(true))
             .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+    }
 
-        assert!(
-            cs.is_satisfied().unwrap(),
-            "Constraints not satisfied: {}",
-            cs.which_is_unsatisfied().unwrap().unwrap_or_default()
-        );
+    #[test]
+    fn groth16_verify_with_commitment_test() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let a = MNT4Fr::rand(&mut rng);
+        let b = MNT4Fr::rand(&mut rng);
+        let circ = Circuit {
+            a: Some(a),
+            b: Some(b),
+            num_constraints: 100,
+            num_variables: 25,
+        };
+        let (pk, vk) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+        let proof = TestSNARK::prove(&pk, circ, &mut rng).unwrap();
+
+        // Commit to the only public input instead of exposing it: the
+        // commitment key is its `gamma_abc_g1` base, and the verifying key
+        // keeps no public inputs.
+        let commitment_base = vk.gamma_abc_g1[1];
+        let d = (commitment_base * (a * b)).into_affine();
+        let mut committed_vk = vk.clone();
+        committed_vk.gamma_abc_g1.truncate(1);
+        let pvk = TestSNARK::process_vk(&committed_vk).unwrap();
+
+        for (opening, expected) in [(a * b, true), (a, false)] {
+            let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+            let input_gadget =
+                <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::InputVar::new_input(ns!(cs, "new_input"), || Ok(vec![]))
+                .unwrap();
+            let proof_gadget = ProofWithCommitmentVar {
+                proof: <TestSNARKGadget as SNARKGadget<
+                    <MNT4 as Pairing>::ScalarField,
+                    <MNT4 as Pairing>::BaseField,
+                    TestSNARK,
+                >>::ProofVar::new_witness(ns!(cs, "alloc_proof"), || {
+                    Ok(&proof)
+                })
+                .unwrap(),
+                d: MNT4G1Var::new_witness(ns!(cs, "alloc_d"), || Ok(d)).unwrap(),
+            };
+            let pvk_gadget = <TestSNARKGadget as SNARKGadget<
+                <MNT4 as Pairing>::ScalarField,
+                <MNT4 as Pairing>::BaseField,
+                TestSNARK,
+            >>::ProcessedVerifyingKeyVar::new_constant(
+                ns!(cs, "alloc_pvk"), &pvk
+            )
+            .unwrap();
+            let commitment_key = vec![MNT4G1Var::constant(commitment_base.into_group())];
+            let opening_gadget =
+                FieldInputVar::new_input(ns!(cs, "opening"), || Ok(vec![opening])).unwrap();
+
+            let result =
+                Groth16CommitmentVerifierGadget::<MNT4, MNT4PairingVar>::verify_with_commitment(
+                    &pvk_gadget,
+                    &input_gadget,
+                    &proof_gadget,
+                    &commitment_key,
+                    &opening_gadget,
+                )
+                .unwrap();
+            assert_eq!(result.value().unwrap(), expected);
+            assert!(cs.is_satisfied().unwrap());
+        }
+    }
+
+    #[test]
+    fn groth16_vk_from_bytes_test() {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+        let circ = Circuit {
+            a: Some(MNT4Fr::rand(&mut rng)),
+            b: Some(MNT4Fr::rand(&mut rng)),
+            num_constraints: 100,
+            num_variables: 25,
+        };
+        let (_, vk) = TestSNARK::circuit_specific_setup(circ, &mut rng).unwrap();
+
+        let cs = ConstraintSystem::<MNT6Fr>::new_ref();
+        let vk_bytes = <TestSNARKGadget as SNARKGadget<
+            <MNT4 as Pairing>::ScalarField,
+            <MNT4 as Pairing>::BaseField,
+            TestSNARK,
+        >>::VerifyingKeyVar::new_constant(ns!(cs, "alloc_vk"), &vk)
+        .unwrap()
+        .to_bytes_le()
+        .unwrap()
+        .value()
+        .unwrap();
+        let bytes =
+            Vec::<UInt8<MNT6Fr>>::new_witness(ns!(cs, "alloc_bytes"), || Ok(vk_bytes.as_slice()))
+                .unwrap();
+
+        assert!(VerifyingKeyVar::<MNT4, MNT4PairingVar>::from_bytes_le(
+            ns!(cs, "too_few"),
+            &bytes,
+            vk.gamma_abc_g1.len() + 1
+        )
+        .is_err());
+        assert!(VerifyingKeyVar::<MNT4, MNT4PairingVar>::from_bytes_le(
+            ns!(cs, "uneven"),
+            &bytes[1..],
+            vk.gamma_abc_g1.len()
+        )
+        .is_err());
+
+        let vk_gadget = VerifyingKeyVar::<MNT4, MNT4PairingVar>::from_bytes_le(
+            ns!(cs, "from_bytes"),
+            &bytes,
+            vk.gamma_abc_g1.len(),
+        )
+        .unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(vk_gadget.alpha_g1.value().unwrap(), vk.alpha_g1);
+        assert_eq!(vk_gadget.delta_g2.value().unwrap(), vk.delta_g2);
+        assert_eq!(vk_gadget.gamma_abc_g1.value().unwrap(), vk.gamma_abc_g1);
     }
 }
@@ -1,11 +1,18 @@
-use ark_crypto_primitives::sponge::Absorb;
-use ark_ec::pairing::Pairing;
-use ark_ff::PrimeField;
+use crate::{interop::point_from_coordinates, Groth16, Groth16Error};
+use ark_crypto_primitives::sponge::{Absorb, CryptographicSponge};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, One, PrimeField, UniformRand, Zero};
+use ark_relations::gr1cs::{ConstraintSynthesizer, SynthesisError};
 use ark_serialize::*;
-use ark_std::vec::Vec;
+use ark_std::{rand::Rng, vec::Vec};
+use sha2::{Digest, Sha256};
 
 /// A proof in the Groth16 SNARK.
-#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+///
+/// With [`Validate::Yes`], deserialization only accepts the canonical
+/// encoding of each point, so that a proof cannot be re-encoded into other
+/// bytes that are accepted as well.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize)]
 pub struct Proof<E: Pairing> {
     /// The `A` element in `G1`.
     pub a: E::G1Affine,
@@ -15,6 +22,70 @@ pub struct Proof<E: Pairing> {
     pub c: E::G1Affine,
 }
 
+/// A reader that keeps a copy of every byte read through it.
+struct RecordingReader<R> {
+    inner: R,
+    bytes: Vec<u8>,
+}
+
+impl<R: Read> Read for RecordingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> ark_std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.bytes.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// Deserializes a `T` and, with [`Validate::Yes`], rejects it unless the
+/// bytes read are exactly its serialization, e.g. if a coordinate is not
+/// reduced or the point at infinity has nonzero coordinate bytes.
+fn deserialize_canonical<T, R>(
+    reader: R,
+    compress: Compress,
+    validate: Validate,
+) -> Result<T, SerializationError>
+where
+    T: CanonicalSerialize + CanonicalDeserialize,
+    R: Read,
+{
+    if validate == Validate::No {
+        return T::deserialize_with_mode(reader, compress, validate);
+    }
+    let mut reader = RecordingReader {
+        inner: reader,
+        bytes: Vec::new(),
+    };
+    let value = T::deserialize_with_mode(&mut reader, compress, validate)?;
+    let mut canonical = Vec::with_capacity(reader.bytes.len());
+    value.serialize_with_mode(&mut canonical, compress)?;
+    if canonical != reader.bytes {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(value)
+}
+
+impl<E: Pairing> Valid for Proof<E> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.a.check()?;
+        self.b.check()?;
+        self.c.check()
+    }
+}
+
+impl<E: Pairing> CanonicalDeserialize for Proof<E> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Ok(Self {
+            a: deserialize_canonical(&mut reader, compress, validate)?,
+            b: deserialize_canonical(&mut reader, compress, validate)?,
+            c: deserialize_canonical(&mut reader, compress, validate)?,
+        })
+    }
+}
+
 impl<E: Pairing> Default for Proof<E> {
     fn default() -> Self {
         Self {
@@ -25,6 +96,166 @@ impl<E: Pairing> Default for Proof<E> {
     }
 }
 
+impl<E: Pairing> Proof<E> {
+    /// Deserializes a proof from untrusted bytes, checking that `a` and `c`
+    /// lie in the prime-order subgroup of `E::G1` and `b` in that of `E::G2`.
+    ///
+    /// This is the same as `deserialize_with_mode(reader, compress,
+    /// Validate::Yes)`, which `deserialize_compressed` and
+    /// `deserialize_uncompressed` also use. The `*_unchecked` variants skip
+    /// the subgroup checks and are only safe for proofs from a trusted source,
+    /// e.g. ones created by this process: [`Groth16::verify_proof`] does not
+    /// repeat the checks, and on curves with a non-trivial cofactor it should
+    /// not be relied upon to reject points outside the subgroup.
+    ///
+    /// [`Groth16::verify_proof`]: crate::Groth16::verify_proof
+    pub fn deserialize_checked<R: Read>(
+        reader: R,
+        compress: Compress,
+    ) -> Result<Self, SerializationError> {
+        Self::deserialize_with_mode(reader, compress, Validate::Yes)
+    }
+
+    /// Deserializes a proof from untrusted bytes like
+    /// [`Proof::deserialize_checked`], additionally requiring that `bytes`
+    /// hold nothing but the proof.
+    ///
+    /// Every proof has exactly one encoding that this accepts for a given
+    /// `compress`, so the bytes can be used to identify the proof, e.g. as
+    /// a nullifier in an on-chain verifier.
+    pub fn deserialize_strict(
+        bytes: &[u8],
+        compress: Compress,
+    ) -> Result<Self, SerializationError> {
+        let mut reader = bytes;
+        let proof = Self::deserialize_checked(&mut reader, compress)?;
+        if !reader.is_empty() {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(proof)
+    }
+
+    /// Returns a proof of the same statement as `self` under `vk`, computed
+    /// with the rerandomization factors `r1` and `r2` as in figure 1 of
+    /// [\[BKSV20\]](https://eprint.iacr.org/2020/811):
+    ///
+    /// `A' = (1/r1) A`, `B' = r1 B + r1 r2 delta_g2`, and `C' = C + r2 A`.
+    ///
+    /// Returns `None` if `r1` is zero. The result is only unlinkable to
+    /// `self` if `r1` and `r2` are nonzero and uniformly random; see
+    /// [`Groth16::rerandomize_proof`](crate::Groth16::rerandomize_proof),
+    /// which samples them.
+    pub fn rerandomize(
+        &self,
+        vk: &VerifyingKey<E>,
+        r1: E::ScalarField,
+        r2: E::ScalarField,
+    ) -> Option<Self> {
+        let r1_inverse = r1.inverse()?;
+
+        let a = self.a * r1_inverse;
+        let b = self.b * r1 + vk.delta_g2 * (r1 * r2);
+        let c = self.a * r2 + self.c;
+
+        Some(Self {
+            a: a.into_affine(),
+            b: b.into_affine(),
+            c: c.into_affine(),
+        })
+    }
+}
+
+/// Appends the affine coordinates of `p` to `dest` as base prime field
+/// elements, `x` first. The point at infinity is written as `(0, 0)`.
+fn push_coordinates<G: AffineRepr>(p: &G, dest: &mut Vec<<G::BaseField as Field>::BasePrimeField>) {
+    let (x, y) = p.xy().unwrap_or_default();
+    dest.extend(x.to_base_prime_field_elements());
+    dest.extend(y.to_base_prime_field_elements());
+}
+
+/// Reads a point written by [`push_coordinates`] from the front of
+/// `coordinates`, checking only that it lies on the curve.
+fn pop_coordinates<G: AffineRepr>(
+    coordinates: &mut &[<G::BaseField as Field>::BasePrimeField],
+) -> Result<G, SerializationError> {
+    let degree = G::BaseField::extension_degree() as usize;
+    if coordinates.len() < 2 * degree {
+        return Err(SerializationError::InvalidData);
+    }
+    let (point, rest) = coordinates.split_at(2 * degree);
+    *coordinates = rest;
+
+    let x = G::BaseField::from_base_prime_field_elems(point[..degree].iter().copied())
+        .ok_or(SerializationError::InvalidData)?;
+    let y = G::BaseField::from_base_prime_field_elems(point[degree..].iter().copied())
+        .ok_or(SerializationError::InvalidData)?;
+    if x.is_zero() && y.is_zero() {
+        return Ok(G::zero());
+    }
+    point_from_coordinates(x, y).ok_or(SerializationError::InvalidData)
+}
+
+impl<E> Proof<E>
+where
+    E: Pairing,
+    <E::G1Affine as AffineRepr>::BaseField: Field<BasePrimeField = E::BaseField>,
+    <E::G2Affine as AffineRepr>::BaseField: Field<BasePrimeField = E::BaseField>,
+{
+    /// Returns the affine coordinates of `a`, `b` and `c`, in that order,
+    /// with `x` before `y` for each point.
+    ///
+    /// The coordinates of `b` lie in an extension of `E::BaseField` and are
+    /// written as their coefficients over it, lowest degree first. The point
+    /// at infinity, which has no affine coordinates, is written as `(0, 0)`.
+    pub fn to_field_coordinates(&self) -> Vec<E::BaseField> {
+        let mut coordinates = Vec::new();
+        push_coordinates(&self.a, &mut coordinates);
+        push_coordinates(&self.b, &mut coordinates);
+        push_coordinates(&self.c, &mut coordinates);
+        coordinates
+    }
+
+    /// Reads a proof from coordinates written by
+    /// [`Proof::to_field_coordinates`].
+    ///
+    /// Fails if `coordinates` has the wrong length, or if any of the points
+    /// is not on the curve or outside the prime-order subgroup.
+    pub fn try_from_field_coordinates(
+        coordinates: &[E::BaseField],
+    ) -> Result<Self, SerializationError> {
+        let mut rest = coordinates;
+        let proof = Self {
+            a: pop_coordinates(&mut rest)?,
+            b: pop_coordinates(&mut rest)?,
+            c: pop_coordinates(&mut rest)?,
+        };
+        if !rest.is_empty() {
+            return Err(SerializationError::InvalidData);
+        }
+        proof.check()?;
+        Ok(proof)
+    }
+}
+
+impl<E> Absorb for Proof<E>
+where
+    E: Pairing,
+    E::G1Affine: Absorb,
+    E::G2Affine: Absorb,
+{
+    fn to_sponge_bytes(&self, dest: &mut Vec<u8>) {
+        self.a.to_sponge_bytes(dest);
+        self.b.to_sponge_bytes(dest);
+        self.c.to_sponge_bytes(dest);
+    }
+
+    fn to_sponge_field_elements<F: PrimeField>(&self, dest: &mut Vec<F>) {
+        self.a.to_sponge_field_elements(dest);
+        self.b.to_sponge_field_elements(dest);
+        self.c.to_sponge_field_elements(dest);
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 
 /// A verification key in the Groth16 SNARK.
@@ -82,17 +313,376 @@ where
     }
 }
 
+/// A public input given as its term `x * base` of the input accumulator,
+/// together with a Schnorr proof of knowledge of `x`, as accepted by
+/// [`Groth16::verify_proof_with_group_inputs`](crate::Groth16::verify_proof_with_group_inputs).
+///
+/// The proof of knowledge is what binds the point to a scalar input: a bare
+/// point can be chosen by the prover to cancel the rest of the accumulator,
+/// after which `(alpha_g1, beta_g2, 0)` verifies for any statement.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct GroupInput<E: Pairing> {
+    /// The term `x * base` in `G1`.
+    pub point: E::G1Affine,
+    /// The commitment `k * base` of the proof of knowledge, for a random `k`.
+    pub pok_commitment: E::G1Affine,
+    /// The response `k + c * x` of the proof of knowledge, for the challenge
+    /// `c` derived from `base`, `point`, and `pok_commitment`.
+    pub pok_response: E::ScalarField,
+}
+
+impl<E: Pairing> GroupInput<E> {
+    /// Returns the group input of `x` for `base`, e.g. for
+    /// [`VerifyingKey::input_base`], with a proof of knowledge of `x`.
+    pub fn new(base: E::G1Affine, x: E::ScalarField, rng: &mut impl Rng) -> Self {
+        let point = (base * x).into_affine();
+        let k = E::ScalarField::rand(rng);
+        let pok_commitment = (base * k).into_affine();
+        let c = Self::challenge(&base, &point, &pok_commitment);
+        Self {
+            point,
+            pok_commitment,
+            pok_response: k + c * x,
+        }
+    }
+
+    /// Returns whether the proof of knowledge of `self` holds for `base`,
+    /// i.e. whether the prover knows the `x` with `point = x * base`.
+    pub fn check(&self, base: &E::G1Affine) -> bool {
+        let c = Self::challenge(base, &self.point, &self.pok_commitment);
+        *base * self.pok_response == self.pok_commitment.into_group() + self.point * c
+    }
+
+    fn challenge(
+        base: &E::G1Affine,
+        point: &E::G1Affine,
+        pok_commitment: &E::G1Affine,
+    ) -> E::ScalarField {
+        let mut bytes = b"groth16-group-input".to_vec();
+        base.serialize_compressed(&mut bytes).unwrap();
+        point.serialize_compressed(&mut bytes).unwrap();
+        pok_commitment.serialize_compressed(&mut bytes).unwrap();
+        E::ScalarField::from_le_bytes_mod_order(&Sha256::digest(&bytes))
+    }
+}
+
+impl<E: Pairing> VerifyingKey<E> {
+    /// Returns the element of `gamma_abc_g1` that the public input at
+    /// position `index` is multiplied by, or `None` if there is no such
+    /// input.
+    ///
+    /// For a public input `x`, `x * input_base(index)` is its term of the
+    /// input accumulator. A point that claims to be such a term is only
+    /// sound as an input if it is bound to a known `x`, as by the proof of
+    /// knowledge of a [`GroupInput`]; otherwise it must come from a trusted
+    /// source, since a prover that picks it can forge proofs.
+    pub fn input_base(&self, index: usize) -> Option<E::G1Affine> {
+        self.gamma_abc_g1.get(index + 1).copied()
+    }
+
+    /// Returns the approximate number of heap bytes owned by `self`, i.e. the
+    /// allocated capacity of `gamma_abc_g1`.
+    pub fn size_in_memory(&self) -> usize {
+        self.gamma_abc_g1.capacity() * core::mem::size_of::<E::G1Affine>()
+    }
+
+    /// Returns the SHA-256 digest of the compressed serialization of `self`.
+    ///
+    /// The serialization is canonical, so equal keys have equal fingerprints
+    /// regardless of how they were built or where they are stored, and the
+    /// fingerprint is stable across runs and platforms. It can therefore be
+    /// used as a cache key, e.g. for prepared verifying keys.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let mut bytes = Vec::with_capacity(self.compressed_size());
+        self.serialize_compressed(&mut bytes).unwrap();
+        Sha256::digest(&bytes).into()
+    }
+
+    /// Compares `self` with `other` field by field, e.g. to audit which
+    /// elements an MPC contribution changed.
+    pub fn diff(&self, other: &Self) -> VkDiff {
+        let num_common = self.gamma_abc_g1.len().min(other.gamma_abc_g1.len());
+        let num_max = self.gamma_abc_g1.len().max(other.gamma_abc_g1.len());
+        let gamma_abc_g1 = (0..num_common)
+            .filter(|&i| self.gamma_abc_g1[i] != other.gamma_abc_g1[i])
+            .chain(num_common..num_max)
+            .collect();
+        VkDiff {
+            alpha_g1: self.alpha_g1 != other.alpha_g1,
+            beta_g2: self.beta_g2 != other.beta_g2,
+            gamma_g2: self.gamma_g2 != other.gamma_g2,
+            delta_g2: self.delta_g2 != other.delta_g2,
+            gamma_abc_g1,
+        }
+    }
+
+    /// Returns the number of public inputs, the curve, and the serialized
+    /// sizes of `self`, e.g. for tools that inspect keys.
+    pub fn summary(&self) -> VkSummary {
+        VkSummary {
+            curve: core::any::type_name::<E>(),
+            num_public_inputs: self.gamma_abc_g1.len().saturating_sub(1),
+            compressed_size: self.compressed_size(),
+            uncompressed_size: self.uncompressed_size(),
+        }
+    }
+
+    /// Returns `self` with `gamma_abc_extra` appended to `gamma_abc_g1`, i.e.
+    /// a verifying key that takes one more public input, multiplied by
+    /// `gamma_abc_extra`.
+    ///
+    /// **The element is trusted, not checked.** The result is only the
+    /// verifying key of the extended circuit if `gamma_abc_extra` is
+    /// `(beta * u(t) + alpha * v(t) + w(t)) / gamma` for the QAP polynomials
+    /// `u`, `v`, `w` of the new input, under the same trapdoor as `self`, and
+    /// if the other elements of `self` are those of the extended circuit as
+    /// well. Neither can be computed or checked from public parameters: the
+    /// element has to come from the party that ran the setup. A wrong element
+    /// makes honest proofs fail to verify, and one chosen by the prover can
+    /// let it forge proofs, so it must be obtained from a trusted source.
+    pub fn with_appended_input(&self, gamma_abc_extra: E::G1Affine) -> Self {
+        let mut vk = self.clone();
+        vk.gamma_abc_g1.push(gamma_abc_extra);
+        vk
+    }
+
+    /// Returns a smaller verifying key for the statements in which the public
+    /// inputs at the positions of `fixed_inputs` have the given values.
+    ///
+    /// The contributions of the fixed inputs are folded into
+    /// `gamma_abc_g1[0]` and their elements are dropped, so proofs for `self`
+    /// verify against the returned key with only the remaining inputs, in
+    /// their original order.
+    ///
+    /// Returns [`Groth16Error::InvalidInputIndex`] if a position is beyond
+    /// the inputs of `self` or is given more than once.
+    pub fn specialize(
+        &self,
+        fixed_inputs: &[(usize, E::ScalarField)],
+    ) -> Result<Self, Groth16Error> {
+        let num_inputs = self
+            .gamma_abc_g1
+            .len()
+            .checked_sub(1)
+            .ok_or(Groth16Error::MalformedVerifyingKey)?;
+        let mut fixed = vec![None; num_inputs];
+        for &(index, value) in fixed_inputs {
+            let slot = fixed
+                .get_mut(index)
+                .ok_or(Groth16Error::InvalidInputIndex { index })?;
+            if slot.replace(value).is_some() {
+                return Err(Groth16Error::InvalidInputIndex { index });
+            }
+        }
+
+        let mut bases = vec![self.gamma_abc_g1[0]];
+        let mut scalars = vec![E::ScalarField::one()];
+        let mut gamma_abc_g1 = vec![E::G1Affine::zero()];
+        for (base, value) in self.gamma_abc_g1[1..].iter().zip(fixed) {
+            match value {
+                Some(value) => {
+                    bases.push(*base);
+                    scalars.push(value);
+                },
+                None => gamma_abc_g1.push(*base),
+            }
+        }
+        gamma_abc_g1[0] = E::G1::msm_unchecked(&bases, &scalars).into_affine();
+
+        Ok(Self {
+            alpha_g1: self.alpha_g1,
+            beta_g2: self.beta_g2,
+            gamma_g2: self.gamma_g2,
+            delta_g2: self.delta_g2,
+            gamma_abc_g1,
+        })
+    }
+}
+
+/// An overview of a verifying key, as returned by [`VerifyingKey::summary`].
+///
+/// Its [`Display`](core::fmt::Display) implementation prints it on one line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VkSummary {
+    /// The name of the pairing type the key is over, as given by
+    /// [`core::any::type_name`].
+    pub curve: &'static str,
+    /// The number of public inputs, without the constant `1`.
+    pub num_public_inputs: usize,
+    /// The length of the compressed serialization in bytes.
+    pub compressed_size: usize,
+    /// The length of the uncompressed serialization in bytes.
+    pub uncompressed_size: usize,
+}
+
+impl core::fmt::Display for VkSummary {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "verifying key over {} with {} public inputs, {} bytes compressed, {} bytes uncompressed",
+            self.curve, self.num_public_inputs, self.compressed_size, self.uncompressed_size
+        )
+    }
+}
+
+/// The fields in which two verifying keys differ, as reported by
+/// [`VerifyingKey::diff`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VkDiff {
+    /// Whether `alpha_g1` differs.
+    pub alpha_g1: bool,
+    /// Whether `beta_g2` differs.
+    pub beta_g2: bool,
+    /// Whether `gamma_g2` differs.
+    pub gamma_g2: bool,
+    /// Whether `delta_g2` differs.
+    pub delta_g2: bool,
+    /// The indices at which `gamma_abc_g1` differs, in increasing order,
+    /// including every index that only one of the keys has.
+    pub gamma_abc_g1: Vec<usize>,
+}
+
+impl VkDiff {
+    /// Returns whether the keys are equal.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns whether `delta_g2` is the only difference, as expected after a
+    /// phase-2 contribution.
+    pub fn is_delta_only(&self) -> bool {
+        *self
+            == Self {
+                delta_g2: true,
+                ..Self::default()
+            }
+    }
+}
+
+impl<E: Pairing> VerifyingKey<E> {
+    /// Returns a builder for a verifying key assembled from its components,
+    /// e.g. after importing them from another tool.
+    pub fn builder() -> VerifyingKeyBuilder<E> {
+        VerifyingKeyBuilder {
+            alpha_g1: None,
+            beta_g2: None,
+            gamma_g2: None,
+            delta_g2: None,
+            gamma_abc_g1: None,
+        }
+    }
+}
+
+/// A builder for a [`VerifyingKey`], created by [`VerifyingKey::builder`].
+///
+/// Unlike setting the public fields of [`VerifyingKey`] directly,
+/// [`Self::build`] validates the key, so that invalid keys are rejected before
+/// they reach [`prepare_verifying_key`](crate::prepare_verifying_key).
+#[derive(Clone, Debug)]
+pub struct VerifyingKeyBuilder<E: Pairing> {
+    alpha_g1: Option<E::G1Affine>,
+    beta_g2: Option<E::G2Affine>,
+    gamma_g2: Option<E::G2Affine>,
+    delta_g2: Option<E::G2Affine>,
+    gamma_abc_g1: Option<Vec<E::G1Affine>>,
+}
+
+impl<E: Pairing> VerifyingKeyBuilder<E> {
+    /// Sets [`VerifyingKey::alpha_g1`].
+    pub fn alpha_g1(mut self, alpha_g1: E::G1Affine) -> Self {
+        self.alpha_g1 = Some(alpha_g1);
+        self
+    }
+
+    /// Sets [`VerifyingKey::beta_g2`].
+    pub fn beta_g2(mut self, beta_g2: E::G2Affine) -> Self {
+        self.beta_g2 = Some(beta_g2);
+        self
+    }
+
+    /// Sets [`VerifyingKey::gamma_g2`].
+    pub fn gamma_g2(mut self, gamma_g2: E::G2Affine) -> Self {
+        self.gamma_g2 = Some(gamma_g2);
+        self
+    }
+
+    /// Sets [`VerifyingKey::delta_g2`].
+    pub fn delta_g2(mut self, delta_g2: E::G2Affine) -> Self {
+        self.delta_g2 = Some(delta_g2);
+        self
+    }
+
+    /// Sets [`VerifyingKey::gamma_abc_g1`].
+    pub fn gamma_abc_g1(mut self, gamma_abc_g1: Vec<E::G1Affine>) -> Self {
+        self.gamma_abc_g1 = Some(gamma_abc_g1);
+        self
+    }
+
+    /// Builds the verifying key.
+    ///
+    /// Returns [`Groth16Error::MalformedVerifyingKey`] if a component was not
+    /// set, if `gamma_abc_g1` is empty, or if a point is not on the curve or
+    /// not in the prime-order subgroup.
+    pub fn build(self) -> Result<VerifyingKey<E>, Groth16Error> {
+        let vk = VerifyingKey {
+            alpha_g1: self.alpha_g1.ok_or(Groth16Error::MalformedVerifyingKey)?,
+            beta_g2: self.beta_g2.ok_or(Groth16Error::MalformedVerifyingKey)?,
+            gamma_g2: self.gamma_g2.ok_or(Groth16Error::MalformedVerifyingKey)?,
+            delta_g2: self.delta_g2.ok_or(Groth16Error::MalformedVerifyingKey)?,
+            gamma_abc_g1: self
+                .gamma_abc_g1
+                .ok_or(Groth16Error::MalformedVerifyingKey)?,
+        };
+        if vk.gamma_abc_g1.is_empty() || vk.check().is_err() {
+            return Err(Groth16Error::MalformedVerifyingKey);
+        }
+        Ok(vk)
+    }
+}
+
+impl<E> VerifyingKey<E>
+where
+    E: Pairing,
+    E::G1Affine: Absorb,
+    E::G2Affine: Absorb,
+{
+    /// Hashes `self` to a single field element by absorbing it into `sponge`.
+    ///
+    /// This matches `VerifyingKeyVar::hash` when both use the same sponge
+    /// parameters.
+    pub fn hash<F: PrimeField>(&self, sponge: &mut impl CryptographicSponge) -> F {
+        sponge.absorb(self);
+        sponge.squeeze_field_elements(1)[0]
+    }
+}
+
 /// Preprocessed verification key parameters that enable faster verification
 /// at the expense of larger size in memory.
-#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+///
+/// Everything that only depends on the verification key is computed once by
+/// [`prepare_verifying_key`](crate::prepare_verifying_key): the pairing
+/// `e(alpha, beta)`, and the negated `gamma` and `delta` G2 elements in
+/// prepared form, which already hold the precomputed Miller-loop line
+/// coefficients. Verifying a proof therefore only prepares `A`, `B`, `C`, and
+/// the accumulated public input.
+///
+/// The prepared `G2` elements are not serialized directly, since their
+/// representation depends on the curve. Instead, the serialized form consists
+/// of `vk`, `alpha_g1_beta_g2`, and the negated `gamma` and `delta` `G2`
+/// elements in affine form, from which deserialization rebuilds the prepared
+/// elements. The pairing `alpha_g1_beta_g2` is stored rather than recomputed,
+/// so it is only as trustworthy as the serialized data.
+#[derive(Clone, Debug, PartialEq)]
 pub struct PreparedVerifyingKey<E: Pairing> {
     /// The unprepared verification key.
     pub vk: VerifyingKey<E>,
     /// The element `e(alpha * G, beta * H)` in `E::GT`.
     pub alpha_g1_beta_g2: E::TargetField,
-    /// The element `- gamma * H` in `E::G2`, prepared for use in pairings.
+    /// The element `- gamma * H` in `E::G2`, prepared for use in pairings,
+    /// i.e. with its Miller-loop line coefficients precomputed.
     pub gamma_g2_neg_pc: E::G2Prepared,
-    /// The element `- delta * H` in `E::G2`, prepared for use in pairings.
+    /// The element `- delta * H` in `E::G2`, prepared for use in pairings,
+    /// i.e. with its Miller-loop line coefficients precomputed.
     pub delta_g2_neg_pc: E::G2Prepared,
 }
 
@@ -108,6 +698,58 @@ impl<E: Pairing> From<VerifyingKey<E>> for PreparedVerifyingKey<E> {
     }
 }
 
+impl<E: Pairing> CanonicalSerialize for PreparedVerifyingKey<E> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.vk.serialize_with_mode(&mut writer, compress)?;
+        self.alpha_g1_beta_g2
+            .serialize_with_mode(&mut writer, compress)?;
+        (-self.vk.gamma_g2).serialize_with_mode(&mut writer, compress)?;
+        (-self.vk.delta_g2).serialize_with_mode(&mut writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.vk.serialized_size(compress)
+            + self.alpha_g1_beta_g2.serialized_size(compress)
+            + self.vk.gamma_g2.serialized_size(compress)
+            + self.vk.delta_g2.serialized_size(compress)
+    }
+}
+
+impl<E: Pairing> Valid for PreparedVerifyingKey<E> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.vk.check()?;
+        self.alpha_g1_beta_g2.check()
+    }
+}
+
+impl<E: Pairing> CanonicalDeserialize for PreparedVerifyingKey<E> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let vk = VerifyingKey::<E>::deserialize_with_mode(&mut reader, compress, validate)?;
+        let alpha_g1_beta_g2 =
+            E::TargetField::deserialize_with_mode(&mut reader, compress, validate)?;
+        let gamma_g2_neg = E::G2Affine::deserialize_with_mode(&mut reader, compress, validate)?;
+        let delta_g2_neg = E::G2Affine::deserialize_with_mode(&mut reader, compress, validate)?;
+        if gamma_g2_neg != -vk.gamma_g2 || delta_g2_neg != -vk.delta_g2 {
+            return Err(SerializationError::InvalidData);
+        }
+
+        Ok(Self {
+            vk,
+            alpha_g1_beta_g2,
+            gamma_g2_neg_pc: gamma_g2_neg.into(),
+            delta_g2_neg_pc: delta_g2_neg.into(),
+        })
+    }
+}
+
 impl<E: Pairing> Default for PreparedVerifyingKey<E> {
     fn default() -> Self {
         Self {
@@ -119,6 +761,76 @@ impl<E: Pairing> Default for PreparedVerifyingKey<E> {
     }
 }
 
+/// A [`PreparedVerifyingKey`] for a circuit with exactly `N` public inputs.
+///
+/// The number of inputs is checked once, when the key is built with
+/// [`FixedPvk::new`], so [`Groth16::verify_fixed`](crate::Groth16::verify_fixed)
+/// takes the inputs as an array of length `N` and cannot encounter a
+/// mismatch.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FixedPvk<E: Pairing, const N: usize> {
+    pvk: PreparedVerifyingKey<E>,
+}
+
+impl<E: Pairing, const N: usize> FixedPvk<E, N> {
+    /// Wraps `pvk`, returning [`SynthesisError::Unsatisfiable`] unless it
+    /// expects exactly `N` public inputs, i.e. unless
+    /// `pvk.vk.gamma_abc_g1.len() == N + 1`.
+    pub fn new(pvk: PreparedVerifyingKey<E>) -> Result<Self, SynthesisError> {
+        if pvk.vk.gamma_abc_g1.len() != N + 1 {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        Ok(Self { pvk })
+    }
+
+    /// Returns the wrapped prepared verifying key.
+    pub fn pvk(&self) -> &PreparedVerifyingKey<E> {
+        &self.pvk
+    }
+
+    /// Unwraps the prepared verifying key.
+    pub fn into_inner(self) -> PreparedVerifyingKey<E> {
+        self.pvk
+    }
+}
+
+/// The public inputs of an instance, folded into the input accumulator
+/// `gamma_abc_g1[0] + sum_i public_inputs[i] * gamma_abc_g1[i + 1]` and
+/// prepared for the pairing.
+///
+/// Build it once with [`PreparedInputs::new`] and pass it to
+/// [`Groth16::verify_proof_with_cached_inputs`] to verify any number of
+/// proofs of the same instance without repeating the MSM over
+/// `gamma_abc_g1`.
+///
+/// [`Groth16::verify_proof_with_cached_inputs`]: crate::Groth16::verify_proof_with_cached_inputs
+#[derive(Clone, Debug)]
+pub struct PreparedInputs<E: Pairing> {
+    pub(crate) g_ic: E::G1Affine,
+    pub(crate) g_ic_prepared: E::G1Prepared,
+}
+
+impl<E: Pairing> PreparedInputs<E> {
+    /// Prepares `public_inputs` for verification against `pvk`, returning
+    /// [`SynthesisError::Unsatisfiable`] if their number does not match `pvk`.
+    pub fn new(
+        pvk: &PreparedVerifyingKey<E>,
+        public_inputs: &[E::ScalarField],
+    ) -> Result<Self, SynthesisError> {
+        let g_ic = crate::Groth16::<E>::prepare_inputs(pvk, public_inputs)?.into_affine();
+        Ok(Self {
+            g_ic,
+            g_ic_prepared: g_ic.into(),
+        })
+    }
+
+    /// Returns the input accumulator, as computed by
+    /// [`Groth16::prepare_inputs`](crate::Groth16::prepare_inputs).
+    pub fn point(&self) -> E::G1Affine {
+        self.g_ic
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 
 /// The prover key for for the Groth16 zkSNARK.
@@ -141,3 +853,301 @@ pub struct ProvingKey<E: Pairing> {
     /// The elements `l_i * G` in `E::G1`.
     pub l_query: Vec<E::G1Affine>,
 }
+
+/// The number of group and field operations that proving with a
+/// [`ProvingKey`] performs, as reported by [`ProvingKey::operation_counts`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProvingCost {
+    /// The number of scalar multiplications in `E::G1`, counting every term
+    /// of a multi-scalar multiplication as one.
+    pub g1_scalar_mults: usize,
+    /// The number of scalar multiplications in `E::G2`, counting every term
+    /// of a multi-scalar multiplication as one.
+    pub g2_scalar_mults: usize,
+    /// The size of the QAP evaluation domain the FFTs are performed over.
+    pub fft_size: usize,
+    /// The number of FFTs and inverse FFTs over that domain.
+    pub num_ffts: usize,
+}
+
+impl<E: Pairing> ProvingKey<E> {
+    /// Returns the underlying verification key.
+    ///
+    /// This and the other accessors below give read-only access to the
+    /// components of the key, e.g. for tools that audit it, and return the
+    /// same values as the [`ProvingKeyStorage`](crate::storage::ProvingKeyStorage)
+    /// implementation without requiring that trait to be in scope.
+    pub fn vk(&self) -> &VerifyingKey<E> {
+        &self.vk
+    }
+
+    /// Returns the element `beta * G` in `E::G1`.
+    pub fn beta_g1(&self) -> E::G1Affine {
+        self.beta_g1
+    }
+
+    /// Returns the element `delta * G` in `E::G1`.
+    pub fn delta_g1(&self) -> E::G1Affine {
+        self.delta_g1
+    }
+
+    /// Returns the elements `a_i * G` in `E::G1`.
+    pub fn a_query(&self) -> &[E::G1Affine] {
+        &self.a_query
+    }
+
+    /// Returns the elements `b_i * G` in `E::G1`.
+    pub fn b_g1_query(&self) -> &[E::G1Affine] {
+        &self.b_g1_query
+    }
+
+    /// Returns the elements `b_i * H` in `E::G2`.
+    pub fn b_g2_query(&self) -> &[E::G2Affine] {
+        &self.b_g2_query
+    }
+
+    /// Returns the elements `h_i * G` in `E::G1`.
+    pub fn h_query(&self) -> &[E::G1Affine] {
+        &self.h_query
+    }
+
+    /// Returns the elements `l_i * G` in `E::G1`.
+    pub fn l_query(&self) -> &[E::G1Affine] {
+        &self.l_query
+    }
+
+    /// Checks the parts of `self` that can be checked without the circuit
+    /// for internal consistency and against `vk`, e.g. to catch keys that
+    /// were corrupted in storage or truncated.
+    ///
+    /// This checks that `self.vk` equals `vk`, that the query vectors have
+    /// matching lengths, that `alpha`, `beta`, `gamma` and `delta` are
+    /// nonzero, that `beta` and `delta` agree in `E::G1` and `E::G2`, and
+    /// that `b_g1_query` and `b_g2_query` hold the same scalars. The last
+    /// check is a single pairing equation over a random linear combination
+    /// of the queries, with coefficients derived from a hash of them. It also
+    /// checks that the trivial proof `(0, 0, 0)` does not verify for the
+    /// all-zero instance, which a degenerate `vk` would accept.
+    ///
+    /// No proof is created, so corruption of `a_query`, `h_query` and
+    /// `l_query` is **not** detected; [`Self::self_check_with_circuit`]
+    /// covers them as well.
+    ///
+    /// This costs four pairings, a verification, and two multi-scalar
+    /// multiplications of the size of the circuit, so it is not run on
+    /// deserialization.
+    pub fn self_check(&self, vk: &VerifyingKey<E>) -> Result<(), Groth16Error> {
+        let num_variables = self.a_query.len();
+        let consistent = self.vk == *vk
+            && self.b_g1_query.len() == num_variables
+            && self.b_g2_query.len() == num_variables
+            && !vk.gamma_abc_g1.is_empty()
+            && vk.gamma_abc_g1.len() + self.l_query.len() == num_variables
+            && !self.beta_g1.is_zero()
+            && !self.delta_g1.is_zero()
+            && !vk.alpha_g1.is_zero()
+            && !vk.beta_g2.is_zero()
+            && !vk.gamma_g2.is_zero()
+            && !vk.delta_g2.is_zero();
+        if !consistent {
+            return Err(Groth16Error::MalformedProvingKey);
+        }
+
+        let trivial = Proof {
+            a: E::G1Affine::zero(),
+            b: E::G2Affine::zero(),
+            c: E::G1Affine::zero(),
+        };
+        let zeros = vec![E::ScalarField::zero(); vk.gamma_abc_g1.len() - 1];
+        if Groth16::<E>::verify_proof_with_vk(vk, &trivial, &zeros)? {
+            return Err(Groth16Error::MalformedVerifyingKey);
+        }
+
+        // `beta_g1 = beta * g1` and `delta_g1 = delta * g1` for the same `g1`,
+        // and likewise in `E::G2`.
+        if E::pairing(self.beta_g1, vk.delta_g2) != E::pairing(self.delta_g1, vk.beta_g2) {
+            return Err(Groth16Error::MalformedProvingKey);
+        }
+
+        // `b_g1_query[i] = b_i * g1` and `b_g2_query[i] = b_i * g2` for every
+        // `i`, checked as `e(sum_i rho^i b_g1_query[i], delta_g2) =
+        // e(delta_g1, sum_i rho^i b_g2_query[i])`.
+        let mut bytes = Vec::new();
+        self.b_g1_query.serialize_compressed(&mut bytes).unwrap();
+        self.b_g2_query.serialize_compressed(&mut bytes).unwrap();
+        let rho = E::ScalarField::from_le_bytes_mod_order(&Sha256::digest(&bytes));
+        let mut powers = Vec::with_capacity(num_variables);
+        let mut power = E::ScalarField::one();
+        for _ in 0..num_variables {
+            powers.push(power);
+            power *= rho;
+        }
+        let b_g1 = E::G1::msm_unchecked(&self.b_g1_query, &powers);
+        let b_g2 = E::G2::msm_unchecked(&self.b_g2_query, &powers);
+        if E::pairing(b_g1, vk.delta_g2) != E::pairing(self.delta_g1, b_g2) {
+            return Err(Groth16Error::MalformedProvingKey);
+        }
+
+        Ok(())
+    }
+
+    /// Runs [`Self::self_check`], then creates a proof for `circuit` under
+    /// `self` and checks that it verifies under `vk` for `public_inputs`.
+    ///
+    /// `circuit` must be the circuit of `self`, with an assignment that
+    /// satisfies it, and `public_inputs` its public inputs. The proof uses
+    /// every query of `self`, so unlike [`Self::self_check`] this also
+    /// detects corruption of `a_query`, `h_query` and `l_query`, at the cost
+    /// of creating and verifying one proof. Returns
+    /// [`Groth16Error::MalformedProvingKey`] if the proof does not verify.
+    pub fn self_check_with_circuit<C: ConstraintSynthesizer<E::ScalarField>>(
+        &self,
+        vk: &VerifyingKey<E>,
+        circuit: C,
+        public_inputs: &[E::ScalarField],
+        rng: &mut impl Rng,
+    ) -> Result<(), Groth16Error> {
+        self.self_check(vk)?;
+        let proof = Groth16::<E>::create_random_proof_with_reduction(circuit, self, rng)?;
+        if !Groth16::<E>::verify_proof_with_vk(vk, &proof, public_inputs)? {
+            return Err(Groth16Error::MalformedProvingKey);
+        }
+        Ok(())
+    }
+
+    /// Returns the number of operations the prover performs with `self`,
+    /// derived from the lengths of the query vectors.
+    ///
+    /// This counts the multi-scalar multiplications against `a_query`,
+    /// `b_g1_query`, `b_g2_query`, `h_query`, and `l_query`, the scalar
+    /// multiplications that blind `A`, `B`, and `C`, and the seven FFTs over
+    /// the QAP domain of the [`LibsnarkReduction`](crate::r1cs_to_qap::LibsnarkReduction)
+    /// witness map, whose size is one more than the length of `h_query`.
+    pub fn operation_counts(&self) -> ProvingCost {
+        // `r * delta_g1`, `s * delta_g1`, `r * s * delta_g1`, `s * A`, and `r * B`.
+        const BLINDING_G1_MULTS: usize = 5;
+        // `s * delta_g2`.
+        const BLINDING_G2_MULTS: usize = 1;
+
+        ProvingCost {
+            g1_scalar_mults: self.a_query.len()
+                + self.b_g1_query.len()
+                + self.h_query.len()
+                + self.l_query.len()
+                + BLINDING_G1_MULTS,
+            g2_scalar_mults: self.b_g2_query.len() + BLINDING_G2_MULTS,
+            fft_size: self.h_query.len() + 1,
+            num_ffts: 7,
+        }
+    }
+
+    /// Returns the approximate number of heap bytes owned by `self`, i.e. the
+    /// allocated capacity of every query vector, including those of the
+    /// underlying verification key.
+    ///
+    /// This does not include `size_of::<Self>()` itself.
+    pub fn size_in_memory(&self) -> usize {
+        let g1_size = core::mem::size_of::<E::G1Affine>();
+        let g2_size = core::mem::size_of::<E::G2Affine>();
+        self.vk.size_in_memory()
+            + (self.a_query.capacity()
+                + self.b_g1_query.capacity()
+                + self.h_query.capacity()
+                + self.l_query.capacity())
+                * g1_size
+            + self.b_g2_query.capacity() * g2_size
+    }
+
+    /// Serializes `self` with compressed points, storing every element of
+    /// `b_g1_query` that coincides with the element of `a_query` at the same
+    /// index as a single bit instead of a second copy of the point.
+    ///
+    /// Variables that appear with the same coefficients in the `A` and `B`
+    /// matrices produce coinciding elements; this includes every variable
+    /// that appears in neither. The layout is the compressed encoding of
+    /// `vk`, `beta_g1`, `delta_g1`, and `a_query`, followed by the length of
+    /// `b_g1_query`, a little-endian bitmap with one bit per element of
+    /// `b_g1_query` that is set for coinciding elements, the non-coinciding
+    /// elements, and finally the compressed encoding of `b_g2_query`,
+    /// `h_query`, and `l_query`.
+    pub fn serialize_compact<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        self.vk.serialize_with_mode(&mut writer, Compress::Yes)?;
+        self.beta_g1
+            .serialize_with_mode(&mut writer, Compress::Yes)?;
+        self.delta_g1
+            .serialize_with_mode(&mut writer, Compress::Yes)?;
+        self.a_query
+            .serialize_with_mode(&mut writer, Compress::Yes)?;
+
+        let coincides = |i: usize| self.a_query.get(i) == Some(&self.b_g1_query[i]);
+        (self.b_g1_query.len() as u64).serialize_with_mode(&mut writer, Compress::Yes)?;
+        let mut bitmap = vec![0u8; self.b_g1_query.len().div_ceil(8)];
+        for i in (0..self.b_g1_query.len()).filter(|&i| coincides(i)) {
+            bitmap[i / 8] |= 1 << (i % 8);
+        }
+        writer.write_all(&bitmap)?;
+        for i in (0..self.b_g1_query.len()).filter(|&i| !coincides(i)) {
+            self.b_g1_query[i].serialize_with_mode(&mut writer, Compress::Yes)?;
+        }
+
+        self.b_g2_query
+            .serialize_with_mode(&mut writer, Compress::Yes)?;
+        self.h_query
+            .serialize_with_mode(&mut writer, Compress::Yes)?;
+        self.l_query
+            .serialize_with_mode(&mut writer, Compress::Yes)?;
+        Ok(())
+    }
+
+    /// Deserializes a proving key written by
+    /// [`serialize_compact`](Self::serialize_compact).
+    ///
+    /// With `Validate::Yes`, every point is checked to be on the curve and in
+    /// the prime-order subgroup; `Validate::No` skips these checks and should
+    /// only be used for trusted input.
+    pub fn deserialize_compact<R: Read>(
+        mut reader: R,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let vk = VerifyingKey::deserialize_with_mode(&mut reader, Compress::Yes, validate)?;
+        let beta_g1 = E::G1Affine::deserialize_with_mode(&mut reader, Compress::Yes, validate)?;
+        let delta_g1 = E::G1Affine::deserialize_with_mode(&mut reader, Compress::Yes, validate)?;
+        let a_query = Vec::deserialize_with_mode(&mut reader, Compress::Yes, validate)?;
+
+        let b_len = u64::deserialize_with_mode(&mut reader, Compress::Yes, validate)? as usize;
+        let mut bitmap = Vec::new();
+        for _ in 0..b_len.div_ceil(8) {
+            bitmap.push(u8::deserialize_with_mode(
+                &mut reader,
+                Compress::Yes,
+                validate,
+            )?);
+        }
+        // The elements are read one at a time, so a corrupted length fails on
+        // a short read instead of triggering a huge allocation.
+        let mut b_g1_query = Vec::new();
+        for i in 0..b_len {
+            let b = if (bitmap[i / 8] >> (i % 8)) & 1 == 1 {
+                *a_query.get(i).ok_or(SerializationError::InvalidData)?
+            } else {
+                E::G1Affine::deserialize_with_mode(&mut reader, Compress::Yes, validate)?
+            };
+            b_g1_query.push(b);
+        }
+
+        let b_g2_query = Vec::deserialize_with_mode(&mut reader, Compress::Yes, validate)?;
+        let h_query = Vec::deserialize_with_mode(&mut reader, Compress::Yes, validate)?;
+        let l_query = Vec::deserialize_with_mode(&mut reader, Compress::Yes, validate)?;
+
+        Ok(Self {
+            vk,
+            beta_g1,
+            delta_g1,
+            a_query,
+            b_g1_query,
+            b_g2_query,
+            h_query,
+            l_query,
+        })
+    }
+}
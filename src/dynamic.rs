@@ -0,0 +1,178 @@
+//! Code that is generic over `E: Pairing` has to pick the curve at compile
+//! time. Services that accept proofs over several curves can instead wrap
+//! keys, proofs, and public inputs in the enums of this module, which have
+//! one variant per supported curve, and [`dyn_verify`] them. The supported
+//! curves are selected by Cargo features: `bn254` enables the `Bn254`
+//! variants and `bls12-381` the `Bls12_381` variants.
+//!
+//! The byte encodings produced by `to_bytes` start with the [`Curve::id`] of
+//! the curve, followed by the compressed canonical encoding of the wrapped
+//! value.
+
+use crate::{Groth16, Groth16Error, Proof, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_snark::SNARK;
+use ark_std::vec::Vec;
+
+#[cfg(feature = "bls12-381")]
+use ark_bls12_381::Bls12_381;
+#[cfg(feature = "bn254")]
+use ark_bn254::Bn254;
+
+/// A pairing-friendly curve supported by the enums of this module.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Curve {
+    /// BN254, also known as BN128 or alt_bn128.
+    #[cfg(feature = "bn254")]
+    Bn254,
+    /// BLS12-381.
+    #[cfg(feature = "bls12-381")]
+    Bls12_381,
+}
+
+impl Curve {
+    /// Returns the byte identifying the curve in the encodings of this
+    /// module. The identifier of a curve does not depend on which other
+    /// curves are enabled.
+    pub fn id(self) -> u8 {
+        match self {
+            #[cfg(feature = "bn254")]
+            Curve::Bn254 => 1,
+            #[cfg(feature = "bls12-381")]
+            Curve::Bls12_381 => 2,
+        }
+    }
+
+    /// Returns the curve identified by `id`, or `None` if it is unknown or
+    /// its feature is disabled.
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            #[cfg(feature = "bn254")]
+            1 => Some(Curve::Bn254),
+            #[cfg(feature = "bls12-381")]
+            2 => Some(Curve::Bls12_381),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! dyn_wrapper {
+    ($(#[$doc:meta])* $name:ident, $inner:ident) => {
+        $(#[$doc])*
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        pub enum $name {
+            /// A value over BN254.
+            #[cfg(feature = "bn254")]
+            Bn254($inner<Bn254>),
+            /// A value over BLS12-381.
+            #[cfg(feature = "bls12-381")]
+            Bls12_381($inner<Bls12_381>),
+        }
+
+        impl $name {
+            /// Returns the curve of the wrapped value.
+            pub fn curve(&self) -> Curve {
+                match self {
+                    #[cfg(feature = "bn254")]
+                    $name::Bn254(_) => Curve::Bn254,
+                    #[cfg(feature = "bls12-381")]
+                    $name::Bls12_381(_) => Curve::Bls12_381,
+                }
+            }
+
+            /// Serializes the value, prefixed with the identifier of its
+            /// curve.
+            pub fn to_bytes(&self) -> Vec<u8> {
+                let mut bytes = vec![self.curve().id()];
+                match self {
+                    #[cfg(feature = "bn254")]
+                    $name::Bn254(v) => v.serialize_compressed(&mut bytes),
+                    #[cfg(feature = "bls12-381")]
+                    $name::Bls12_381(v) => v.serialize_compressed(&mut bytes),
+                }
+                .expect("serializing into a vector does not fail");
+                bytes
+            }
+
+            /// Deserializes a value written by [`Self::to_bytes`], choosing
+            /// the curve from its first byte. Trailing bytes are rejected, so
+            /// that each value has a single encoding.
+            pub fn from_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+                let (&id, mut rest) = bytes
+                    .split_first()
+                    .ok_or(SerializationError::InvalidData)?;
+                let value = match Curve::from_id(id).ok_or(SerializationError::InvalidData)? {
+                    #[cfg(feature = "bn254")]
+                    Curve::Bn254 => {
+                        $name::Bn254(CanonicalDeserialize::deserialize_compressed(&mut rest)?)
+                    },
+                    #[cfg(feature = "bls12-381")]
+                    Curve::Bls12_381 => {
+                        $name::Bls12_381(CanonicalDeserialize::deserialize_compressed(&mut rest)?)
+                    },
+                };
+                if !rest.is_empty() {
+                    return Err(SerializationError::InvalidData);
+                }
+                Ok(value)
+            }
+        }
+
+        #[cfg(feature = "bn254")]
+        impl From<$inner<Bn254>> for $name {
+            fn from(v: $inner<Bn254>) -> Self {
+                $name::Bn254(v)
+            }
+        }
+
+        #[cfg(feature = "bls12-381")]
+        impl From<$inner<Bls12_381>> for $name {
+            fn from(v: $inner<Bls12_381>) -> Self {
+                $name::Bls12_381(v)
+            }
+        }
+    };
+}
+
+/// The public inputs of a proof over `E`.
+pub type Inputs<E> = Vec<<E as ark_ec::pairing::Pairing>::ScalarField>;
+
+dyn_wrapper!(
+    /// A [`Proof`] over a curve that is chosen at runtime.
+    DynProof,
+    Proof
+);
+dyn_wrapper!(
+    /// A [`VerifyingKey`] over a curve that is chosen at runtime.
+    DynVerifyingKey,
+    VerifyingKey
+);
+dyn_wrapper!(
+    /// The public inputs of a proof over a curve that is chosen at runtime.
+    DynInputs,
+    Inputs
+);
+
+/// Verifies `proof` against `vk` and the public `inputs`, dispatching on
+/// their curve.
+///
+/// Returns [`Groth16Error::CurveMismatch`] if the three values are not over
+/// the same curve.
+pub fn dyn_verify(
+    vk: &DynVerifyingKey,
+    inputs: &DynInputs,
+    proof: &DynProof,
+) -> Result<bool, Groth16Error> {
+    #[allow(unreachable_patterns)]
+    match (vk, inputs, proof) {
+        #[cfg(feature = "bn254")]
+        (DynVerifyingKey::Bn254(vk), DynInputs::Bn254(x), DynProof::Bn254(proof)) => {
+            Groth16::<Bn254>::verify(vk, x, proof)
+        },
+        #[cfg(feature = "bls12-381")]
+        (DynVerifyingKey::Bls12_381(vk), DynInputs::Bls12_381(x), DynProof::Bls12_381(proof)) => {
+            Groth16::<Bls12_381>::verify(vk, x, proof)
+        },
+        _ => Err(Groth16Error::CurveMismatch),
+    }
+}
@@ -0,0 +1,120 @@
+use ark_relations::gr1cs::SynthesisError;
+use ark_std::string::String;
+
+/// The errors returned by the native Groth16 APIs.
+///
+/// These are the errors of [`Groth16`](crate::Groth16)'s `SNARK`
+/// implementation, i.e. of its setup, `prove`, and `verify`. Conversions
+/// from and to [`SynthesisError`] are provided, so `?` keeps working in
+/// functions that return either type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Groth16Error {
+    /// The circuit could not be synthesized, or its assignment does not
+    /// satisfy it or does not fit the proving key.
+    Synthesis(SynthesisError),
+    /// The verifying key is malformed, e.g. it has no `gamma_abc_g1`
+    /// elements.
+    MalformedVerifyingKey,
+    /// The proving key is internally inconsistent or does not match the
+    /// verifying key, see [`ProvingKey::self_check`].
+    ///
+    /// [`ProvingKey::self_check`]: crate::ProvingKey::self_check
+    MalformedProvingKey,
+    /// The verifying key does not match the commitment it was checked
+    /// against, see [`Groth16::verify_against_commitment`].
+    ///
+    /// [`Groth16::verify_against_commitment`]: crate::Groth16::verify_against_commitment
+    VkCommitmentMismatch,
+    /// The circuit has constraints of a `gr1cs` predicate other than R1CS,
+    /// e.g. lookups or custom gates, which a QAP cannot encode.
+    UnsupportedPredicate {
+        /// The label of the predicate.
+        label: String,
+    },
+    /// The number of public inputs does not match the verifying key.
+    InputLengthMismatch {
+        /// The number of public inputs expected by the verifying key.
+        expected: usize,
+        /// The number of public inputs provided.
+        got: usize,
+    },
+    /// A public input position passed to
+    /// [`VerifyingKey::specialize`](crate::VerifyingKey::specialize) is beyond
+    /// the inputs of the verifying key, or is given more than once.
+    InvalidInputIndex {
+        /// The offending position.
+        index: usize,
+    },
+    /// The verifying key, public inputs, and proof passed to
+    /// [`dyn_verify`](crate::dynamic::dyn_verify) are not all over the same
+    /// curve.
+    CurveMismatch,
+}
+
+impl core::fmt::Display for Groth16Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Groth16Error::Synthesis(e) => write!(f, "constraint synthesis failed: {}", e),
+            Groth16Error::MalformedVerifyingKey => write!(f, "malformed verifying key"),
+            Groth16Error::MalformedProvingKey => write!(f, "malformed proving key"),
+            Groth16Error::VkCommitmentMismatch => {
+                write!(f, "verifying key does not match its commitment")
+            },
+            Groth16Error::UnsupportedPredicate { label } => write!(
+                f,
+                "the circuit has constraints of predicate `{}`, but Groth16 only supports R1CS",
+                label
+            ),
+            Groth16Error::InputLengthMismatch { expected, got } => write!(
+                f,
+                "expected {} public inputs, but {} were provided",
+                expected, got
+            ),
+            Groth16Error::InvalidInputIndex { index } => {
+                write!(
+                    f,
+                    "public input position {} is out of range or repeated",
+                    index
+                )
+            },
+            Groth16Error::CurveMismatch => write!(
+                f,
+                "the verifying key, public inputs, and proof are over different curves"
+            ),
+        }
+    }
+}
+
+impl ark_std::error::Error for Groth16Error {
+    fn source(&self) -> Option<&(dyn ark_std::error::Error + 'static)> {
+        match self {
+            Groth16Error::Synthesis(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<SynthesisError> for Groth16Error {
+    fn from(e: SynthesisError) -> Self {
+        match e {
+            SynthesisError::MalformedVerifyingKey => Groth16Error::MalformedVerifyingKey,
+            e => Groth16Error::Synthesis(e),
+        }
+    }
+}
+
+impl From<Groth16Error> for SynthesisError {
+    fn from(e: Groth16Error) -> Self {
+        match e {
+            Groth16Error::Synthesis(e) => e,
+            Groth16Error::MalformedVerifyingKey | Groth16Error::VkCommitmentMismatch => {
+                SynthesisError::MalformedVerifyingKey
+            },
+            Groth16Error::MalformedProvingKey
+            | Groth16Error::UnsupportedPredicate { .. }
+            | Groth16Error::InputLengthMismatch { .. }
+            | Groth16Error::InvalidInputIndex { .. }
+            | Groth16Error::CurveMismatch => SynthesisError::Unsatisfiable,
+        }
+    }
+}
@@ -1,17 +1,153 @@
-use crate::{r1cs_to_qap::R1CSToQAP, Groth16, ProvingKey, Vec, VerifyingKey};
-use ark_ec::{pairing::Pairing, scalar_mul::BatchMulPreprocessing, CurveGroup};
-use ark_ff::{Field, UniformRand, Zero};
+use crate::{
+    r1cs_to_qap::{check_r1cs_only, qap_domain, R1CSToQAP},
+    Groth16, Groth16Error, ProvingKey, Vec, VerifyingKey,
+};
+use ark_ec::{
+    pairing::Pairing,
+    scalar_mul::{BatchMulPreprocessing, ScalarMul},
+    CurveGroup,
+};
+use ark_ff::{Field, PrimeField, UniformRand, Zero};
 use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
 use ark_relations::gr1cs::{
-    ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, Result as R1CSResult,
-    SynthesisError, SynthesisMode,
+    ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, OptimizationGoal,
+    Result as R1CSResult, SynthesisError, SynthesisMode, R1CS_PREDICATE_LABEL,
 };
+#[cfg(feature = "parallel")]
+use ark_std::cfg_chunks;
 use ark_std::rand::Rng;
 use ark_std::{cfg_into_iter, cfg_iter};
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
+/// Overwrites a trapdoor-derived value with zeros when the `zeroize` feature
+/// is enabled, and does nothing otherwise.
+#[cfg(feature = "zeroize")]
+#[inline]
+pub(crate) fn wipe<T: zeroize::Zeroize>(secret: &mut T) {
+    secret.zeroize();
+}
+
+/// Overwrites a trapdoor-derived value with zeros when the `zeroize` feature
+/// is enabled, and does nothing otherwise.
+#[cfg(not(feature = "zeroize"))]
+#[inline]
+pub(crate) fn wipe<T>(_secret: &mut T) {}
+
+/// A trapdoor-derived value that is overwritten with zeros when it is dropped
+/// if the `zeroize` feature is enabled.
+///
+/// Unlike [`wipe`], this also covers early returns, and dropping a `Secret`
+/// explicitly frees a large vector as soon as it is no longer needed.
+#[cfg(feature = "zeroize")]
+pub(crate) type Secret<T> = zeroize::Zeroizing<T>;
+
+/// A trapdoor-derived value that is overwritten with zeros when it is dropped
+/// if the `zeroize` feature is enabled.
+///
+/// Unlike [`wipe`], this also covers early returns, and dropping a `Secret`
+/// explicitly frees a large vector as soon as it is no longer needed.
+#[cfg(not(feature = "zeroize"))]
+pub(crate) struct Secret<T>(T);
+
+#[cfg(not(feature = "zeroize"))]
+impl<T> Secret<T> {
+    #[inline]
+    pub(crate) fn new(secret: T) -> Self {
+        Secret(secret)
+    }
+}
+
+#[cfg(not(feature = "zeroize"))]
+impl<T> core::ops::Deref for Secret<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+#[cfg(not(feature = "zeroize"))]
+impl<T> core::ops::DerefMut for Secret<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+/// Computes `scalar * G` for every element of `scalars`, where `G` is the
+/// base of `table`.
+///
+/// When the `parallel` feature is enabled, `scalars` is split into one chunk
+/// per thread and the chunks are multiplied and normalized concurrently.
+/// Affine coordinates are unique, so the result is identical to that of a
+/// single `table.batch_mul(scalars)`.
+pub(crate) fn fixed_base_mul<G: ScalarMul>(
+    table: &BatchMulPreprocessing<G>,
+    scalars: &[G::ScalarField],
+) -> Vec<G::MulBase> {
+    #[cfg(feature = "parallel")]
+    {
+        const MIN_CHUNK_SIZE: usize = 1 << 10;
+        let chunk_size = scalars
+            .len()
+            .div_ceil(rayon::current_num_threads())
+            .max(MIN_CHUNK_SIZE);
+        cfg_chunks!(scalars, chunk_size)
+            .map(|chunk| table.batch_mul(chunk))
+            .collect::<Vec<_>>()
+            .concat()
+    }
+    #[cfg(not(feature = "parallel"))]
+    table.batch_mul(scalars)
+}
+
+/// Tuning parameters for the generator.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GeneratorConfig {
+    /// The window size, in bits, of the fixed-base tables used to compute the
+    /// query vectors, or `None` to choose it from the number of scalars each
+    /// table is used for.
+    ///
+    /// Each table holds `2^window_size` multiples of the generator per
+    /// window, so large values trade memory for fewer additions. Values are
+    /// clamped to between 1 and [`Self::MAX_WINDOW_SIZE`].
+    pub window_size: Option<usize>,
+}
+
+impl GeneratorConfig {
+    /// The largest window size that is used. A table holds `2^window_size`
+    /// points per window, so larger windows take many gigabytes of memory
+    /// for little gain.
+    pub const MAX_WINDOW_SIZE: usize = 20;
+
+    /// Returns the window size that is used for scalars of `F`, if one is
+    /// set.
+    pub(crate) fn clamped_window_size<F: PrimeField>(&self) -> Option<usize> {
+        let scalar_size = F::MODULUS_BIT_SIZE as usize;
+        self.window_size
+            .map(|window_size| window_size.clamp(1, Self::MAX_WINDOW_SIZE.min(scalar_size)))
+    }
+
+    /// Builds a fixed-base table for `base` that will be used to multiply
+    /// `num_scalars` scalars.
+    fn table<G: ScalarMul>(&self, base: G, num_scalars: usize) -> BatchMulPreprocessing<G> {
+        match self.clamped_window_size::<G::ScalarField>() {
+            None => BatchMulPreprocessing::new(base, num_scalars),
+            Some(window_size) => {
+                let scalar_size = G::ScalarField::MODULUS_BIT_SIZE as usize;
+                BatchMulPreprocessing::with_num_windows_and_window_size(
+                    base,
+                    window_size,
+                    scalar_size.div_ceil(window_size),
+                )
+            },
+        }
+    }
+}
+
 impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
     /// Generates a random common reference string for
     /// a circuit using the provided R1CS-to-QAP reduction.
@@ -19,31 +155,116 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
     pub fn generate_random_parameters_with_reduction<C>(
         circuit: C,
         rng: &mut impl Rng,
-    ) -> R1CSResult<ProvingKey<E>>
+    ) -> Result<ProvingKey<E>, Groth16Error>
     where
         C: ConstraintSynthesizer<E::ScalarField>,
     {
-        let alpha = E::ScalarField::rand(rng);
-        let beta = E::ScalarField::rand(rng);
-        let gamma = E::ScalarField::rand(rng);
-        let delta = E::ScalarField::rand(rng);
+        Self::generate_random_parameters_with_config(circuit, &GeneratorConfig::default(), rng)
+    }
+
+    /// Generates a random common reference string for a circuit using the
+    /// provided R1CS-to-QAP reduction, with the generator tuned by `config`.
+    ///
+    /// The parameters are distributed exactly as those of
+    /// [`Self::generate_random_parameters_with_reduction`]; `config` only
+    /// affects how they are computed.
+    pub fn generate_random_parameters_with_config<C>(
+        circuit: C,
+        config: &GeneratorConfig,
+        rng: &mut impl Rng,
+    ) -> Result<ProvingKey<E>, Groth16Error>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let cs = Self::synthesize_for_setup(circuit)?;
+        Ok(Self::generate_random_parameters_with_cs(cs, config, rng)?)
+    }
+
+    /// Generates a random common reference string for an already synthesized
+    /// constraint system, using the provided R1CS-to-QAP reduction.
+    ///
+    /// This avoids running `generate_constraints` again when `cs` comes from
+    /// an earlier synthesis pass. `cs` must have been finalized with
+    /// [`ConstraintSystemRef::finalize`] in a mode that constructs matrices,
+    /// e.g. [`SynthesisMode::Setup`]; if its R1CS matrices cannot be computed,
+    /// the error from computing them or [`SynthesisError::Unsatisfiable`] is
+    /// returned, or [`Groth16Error::UnsupportedPredicate`] if `cs` has
+    /// constraints other than R1CS constraints.
+    pub fn generate_parameters_from_cs(
+        cs: ConstraintSystemRef<E::ScalarField>,
+        rng: &mut impl Rng,
+    ) -> Result<ProvingKey<E>, Groth16Error> {
+        check_r1cs_only(&cs)?;
+        let matrices = cs.to_matrices()?;
+        let num_constraints = cs.num_constraints();
+        match matrices.get(R1CS_PREDICATE_LABEL) {
+            Some(r1cs) if r1cs.len() == 3 && r1cs.iter().all(|m| m.len() == num_constraints) => {},
+            _ => return Err(SynthesisError::Unsatisfiable.into()),
+        }
+        drop(matrices);
+
+        Ok(Self::generate_random_parameters_with_cs(
+            cs,
+            &GeneratorConfig::default(),
+            rng,
+        )?)
+    }
+
+    /// Samples the toxic waste and generates parameters for the synthesized
+    /// constraint system `cs`.
+    fn generate_random_parameters_with_cs(
+        cs: ConstraintSystemRef<E::ScalarField>,
+        config: &GeneratorConfig,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<ProvingKey<E>> {
+        let alpha = Secret::new(E::ScalarField::rand(rng));
+        let beta = Secret::new(E::ScalarField::rand(rng));
+        let gamma = Secret::new(E::ScalarField::rand(rng));
+        let delta = Secret::new(E::ScalarField::rand(rng));
 
         let g1_generator = E::G1::rand(rng);
         let g2_generator = E::G2::rand(rng);
 
-        Self::generate_parameters_with_qap(
-            circuit,
-            alpha,
-            beta,
-            gamma,
-            delta,
+        Self::generate_parameters_with_qap_and_cs(
+            cs,
+            *alpha,
+            *beta,
+            *gamma,
+            *delta,
             g1_generator,
             g2_generator,
+            config,
             rng,
         )
     }
 
+    /// Generates a common reference string for a circuit with all randomness
+    /// drawn from a ChaCha20 rng seeded with `seed`.
+    ///
+    /// The same circuit and seed always produce byte-identical keys, which is
+    /// useful for reproducible test vectors. Anyone who knows the seed can
+    /// recover the toxic waste and forge proofs, so these parameters must never
+    /// be used in production.
+    #[cfg(feature = "seeded-setup")]
+    pub fn generate_parameters_with_seed<C>(
+        circuit: C,
+        seed: [u8; 32],
+    ) -> Result<ProvingKey<E>, Groth16Error>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        use ark_std::rand::SeedableRng;
+
+        let mut rng = rand_chacha::ChaCha20Rng::from_seed(seed);
+        Self::generate_random_parameters_with_reduction(circuit, &mut rng)
+    }
+
     /// Create parameters for a circuit, given some toxic waste, R1CS to QAP calculator and group generators
+    ///
+    /// With the `zeroize` feature, the trapdoor-derived scalars held by this
+    /// function are wiped before it returns, including when it returns an
+    /// error. The caller is responsible for wiping its own copies of `alpha`,
+    /// `beta`, `gamma`, and `delta`.
     pub fn generate_parameters_with_qap<C>(
         circuit: C,
         alpha: E::ScalarField,
@@ -53,13 +274,39 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         g1_generator: E::G1,
         g2_generator: E::G2,
         rng: &mut impl Rng,
-    ) -> R1CSResult<ProvingKey<E>>
+    ) -> Result<ProvingKey<E>, Groth16Error>
     where
         C: ConstraintSynthesizer<E::ScalarField>,
     {
-        type D<F> = GeneralEvaluationDomain<F>;
+        let alpha = Secret::new(alpha);
+        let beta = Secret::new(beta);
+        let gamma = Secret::new(gamma);
+        let delta = Secret::new(delta);
+
+        let cs = Self::synthesize_for_setup(circuit)?;
+        Ok(Self::generate_parameters_with_qap_and_cs(
+            cs,
+            *alpha,
+            *beta,
+            *gamma,
+            *delta,
+            g1_generator,
+            g2_generator,
+            &GeneratorConfig::default(),
+            rng,
+        )?)
+    }
 
-        let setup_time = start_timer!(|| "Groth16::Generator");
+    /// Synthesizes `circuit` into a finalized constraint system in setup mode.
+    ///
+    /// Returns [`Groth16Error::UnsupportedPredicate`] if the circuit has
+    /// constraints other than R1CS constraints.
+    pub(crate) fn synthesize_for_setup<C>(
+        circuit: C,
+    ) -> Result<ConstraintSystemRef<E::ScalarField>, Groth16Error>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
         let cs = ConstraintSystem::new_ref();
         cs.set_optimization_goal(OptimizationGoal::Constraints);
         cs.set_mode(SynthesisMode::Setup);
@@ -73,6 +320,36 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         cs.finalize();
         end_timer!(lc_time);
 
+        check_r1cs_only(&cs)?;
+        Ok(cs)
+    }
+
+    /// Creates parameters for the synthesized constraint system `cs`, given
+    /// some toxic waste and group generators.
+    ///
+    /// Every trapdoor-derived value is held in a [`Secret`], so that it is
+    /// wiped on every exit path when the `zeroize` feature is enabled.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn generate_parameters_with_qap_and_cs(
+        cs: ConstraintSystemRef<E::ScalarField>,
+        alpha: E::ScalarField,
+        beta: E::ScalarField,
+        gamma: E::ScalarField,
+        delta: E::ScalarField,
+        g1_generator: E::G1,
+        g2_generator: E::G2,
+        config: &GeneratorConfig,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<ProvingKey<E>> {
+        type D<F> = GeneralEvaluationDomain<F>;
+
+        let setup_time = start_timer!(|| "Groth16::Generator");
+
+        let alpha = Secret::new(alpha);
+        let beta = Secret::new(beta);
+        let gamma = Secret::new(gamma);
+        let delta = Secret::new(delta);
+
         // Following is the mapping of symbols from the Groth16 paper to this implementation
         // l -> num_instance_variables
         // m -> qap_num_variables
@@ -86,8 +363,8 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         let domain_time = start_timer!(|| "Constructing evaluation domain");
 
         let domain_size = cs.num_constraints() + cs.num_instance_variables();
-        let domain = D::new(domain_size).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
-        let t = domain.sample_element_outside_domain(rng);
+        let domain = qap_domain::<_, D<E::ScalarField>>(domain_size, QAP::domain_offset())?;
+        let t = Secret::new(domain.sample_element_outside_domain(rng));
 
         end_timer!(domain_time);
         ///////////////////////////////////////////////////////////////////////////
@@ -96,6 +373,12 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         let num_instance_variables = cs.num_instance_variables();
         let (a, b, c, zt, qap_num_variables, m_raw) =
             QAP::instance_map_with_evaluation::<E::ScalarField, D<E::ScalarField>>(cs, &t)?;
+        let (a, b, c, zt) = (
+            Secret::new(a),
+            Secret::new(b),
+            Secret::new(c),
+            Secret::new(zt),
+        );
         end_timer!(reduction_time);
 
         // Compute query densities
@@ -107,71 +390,80 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
             .map(|i| usize::from(!b[i].is_zero()))
             .sum();
 
-        let gamma_inverse = gamma.inverse().unwrap();
-        let delta_inverse = delta.inverse().unwrap();
-
-        let gamma_abc = cfg_iter!(a[..num_instance_variables])
-            .zip(&b[..num_instance_variables])
-            .zip(&c[..num_instance_variables])
-            .map(|((a, b), c)| (beta * a + &(alpha * b) + c) * &gamma_inverse)
-            .collect::<Vec<_>>();
-
-        let l = cfg_iter!(a[num_instance_variables..])
-            .zip(&b[num_instance_variables..])
-            .zip(&c[num_instance_variables..])
-            .map(|((a, b), c)| (beta * a + &(alpha * b) + c) * &delta_inverse)
-            .collect::<Vec<_>>();
+        let gamma_inverse = Secret::new(gamma.inverse().unwrap());
+        let delta_inverse = Secret::new(delta.inverse().unwrap());
+
+        let gamma_abc = Secret::new(
+            cfg_iter!(a[..num_instance_variables])
+                .zip(&b[..num_instance_variables])
+                .zip(&c[..num_instance_variables])
+                .map(|((a, b), c)| (*beta * a + &(*alpha * b) + c) * &*gamma_inverse)
+                .collect::<Vec<_>>(),
+        );
+
+        let l = Secret::new(
+            cfg_iter!(a[num_instance_variables..])
+                .zip(&b[num_instance_variables..])
+                .zip(&c[num_instance_variables..])
+                .map(|((a, b), c)| (*beta * a + &(*alpha * b) + c) * &*delta_inverse)
+                .collect::<Vec<_>>(),
+        );
 
         drop(c);
 
         // Compute B window table
         let g2_time = start_timer!(|| "Compute G2 table");
-        let g2_table = BatchMulPreprocessing::new(g2_generator, non_zero_b);
+        let g2_table = config.table(g2_generator, non_zero_b);
         end_timer!(g2_time);
 
         // Compute the B-query in G2
         let b_g2_time = start_timer!(|| format!("Calculate B G2 of size {}", b.len()));
-        let b_g2_query = g2_table.batch_mul(&b);
+        let b_g2_query = fixed_base_mul(&g2_table, &b);
         drop(g2_table);
         end_timer!(b_g2_time);
 
         // Compute G window table
         let g1_window_time = start_timer!(|| "Compute G1 window table");
         let num_scalars = non_zero_a + non_zero_b + qap_num_variables + m_raw + 1;
-        let g1_table = BatchMulPreprocessing::new(g1_generator, num_scalars);
+        let g1_table = config.table(g1_generator, num_scalars);
         end_timer!(g1_window_time);
 
         // Generate the R1CS proving key
         let proving_key_time = start_timer!(|| "Generate the R1CS proving key");
 
-        let alpha_g1 = g1_generator * &alpha;
-        let beta_g1 = g1_generator * &beta;
-        let beta_g2 = g2_generator * &beta;
-        let delta_g1 = g1_generator * &delta;
-        let delta_g2 = g2_generator * &delta;
+        let alpha_g1 = g1_generator * &*alpha;
+        let beta_g1 = g1_generator * &*beta;
+        let beta_g2 = g2_generator * &*beta;
+        let delta_g1 = g1_generator * &*delta;
+        let delta_g2 = g2_generator * &*delta;
 
         // Compute the A-query
         let a_time = start_timer!(|| "Calculate A");
-        let a_query = g1_table.batch_mul(&a);
+        let a_query = fixed_base_mul(&g1_table, &a);
         drop(a);
         end_timer!(a_time);
 
         // Compute the B-query in G1
         let b_g1_time = start_timer!(|| "Calculate B G1");
-        let b_g1_query = g1_table.batch_mul(&b);
+        let b_g1_query = fixed_base_mul(&g1_table, &b);
         drop(b);
         end_timer!(b_g1_time);
 
         // Compute the H-query
         let h_time = start_timer!(|| "Calculate H");
-        let h_scalars =
-            QAP::h_query_scalars::<_, D<E::ScalarField>>(m_raw - 1, t, zt, delta_inverse)?;
-        let h_query = g1_table.batch_mul(&h_scalars);
+        let h_scalars = Secret::new(QAP::h_query_scalars::<_, D<E::ScalarField>>(
+            m_raw - 1,
+            *t,
+            *zt,
+            *delta_inverse,
+        )?);
+        let h_query = fixed_base_mul(&g1_table, &h_scalars);
+        drop(h_scalars);
         end_timer!(h_time);
 
         // Compute the L-query
         let l_time = start_timer!(|| "Calculate L");
-        let l_query = g1_table.batch_mul(&l);
+        let l_query = fixed_base_mul(&g1_table, &l);
         drop(l);
         end_timer!(l_time);
 
@@ -179,8 +471,9 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
 
         // Generate R1CS verification key
         let verifying_key_time = start_timer!(|| "Generate the R1CS verification key");
-        let gamma_g2 = g2_generator * &gamma;
-        let gamma_abc_g1 = g1_table.batch_mul(&gamma_abc);
+        let gamma_g2 = g2_generator * &*gamma;
+        let gamma_abc_g1 = fixed_base_mul(&g1_table, &gamma_abc);
+        drop(gamma_abc);
         drop(g1_table);
 
         end_timer!(verifying_key_time);
@@ -0,0 +1,115 @@
+//! Encodings of proofs and verifying keys in the layout expected by the
+//! Ethereum `ecAdd`, `ecMul`, and `ecPairing` precompiles
+//! ([EIP-196](https://eips.ethereum.org/EIPS/eip-196) and
+//! [EIP-197](https://eips.ethereum.org/EIPS/eip-197)), and by Solidity
+//! verifiers built on top of them.
+//!
+//! Points are encoded as uncompressed affine coordinates, with each base
+//! field element written as a 32-byte big-endian integer. `G2` coordinates
+//! live in a quadratic extension and are written imaginary part first, i.e.
+//! `(x.c1, x.c0, y.c1, y.c0)`. The point at infinity is encoded as all zeros.
+//!
+//! The precompiles only exist for BN254, so these encodings are only useful
+//! for `Proof<Bn254>` and `VerifyingKey<Bn254>`.
+
+use super::{element_len, point_from_coordinates, read_field_be, write_field_be};
+use crate::{Proof, VerifyingKey};
+use ark_ec::{pairing::Pairing, AffineRepr};
+use ark_ff::Field;
+use ark_serialize::{SerializationError, Valid};
+use ark_std::vec::Vec;
+
+/// The length in bytes of an encoded point.
+fn point_len<G: AffineRepr>() -> usize {
+    2 * G::BaseField::extension_degree() as usize * element_len::<G::BaseField>()
+}
+
+fn write_point<G: AffineRepr>(p: &G, out: &mut Vec<u8>) {
+    let (x, y) = p.xy().unwrap_or_default();
+    write_field_be(&x, out);
+    write_field_be(&y, out);
+}
+
+fn read_point<G: AffineRepr>(bytes: &[u8]) -> Result<G, SerializationError> {
+    if bytes.iter().all(|b| *b == 0) {
+        return Ok(G::zero());
+    }
+
+    let (x, y) = bytes.split_at(bytes.len() / 2);
+    let point = point_from_coordinates::<G>(read_field_be(x)?, read_field_be(y)?)
+        .ok_or(SerializationError::InvalidData)?;
+    point.check()?;
+    Ok(point)
+}
+
+impl<E: Pairing> Proof<E> {
+    /// Encodes the proof as `A || B || C` in the Ethereum precompile layout,
+    /// which is 256 bytes for BN254.
+    pub fn to_ethereum_bytes(&self) -> Vec<u8> {
+        let (g1_len, g2_len) = (point_len::<E::G1Affine>(), point_len::<E::G2Affine>());
+        let mut out = Vec::with_capacity(2 * g1_len + g2_len);
+        write_point(&self.a, &mut out);
+        write_point(&self.b, &mut out);
+        write_point(&self.c, &mut out);
+        out
+    }
+
+    /// Decodes a proof produced by [`Self::to_ethereum_bytes`], checking that
+    /// its points are on the curve and in the prime-order subgroup.
+    pub fn from_ethereum_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+        let (g1_len, g2_len) = (point_len::<E::G1Affine>(), point_len::<E::G2Affine>());
+        if bytes.len() != 2 * g1_len + g2_len {
+            return Err(SerializationError::InvalidData);
+        }
+        let (a, rest) = bytes.split_at(g1_len);
+        let (b, c) = rest.split_at(g2_len);
+        Ok(Self {
+            a: read_point(a)?,
+            b: read_point(b)?,
+            c: read_point(c)?,
+        })
+    }
+}
+
+impl<E: Pairing> VerifyingKey<E> {
+    /// Encodes the verifying key as
+    /// `alpha_g1 || beta_g2 || gamma_g2 || delta_g2 || gamma_abc_g1[0] || ...`
+    /// in the Ethereum precompile layout.
+    pub fn to_ethereum_calldata(&self) -> Vec<u8> {
+        let (g1_len, g2_len) = (point_len::<E::G1Affine>(), point_len::<E::G2Affine>());
+        let mut out = Vec::with_capacity((1 + self.gamma_abc_g1.len()) * g1_len + 3 * g2_len);
+        write_point(&self.alpha_g1, &mut out);
+        write_point(&self.beta_g2, &mut out);
+        write_point(&self.gamma_g2, &mut out);
+        write_point(&self.delta_g2, &mut out);
+        for g in &self.gamma_abc_g1 {
+            write_point(g, &mut out);
+        }
+        out
+    }
+
+    /// Decodes a verifying key produced by [`Self::to_ethereum_calldata`],
+    /// checking that its points are on the curve and in the prime-order
+    /// subgroup.
+    pub fn from_ethereum_calldata(bytes: &[u8]) -> Result<Self, SerializationError> {
+        let (g1_len, g2_len) = (point_len::<E::G1Affine>(), point_len::<E::G2Affine>());
+        let fixed_len = g1_len + 3 * g2_len;
+        if bytes.len() < fixed_len + g1_len || (bytes.len() - fixed_len) % g1_len != 0 {
+            return Err(SerializationError::InvalidData);
+        }
+        let (alpha_g1, rest) = bytes.split_at(g1_len);
+        let (beta_g2, rest) = rest.split_at(g2_len);
+        let (gamma_g2, rest) = rest.split_at(g2_len);
+        let (delta_g2, rest) = rest.split_at(g2_len);
+        Ok(Self {
+            alpha_g1: read_point(alpha_g1)?,
+            beta_g2: read_point(beta_g2)?,
+            gamma_g2: read_point(gamma_g2)?,
+            delta_g2: read_point(delta_g2)?,
+            gamma_abc_g1: rest
+                .chunks(g1_len)
+                .map(read_point)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
@@ -0,0 +1,241 @@
+//! Import and export of proofs and verifying keys in the binary format
+//! written by [gnark](https://github.com/Consensys/gnark)'s `WriteTo` on
+//! BN254 and BLS12-381.
+//!
+//! gnark encodes points in compressed form: the big-endian `x` coordinate
+//! (imaginary part first for `G2`), with the most significant bits of the
+//! first byte flagging whether `y` is the lexicographically smallest or
+//! largest root, or whether the point is at infinity. The flag layout depends
+//! on the curve: BN254 uses the two top bits, while BLS12-381 uses the three
+//! top bits as in the Zcash encoding. The functions of this module are
+//! therefore only available for the curves that implement [`GnarkCurve`],
+//! which are enabled by the `bn254` and `bls12-381` features. Field elements
+//! are written in canonical (non-Montgomery) form. Slices are prefixed with
+//! their length as a big-endian `u32`.
+//!
+//! Only proofs and keys without gnark's Pedersen commitment extension are
+//! supported: the importers accept the empty commitment fields that recent
+//! gnark versions always append, and reject anything else.
+
+use super::{element_len, points_from_x, read_field_be, write_field_be};
+use crate::{Proof, ProvingKey, VerifyingKey};
+use ark_ec::{pairing::Pairing, AffineRepr};
+use ark_ff::{Field, PrimeField, Zero};
+use ark_serialize::{SerializationError, Valid};
+use ark_std::vec::Vec;
+
+#[cfg(feature = "bls12-381")]
+use ark_bls12_381::Bls12_381;
+#[cfg(feature = "bn254")]
+use ark_bn254::Bn254;
+
+/// The flags that gnark sets in the first byte of a compressed point of a
+/// curve.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PointFlags {
+    mask: u8,
+    compressed_smallest: u8,
+    compressed_largest: u8,
+    compressed_infinity: u8,
+}
+
+/// A curve whose points gnark encodes with a flag layout known to this
+/// module.
+pub trait GnarkCurve: Pairing {
+    /// The flags of compressed points in `G1` and `G2`.
+    const POINT_FLAGS: PointFlags;
+}
+
+#[cfg(feature = "bn254")]
+impl GnarkCurve for Bn254 {
+    const POINT_FLAGS: PointFlags = PointFlags {
+        mask: 0b11 << 6,
+        compressed_smallest: 0b10 << 6,
+        compressed_largest: 0b11 << 6,
+        compressed_infinity: 0b01 << 6,
+    };
+}
+
+#[cfg(feature = "bls12-381")]
+impl GnarkCurve for Bls12_381 {
+    const POINT_FLAGS: PointFlags = PointFlags {
+        mask: 0b111 << 5,
+        compressed_smallest: 0b100 << 5,
+        compressed_largest: 0b101 << 5,
+        compressed_infinity: 0b110 << 5,
+    };
+}
+
+/// The length in bytes of a compressed point.
+fn point_len<G: AffineRepr>() -> usize {
+    G::BaseField::extension_degree() as usize * element_len::<G::BaseField>()
+}
+
+/// Returns whether `y` is larger than `-y` in gnark's lexicographic order,
+/// which compares the highest-degree non-zero coefficient.
+fn lexicographically_largest<F: Field>(y: &F) -> bool {
+    let elements = y.to_base_prime_field_elements().collect::<Vec<_>>();
+    elements
+        .into_iter()
+        .rev()
+        .find(|e| !e.is_zero())
+        .is_some_and(|e| e.into_bigint() > (-e).into_bigint())
+}
+
+fn write_point<G: AffineRepr>(flags: PointFlags, p: &G, out: &mut Vec<u8>) {
+    let start = out.len();
+    match p.xy() {
+        Some((x, y)) => {
+            write_field_be(&x, out);
+            out[start] |= if lexicographically_largest(&y) {
+                flags.compressed_largest
+            } else {
+                flags.compressed_smallest
+            };
+        },
+        None => {
+            out.resize(start + point_len::<G>(), 0);
+            out[start] = flags.compressed_infinity;
+        },
+    }
+}
+
+/// Reads a compressed point from the front of `bytes`, advancing it.
+fn read_point<G: AffineRepr>(
+    flags: PointFlags,
+    bytes: &mut &[u8],
+) -> Result<G, SerializationError> {
+    if bytes.len() < point_len::<G>() {
+        return Err(SerializationError::InvalidData);
+    }
+    let (encoded, rest) = bytes.split_at(point_len::<G>());
+    *bytes = rest;
+
+    let flag = encoded[0] & flags.mask;
+    let mut x = encoded.to_vec();
+    x[0] &= !flags.mask;
+    match flag {
+        _ if flag == flags.compressed_infinity && x.iter().all(|b| *b == 0) => Ok(G::zero()),
+        _ if flag == flags.compressed_smallest || flag == flags.compressed_largest => {
+            let largest = flag == flags.compressed_largest;
+            let point = points_from_x::<G>(read_field_be(&x)?)
+                .ok_or(SerializationError::InvalidData)?
+                .into_iter()
+                .find(|p| p.xy().map(|(_, y)| lexicographically_largest(&y)) == Some(largest))
+                .ok_or(SerializationError::InvalidData)?;
+            point.check()?;
+            Ok(point)
+        },
+        // Uncompressed points (written by gnark's `WriteRawTo`) are not
+        // supported.
+        _ => Err(SerializationError::InvalidData),
+    }
+}
+
+fn read_u32(bytes: &mut &[u8]) -> Result<u32, SerializationError> {
+    if bytes.len() < 4 {
+        return Err(SerializationError::InvalidData);
+    }
+    let (len, rest) = bytes.split_at(4);
+    *bytes = rest;
+    Ok(u32::from_be_bytes([len[0], len[1], len[2], len[3]]))
+}
+
+/// Reads a proof written by gnark's `Proof.WriteTo`.
+///
+/// Returns an error if the proof carries commitments, or if there is any
+/// data left after the proof.
+pub fn proof_from_bytes<E: GnarkCurve>(mut bytes: &[u8]) -> Result<Proof<E>, SerializationError> {
+    let proof = Proof {
+        a: read_point(E::POINT_FLAGS, &mut bytes)?,
+        b: read_point(E::POINT_FLAGS, &mut bytes)?,
+        c: read_point(E::POINT_FLAGS, &mut bytes)?,
+    };
+
+    // Recent gnark versions append the (here empty) commitments and their
+    // proof of knowledge.
+    if !bytes.is_empty() {
+        if read_u32(&mut bytes)? != 0 {
+            return Err(SerializationError::InvalidData);
+        }
+        let pok: E::G1Affine = read_point(E::POINT_FLAGS, &mut bytes)?;
+        if !pok.is_zero() || !bytes.is_empty() {
+            return Err(SerializationError::InvalidData);
+        }
+    }
+    Ok(proof)
+}
+
+/// Writes `proof` as gnark's `Proof.WriteTo` does, with empty commitments.
+pub fn proof_to_bytes<E: GnarkCurve>(proof: &Proof<E>) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_point(E::POINT_FLAGS, &proof.a, &mut out);
+    write_point(E::POINT_FLAGS, &proof.b, &mut out);
+    write_point(E::POINT_FLAGS, &proof.c, &mut out);
+    out.extend_from_slice(&0u32.to_be_bytes());
+    write_point(E::POINT_FLAGS, &E::G1Affine::zero(), &mut out);
+    out
+}
+
+/// Reads a verifying key written by gnark's `VerifyingKey.WriteTo`.
+///
+/// gnark's key additionally holds `beta` and `delta` in `G1`, which are not
+/// part of [`VerifyingKey`] and are discarded. Returns an error if the key
+/// has commitments, or if there is any data left after the key.
+pub fn vk_from_bytes<E: GnarkCurve>(
+    mut bytes: &[u8],
+) -> Result<VerifyingKey<E>, SerializationError> {
+    let alpha_g1 = read_point(E::POINT_FLAGS, &mut bytes)?;
+    let _beta_g1: E::G1Affine = read_point(E::POINT_FLAGS, &mut bytes)?;
+    let beta_g2 = read_point(E::POINT_FLAGS, &mut bytes)?;
+    let gamma_g2 = read_point(E::POINT_FLAGS, &mut bytes)?;
+    let _delta_g1: E::G1Affine = read_point(E::POINT_FLAGS, &mut bytes)?;
+    let delta_g2 = read_point(E::POINT_FLAGS, &mut bytes)?;
+
+    let num_inputs = read_u32(&mut bytes)?;
+    if num_inputs == 0 {
+        return Err(SerializationError::InvalidData);
+    }
+    let gamma_abc_g1 = (0..num_inputs)
+        .map(|_| read_point(E::POINT_FLAGS, &mut bytes))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Recent gnark versions append the (here empty) list of committed public
+    // inputs and the number of commitment keys.
+    if !bytes.is_empty()
+        && (read_u32(&mut bytes)? != 0 || read_u32(&mut bytes)? != 0 || !bytes.is_empty())
+    {
+        return Err(SerializationError::InvalidData);
+    }
+
+    Ok(VerifyingKey {
+        alpha_g1,
+        beta_g2,
+        gamma_g2,
+        delta_g2,
+        gamma_abc_g1,
+    })
+}
+
+/// Writes the verifying key of `pk` as gnark's `VerifyingKey.WriteTo` does,
+/// with no commitments.
+///
+/// This takes the proving key because gnark's verifying key also holds
+/// `beta` and `delta` in `G1`.
+pub fn vk_to_bytes<E: GnarkCurve>(pk: &ProvingKey<E>) -> Vec<u8> {
+    let vk = &pk.vk;
+    let mut out = Vec::new();
+    write_point(E::POINT_FLAGS, &vk.alpha_g1, &mut out);
+    write_point(E::POINT_FLAGS, &pk.beta_g1, &mut out);
+    write_point(E::POINT_FLAGS, &vk.beta_g2, &mut out);
+    write_point(E::POINT_FLAGS, &vk.gamma_g2, &mut out);
+    write_point(E::POINT_FLAGS, &pk.delta_g1, &mut out);
+    write_point(E::POINT_FLAGS, &vk.delta_g2, &mut out);
+    out.extend_from_slice(&(vk.gamma_abc_g1.len() as u32).to_be_bytes());
+    for g in &vk.gamma_abc_g1 {
+        write_point(E::POINT_FLAGS, g, &mut out);
+    }
+    out.extend_from_slice(&0u32.to_be_bytes());
+    out.extend_from_slice(&0u32.to_be_bytes());
+    out
+}
@@ -0,0 +1,70 @@
+/// Byte layouts expected by the Ethereum BN254 precompiles.
+pub mod ethereum;
+
+/// The binary format of gnark proofs and verifying keys.
+#[cfg(any(feature = "bn254", feature = "bls12-381"))]
+pub mod gnark;
+
+/// The JSON format of snarkjs proofs and verifying keys.
+#[cfg(feature = "snarkjs")]
+pub mod snarkjs;
+
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, Field, PrimeField, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_std::vec::Vec;
+
+/// The length in bytes of a big-endian encoded element of the base prime
+/// field of `F`.
+pub(crate) fn element_len<F: Field>() -> usize {
+    F::BasePrimeField::zero().uncompressed_size()
+}
+
+/// Writes `f` as big-endian base prime field elements, highest-degree
+/// coefficient first (i.e. `c1 || c0` for a quadratic extension).
+pub(crate) fn write_field_be<F: Field>(f: &F, out: &mut Vec<u8>) {
+    let mut elements = f.to_base_prime_field_elements().collect::<Vec<_>>();
+    elements.reverse();
+    for e in elements {
+        let bytes = e.into_bigint().to_bytes_be();
+        out.extend_from_slice(&bytes[bytes.len() - element_len::<F>()..]);
+    }
+}
+
+/// Reads a field element written by [`write_field_be`], rejecting
+/// non-canonical encodings.
+pub(crate) fn read_field_be<F: Field>(bytes: &[u8]) -> Result<F, SerializationError> {
+    if bytes.len() != F::extension_degree() as usize * element_len::<F>() {
+        return Err(SerializationError::InvalidData);
+    }
+    let mut elements = bytes
+        .chunks(element_len::<F>())
+        .map(|e| {
+            let mut le = e.to_vec();
+            le.reverse();
+            F::BasePrimeField::deserialize_uncompressed(&le[..])
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    elements.reverse();
+    F::from_base_prime_field_elems(elements).ok_or(SerializationError::InvalidData)
+}
+
+/// Returns the two affine points with `x` coordinate `x`, or `None` if there
+/// are none.
+pub(crate) fn points_from_x<G: AffineRepr>(x: G::BaseField) -> Option<[G; 2]> {
+    let mut x_bytes = Vec::new();
+    x.serialize_compressed(&mut x_bytes).ok()?;
+    let point = G::from_random_bytes(&x_bytes)?;
+    Some([point, (-point.into_group()).into_affine()])
+}
+
+/// Returns the affine point with coordinates `(x, y)`, or `None` if it is not
+/// on the curve.
+///
+/// This only checks that the point lies on the curve; callers handling
+/// untrusted data must still check subgroup membership.
+pub(crate) fn point_from_coordinates<G: AffineRepr>(x: G::BaseField, y: G::BaseField) -> Option<G> {
+    points_from_x::<G>(x)?
+        .into_iter()
+        .find(|p| p.xy().map(|(_, p_y)| p_y) == Some(y))
+}
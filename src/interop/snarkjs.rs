@@ -0,0 +1,184 @@
+//! Import and export of proofs and verifying keys in the JSON format used by
+//! [snarkjs](https://github.com/iden3/snarkjs) (`proof.json` and
+//! `verification_key.json`).
+//!
+//! snarkjs writes points in projective coordinates as decimal strings: a `G1`
+//! point is `[x, y, z]` and a `G2` point is `[[x.c0, x.c1], [y.c0, y.c1],
+//! [z.c0, z.c1]]`, where `z` is `1` (resp. `[1, 0]`) for affine points and `0`
+//! for the point at infinity. Note that, unlike the Solidity calldata snarkjs
+//! exports, the JSON files list the `G2` coordinates real part first.
+//!
+//! The importers only accept the canonical decimal encoding of each field
+//! element, as snarkjs writes it: no sign, no leading zeros, and a value below
+//! the modulus.
+
+use super::point_from_coordinates;
+use crate::{Proof, VerifyingKey};
+use ark_ec::{pairing::Pairing, AffineRepr};
+use ark_ff::{Field, One, PrimeField, Zero};
+use ark_serialize::{SerializationError, Valid};
+use ark_std::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::str::FromStr;
+use serde_json::{json, Value};
+
+fn field_to_json<F: Field>(f: &F) -> Value {
+    let elements = f
+        .to_base_prime_field_elements()
+        .map(|e| Value::String(e.into_bigint().to_string()))
+        .collect::<Vec<_>>();
+    if elements.len() == 1 {
+        elements.into_iter().next().unwrap()
+    } else {
+        Value::Array(elements)
+    }
+}
+
+/// Parses the canonical decimal encoding of an element of `F`.
+fn prime_field_from_decimal<F: PrimeField>(s: &str) -> Option<F> {
+    // `from_str` reduces modulo the characteristic, so anything that does
+    // not print back identically was not canonical.
+    let f = F::from_str(s).ok()?;
+    (f.into_bigint().to_string() == s).then_some(f)
+}
+
+fn field_from_json<F: Field>(value: &Value) -> Result<F, SerializationError> {
+    let element = |v: &Value| {
+        v.as_str()
+            .and_then(prime_field_from_decimal::<F::BasePrimeField>)
+            .ok_or(SerializationError::InvalidData)
+    };
+    let elements = match value {
+        Value::Array(elements) if F::extension_degree() > 1 => elements
+            .iter()
+            .map(element)
+            .collect::<Result<Vec<_>, _>>()?,
+        _ if F::extension_degree() == 1 => ark_std::vec![element(value)?],
+        _ => return Err(SerializationError::InvalidData),
+    };
+    F::from_base_prime_field_elems(elements).ok_or(SerializationError::InvalidData)
+}
+
+fn point_to_json<G: AffineRepr>(p: &G) -> Value {
+    match p.xy() {
+        Some((x, y)) => json!([
+            field_to_json(&x),
+            field_to_json(&y),
+            field_to_json(&G::BaseField::one())
+        ]),
+        None => json!([
+            field_to_json(&G::BaseField::zero()),
+            field_to_json(&G::BaseField::one()),
+            field_to_json(&G::BaseField::zero())
+        ]),
+    }
+}
+
+fn point_from_json<G: AffineRepr>(value: &Value) -> Result<G, SerializationError> {
+    let coordinates = value.as_array().ok_or(SerializationError::InvalidData)?;
+    let (x, y, z) = match &coordinates[..] {
+        [x, y, z] => (
+            field_from_json::<G::BaseField>(x)?,
+            field_from_json::<G::BaseField>(y)?,
+            field_from_json::<G::BaseField>(z)?,
+        ),
+        _ => return Err(SerializationError::InvalidData),
+    };
+
+    if z.is_zero() {
+        return Ok(G::zero());
+    }
+    if !z.is_one() {
+        return Err(SerializationError::InvalidData);
+    }
+    let point = point_from_coordinates::<G>(x, y).ok_or(SerializationError::InvalidData)?;
+    point.check()?;
+    Ok(point)
+}
+
+fn parse(json: &str) -> Result<Value, SerializationError> {
+    let value: Value = serde_json::from_str(json).map_err(|_| SerializationError::InvalidData)?;
+    match value.get("protocol") {
+        None => Ok(value),
+        Some(protocol) if protocol == "groth16" => Ok(value),
+        Some(_) => Err(SerializationError::InvalidData),
+    }
+}
+
+fn field<'a>(value: &'a Value, key: &str) -> Result<&'a Value, SerializationError> {
+    value.get(key).ok_or(SerializationError::InvalidData)
+}
+
+/// Reads a proof from the contents of a snarkjs `proof.json` file.
+pub fn proof_from_json<E: Pairing>(json: &str) -> Result<Proof<E>, SerializationError> {
+    let value = parse(json)?;
+    Ok(Proof {
+        a: point_from_json(field(&value, "pi_a")?)?,
+        b: point_from_json(field(&value, "pi_b")?)?,
+        c: point_from_json(field(&value, "pi_c")?)?,
+    })
+}
+
+/// Writes `proof` in the format of a snarkjs `proof.json` file, for a proof
+/// over `curve` (e.g. `"bn128"`).
+pub fn proof_to_json<E: Pairing>(proof: &Proof<E>, curve: &str) -> String {
+    json!({
+        "pi_a": point_to_json(&proof.a),
+        "pi_b": point_to_json(&proof.b),
+        "pi_c": point_to_json(&proof.c),
+        "protocol": "groth16",
+        "curve": curve,
+    })
+    .to_string()
+}
+
+/// Reads a verifying key from the contents of a snarkjs
+/// `verification_key.json` file.
+pub fn vk_from_json<E: Pairing>(json: &str) -> Result<VerifyingKey<E>, SerializationError> {
+    let value = parse(json)?;
+    let gamma_abc_g1 = field(&value, "IC")?
+        .as_array()
+        .ok_or(SerializationError::InvalidData)?
+        .iter()
+        .map(point_from_json)
+        .collect::<Result<Vec<_>, _>>()?;
+    if gamma_abc_g1.is_empty() {
+        return Err(SerializationError::InvalidData);
+    }
+    if let Some(n_public) = value.get("nPublic") {
+        if n_public.as_u64() != Some(gamma_abc_g1.len() as u64 - 1) {
+            return Err(SerializationError::InvalidData);
+        }
+    }
+
+    Ok(VerifyingKey {
+        alpha_g1: point_from_json(field(&value, "vk_alpha_1")?)?,
+        beta_g2: point_from_json(field(&value, "vk_beta_2")?)?,
+        gamma_g2: point_from_json(field(&value, "vk_gamma_2")?)?,
+        delta_g2: point_from_json(field(&value, "vk_delta_2")?)?,
+        gamma_abc_g1,
+    })
+}
+
+/// Writes `vk` in the format of a snarkjs `verification_key.json` file, for a
+/// key over `curve` (e.g. `"bn128"`).
+pub fn vk_to_json<E: Pairing>(vk: &VerifyingKey<E>, curve: &str) -> String {
+    let ic = vk
+        .gamma_abc_g1
+        .iter()
+        .map(point_to_json)
+        .collect::<Vec<_>>();
+    json!({
+        "protocol": "groth16",
+        "curve": curve,
+        "nPublic": vk.gamma_abc_g1.len().saturating_sub(1),
+        "vk_alpha_1": point_to_json(&vk.alpha_g1),
+        "vk_beta_2": point_to_json(&vk.beta_g2),
+        "vk_gamma_2": point_to_json(&vk.gamma_g2),
+        "vk_delta_2": point_to_json(&vk.delta_g2),
+        "IC": ic,
+    })
+    .to_string()
+}
@@ -10,7 +10,10 @@
     missing_docs
 )]
 #![allow(clippy::many_single_char_names, clippy::op_ref)]
-#![forbid(unsafe_code)]
+#![cfg_attr(not(feature = "mmap"), forbid(unsafe_code))]
+// Memory-mapping a file is inherently unsafe; `ProvingKey::deserialize_mmap`
+// is the only place that opts out of this lint.
+#![cfg_attr(feature = "mmap", deny(unsafe_code))]
 
 #[macro_use]
 extern crate ark_std;
@@ -30,19 +33,55 @@ pub mod prover;
 /// Verify proofs for the Groth16 zkSNARK construction.
 pub mod verifier;
 
+/// The error type of the native Groth16 APIs.
+pub mod error;
+
+/// Proving with a witness that is contributed in parts.
+pub mod collaborative;
+
+/// Phase-2 multi-party computation ceremonies for Groth16 parameters.
+pub mod mpc;
+
+/// Groth16 with public inputs that are committed to instead of revealed.
+pub mod commitment;
+
+/// Storage backends for proving keys.
+pub mod storage;
+
+/// Pluggable multi-scalar multiplication backends for the prover.
+pub mod msm;
+
+/// Import and export of proofs and keys in formats used by other Groth16
+/// implementations.
+pub mod interop;
+
+/// Proofs and verifying keys over a curve that is chosen at runtime.
+#[cfg(any(feature = "bn254", feature = "bls12-381"))]
+pub mod dynamic;
+
+/// Fixed proving keys, verifying keys and proofs for regression testing
+/// across versions.
+#[cfg(feature = "test-vectors")]
+pub mod test_vectors;
+
 /// Constraints for the Groth16 verifier.
 #[cfg(feature = "r1cs")]
 pub mod constraints;
 
+/// `serde` support for the Groth16 data structures.
+#[cfg(feature = "serde")]
+mod serde_impls;
+
 #[cfg(test)]
 mod test;
 
-pub use self::{data_structures::*, verifier::*};
+pub use self::{data_structures::*, error::*, verifier::*};
 
 use ark_ec::pairing::Pairing;
-use ark_relations::gr1cs::{ConstraintSynthesizer, SynthesisError};
+use ark_ff::{PrimeField, Zero};
+use ark_relations::gr1cs::ConstraintSynthesizer;
 use ark_snark::*;
-use ark_std::{marker::PhantomData, rand::RngCore, vec::Vec};
+use ark_std::{marker::PhantomData, rand::RngCore, vec::Vec, UniformRand};
 use r1cs_to_qap::{LibsnarkReduction, R1CSToQAP};
 
 /// The SNARK of [[Groth16]](https://eprint.iacr.org/2016/260.pdf).
@@ -55,7 +94,7 @@ impl<E: Pairing, QAP: R1CSToQAP> SNARK<E::ScalarField> for Groth16<E, QAP> {
     type VerifyingKey = VerifyingKey<E>;
     type Proof = Proof<E>;
     type ProcessedVerifyingKey = PreparedVerifyingKey<E>;
-    type Error = SynthesisError;
+    type Error = Groth16Error;
 
     fn circuit_specific_setup<C: ConstraintSynthesizer<E::ScalarField>, R: RngCore>(
         circuit: C,
@@ -76,7 +115,10 @@ impl<E: Pairing, QAP: R1CSToQAP> SNARK<E::ScalarField> for Groth16<E, QAP> {
         circuit: C,
         rng: &mut R,
     ) -> Result<Self::Proof, Self::Error> {
-        Self::create_random_proof_with_reduction(circuit, pk, rng)
+        let r = E::ScalarField::rand(rng);
+        let s = E::ScalarField::rand(rng);
+
+        Self::prove_with_backend(circuit, pk, r, s, &msm::CpuBackend)
     }
 
     fn process_vk(
@@ -85,13 +127,66 @@ impl<E: Pairing, QAP: R1CSToQAP> SNARK<E::ScalarField> for Groth16<E, QAP> {
         Ok(prepare_verifying_key(circuit_vk))
     }
 
+    fn verify(
+        circuit_vk: &Self::VerifyingKey,
+        x: &[E::ScalarField],
+        proof: &Self::Proof,
+    ) -> Result<bool, Self::Error> {
+        check_input_len(&circuit_vk.gamma_abc_g1, x)?;
+        Ok(Self::verify_proof_with_vk(circuit_vk, proof, x)?)
+    }
+
     fn verify_with_processed_vk(
         circuit_pvk: &Self::ProcessedVerifyingKey,
         x: &[E::ScalarField],
         proof: &Self::Proof,
     ) -> Result<bool, Self::Error> {
+        check_input_len(&circuit_pvk.vk.gamma_abc_g1, x)?;
         Ok(Self::verify_proof(&circuit_pvk, proof, &x)?)
     }
 }
 
+/// Checks that `x` has one element less than `gamma_abc_g1`.
+fn check_input_len<G, F>(gamma_abc_g1: &[G], x: &[F]) -> Result<(), Groth16Error> {
+    let expected = gamma_abc_g1
+        .len()
+        .checked_sub(1)
+        .ok_or(Groth16Error::MalformedVerifyingKey)?;
+    if x.len() != expected {
+        return Err(Groth16Error::InputLengthMismatch {
+            expected,
+            got: x.len(),
+        });
+    }
+    Ok(())
+}
+
 impl<E: Pairing, QAP: R1CSToQAP> CircuitSpecificSetupSNARK<E::ScalarField> for Groth16<E, QAP> {}
+
+/// A [`SNARK`] that can also create proofs without zero knowledge, which is
+/// faster when the witness does not need to be hidden.
+pub trait NoZkSNARK<F: PrimeField>: SNARK<F> {
+    /// Creates a proof for `circuit` that is **not** zero-knowledge.
+    ///
+    /// The proof verifies like any other, but it is a deterministic function
+    /// of the witness, so it leaks information about it: e.g. anyone who can
+    /// guess the witness can confirm the guess by recomputing the proof. Only
+    /// use this when confidentiality of the witness does not matter.
+    fn prove_no_zk<C: ConstraintSynthesizer<F>>(
+        pk: &Self::ProvingKey,
+        circuit: C,
+    ) -> Result<Self::Proof, Self::Error>;
+}
+
+impl<E: Pairing, QAP: R1CSToQAP> NoZkSNARK<E::ScalarField> for Groth16<E, QAP> {
+    /// With `r = s = 0`, the prover skips the blinding terms, which are all
+    /// multiples of `delta`, and the multi-scalar multiplication of `B` in
+    /// `G1`.
+    fn prove_no_zk<C: ConstraintSynthesizer<E::ScalarField>>(
+        pk: &Self::ProvingKey,
+        circuit: C,
+    ) -> Result<Self::Proof, Self::Error> {
+        let zero = E::ScalarField::zero();
+        Self::prove_with_backend(circuit, pk, zero, zero, &msm::CpuBackend)
+    }
+}
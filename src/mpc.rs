@@ -0,0 +1,180 @@
+use crate::{generator::wipe, verifier::batch_sponge_config, ProvingKey, VerifyingKey};
+use ark_crypto_primitives::sponge::{poseidon::PoseidonSponge, CryptographicSponge};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, One, UniformRand, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{cfg_iter, rand::Rng, vec::Vec};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// A record of a single phase-2 contribution, produced by [`contribute`].
+///
+/// It proves knowledge of the scalar `d` by which the contributor multiplied
+/// `delta`, without revealing it, and binds that proof to the parameters
+/// before and after the contribution.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Contribution<E: Pairing> {
+    /// The Schnorr commitment `k * delta_g1`, where `delta_g1` is taken from
+    /// the parameters before the contribution.
+    pub commitment: E::G1Affine,
+    /// The Schnorr response `k + c * d`.
+    pub response: E::ScalarField,
+    /// The hash of the `delta`-dependent parameters before and after the
+    /// contribution. Publishing it lets participants check that their
+    /// contribution was included in the final parameters.
+    pub hash: E::ScalarField,
+}
+
+/// Absorbs every element of `pk` that a contribution changes.
+fn absorb_delta_terms<E: Pairing>(sponge: &mut PoseidonSponge<E::ScalarField>, pk: &ProvingKey<E>) {
+    let mut bytes = Vec::new();
+    pk.delta_g1.serialize_compressed(&mut bytes).unwrap();
+    pk.vk.delta_g2.serialize_compressed(&mut bytes).unwrap();
+    pk.l_query.serialize_compressed(&mut bytes).unwrap();
+    pk.h_query.serialize_compressed(&mut bytes).unwrap();
+    sponge.absorb(&bytes);
+}
+
+/// Squeezes the Schnorr challenge for `commitment` out of `sponge`.
+fn challenge<E: Pairing>(
+    sponge: &mut PoseidonSponge<E::ScalarField>,
+    commitment: &E::G1Affine,
+) -> E::ScalarField {
+    let mut bytes = Vec::new();
+    commitment.serialize_compressed(&mut bytes).unwrap();
+    sponge.absorb(&bytes);
+    sponge.squeeze_field_elements(1)[0]
+}
+
+/// Multiplies every element of `points` by `scalar`.
+fn rescale<G: AffineRepr>(points: &[G], scalar: G::ScalarField) -> Vec<G> {
+    let scaled = cfg_iter!(points).map(|p| *p * scalar).collect::<Vec<_>>();
+    G::Group::normalize_batch(&scaled)
+}
+
+/// Contributes fresh randomness to a phase-2 ceremony.
+///
+/// A secret `d` is sampled from `entropy`, `delta` is replaced by
+/// `d * delta` in `params` and `vk`, and `l_query` and `h_query` are divided
+/// by `d` so that the parameters still describe the same circuit. The secret
+/// is discarded afterwards; the contribution is secure as long as it is never
+/// recovered, or as long as any other participant's secret is not.
+///
+/// The returned [`Contribution`] can be checked with [`verify_contribution`]
+/// against the parameters before and after this call.
+pub fn contribute<E: Pairing>(
+    params: &mut ProvingKey<E>,
+    vk: &mut VerifyingKey<E>,
+    entropy: &mut impl Rng,
+) -> Contribution<E> {
+    let mut sponge = PoseidonSponge::new(&batch_sponge_config::<E::ScalarField>());
+    absorb_delta_terms(&mut sponge, params);
+
+    let mut d = loop {
+        let d = E::ScalarField::rand(entropy);
+        if !d.is_zero() {
+            break d;
+        }
+    };
+    let mut d_inverse = d.inverse().unwrap();
+
+    let delta_g1 = params.delta_g1;
+    params.delta_g1 = (delta_g1 * d).into_affine();
+    params.vk.delta_g2 = (params.vk.delta_g2 * d).into_affine();
+    params.l_query = rescale(&params.l_query, d_inverse);
+    params.h_query = rescale(&params.h_query, d_inverse);
+    vk.delta_g2 = params.vk.delta_g2;
+
+    absorb_delta_terms(&mut sponge, params);
+    let hash: E::ScalarField = sponge.squeeze_field_elements(1)[0];
+
+    let mut k = E::ScalarField::rand(entropy);
+    let commitment = (delta_g1 * k).into_affine();
+    let c = challenge::<E>(&mut sponge, &commitment);
+    let response = k + c * d;
+
+    wipe(&mut d);
+    wipe(&mut d_inverse);
+    wipe(&mut k);
+
+    Contribution {
+        commitment,
+        response,
+        hash,
+    }
+}
+
+/// Checks that `after` was obtained from `before` by the single contribution
+/// described by `contribution`.
+///
+/// This verifies that only `delta` and the `delta`-dependent elements
+/// changed, that the contributor knew the scalar by which `delta` was
+/// multiplied, that `delta` was updated consistently in `E::G1` and `E::G2`,
+/// and that `l_query` and `h_query` were divided by that same scalar.
+pub fn verify_contribution<E: Pairing>(
+    before: &ProvingKey<E>,
+    after: &ProvingKey<E>,
+    contribution: &Contribution<E>,
+) -> bool {
+    let unchanged = before.vk.alpha_g1 == after.vk.alpha_g1
+        && before.vk.beta_g2 == after.vk.beta_g2
+        && before.vk.gamma_g2 == after.vk.gamma_g2
+        && before.vk.gamma_abc_g1 == after.vk.gamma_abc_g1
+        && before.beta_g1 == after.beta_g1
+        && before.a_query == after.a_query
+        && before.b_g1_query == after.b_g1_query
+        && before.b_g2_query == after.b_g2_query
+        && before.l_query.len() == after.l_query.len()
+        && before.h_query.len() == after.h_query.len();
+    if !unchanged || after.delta_g1.is_zero() || after.vk.delta_g2.is_zero() {
+        return false;
+    }
+
+    let mut sponge = PoseidonSponge::new(&batch_sponge_config::<E::ScalarField>());
+    absorb_delta_terms(&mut sponge, before);
+    absorb_delta_terms(&mut sponge, after);
+    let hash: E::ScalarField = sponge.squeeze_field_elements(1)[0];
+    if hash != contribution.hash {
+        return false;
+    }
+
+    // The contributor knows `d` such that `after.delta_g1 = d * before.delta_g1`.
+    let c = challenge::<E>(&mut sponge, &contribution.commitment);
+    if before.delta_g1 * contribution.response != after.delta_g1 * c + contribution.commitment {
+        return false;
+    }
+
+    // `delta_g2` was multiplied by the same `d`.
+    if E::pairing(after.delta_g1, before.vk.delta_g2)
+        != E::pairing(before.delta_g1, after.vk.delta_g2)
+    {
+        return false;
+    }
+
+    // Every element of `l_query` and `h_query` was divided by `d`. Instead of
+    // one pairing check per element, check a random linear combination with
+    // coefficients derived from the transcript.
+    let rho: E::ScalarField = sponge.squeeze_field_elements(1)[0];
+    let n = before.l_query.len() + before.h_query.len();
+    let mut powers = Vec::with_capacity(n);
+    let mut power = E::ScalarField::one();
+    for _ in 0..n {
+        powers.push(power);
+        power *= rho;
+    }
+    let combine = |pk: &ProvingKey<E>| {
+        let bases = pk
+            .l_query
+            .iter()
+            .chain(&pk.h_query)
+            .copied()
+            .collect::<Vec<_>>();
+        E::G1::msm_unchecked(&bases, &powers)
+    };
+    E::multi_pairing(
+        [combine(after), -combine(before)],
+        [after.vk.delta_g2, before.vk.delta_g2],
+    )
+    .is_zero()
+}
@@ -0,0 +1,47 @@
+use crate::storage::QuerySource;
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+
+/// The big-integer representation of the scalars of `E`.
+pub type ScalarBigInt<E> = <<E as Pairing>::ScalarField as PrimeField>::BigInt;
+
+/// Computes the multi-scalar multiplications of the prover.
+///
+/// Every method defaults to [`QuerySource::msm_bigint`], i.e. to the arkworks
+/// CPU implementation for query vectors held in memory, so an implementation
+/// only needs to override the operations it accelerates. Backends that need
+/// the bases in contiguous memory, such as GPU implementations, can obtain
+/// them from [`QuerySource::as_slice`] and fall back to the default for
+/// query vectors stored differently.
+///
+/// The prover is generic over the backend, so the default [`CpuBackend`]
+/// compiles to the same code as calling the query vectors directly.
+pub trait MsmBackend<E: Pairing> {
+    /// Computes `sum_i scalars[i] * bases[offset + i]` in `E::G1`. Scalars
+    /// beyond the end of `bases` are ignored.
+    fn msm_g1(
+        &self,
+        bases: &impl QuerySource<E::G1Affine>,
+        offset: usize,
+        scalars: &[ScalarBigInt<E>],
+    ) -> E::G1 {
+        bases.msm_bigint(offset, scalars)
+    }
+
+    /// Computes `sum_i scalars[i] * bases[offset + i]` in `E::G2`. Scalars
+    /// beyond the end of `bases` are ignored.
+    fn msm_g2(
+        &self,
+        bases: &impl QuerySource<E::G2Affine>,
+        offset: usize,
+        scalars: &[ScalarBigInt<E>],
+    ) -> E::G2 {
+        bases.msm_bigint(offset, scalars)
+    }
+}
+
+/// The default [`MsmBackend`], which uses the arkworks CPU implementation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CpuBackend;
+
+impl<E: Pairing> MsmBackend<E> for CpuBackend {}
@@ -1,14 +1,22 @@
-use crate::{r1cs_to_qap::R1CSToQAP, Groth16, Proof, ProvingKey, VerifyingKey};
-use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
-use ark_ff::{Field, PrimeField, UniformRand, Zero};
+#[cfg(feature = "debug-internals")]
+use crate::r1cs_to_qap::LibsnarkReduction;
+use crate::{
+    generator::Secret,
+    msm::{CpuBackend, MsmBackend, ScalarBigInt},
+    r1cs_to_qap::{check_r1cs_only, QapBuffers, R1CSToQAP},
+    storage::{PreparedProvingKey, ProvingKeyStorage, QuerySource},
+    Groth16, Groth16Error, Proof, ProvingKey, VerifyingKey,
+};
+use ark_ec::{pairing::Pairing, scalar_mul::BatchMulPreprocessing, AffineRepr, CurveGroup};
+use ark_ff::{BigInt, Fp, FpConfig, One, PrimeField, UniformRand, Zero};
 use ark_poly::GeneralEvaluationDomain;
 use ark_relations::gr1cs::{
-    ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, Result as R1CSResult, SynthesisMode,
+    ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, OptimizationGoal,
+    Result as R1CSResult, SynthesisError, SynthesisMode, R1CS_PREDICATE_LABEL,
 };
 use ark_relations::utils::matrix::Matrix;
 use ark_std::rand::Rng;
 use ark_std::{
-    cfg_into_iter, cfg_iter,
     ops::{AddAssign, Mul},
     vec::Vec,
 };
@@ -18,6 +26,142 @@ use rayon::prelude::*;
 
 type D<F> = GeneralEvaluationDomain<F>;
 
+/// A stage of proof creation, reported to a [`ProofProgress`] once it is
+/// done.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProvingPhase {
+    /// The circuit was synthesized and its linear combinations inlined.
+    ConstraintSynthesis,
+    /// The coefficients of `h(x)` were computed.
+    WitnessMap,
+    /// The multi-scalar multiplication with `h_query` was computed.
+    HQueryMsm,
+    /// The multi-scalar multiplication with `l_query` was computed.
+    LQueryMsm,
+    /// The multi-scalar multiplication with `a_query` was computed.
+    AQueryMsm,
+    /// The multi-scalar multiplication with `b_g1_query` was computed.
+    BG1QueryMsm,
+    /// The multi-scalar multiplication with `b_g2_query` was computed.
+    BG2QueryMsm,
+}
+
+impl ProvingPhase {
+    const ALL: [Self; 7] = [
+        Self::ConstraintSynthesis,
+        Self::WitnessMap,
+        Self::HQueryMsm,
+        Self::LQueryMsm,
+        Self::AQueryMsm,
+        Self::BG1QueryMsm,
+        Self::BG2QueryMsm,
+    ];
+
+    /// The fraction of the stages of proof creation that are done once this
+    /// stage is.
+    pub fn fraction(self) -> f64 {
+        let index = Self::ALL.iter().position(|phase| *phase == self).unwrap();
+        (index + 1) as f64 / Self::ALL.len() as f64
+    }
+}
+
+/// Receives progress reports from [`Groth16::create_proof_with_progress`].
+///
+/// This is implemented for every `FnMut(ProvingPhase, f64)`.
+pub trait ProofProgress {
+    /// Called once `phase` is done, with the fraction of the stages of proof
+    /// creation that are done.
+    fn on_progress(&mut self, phase: ProvingPhase, fraction: f64);
+}
+
+impl<F: FnMut(ProvingPhase, f64)> ProofProgress for F {
+    fn on_progress(&mut self, phase: ProvingPhase, fraction: f64) {
+        self(phase, fraction)
+    }
+}
+
+fn report(progress: &mut Option<&mut dyn ProofProgress>, phase: ProvingPhase) {
+    if let Some(progress) = progress {
+        progress.on_progress(phase, phase.fraction());
+    }
+}
+
+/// The blinding terms of a proof that only depend on the randomness `r` and
+/// `s` and on `delta`.
+struct Blinding<E: Pairing> {
+    /// `r * delta_g1`.
+    r_g1: E::G1,
+    /// `s * delta_g1`.
+    s_g1: E::G1,
+    /// `r * s * delta_g1`.
+    r_s_g1: E::G1,
+    /// `s * delta_g2`.
+    s_g2: E::G2,
+}
+
+/// Buffers that [`Groth16::create_proof_with_scratch`] reuses across proofs,
+/// so that proving circuits of the same size repeatedly does not allocate the
+/// large temporary vectors of the prover again: the QAP evaluations, which
+/// have the size of the evaluation domain, the full assignment, and the
+/// scalars of the multi-scalar multiplications.
+///
+/// A new scratch holds no memory; it grows to the size of the largest circuit
+/// it has been used for and keeps that memory until it is dropped.
+#[derive(Clone, Debug)]
+pub struct ProverScratch<E: Pairing> {
+    qap: QapBuffers<E::ScalarField>,
+    full_assignment: Vec<E::ScalarField>,
+    scalars: Vec<ScalarBigInt<E>>,
+}
+
+impl<E: Pairing> ProverScratch<E> {
+    /// Returns an empty scratch.
+    pub fn new() -> Self {
+        Self {
+            qap: QapBuffers::default(),
+            full_assignment: Vec::new(),
+            scalars: Vec::new(),
+        }
+    }
+
+    /// Returns the number of bytes that the buffers have reserved.
+    pub fn reserved_bytes(&self) -> usize {
+        let field_elements = self.qap.a.capacity()
+            + self.qap.b.capacity()
+            + self.qap.c.capacity()
+            + self.full_assignment.capacity();
+        field_elements * core::mem::size_of::<E::ScalarField>()
+            + self.scalars.capacity() * core::mem::size_of::<ScalarBigInt<E>>()
+    }
+}
+
+impl<E: Pairing> Default for ProverScratch<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Appends the big-integer representations of `scalars` to `out`.
+fn extend_bigints<F: PrimeField>(out: &mut Vec<F::BigInt>, scalars: &[F]) {
+    #[cfg(feature = "parallel")]
+    out.par_extend(scalars.par_iter().map(|s| s.into_bigint()));
+    #[cfg(not(feature = "parallel"))]
+    out.extend(scalars.iter().map(|s| s.into_bigint()));
+}
+
+impl<E: Pairing> Blinding<E> {
+    /// The blinding terms for `r = s = 0`, i.e. of a proof that is not
+    /// zero-knowledge.
+    fn zero() -> Self {
+        Self {
+            r_g1: E::G1::zero(),
+            s_g1: E::G1::zero(),
+            r_s_g1: E::G1::zero(),
+            s_g2: E::G2::zero(),
+        }
+    }
+}
+
 impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
     /// Create a Groth16 proof using randomness `r` and `s` and
     /// the provided R1CS-to-QAP reduction, using the provided
@@ -43,62 +187,235 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         end_timer!(witness_map_time);
         let input_assignment = &full_assignment[1..num_inputs];
         let aux_assignment = &full_assignment[num_inputs..];
-        let proof =
-            Self::create_proof_with_assignment(pk, r, s, &h, input_assignment, aux_assignment)?;
+        let proof = Self::create_proof_with_assignment(
+            pk,
+            &CpuBackend,
+            r,
+            s,
+            &h,
+            input_assignment,
+            aux_assignment,
+            &mut Vec::new(),
+            &mut None,
+        )?;
         end_timer!(prover_time);
 
         Ok(proof)
     }
 
-    #[inline]
-    fn create_proof_with_assignment(
+    /// Create a zero-knowledge Groth16 proof directly from a precomputed
+    /// assignment, skipping constraint synthesis.
+    ///
+    /// `matrices` are the R1CS matrices `[A, B, C]` of the circuit that `pk`
+    /// was generated for, `public` holds the instance assignment *without* the
+    /// leading constant `1`, and `witness` holds the witness assignment. This
+    /// lets a circuit be synthesized once and then proven many times for
+    /// different witnesses. Returns [`SynthesisError::Unsatisfiable`] if the
+    /// assignment lengths do not match `pk`.
+    pub fn prove_from_assignment(
+        pk: &ProvingKey<E>,
+        matrices: &[Matrix<E::ScalarField>],
+        public: &[E::ScalarField],
+        witness: &[E::ScalarField],
+        rng: &mut impl Rng,
+    ) -> R1CSResult<Proof<E>> {
+        Self::check_assignment_lengths(pk, matrices, public.len(), witness.len())?;
+
+        let r = E::ScalarField::rand(rng);
+        let s = E::ScalarField::rand(rng);
+
+        let full_assignment = [&[E::ScalarField::one()][..], public, witness].concat();
+        Self::create_proof_with_reduction_and_matrices(
+            pk,
+            r,
+            s,
+            matrices,
+            public.len() + 1,
+            matrices[0].len(),
+            &full_assignment,
+        )
+    }
+
+    /// Like [`Self::prove_from_assignment`], but takes the witness as the raw
+    /// limbs of its Montgomery representation, e.g. as produced by another
+    /// component that uses the same field implementation.
+    ///
+    /// This is the internal representation of [`Fp`], so the limbs are used
+    /// as is instead of being converted with a multiplication per element as
+    /// [`PrimeField::from_bigint`] would. Returns
+    /// [`SynthesisError::Unsatisfiable`] if the assignment lengths do not
+    /// match `pk` or if any limbs are not reduced modulo the field modulus.
+    pub fn create_proof_from_montgomery<P: FpConfig<N>, const N: usize>(
+        pk: &ProvingKey<E>,
+        matrices: &[Matrix<E::ScalarField>],
+        public: &[E::ScalarField],
+        witness_mont: &[BigInt<N>],
+        rng: &mut impl Rng,
+    ) -> R1CSResult<Proof<E>>
+    where
+        E: Pairing<ScalarField = Fp<P, N>>,
+    {
+        Self::check_assignment_lengths(pk, matrices, public.len(), witness_mont.len())?;
+
+        // The witness is converted straight into the full assignment, so it
+        // is copied only once.
+        let mut full_assignment = Vec::with_capacity(1 + public.len() + witness_mont.len());
+        full_assignment.push(E::ScalarField::one());
+        full_assignment.extend_from_slice(public);
+        for &limbs in witness_mont {
+            if limbs >= P::MODULUS {
+                return Err(SynthesisError::Unsatisfiable);
+            }
+            full_assignment.push(Fp::new_unchecked(limbs));
+        }
+
+        let r = E::ScalarField::rand(rng);
+        let s = E::ScalarField::rand(rng);
+        Self::create_proof_with_reduction_and_matrices(
+            pk,
+            r,
+            s,
+            matrices,
+            public.len() + 1,
+            matrices[0].len(),
+            &full_assignment,
+        )
+    }
+
+    /// Checks that `matrices` are three matrices with the same number of
+    /// rows, and that an assignment with `num_public` public inputs and
+    /// `num_witness` witness variables fits `pk`.
+    fn check_assignment_lengths(
         pk: &ProvingKey<E>,
+        matrices: &[Matrix<E::ScalarField>],
+        num_public: usize,
+        num_witness: usize,
+    ) -> R1CSResult<()> {
+        if matrices.len() != 3
+            || matrices[1].len() != matrices[0].len()
+            || matrices[2].len() != matrices[0].len()
+            || num_public + 1 != pk.vk.gamma_abc_g1.len()
+            || num_witness != pk.l_query.len()
+        {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        Ok(())
+    }
+
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn create_proof_with_assignment<PK: ProvingKeyStorage<E>, B: MsmBackend<E>>(
+        pk: &PK,
+        backend: &B,
         r: E::ScalarField,
         s: E::ScalarField,
         h: &[E::ScalarField],
         input_assignment: &[E::ScalarField],
         aux_assignment: &[E::ScalarField],
+        scalars: &mut Vec<ScalarBigInt<E>>,
+        progress: &mut Option<&mut dyn ProofProgress>,
     ) -> R1CSResult<Proof<E>> {
-        let c_acc_time = start_timer!(|| "Compute C");
-        let h_assignment = cfg_into_iter!(h)
-            .map(|s| s.into_bigint())
-            .collect::<Vec<_>>();
-        let h_acc = E::G1::msm_bigint(&pk.h_query, &h_assignment);
-        drop(h_assignment);
+        let blinding = if r.is_zero() && s.is_zero() {
+            Blinding::zero()
+        } else {
+            let delta_g1 = pk.delta_g1();
+            Blinding {
+                r_g1: delta_g1.mul(r),
+                s_g1: delta_g1.mul(s),
+                r_s_g1: delta_g1 * (r * s),
+                s_g2: pk.vk().delta_g2.mul(s),
+            }
+        };
+        Self::create_proof_with_assignment_and_blinding(
+            pk,
+            backend,
+            r,
+            s,
+            &blinding,
+            h,
+            input_assignment,
+            aux_assignment,
+            scalars,
+            progress,
+        )
+    }
 
-        // Compute C
-        let aux_assignment = cfg_iter!(aux_assignment)
-            .map(|s| s.into_bigint())
-            .collect::<Vec<_>>();
+    /// Returns [`SynthesisError::Unsatisfiable`] unless the assignment and
+    /// the QAP witness `h` have the number of elements that `pk` expects, so
+    /// that a circuit that does not match `pk` is rejected instead of causing
+    /// a panic or an invalid proof.
+    fn check_assignment_shape<PK: ProvingKeyStorage<E>>(
+        pk: &PK,
+        h: &[E::ScalarField],
+        input_assignment: &[E::ScalarField],
+        aux_assignment: &[E::ScalarField],
+    ) -> R1CSResult<()> {
+        let num_variables = 1 + input_assignment.len() + aux_assignment.len();
+        if input_assignment.len() + 1 != pk.vk().gamma_abc_g1.len()
+            || aux_assignment.len() != pk.l_query().len()
+            || pk.a_query().len() != num_variables
+            || pk.b_g1_query().len() != num_variables
+            || pk.b_g2_query().len() != num_variables
+            || h.len() > pk.h_query().len() + 1
+        {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        Ok(())
+    }
 
-        let l_aux_acc = E::G1::msm_bigint(&pk.l_query, &aux_assignment);
+    #[inline]
+    #[allow(clippy::too_many_arguments)]
+    fn create_proof_with_assignment_and_blinding<PK: ProvingKeyStorage<E>, B: MsmBackend<E>>(
+        pk: &PK,
+        backend: &B,
+        r: E::ScalarField,
+        s: E::ScalarField,
+        blinding: &Blinding<E>,
+        h: &[E::ScalarField],
+        input_assignment: &[E::ScalarField],
+        aux_assignment: &[E::ScalarField],
+        scalars: &mut Vec<ScalarBigInt<E>>,
+        progress: &mut Option<&mut dyn ProofProgress>,
+    ) -> R1CSResult<Proof<E>> {
+        Self::check_assignment_shape(pk, h, input_assignment, aux_assignment)?;
 
-        let r_s_delta_g1 = pk.delta_g1 * (r * s);
+        // `scalars` holds the scalars of `h` first, and then those of the
+        // assignment, so that a caller-provided buffer is reused for both.
+        let c_acc_time = start_timer!(|| "Compute C");
+        scalars.clear();
+        extend_bigints(scalars, h);
+        let h_acc = backend.msm_g1(&pk.h_query(), 0, scalars);
+        report(progress, ProvingPhase::HQueryMsm);
 
-        end_timer!(c_acc_time);
+        // Compute C
+        scalars.clear();
+        extend_bigints(scalars, input_assignment);
+        extend_bigints(scalars, aux_assignment);
+        let num_inputs = input_assignment.len();
+        let assignment = &scalars[..];
 
-        let input_assignment = input_assignment
-            .iter()
-            .map(|s| s.into_bigint())
-            .collect::<Vec<_>>();
+        let l_aux_acc = backend.msm_g1(&pk.l_query(), 0, &assignment[num_inputs..]);
+        report(progress, ProvingPhase::LQueryMsm);
 
-        let assignment = [&input_assignment[..], &aux_assignment[..]].concat();
-        drop(aux_assignment);
+        end_timer!(c_acc_time);
 
         // Compute A
         let a_acc_time = start_timer!(|| "Compute A");
-        let r_g1 = pk.delta_g1.mul(r);
-
-        let g_a = Self::calculate_coeff(r_g1, &pk.a_query, pk.vk.alpha_g1, &assignment);
+        let a_query = pk.a_query();
+        let a_acc = backend.msm_g1(&a_query, 1, assignment);
+        let g_a = Self::calculate_coeff(blinding.r_g1, a_query.get(0), a_acc, pk.vk().alpha_g1);
 
-        let s_g_a = g_a * &s;
+        let s_g_a = if s.is_zero() { E::G1::zero() } else { g_a * &s };
         end_timer!(a_acc_time);
+        report(progress, ProvingPhase::AQueryMsm);
 
         // Compute B in G1 if needed
         let g1_b = if !r.is_zero() {
             let b_g1_acc_time = start_timer!(|| "Compute B in G1");
-            let s_g1 = pk.delta_g1.mul(s);
-            let g1_b = Self::calculate_coeff(s_g1, &pk.b_g1_query, pk.beta_g1, &assignment);
+            let b_g1_query = pk.b_g1_query();
+            let b_g1_acc = backend.msm_g1(&b_g1_query, 1, assignment);
+            let g1_b =
+                Self::calculate_coeff(blinding.s_g1, b_g1_query.get(0), b_g1_acc, pk.beta_g1());
 
             end_timer!(b_g1_acc_time);
 
@@ -106,20 +423,27 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         } else {
             E::G1::zero()
         };
+        report(progress, ProvingPhase::BG1QueryMsm);
 
         // Compute B in G2
         let b_g2_acc_time = start_timer!(|| "Compute B in G2");
-        let s_g2 = pk.vk.delta_g2.mul(s);
-        let g2_b = Self::calculate_coeff(s_g2, &pk.b_g2_query, pk.vk.beta_g2, &assignment);
-        let r_g1_b = g1_b * &r;
-        drop(assignment);
+        let b_g2_query = pk.b_g2_query();
+        let b_g2_acc = backend.msm_g2(&b_g2_query, 1, assignment);
+        let g2_b =
+            Self::calculate_coeff(blinding.s_g2, b_g2_query.get(0), b_g2_acc, pk.vk().beta_g2);
+        let r_g1_b = if r.is_zero() {
+            E::G1::zero()
+        } else {
+            g1_b * &r
+        };
 
         end_timer!(b_g2_acc_time);
+        report(progress, ProvingPhase::BG2QueryMsm);
 
         let c_time = start_timer!(|| "Finish C");
         let mut g_c = s_g_a;
         g_c += &r_g1_b;
-        g_c -= &r_s_delta_g1;
+        g_c -= &blinding.r_s_g1;
         g_c += &l_aux_acc;
         g_c += &h_acc;
         end_timer!(c_time);
@@ -134,18 +458,59 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
     /// Create a Groth16 proof that is zero-knowledge using the provided
     /// R1CS-to-QAP reduction.
     /// This method samples randomness for zero knowledges via `rng`.
+    ///
+    /// `pk` can be any [`ProvingKeyStorage`], e.g. a [`ProvingKey`] held in
+    /// memory.
     #[inline]
-    pub fn create_random_proof_with_reduction<C>(
+    pub fn create_random_proof_with_reduction<C, PK>(
         circuit: C,
-        pk: &ProvingKey<E>,
+        pk: &PK,
         rng: &mut impl Rng,
     ) -> R1CSResult<Proof<E>>
     where
         C: ConstraintSynthesizer<E::ScalarField>,
+        PK: ProvingKeyStorage<E>,
     {
         let r = E::ScalarField::rand(rng);
         let s = E::ScalarField::rand(rng);
 
+        Self::create_proof_with_randomness(pk, circuit, r, s)
+    }
+
+    /// Create a zero-knowledge Groth16 proof with the provided R1CS-to-QAP
+    /// reduction from a [`PreparedProvingKey`], whose precomputed tables make
+    /// the multi-scalar multiplications faster when proving repeatedly with the
+    /// same key.
+    #[inline]
+    pub fn create_proof_with_prepared_pk<C>(
+        pk: &PreparedProvingKey<E>,
+        circuit: C,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<Proof<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        Self::create_random_proof_with_reduction(circuit, pk, rng)
+    }
+
+    /// Create a Groth16 proof with the provided R1CS-to-QAP reduction, using
+    /// caller-supplied randomness `r` and `s`.
+    ///
+    /// This is useful when the blinding factors are derived from a shared
+    /// transcript, or to produce reproducible test vectors. The proof is only
+    /// zero-knowledge if `r` and `s` are uniformly random and kept secret;
+    /// passing `r = s = 0` is equivalent to [`Self::prove_deterministic`].
+    #[inline]
+    pub fn create_proof_with_randomness<C, PK>(
+        pk: &PK,
+        circuit: C,
+        r: E::ScalarField,
+        s: E::ScalarField,
+    ) -> R1CSResult<Proof<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+        PK: ProvingKeyStorage<E>,
+    {
         Self::create_proof_with_reduction(circuit, pk, r, s)
     }
 
@@ -167,21 +532,408 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         )
     }
 
+    /// Create a deterministic Groth16 proof with the provided R1CS-to-QAP
+    /// reduction, by fixing the randomness `r = s = 0`.
+    ///
+    /// **The resulting proof is NOT zero-knowledge**: it leaks information
+    /// about the witness and must never be used where the witness is secret.
+    /// It is intended for reproducible proofs, e.g. in golden-file tests.
+    #[inline]
+    pub fn prove_deterministic<C>(pk: &ProvingKey<E>, circuit: C) -> R1CSResult<Proof<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        Self::create_proof_with_reduction_no_zk(circuit, pk)
+    }
+
     /// Create a Groth16 proof using randomness `r` and `s` and the provided
     /// R1CS-to-QAP reduction.
     #[inline]
-    pub fn create_proof_with_reduction<C>(
+    pub fn create_proof_with_reduction<C, PK>(
         circuit: C,
-        pk: &ProvingKey<E>,
+        pk: &PK,
         r: E::ScalarField,
         s: E::ScalarField,
     ) -> R1CSResult<Proof<E>>
     where
         E: Pairing,
         C: ConstraintSynthesizer<E::ScalarField>,
+        PK: ProvingKeyStorage<E>,
         QAP: R1CSToQAP,
+    {
+        Self::create_proof_with_backend(circuit, pk, r, s, &CpuBackend)
+    }
+
+    /// Create a Groth16 proof using randomness `r` and `s` and the provided
+    /// R1CS-to-QAP reduction, computing the multi-scalar multiplications with
+    /// `backend`.
+    ///
+    /// With [`CpuBackend`] this is the same as
+    /// [`Self::create_proof_with_reduction`].
+    #[inline]
+    pub fn create_proof_with_backend<C, PK, B>(
+        circuit: C,
+        pk: &PK,
+        r: E::ScalarField,
+        s: E::ScalarField,
+        backend: &B,
+    ) -> R1CSResult<Proof<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+        PK: ProvingKeyStorage<E>,
+        B: MsmBackend<E>,
+    {
+        Ok(Self::prove_with_backend(circuit, pk, r, s, backend)?)
+    }
+
+    /// Does the work of [`Self::create_proof_with_backend`], keeping the
+    /// name of the predicate in [`Groth16Error::UnsupportedPredicate`] for
+    /// callers that return [`Groth16Error`].
+    pub(crate) fn prove_with_backend<C, PK, B>(
+        circuit: C,
+        pk: &PK,
+        r: E::ScalarField,
+        s: E::ScalarField,
+        backend: &B,
+    ) -> Result<Proof<E>, Groth16Error>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+        PK: ProvingKeyStorage<E>,
+        B: MsmBackend<E>,
     {
         let prover_time = start_timer!(|| "Groth16::Prover");
+        let (h, input_assignment, aux_assignment) = Self::synthesize_witness(circuit, &mut None)?;
+        let proof = Self::create_proof_with_assignment(
+            pk,
+            backend,
+            r,
+            s,
+            &h,
+            &input_assignment,
+            &aux_assignment,
+            &mut Vec::new(),
+            &mut None,
+        )?;
+        end_timer!(prover_time);
+
+        Ok(proof)
+    }
+
+    /// Create a zero-knowledge Groth16 proof with the provided R1CS-to-QAP
+    /// reduction, reporting to `progress` whenever a stage of proof creation
+    /// is done. This method samples randomness for zero knowledge via `rng`.
+    ///
+    /// The stages are reported in the order of [`ProvingPhase`]. With `None`,
+    /// this is the same as [`Self::create_random_proof_with_reduction`].
+    pub fn create_proof_with_progress<C>(
+        pk: &ProvingKey<E>,
+        circuit: C,
+        rng: &mut impl Rng,
+        mut progress: Option<&mut dyn ProofProgress>,
+    ) -> R1CSResult<Proof<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let r = E::ScalarField::rand(rng);
+        let s = E::ScalarField::rand(rng);
+
+        let prover_time = start_timer!(|| "Groth16::Prover");
+        let (h, input_assignment, aux_assignment) =
+            Self::synthesize_witness(circuit, &mut progress)?;
+        let proof = Self::create_proof_with_assignment(
+            pk,
+            &CpuBackend,
+            r,
+            s,
+            &h,
+            &input_assignment,
+            &aux_assignment,
+            &mut Vec::new(),
+            &mut progress,
+        )?;
+        end_timer!(prover_time);
+
+        Ok(proof)
+    }
+
+    /// Create a zero-knowledge Groth16 proof with the provided R1CS-to-QAP
+    /// reduction, computing in the buffers of `scratch` instead of allocating
+    /// new ones. This method samples randomness for zero knowledge via `rng`.
+    ///
+    /// Apart from the allocations, this is the same as
+    /// [`Self::create_random_proof_with_reduction`], so given the same `rng`
+    /// both produce the same proof. Constraint synthesis still allocates the
+    /// constraint system and its matrices for every proof.
+    pub fn create_proof_with_scratch<C>(
+        pk: &ProvingKey<E>,
+        circuit: C,
+        rng: &mut impl Rng,
+        scratch: &mut ProverScratch<E>,
+    ) -> R1CSResult<Proof<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let r = E::ScalarField::rand(rng);
+        let s = E::ScalarField::rand(rng);
+
+        let prover_time = start_timer!(|| "Groth16::Prover");
+        let cs = Self::synthesize_for_proving(circuit)?;
+        debug_assert!(cs.is_satisfied().unwrap());
+
+        let witness_map_time = start_timer!(|| "R1CS to QAP witness map");
+        let matrices = &cs.to_matrices().unwrap()[R1CS_PREDICATE_LABEL];
+        let num_inputs = cs.num_instance_variables();
+        let num_constraints = cs.num_constraints();
+        let ProverScratch {
+            qap,
+            full_assignment,
+            scalars,
+        } = scratch;
+        {
+            let prover = cs.borrow().unwrap();
+            full_assignment.clear();
+            full_assignment.extend_from_slice(prover.instance_assignment().unwrap());
+            full_assignment.extend_from_slice(prover.witness_assignment().unwrap());
+        }
+        let h = QAP::witness_map_with_buffers::<E::ScalarField, D<E::ScalarField>>(
+            matrices,
+            num_inputs,
+            num_constraints,
+            full_assignment,
+            qap,
+        )?;
+        end_timer!(witness_map_time);
+
+        let proof = Self::create_proof_with_assignment(
+            pk,
+            &CpuBackend,
+            r,
+            s,
+            h,
+            &full_assignment[1..num_inputs],
+            &full_assignment[num_inputs..],
+            scalars,
+            &mut None,
+        )?;
+        end_timer!(prover_time);
+
+        Ok(proof)
+    }
+
+    /// Create a zero-knowledge Groth16 proof with the provided R1CS-to-QAP
+    /// reduction, computing the multi-scalar multiplications with `backend`.
+    /// This method samples randomness for zero knowledge via `rng`.
+    #[inline]
+    pub fn create_random_proof_with_backend<C, PK, B>(
+        circuit: C,
+        pk: &PK,
+        backend: &B,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<Proof<E>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+        PK: ProvingKeyStorage<E>,
+        B: MsmBackend<E>,
+    {
+        let r = E::ScalarField::rand(rng);
+        let s = E::ScalarField::rand(rng);
+
+        Self::create_proof_with_backend(circuit, pk, r, s, backend)
+    }
+
+    /// Create one zero-knowledge Groth16 proof per circuit in `circuits`, all
+    /// under the same proving key, using the provided R1CS-to-QAP reduction.
+    ///
+    /// Every proof is independently randomized. The query vectors are
+    /// prepared once for the whole batch, as by [`ProvingKey::prepare`], and
+    /// every proof is created from the prepared tables, as in
+    /// [`Self::prove_batch_with_prepared_pk`]. Preparing the key costs about as
+    /// many group operations as 16 multi-scalar multiplications over its
+    /// queries, so a few proofs are cheaper one at a time with
+    /// [`Self::create_random_proof_with_reduction`].
+    pub fn prove_batch<C>(
+        pk: &ProvingKey<E>,
+        circuits: impl IntoIterator<Item = C>,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<Vec<Proof<E>>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let mut circuits = circuits.into_iter().peekable();
+        if circuits.peek().is_none() {
+            return Ok(Vec::new());
+        }
+        let prepare_time = start_timer!(|| "Prepare proving key");
+        let prepared = pk.prepare();
+        end_timer!(prepare_time);
+        Self::prove_batch_with_storage(&prepared, circuits, rng)
+    }
+
+    /// Create one zero-knowledge Groth16 proof per circuit in `circuits`, all
+    /// under the same [`PreparedProvingKey`], using the provided R1CS-to-QAP
+    /// reduction.
+    ///
+    /// Every proof is independently randomized. The query multi-scalar
+    /// multiplications of every proof use the tables of `pk`, and the
+    /// fixed-base tables for the blinding terms `r * delta_g1`, `s *
+    /// delta_g1`, `r * s * delta_g1`, and `s * delta_g2` are built once for
+    /// each chunk of up to 64 circuits instead of once per proof. Circuits are
+    /// drawn from `circuits` one chunk at a time, so the whole batch is never
+    /// held in memory at once.
+    pub fn prove_batch_with_prepared_pk<C>(
+        pk: &PreparedProvingKey<E>,
+        circuits: impl IntoIterator<Item = C>,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<Vec<Proof<E>>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        Self::prove_batch_with_storage(pk, circuits, rng)
+    }
+
+    /// Proves every circuit of `circuits` under `pk`, sharing the fixed-base
+    /// tables for the blinding terms across each chunk of the batch.
+    fn prove_batch_with_storage<C, PK>(
+        pk: &PK,
+        circuits: impl IntoIterator<Item = C>,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<Vec<Proof<E>>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+        PK: ProvingKeyStorage<E>,
+    {
+        // Bounds how many circuits are held at once, while keeping enough of
+        // them per chunk to amortize the blinding tables.
+        const CHUNK_SIZE: usize = 64;
+
+        let prover_time = start_timer!(|| "Groth16::Batch prover");
+        let mut circuits = circuits.into_iter();
+        let mut proofs = Vec::new();
+        loop {
+            let chunk = circuits.by_ref().take(CHUNK_SIZE).collect::<Vec<_>>();
+            if chunk.is_empty() {
+                break;
+            }
+            proofs.extend(Self::prove_chunk(pk, chunk, rng)?);
+        }
+        end_timer!(prover_time);
+
+        Ok(proofs)
+    }
+
+    /// Proves every circuit of `circuits` under `pk`, building the fixed-base
+    /// tables for the blinding terms once for all of them.
+    fn prove_chunk<C, PK>(
+        pk: &PK,
+        circuits: Vec<C>,
+        rng: &mut impl Rng,
+    ) -> R1CSResult<Vec<Proof<E>>>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+        PK: ProvingKeyStorage<E>,
+    {
+        let n = circuits.len();
+
+        let r = Secret::new(
+            (0..n)
+                .map(|_| E::ScalarField::rand(rng))
+                .collect::<Vec<_>>(),
+        );
+        let s = Secret::new(
+            (0..n)
+                .map(|_| E::ScalarField::rand(rng))
+                .collect::<Vec<_>>(),
+        );
+        let rs = Secret::new(
+            r.iter()
+                .zip(s.iter())
+                .map(|(r, s)| *r * s)
+                .collect::<Vec<_>>(),
+        );
+
+        let blinding_time = start_timer!(|| "Compute blinding terms");
+        let g1_table = BatchMulPreprocessing::new(pk.delta_g1().into_group(), 3 * n);
+        let r_g1 = g1_table.batch_mul(&r);
+        let s_g1 = g1_table.batch_mul(&s);
+        let r_s_g1 = g1_table.batch_mul(&rs);
+        drop(g1_table);
+        drop(rs);
+        let g2_table = BatchMulPreprocessing::new(pk.vk().delta_g2.into_group(), n);
+        let s_g2 = g2_table.batch_mul(&s);
+        drop(g2_table);
+        end_timer!(blinding_time);
+
+        circuits
+            .into_iter()
+            .enumerate()
+            .map(|(i, circuit)| {
+                let (h, input_assignment, aux_assignment) =
+                    Self::synthesize_witness(circuit, &mut None)?;
+                let blinding = Blinding {
+                    r_g1: r_g1[i].into_group(),
+                    s_g1: s_g1[i].into_group(),
+                    r_s_g1: r_s_g1[i].into_group(),
+                    s_g2: s_g2[i].into_group(),
+                };
+                Self::create_proof_with_assignment_and_blinding(
+                    pk,
+                    &CpuBackend,
+                    r[i],
+                    s[i],
+                    &blinding,
+                    &h,
+                    &input_assignment,
+                    &aux_assignment,
+                    &mut Vec::new(),
+                    &mut None,
+                )
+            })
+            .collect::<R1CSResult<Vec<_>>>()
+    }
+
+    /// Synthesizes `circuit` and computes its QAP witness, returning `h` along
+    /// with the instance assignment (without the leading `1`) and the witness
+    /// assignment.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn synthesize_witness<C>(
+        circuit: C,
+        progress: &mut Option<&mut dyn ProofProgress>,
+    ) -> Result<
+        (
+            Vec<E::ScalarField>,
+            Vec<E::ScalarField>,
+            Vec<E::ScalarField>,
+        ),
+        Groth16Error,
+    >
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        let cs = Self::synthesize_for_proving(circuit)?;
+        debug_assert!(cs.is_satisfied().unwrap());
+        report(progress, ProvingPhase::ConstraintSynthesis);
+
+        let witness_map_time = start_timer!(|| "R1CS to QAP witness map");
+        let h = QAP::witness_map::<E::ScalarField, D<E::ScalarField>>(cs.clone())?;
+        end_timer!(witness_map_time);
+        report(progress, ProvingPhase::WitnessMap);
+
+        let prover = cs.borrow().unwrap();
+        let input_assignment = prover.instance_assignment().unwrap()[1..].to_vec();
+        let aux_assignment = prover.witness_assignment().unwrap().to_vec();
+
+        Ok((h, input_assignment, aux_assignment))
+    }
+
+    /// Synthesizes `circuit` in proving mode and returns the finalized
+    /// constraint system, or [`Groth16Error::UnsupportedPredicate`] if it has
+    /// constraints other than R1CS constraints.
+    fn synthesize_for_proving<C>(
+        circuit: C,
+    ) -> Result<ConstraintSystemRef<E::ScalarField>, Groth16Error>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
         let cs = ConstraintSystem::new_ref();
 
         // Set the optimization goal
@@ -200,25 +952,8 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         cs.finalize();
         end_timer!(lc_time);
 
-        debug_assert!(cs.is_satisfied().unwrap());
-
-        let witness_map_time = start_timer!(|| "R1CS to QAP witness map");
-        let h = QAP::witness_map::<E::ScalarField, D<E::ScalarField>>(cs.clone())?;
-        end_timer!(witness_map_time);
-
-        let prover = cs.borrow().unwrap();
-        let proof = Self::create_proof_with_assignment(
-            pk,
-            r,
-            s,
-            &h,
-            &prover.instance_assignment().unwrap()[1..],
-            &prover.witness_assignment().unwrap(),
-        )?;
-
-        end_timer!(prover_time);
-
-        Ok(proof)
+        check_r1cs_only(&cs)?;
+        Ok(cs)
     }
 
     /// Given a Groth16 proof, returns a fresh proof of the same statement. For a proof π of a
@@ -237,35 +972,16 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
             r2 = E::ScalarField::rand(rng);
         }
 
-        // See figure 1 in the paper referenced above:
-        //   A' = (1/r₁)A
-        //   B' = r₁B + r₁r₂(δG₂)
-        //   C' = C + r₂A
-
         // We can unwrap() this because r₁ is guaranteed to be nonzero
-        let new_a = proof.a.mul(r1.inverse().unwrap());
-        let new_b = proof.b.mul(r1) + &vk.delta_g2.mul(r1 * &r2);
-        let new_c = proof.c + proof.a.mul(r2).into_affine();
-
-        Proof {
-            a: new_a.into_affine(),
-            b: new_b.into_affine(),
-            c: new_c.into_affine(),
-        }
+        proof.rerandomize(vk, r1, r2).unwrap()
     }
 
     fn calculate_coeff<G: AffineRepr>(
         initial: G::Group,
-        query: &[G],
+        el: G,
+        acc: G::Group,
         vk_param: G,
-        assignment: &[<G::ScalarField as PrimeField>::BigInt],
-    ) -> G::Group
-    where
-        G::Group: VariableBaseMSM<MulBase = G>,
-    {
-        let el = query[0];
-        let acc = G::Group::msm_bigint(&query[1..], assignment);
-
+    ) -> G::Group {
         let mut res = initial;
         res.add_assign(&el);
         res += &acc;
@@ -274,3 +990,70 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         res
     }
 }
+
+#[cfg(feature = "debug-internals")]
+impl<E: Pairing> Groth16<E, LibsnarkReduction> {
+    /// Computes the QAP witness of `circuit`, for debugging failing proofs.
+    ///
+    /// Returns `(h, a, b, c)`, where `h` holds the coefficients of `h(x)` as
+    /// computed by [`R1CSToQAP::witness_map`], and `a`, `b`, and `c` hold the
+    /// evaluations of the QAP polynomials over the evaluation domain, in the
+    /// layout of [`LibsnarkReduction`]: one entry per constraint, followed
+    /// (for `a` only) by the public inputs. An honest witness satisfies
+    /// `a(x) * b(x) - c(x) = h(x) * z(x)`, where `z(x)` is the vanishing
+    /// polynomial of the domain.
+    ///
+    /// Other reductions lay out and evaluate their polynomials differently,
+    /// so this is only available for [`LibsnarkReduction`].
+    #[allow(clippy::type_complexity)]
+    pub fn debug_qap_witness<C>(
+        circuit: C,
+    ) -> R1CSResult<(
+        Vec<E::ScalarField>,
+        Vec<E::ScalarField>,
+        Vec<E::ScalarField>,
+        Vec<E::ScalarField>,
+    )>
+    where
+        C: ConstraintSynthesizer<E::ScalarField>,
+    {
+        use crate::r1cs_to_qap::evaluate_constraint;
+        use ark_poly::EvaluationDomain;
+        use ark_relations::gr1cs::R1CS_PREDICATE_LABEL;
+        use ark_std::vec;
+
+        let cs = Self::synthesize_for_proving(circuit)?;
+        let h = LibsnarkReduction::witness_map::<E::ScalarField, D<E::ScalarField>>(cs.clone())?;
+
+        let matrices = &cs.to_matrices().unwrap()[R1CS_PREDICATE_LABEL];
+        let num_inputs = cs.num_instance_variables();
+        let num_constraints = cs.num_constraints();
+        let domain = D::<E::ScalarField>::new(num_constraints + num_inputs)
+            .ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
+
+        let prover = cs.borrow().unwrap();
+        let full_assignment = [
+            prover.instance_assignment().unwrap(),
+            prover.witness_assignment().unwrap(),
+        ]
+        .concat();
+
+        let mut a = vec![E::ScalarField::zero(); domain.size()];
+        let mut b = vec![E::ScalarField::zero(); domain.size()];
+        let mut c = vec![E::ScalarField::zero(); domain.size()];
+        for (i, ((a_i, b_i), c_i)) in a[..num_constraints]
+            .iter_mut()
+            .zip(&mut b)
+            .zip(&mut c)
+            .enumerate()
+        {
+            *a_i = evaluate_constraint(&matrices[0][i], &full_assignment);
+            *b_i = evaluate_constraint(&matrices[1][i], &full_assignment);
+            *c_i = evaluate_constraint(&matrices[2][i], &full_assignment);
+        }
+        a[num_constraints..num_constraints + num_inputs]
+            .copy_from_slice(&full_assignment[..num_inputs]);
+
+        Ok((h, a, b, c))
+    }
+}
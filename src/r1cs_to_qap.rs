@@ -1,12 +1,15 @@
 use ark_ff::{One, PrimeField, Zero};
-use ark_poly::EvaluationDomain;
-use ark_std::{cfg_iter, cfg_iter_mut, vec};
+use ark_poly::{EvaluationDomain, MixedRadixEvaluationDomain, Radix2EvaluationDomain};
+use ark_std::{cfg_iter, cfg_iter_mut, string::ToString, vec};
 
-use crate::Vec;
+use crate::{generator::Secret, Groth16Error, Vec};
 use ark_relations::gr1cs::{
     ConstraintSystemRef, Matrix, Result as R1CSResult, SynthesisError, R1CS_PREDICATE_LABEL,
 };
-use core::ops::{AddAssign, Deref};
+use core::{
+    marker::PhantomData,
+    ops::{AddAssign, Deref},
+};
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
@@ -44,9 +47,257 @@ where
     return res;
 }
 
+/// Runs `a` and `b`, in parallel when the `parallel` feature is enabled.
+#[inline]
+fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send,
+    RA: Send,
+    RB: Send,
+{
+    #[cfg(feature = "parallel")]
+    return rayon::join(a, b);
+    #[cfg(not(feature = "parallel"))]
+    return (a(), b());
+}
+
+/// Returns [`Groth16Error::UnsupportedPredicate`] if `cs` has constraints of
+/// a predicate other than R1CS, which a QAP cannot encode.
+///
+/// This only reads the number of constraints of each registered predicate,
+/// so it does not build the matrices that the QAP reduction builds anyway.
+pub(crate) fn check_r1cs_only<F: PrimeField>(
+    cs: &ConstraintSystemRef<F>,
+) -> Result<(), Groth16Error> {
+    let cs = cs.borrow().ok_or(SynthesisError::MissingCS)?;
+    match cs
+        .get_all_predicates_num_constraints()
+        .into_iter()
+        .find(|(label, num_constraints)| label != R1CS_PREDICATE_LABEL && *num_constraints > 0)
+    {
+        Some((label, _)) => Err(Groth16Error::UnsupportedPredicate {
+            label: label.to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Returns the evaluation domain of a QAP with at least `size` points,
+/// shifted by `offset`, as returned by [`R1CSToQAP::domain_offset`].
+pub(crate) fn qap_domain<F: PrimeField, D: EvaluationDomain<F>>(
+    size: usize,
+    offset: F,
+) -> R1CSResult<D> {
+    let domain = D::new(size).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
+    if offset.is_one() {
+        return Ok(domain);
+    }
+    if offset.is_zero() {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+    domain
+        .get_coset(offset)
+        .ok_or(SynthesisError::PolynomialDegreeTooLarge)
+}
+
+/// Evaluates the QAP polynomials of `cs` at `t`, over the domain shifted by
+/// `offset`.
+///
+/// When `input_constraints` is set, the domain has one extra point per
+/// instance variable at which only that variable's `u_i` is nonzero, as in
+/// [`LibsnarkReduction`].
+#[allow(clippy::type_complexity)]
+fn instance_map<F: PrimeField, D: EvaluationDomain<F>>(
+    cs: ConstraintSystemRef<F>,
+    t: &F,
+    input_constraints: bool,
+    offset: F,
+) -> R1CSResult<(Vec<F>, Vec<F>, Vec<F>, F, usize, usize)> {
+    let matrices = &cs.to_matrices().unwrap()[R1CS_PREDICATE_LABEL];
+    let num_input_constraints = if input_constraints {
+        cs.num_instance_variables()
+    } else {
+        0
+    };
+    let domain_size = cs.num_constraints() + num_input_constraints;
+    let domain = qap_domain::<F, D>(domain_size, offset)?;
+    let domain_size = domain.size();
+
+    let zt = domain.evaluate_vanishing_polynomial(*t);
+
+    // Evaluate all Lagrange polynomials
+    let coefficients_time = start_timer!(|| "Evaluate Lagrange coefficients");
+    let u = Secret::new(domain.evaluate_all_lagrange_coefficients(*t));
+    end_timer!(coefficients_time);
+
+    let qap_num_variables = (cs.num_instance_variables() - 1) + cs.num_witness_variables();
+
+    let mut a = vec![F::zero(); qap_num_variables + 1];
+    let mut b = vec![F::zero(); qap_num_variables + 1];
+    let mut c = vec![F::zero(); qap_num_variables + 1];
+
+    if input_constraints {
+        let start = 0;
+        let end = cs.num_instance_variables();
+        let num_constraints = cs.num_constraints();
+        a[start..end].copy_from_slice(&u[(start + num_constraints)..(end + num_constraints)]);
+    }
+
+    for (i, u_i) in u.iter().enumerate().take(cs.num_constraints()) {
+        for &(ref coeff, index) in &matrices[0][i] {
+            a[index] += &(*u_i * coeff);
+        }
+        for &(ref coeff, index) in &matrices[1][i] {
+            b[index] += &(*u_i * coeff);
+        }
+        for &(ref coeff, index) in &matrices[2][i] {
+            c[index] += &(*u_i * coeff);
+        }
+    }
+
+    Ok((a, b, c, zt, qap_num_variables, domain_size))
+}
+
+/// Computes the coefficients of `h(x)` for `full_assignment`.
+///
+/// `input_constraints` and `offset` must match the values passed to
+/// [`instance_map`].
+fn witness_map<F: PrimeField, D: EvaluationDomain<F>>(
+    matrices: &[Matrix<F>],
+    num_inputs: usize,
+    num_constraints: usize,
+    full_assignment: &[F],
+    input_constraints: bool,
+    offset: F,
+) -> R1CSResult<Vec<F>> {
+    let mut buffers = QapBuffers::default();
+    witness_map_into::<F, D>(
+        matrices,
+        num_inputs,
+        num_constraints,
+        full_assignment,
+        input_constraints,
+        offset,
+        &mut buffers,
+    )?;
+    Ok(buffers.a)
+}
+
+/// Like [`witness_map`], but computes `h(x)` in `buffers.a`, reusing the
+/// allocations of `buffers`.
+fn witness_map_into<F: PrimeField, D: EvaluationDomain<F>>(
+    matrices: &[Matrix<F>],
+    num_inputs: usize,
+    num_constraints: usize,
+    full_assignment: &[F],
+    input_constraints: bool,
+    offset: F,
+    buffers: &mut QapBuffers<F>,
+) -> R1CSResult<()> {
+    let num_input_constraints = if input_constraints { num_inputs } else { 0 };
+    let domain = qap_domain::<F, D>(num_constraints + num_input_constraints, offset)?;
+    let domain_size = domain.size();
+    let zero = F::zero();
+    let QapBuffers { a, b, c } = buffers;
+
+    // `h(x)` is computed over a coset that is disjoint from the domain: the
+    // vanishing polynomial `x^n - offset^n` is nonzero at `offset * GENERATOR`
+    // because `GENERATOR` has order `|F| - 1 > n`.
+    let coset_offset = offset * F::GENERATOR;
+    let coset_domain = domain.get_coset(coset_offset).unwrap();
+
+    // The `a` and `b` polynomials are independent, so when `parallel` is
+    // enabled they are evaluated and moved to the coset concurrently.
+    join(
+        || {
+            a.clear();
+            a.resize(domain_size, zero);
+            cfg_iter_mut!(a[..num_constraints])
+                .zip(&matrices[0])
+                .for_each(|(a, at_i)| {
+                    *a = evaluate_constraint(&at_i, &full_assignment);
+                });
+
+            if input_constraints {
+                let start = num_constraints;
+                let end = start + num_inputs;
+                a[start..end].clone_from_slice(&full_assignment[..num_inputs]);
+            }
+
+            domain.ifft_in_place(a);
+            coset_domain.fft_in_place(a);
+        },
+        || {
+            b.clear();
+            b.resize(domain_size, zero);
+            cfg_iter_mut!(b[..num_constraints])
+                .zip(&matrices[1])
+                .for_each(|(b, bt_i)| {
+                    *b = evaluate_constraint(&bt_i, &full_assignment);
+                });
+
+            domain.ifft_in_place(b);
+            coset_domain.fft_in_place(b);
+        },
+    );
+
+    let ab = a;
+    cfg_iter_mut!(ab)
+        .zip(&*b)
+        .for_each(|(ab_i, b_i)| *ab_i *= b_i);
+
+    c.clear();
+    c.resize(domain_size, zero);
+    cfg_iter_mut!(c[..num_constraints])
+        .enumerate()
+        .for_each(|(i, c)| {
+            *c = evaluate_constraint(&matrices[2][i], &full_assignment);
+        });
+
+    domain.ifft_in_place(c);
+    coset_domain.fft_in_place(c);
+
+    let vanishing_polynomial_over_coset = domain
+        .evaluate_vanishing_polynomial(coset_offset)
+        .inverse()
+        .unwrap();
+    cfg_iter_mut!(ab).zip(&*c).for_each(|(ab_i, c_i)| {
+        *ab_i -= c_i;
+        *ab_i *= &vanishing_polynomial_over_coset;
+    });
+
+    coset_domain.ifft_in_place(ab);
+
+    Ok(())
+}
+
+/// Buffers that [`R1CSToQAP::witness_map_with_buffers`] computes in, so that
+/// repeated witness maps of circuits of the same size do not allocate.
+///
+/// The buffers have no meaningful contents between calls.
+#[derive(Clone, Debug, Default)]
+pub struct QapBuffers<F> {
+    /// A buffer of the size of the evaluation domain, which holds the
+    /// coefficients of `h(x)` after a witness map.
+    pub a: Vec<F>,
+    /// A buffer of the size of the evaluation domain.
+    pub b: Vec<F>,
+    /// A buffer of the size of the evaluation domain.
+    pub c: Vec<F>,
+}
+
 /// Computes instance and witness reductions from R1CS to
 /// Quadratic Arithmetic Programs (QAPs).
 pub trait R1CSToQAP {
+    /// Returns the offset of the coset of the multiplicative subgroup over
+    /// which the QAP is defined, which the generator and the prover must
+    /// agree on. This is `1`, i.e. the subgroup itself, unless overridden.
+    #[inline]
+    fn domain_offset<F: PrimeField>() -> F {
+        F::one()
+    }
+
     /// Computes a QAP instance corresponding to the R1CS instance defined by `cs`.
     fn instance_map_with_evaluation<F: PrimeField, D: EvaluationDomain<F>>(
         cs: ConstraintSystemRef<F>,
@@ -87,8 +338,77 @@ pub trait R1CSToQAP {
         full_assignment: &[F],
     ) -> R1CSResult<Vec<F>>;
 
+    /// Computes the same QAP witness as [`Self::witness_map_from_matrices`]
+    /// in `buffers`, returning the coefficients of `h(x)`.
+    ///
+    /// Reductions can override this to reuse the allocations of `buffers`
+    /// across calls; by default, the result of
+    /// [`Self::witness_map_from_matrices`] is moved into `buffers.a`.
+    fn witness_map_with_buffers<'b, F: PrimeField, D: EvaluationDomain<F>>(
+        matrices: &[Matrix<F>],
+        num_inputs: usize,
+        num_constraints: usize,
+        full_assignment: &[F],
+        buffers: &'b mut QapBuffers<F>,
+    ) -> R1CSResult<&'b [F]> {
+        buffers.a = Self::witness_map_from_matrices::<F, D>(
+            matrices,
+            num_inputs,
+            num_constraints,
+            full_assignment,
+        )?;
+        Ok(&buffers.a)
+    }
+
     /// Computes the exponents that the generator uses to calculate base
     /// elements which the prover later uses to compute `h(x)t(x)/delta`.
+    ///
+    /// Given the evaluation point `t`, the vanishing polynomial's value
+    /// `zt = z(t)` returned by [`Self::instance_map_with_evaluation`] and
+    /// `delta_inverse`, this returns the `max_power` scalars of which
+    /// [`ProvingKey::h_query`](crate::ProvingKey::h_query) holds the encodings
+    /// in `E::G1`; the generator uses `max_power = domain_size - 1`. The
+    /// prover computes the `h` term of `C` as the inner product of
+    /// `h_query` with the coefficients of `h(x)` returned by
+    /// [`Self::witness_map`], which a custom prover over the same QAP can do
+    /// in the same way:
+    ///
+    /// ```
+    /// use ark_bls12_381::Fr;
+    /// use ark_ff::{Field, UniformRand};
+    /// use ark_groth16::r1cs_to_qap::{LibsnarkReduction, R1CSToQAP};
+    /// use ark_poly::GeneralEvaluationDomain;
+    /// use ark_relations::{gr1cs::ConstraintSystem, lc};
+    ///
+    /// type D = GeneralEvaluationDomain<Fr>;
+    ///
+    /// // A circuit proving knowledge of `a` and `b` with `a * b = c`.
+    /// let cs = ConstraintSystem::<Fr>::new_ref();
+    /// let a = cs.new_witness_variable(|| Ok(Fr::from(3u64))).unwrap();
+    /// let b = cs.new_witness_variable(|| Ok(Fr::from(5u64))).unwrap();
+    /// let c = cs.new_input_variable(|| Ok(Fr::from(15u64))).unwrap();
+    /// cs.enforce_r1cs_constraint(|| lc!() + a, || lc!() + b, || lc!() + c)
+    ///     .unwrap();
+    /// cs.finalize();
+    ///
+    /// let mut rng = ark_std::test_rng();
+    /// let t = Fr::rand(&mut rng);
+    /// let delta_inverse = Fr::rand(&mut rng).inverse().unwrap();
+    /// let (u, v, w, zt, _, domain_size) =
+    ///     LibsnarkReduction::instance_map_with_evaluation::<Fr, D>(cs.clone(), &t).unwrap();
+    /// let h_query_scalars =
+    ///     LibsnarkReduction::h_query_scalars::<Fr, D>(domain_size - 1, t, zt, delta_inverse)
+    ///         .unwrap();
+    /// let h = LibsnarkReduction::witness_map::<Fr, D>(cs.clone()).unwrap();
+    ///
+    /// // In the exponent, the prover computes `sum_i h_i * h_query_scalars[i]`,
+    /// // which is `h(t) z(t) / delta = (u(t) v(t) - w(t)) / delta`.
+    /// let h_term: Fr = h.iter().zip(&h_query_scalars).map(|(h, s)| *h * s).sum();
+    ///
+    /// let z = [Fr::from(1u64), Fr::from(15u64), Fr::from(3u64), Fr::from(5u64)];
+    /// let eval = |p: &[Fr]| z.iter().zip(p).map(|(z, p)| *z * p).sum::<Fr>();
+    /// assert_eq!(h_term, (eval(&u) * eval(&v) - eval(&w)) * delta_inverse);
+    /// ```
     fn h_query_scalars<F: PrimeField, D: EvaluationDomain<F>>(
         max_power: usize,
         t: F,
@@ -107,44 +427,165 @@ impl R1CSToQAP for LibsnarkReduction {
         cs: ConstraintSystemRef<F>,
         t: &F,
     ) -> R1CSResult<(Vec<F>, Vec<F>, Vec<F>, F, usize, usize)> {
-        let matrices = &cs.to_matrices().unwrap()[R1CS_PREDICATE_LABEL];
-        let domain_size = cs.num_constraints() + cs.num_instance_variables();
-        let domain = D::new(domain_size).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
-        let domain_size = domain.size();
+        instance_map::<F, D>(cs, t, true, F::one())
+    }
 
-        let zt = domain.evaluate_vanishing_polynomial(*t);
+    fn witness_map_from_matrices<F: PrimeField, D: EvaluationDomain<F>>(
+        matrices: &[Matrix<F>],
+        num_inputs: usize,
+        num_constraints: usize,
+        full_assignment: &[F],
+    ) -> R1CSResult<Vec<F>> {
+        witness_map::<F, D>(
+            matrices,
+            num_inputs,
+            num_constraints,
+            full_assignment,
+            true,
+            F::one(),
+        )
+    }
 
-        // Evaluate all Lagrange polynomials
-        let coefficients_time = start_timer!(|| "Evaluate Lagrange coefficients");
-        let u = domain.evaluate_all_lagrange_coefficients(*t);
-        end_timer!(coefficients_time);
+    fn witness_map_with_buffers<'b, F: PrimeField, D: EvaluationDomain<F>>(
+        matrices: &[Matrix<F>],
+        num_inputs: usize,
+        num_constraints: usize,
+        full_assignment: &[F],
+        buffers: &'b mut QapBuffers<F>,
+    ) -> R1CSResult<&'b [F]> {
+        witness_map_into::<F, D>(
+            matrices,
+            num_inputs,
+            num_constraints,
+            full_assignment,
+            true,
+            F::one(),
+            buffers,
+        )?;
+        Ok(&buffers.a)
+    }
 
-        let qap_num_variables = (cs.num_instance_variables() - 1) + cs.num_witness_variables();
+    fn h_query_scalars<F: PrimeField, D: EvaluationDomain<F>>(
+        max_power: usize,
+        t: F,
+        zt: F,
+        delta_inverse: F,
+    ) -> Result<Vec<F>, SynthesisError> {
+        let scalars = cfg_into_iter!(0..max_power)
+            .map(|i| zt * &delta_inverse * &t.pow([i as u64]))
+            .collect::<Vec<_>>();
+        Ok(scalars)
+    }
+}
 
-        let mut a = vec![F::zero(); qap_num_variables + 1];
-        let mut b = vec![F::zero(); qap_num_variables + 1];
-        let mut c = vec![F::zero(); qap_num_variables + 1];
+/// Computes the R1CS-to-QAP reduction exactly as described in
+/// [\[Groth16\]](https://eprint.iacr.org/2016/260): the QAP has one
+/// evaluation point per constraint, and `u_i(x)`, `v_i(x)`, and `w_i(x)`
+/// interpolate the coefficients of variable `i` in the `A`, `B`, and `C`
+/// matrices.
+///
+/// Unlike [`LibsnarkReduction`], this adds no constraints binding the public
+/// inputs, so the resulting QAP is smaller by `num_instance_variables` points.
+/// The paper assumes the `u_i(x)` of the instance variables are linearly
+/// independent, which then has to be guaranteed by the circuit itself.
+pub struct GrothPaperReduction;
+
+impl R1CSToQAP for GrothPaperReduction {
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    fn instance_map_with_evaluation<F: PrimeField, D: EvaluationDomain<F>>(
+        cs: ConstraintSystemRef<F>,
+        t: &F,
+    ) -> R1CSResult<(Vec<F>, Vec<F>, Vec<F>, F, usize, usize)> {
+        instance_map::<F, D>(cs, t, false, F::one())
+    }
 
-        {
-            let start = 0;
-            let end = cs.num_instance_variables();
-            let num_constraints = cs.num_constraints();
-            a[start..end].copy_from_slice(&u[(start + num_constraints)..(end + num_constraints)]);
-        }
+    fn witness_map_from_matrices<F: PrimeField, D: EvaluationDomain<F>>(
+        matrices: &[Matrix<F>],
+        num_inputs: usize,
+        num_constraints: usize,
+        full_assignment: &[F],
+    ) -> R1CSResult<Vec<F>> {
+        witness_map::<F, D>(
+            matrices,
+            num_inputs,
+            num_constraints,
+            full_assignment,
+            false,
+            F::one(),
+        )
+    }
 
-        for (i, u_i) in u.iter().enumerate().take(cs.num_constraints()) {
-            for &(ref coeff, index) in &matrices[0][i] {
-                a[index] += &(*u_i * coeff);
-            }
-            for &(ref coeff, index) in &matrices[1][i] {
-                b[index] += &(*u_i * coeff);
-            }
-            for &(ref coeff, index) in &matrices[2][i] {
-                c[index] += &(*u_i * coeff);
-            }
+    fn witness_map_with_buffers<'b, F: PrimeField, D: EvaluationDomain<F>>(
+        matrices: &[Matrix<F>],
+        num_inputs: usize,
+        num_constraints: usize,
+        full_assignment: &[F],
+        buffers: &'b mut QapBuffers<F>,
+    ) -> R1CSResult<&'b [F]> {
+        witness_map_into::<F, D>(
+            matrices,
+            num_inputs,
+            num_constraints,
+            full_assignment,
+            false,
+            F::one(),
+            buffers,
+        )?;
+        Ok(&buffers.a)
+    }
+
+    fn h_query_scalars<F: PrimeField, D: EvaluationDomain<F>>(
+        max_power: usize,
+        t: F,
+        zt: F,
+        delta_inverse: F,
+    ) -> Result<Vec<F>, SynthesisError> {
+        LibsnarkReduction::h_query_scalars::<F, D>(max_power, t, zt, delta_inverse)
+    }
+}
+
+/// Computes the same R1CS-to-QAP reduction as [`LibsnarkReduction`], but over a
+/// [`MixedRadixEvaluationDomain`] whenever that yields a smaller domain than
+/// the power-of-two one.
+///
+/// This avoids padding circuits whose size is highly composite but not a power
+/// of two, at the cost of slower FFTs per element. The domain is chosen from
+/// the circuit size alone, so the generator and the prover always agree, as
+/// long as both use `Groth16<E, MixedRadixReduction>`. Fields whose
+/// [`FftField::SMALL_SUBGROUP_BASE`](ark_ff::FftField::SMALL_SUBGROUP_BASE)
+/// is unset always fall back to the power-of-two domain.
+pub struct MixedRadixReduction;
+
+impl MixedRadixReduction {
+    /// Returns whether a mixed-radix domain is strictly smaller than the
+    /// radix-2 domain for a QAP of `size` evaluation points.
+    pub fn uses_mixed_radix<F: PrimeField>(size: usize) -> bool {
+        let radix2 = Radix2EvaluationDomain::<F>::compute_size_of_domain(size);
+        let mixed = MixedRadixEvaluationDomain::<F>::compute_size_of_domain(size);
+        match (radix2, mixed) {
+            (Some(radix2), Some(mixed)) => mixed < radix2,
+            (None, Some(_)) => true,
+            _ => false,
         }
+    }
+}
 
-        Ok((a, b, c, zt, qap_num_variables, domain_size))
+impl R1CSToQAP for MixedRadixReduction {
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    fn instance_map_with_evaluation<F: PrimeField, D: EvaluationDomain<F>>(
+        cs: ConstraintSystemRef<F>,
+        t: &F,
+    ) -> R1CSResult<(Vec<F>, Vec<F>, Vec<F>, F, usize, usize)> {
+        let domain_size = cs.num_constraints() + cs.num_instance_variables();
+        if Self::uses_mixed_radix::<F>(domain_size) {
+            LibsnarkReduction::instance_map_with_evaluation::<F, MixedRadixEvaluationDomain<F>>(
+                cs, t,
+            )
+        } else {
+            LibsnarkReduction::instance_map_with_evaluation::<F, D>(cs, t)
+        }
     }
 
     fn witness_map_from_matrices<F: PrimeField, D: EvaluationDomain<F>>(
@@ -153,63 +594,126 @@ impl R1CSToQAP for LibsnarkReduction {
         num_constraints: usize,
         full_assignment: &[F],
     ) -> R1CSResult<Vec<F>> {
-        let domain =
-            D::new(num_constraints + num_inputs).ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
-        let domain_size = domain.size();
-        let zero = F::zero();
-
-        let mut a = vec![zero; domain_size];
-        let mut b = vec![zero; domain_size];
-
-        cfg_iter_mut!(a[..num_constraints])
-            .zip(&mut b[..num_constraints])
-            .zip(&matrices[0])
-            .zip(&matrices[1])
-            .for_each(|(((a, b), at_i), bt_i)| {
-                *a = evaluate_constraint(&at_i, &full_assignment);
-                *b = evaluate_constraint(&bt_i, &full_assignment);
-            });
-
-        {
-            let start = num_constraints;
-            let end = start + num_inputs;
-            a[start..end].clone_from_slice(&full_assignment[..num_inputs]);
+        if Self::uses_mixed_radix::<F>(num_constraints + num_inputs) {
+            LibsnarkReduction::witness_map_from_matrices::<F, MixedRadixEvaluationDomain<F>>(
+                matrices,
+                num_inputs,
+                num_constraints,
+                full_assignment,
+            )
+        } else {
+            LibsnarkReduction::witness_map_from_matrices::<F, D>(
+                matrices,
+                num_inputs,
+                num_constraints,
+                full_assignment,
+            )
         }
+    }
 
-        domain.ifft_in_place(&mut a);
-        domain.ifft_in_place(&mut b);
-
-        let coset_domain = domain.get_coset(F::GENERATOR).unwrap();
+    fn witness_map_with_buffers<'b, F: PrimeField, D: EvaluationDomain<F>>(
+        matrices: &[Matrix<F>],
+        num_inputs: usize,
+        num_constraints: usize,
+        full_assignment: &[F],
+        buffers: &'b mut QapBuffers<F>,
+    ) -> R1CSResult<&'b [F]> {
+        if Self::uses_mixed_radix::<F>(num_constraints + num_inputs) {
+            LibsnarkReduction::witness_map_with_buffers::<F, MixedRadixEvaluationDomain<F>>(
+                matrices,
+                num_inputs,
+                num_constraints,
+                full_assignment,
+                buffers,
+            )
+        } else {
+            LibsnarkReduction::witness_map_with_buffers::<F, D>(
+                matrices,
+                num_inputs,
+                num_constraints,
+                full_assignment,
+                buffers,
+            )
+        }
+    }
 
-        coset_domain.fft_in_place(&mut a);
-        coset_domain.fft_in_place(&mut b);
+    fn h_query_scalars<F: PrimeField, D: EvaluationDomain<F>>(
+        max_power: usize,
+        t: F,
+        zt: F,
+        delta_inverse: F,
+    ) -> Result<Vec<F>, SynthesisError> {
+        LibsnarkReduction::h_query_scalars::<F, D>(max_power, t, zt, delta_inverse)
+    }
+}
 
-        let mut ab = domain.mul_polynomials_in_evaluation_domain(&a, &b);
-        drop(a);
-        drop(b);
+/// Supplies the offset of the evaluation domain of a [`CosetReduction`].
+pub trait CosetOffset {
+    /// Returns the offset, which must be nonzero.
+    fn offset<F: PrimeField>() -> F;
+}
 
-        let mut c = vec![zero; domain_size];
-        cfg_iter_mut!(c[..num_constraints])
-            .enumerate()
-            .for_each(|(i, c)| {
-                *c = evaluate_constraint(&matrices[2][i], &full_assignment);
-            });
+/// Computes the same R1CS-to-QAP reduction as [`LibsnarkReduction`], but with
+/// the evaluation points moved from the multiplicative subgroup `H` to its
+/// coset `O::offset() * H`.
+///
+/// The vanishing polynomial becomes `x^n - offset^n`, and `u_i(x)`, `v_i(x)`
+/// and `w_i(x)` interpolate the matrix coefficients over the coset. Proving
+/// keys are only usable with the reduction that they were generated with,
+/// so the generator and the prover must both use
+/// `Groth16<E, CosetReduction<O>>` with the same `O`.
+pub struct CosetReduction<O: CosetOffset> {
+    _offset: PhantomData<O>,
+}
 
-        domain.ifft_in_place(&mut c);
-        coset_domain.fft_in_place(&mut c);
+impl<O: CosetOffset> R1CSToQAP for CosetReduction<O> {
+    #[inline]
+    fn domain_offset<F: PrimeField>() -> F {
+        O::offset()
+    }
 
-        let vanishing_polynomial_over_coset = domain
-            .evaluate_vanishing_polynomial(F::GENERATOR)
-            .inverse()
-            .unwrap();
-        cfg_iter_mut!(ab).zip(c).for_each(|(ab_i, c_i)| {
-            *ab_i -= &c_i;
-            *ab_i *= &vanishing_polynomial_over_coset;
-        });
+    #[inline]
+    #[allow(clippy::type_complexity)]
+    fn instance_map_with_evaluation<F: PrimeField, D: EvaluationDomain<F>>(
+        cs: ConstraintSystemRef<F>,
+        t: &F,
+    ) -> R1CSResult<(Vec<F>, Vec<F>, Vec<F>, F, usize, usize)> {
+        instance_map::<F, D>(cs, t, true, O::offset())
+    }
 
-        coset_domain.ifft_in_place(&mut ab);
+    fn witness_map_from_matrices<F: PrimeField, D: EvaluationDomain<F>>(
+        matrices: &[Matrix<F>],
+        num_inputs: usize,
+        num_constraints: usize,
+        full_assignment: &[F],
+    ) -> R1CSResult<Vec<F>> {
+        witness_map::<F, D>(
+            matrices,
+            num_inputs,
+            num_constraints,
+            full_assignment,
+            true,
+            O::offset(),
+        )
+    }
 
-        Ok(ab)
+    fn witness_map_with_buffers<'b, F: PrimeField, D: EvaluationDomain<F>>(
+        matrices: &[Matrix<F>],
+        num_inputs: usize,
+        num_constraints: usize,
+        full_assignment: &[F],
+        buffers: &'b mut QapBuffers<F>,
+    ) -> R1CSResult<&'b [F]> {
+        witness_map_into::<F, D>(
+            matrices,
+            num_inputs,
+            num_constraints,
+            full_assignment,
+            true,
+            O::offset(),
+            buffers,
+        )?;
+        Ok(&buffers.a)
     }
 
     fn h_query_scalars<F: PrimeField, D: EvaluationDomain<F>>(
@@ -218,9 +722,6 @@ impl R1CSToQAP for LibsnarkReduction {
         zt: F,
         delta_inverse: F,
     ) -> Result<Vec<F>, SynthesisError> {
-        let scalars = cfg_into_iter!(0..max_power)
-            .map(|i| zt * &delta_inverse * &t.pow([i as u64]))
-            .collect::<Vec<_>>();
-        Ok(scalars)
+        LibsnarkReduction::h_query_scalars::<F, D>(max_power, t, zt, delta_inverse)
     }
 }
@@ -0,0 +1,239 @@
+use super::{ProvingKeyStorage, QuerySource};
+use crate::{ProvingKey, VerifyingKey};
+use ark_ec::{pairing::Pairing, AffineRepr, VariableBaseMSM};
+use ark_ff::{PrimeField, Zero};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Validate,
+};
+use ark_std::{cfg_into_iter, marker::PhantomData, ops::Range, vec::Vec};
+use memmap2::Mmap;
+use std::{fs::File, path::Path};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// The number of elements that are decoded at a time while streaming a
+/// mapped query vector.
+const CHUNK_SIZE: usize = 1 << 16;
+
+/// A proving key whose query vectors stay in a memory-mapped file.
+///
+/// This is created by [`ProvingKey::deserialize_mmap`] from a file holding
+/// the uncompressed [`CanonicalSerialize`] encoding of a [`ProvingKey`]. The
+/// verification key, `beta_g1`, and `delta_g1` are decoded and validated
+/// eagerly. The query vectors are decoded on demand, in chunks of at most
+/// `2^16` elements, so that only the pages the prover is currently reading
+/// need to be resident.
+///
+/// Every element of the file is validated once, when it is mapped, after
+/// which the query elements are decoded without validation. This relies on
+/// the file not being modified while it is mapped, which the caller of
+/// [`ProvingKey::deserialize_mmap`] must guarantee. Should that guarantee be
+/// broken, the prover panics on an element that can no longer be decoded.
+pub struct MmapProvingKey<E: Pairing> {
+    mmap: Mmap,
+    vk: VerifyingKey<E>,
+    beta_g1: E::G1Affine,
+    delta_g1: E::G1Affine,
+    a_query: Range<usize>,
+    b_g1_query: Range<usize>,
+    b_g2_query: Range<usize>,
+    h_query: Range<usize>,
+    l_query: Range<usize>,
+}
+
+/// A query vector stored uncompressed in a memory-mapped file.
+pub struct MappedQuery<'a, G> {
+    bytes: &'a [u8],
+    len: usize,
+    element_size: usize,
+    _group: PhantomData<G>,
+}
+
+impl<G: AffineRepr> MappedQuery<'_, G> {
+    fn decode(&self, indices: Range<usize>) -> Vec<G> {
+        cfg_into_iter!(indices)
+            .map(|i| {
+                let start = i * self.element_size;
+                G::deserialize_with_mode(
+                    &self.bytes[start..start + self.element_size],
+                    Compress::No,
+                    Validate::No,
+                )
+                .expect("memory-mapped proving key was modified while mapped")
+            })
+            .collect()
+    }
+}
+
+impl<G: AffineRepr> QuerySource<G> for MappedQuery<'_, G> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn get(&self, index: usize) -> G {
+        assert!(index < self.len, "index out of bounds");
+        self.decode(index..index + 1)[0]
+    }
+
+    fn msm_bigint(
+        &self,
+        offset: usize,
+        scalars: &[<G::ScalarField as PrimeField>::BigInt],
+    ) -> G::Group
+    where
+        G::Group: VariableBaseMSM<MulBase = G>,
+    {
+        let num_scalars = scalars.len().min(self.len.saturating_sub(offset));
+        scalars[..num_scalars].chunks(CHUNK_SIZE).enumerate().fold(
+            G::Group::zero(),
+            |acc, (i, chunk)| {
+                let start = offset + i * CHUNK_SIZE;
+                let bases = self.decode(start..start + chunk.len());
+                acc + G::Group::msm_bigint(&bases, chunk)
+            },
+        )
+    }
+}
+
+/// Checks that `bytes` holds valid uncompressed elements of `G`.
+fn validate_query<G: AffineRepr>(bytes: &[u8]) -> Result<(), SerializationError> {
+    let element_size = G::zero().uncompressed_size();
+    cfg_into_iter!(0..bytes.len() / element_size).try_for_each(|i| {
+        let start = i * element_size;
+        G::deserialize_with_mode(
+            &bytes[start..start + element_size],
+            Compress::No,
+            Validate::Yes,
+        )
+        .map(|_| ())
+    })
+}
+
+/// Reads the length prefix of a vector of `element_size`-byte elements from
+/// `reader`, skips its elements, and returns their byte range within `bytes`.
+fn skip_vec(
+    bytes: &[u8],
+    reader: &mut &[u8],
+    element_size: usize,
+) -> Result<Range<usize>, SerializationError> {
+    let len = u64::deserialize_uncompressed(&mut *reader)? as usize;
+    let size = len
+        .checked_mul(element_size)
+        .filter(|size| *size <= reader.len())
+        .ok_or(SerializationError::InvalidData)?;
+    let start = bytes.len() - reader.len();
+    *reader = &reader[size..];
+    Ok(start..start + size)
+}
+
+impl<E: Pairing> MmapProvingKey<E> {
+    fn query<G: AffineRepr>(&self, range: &Range<usize>) -> MappedQuery<'_, G> {
+        let element_size = G::zero().uncompressed_size();
+        MappedQuery {
+            bytes: &self.mmap[range.clone()],
+            len: range.len() / element_size,
+            element_size,
+            _group: PhantomData,
+        }
+    }
+}
+
+impl<E: Pairing> ProvingKey<E> {
+    /// Memory-maps the uncompressed encoding of a proving key at `path`,
+    /// e.g. as written by `serialize_uncompressed`.
+    ///
+    /// The whole file is validated as by `deserialize_uncompressed` before
+    /// this returns, and an error is returned if it does not hold a valid
+    /// proving key.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this or any other
+    /// process, until the returned [`MmapProvingKey`] is dropped. Doing so is
+    /// undefined behavior, since the mapped bytes back shared references.
+    #[allow(unsafe_code)]
+    pub unsafe fn deserialize_mmap(
+        path: impl AsRef<Path>,
+    ) -> Result<MmapProvingKey<E>, SerializationError> {
+        let file = File::open(path)?;
+        // SAFETY: the mapping is read-only, and the caller guarantees that the
+        // file is not modified while it is mapped.
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        let mut reader = &mmap[..];
+        let vk = VerifyingKey::deserialize_uncompressed(&mut reader)?;
+        let beta_g1 = E::G1Affine::deserialize_uncompressed(&mut reader)?;
+        let delta_g1 = E::G1Affine::deserialize_uncompressed(&mut reader)?;
+
+        let g1_size = E::G1Affine::zero().uncompressed_size();
+        let g2_size = E::G2Affine::zero().uncompressed_size();
+        let a_query = skip_vec(&mmap, &mut reader, g1_size)?;
+        let b_g1_query = skip_vec(&mmap, &mut reader, g1_size)?;
+        let b_g2_query = skip_vec(&mmap, &mut reader, g2_size)?;
+        let h_query = skip_vec(&mmap, &mut reader, g1_size)?;
+        let l_query = skip_vec(&mmap, &mut reader, g1_size)?;
+        if !reader.is_empty() {
+            return Err(SerializationError::InvalidData);
+        }
+        for range in [&a_query, &b_g1_query, &h_query, &l_query] {
+            validate_query::<E::G1Affine>(&mmap[range.clone()])?;
+        }
+        validate_query::<E::G2Affine>(&mmap[b_g2_query.clone()])?;
+
+        Ok(MmapProvingKey {
+            mmap,
+            vk,
+            beta_g1,
+            delta_g1,
+            a_query,
+            b_g1_query,
+            b_g2_query,
+            h_query,
+            l_query,
+        })
+    }
+}
+
+impl<E: Pairing> ProvingKeyStorage<E> for MmapProvingKey<E> {
+    type G1Query<'a>
+        = MappedQuery<'a, E::G1Affine>
+    where
+        Self: 'a;
+    type G2Query<'a>
+        = MappedQuery<'a, E::G2Affine>
+    where
+        Self: 'a;
+
+    fn vk(&self) -> &VerifyingKey<E> {
+        &self.vk
+    }
+
+    fn beta_g1(&self) -> E::G1Affine {
+        self.beta_g1
+    }
+
+    fn delta_g1(&self) -> E::G1Affine {
+        self.delta_g1
+    }
+
+    fn a_query(&self) -> Self::G1Query<'_> {
+        self.query(&self.a_query)
+    }
+
+    fn b_g1_query(&self) -> Self::G1Query<'_> {
+        self.query(&self.b_g1_query)
+    }
+
+    fn b_g2_query(&self) -> Self::G2Query<'_> {
+        self.query(&self.b_g2_query)
+    }
+
+    fn h_query(&self) -> Self::G1Query<'_> {
+        self.query(&self.h_query)
+    }
+
+    fn l_query(&self) -> Self::G1Query<'_> {
+        self.query(&self.l_query)
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{ProvingKey, VerifyingKey};
+use ark_ec::{pairing::Pairing, AffineRepr, VariableBaseMSM};
+use ark_ff::PrimeField;
+
+#[cfg(feature = "mmap")]
+mod mmap;
+mod prepared;
+
+#[cfg(feature = "mmap")]
+pub use mmap::{MappedQuery, MmapProvingKey};
+pub use prepared::{PreparedProvingKey, PreparedQuery};
+
+/// A query vector of a proving key, i.e. a sequence of curve points that the
+/// prover combines with the witness.
+///
+/// The prover only ever reads single elements or computes multi-scalar
+/// multiplications over a suffix of the vector, so an implementation is free
+/// to keep the points anywhere, as long as it can produce them on demand.
+pub trait QuerySource<G: AffineRepr> {
+    /// Returns the number of elements.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if there are no elements.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the element at `index`.
+    fn get(&self, index: usize) -> G;
+
+    /// Returns the elements as a contiguous slice, if they are stored as one.
+    fn as_slice(&self) -> Option<&[G]> {
+        None
+    }
+
+    /// Computes `sum_i scalars[i] * self[offset + i]`. Scalars beyond the end
+    /// of the vector are ignored.
+    fn msm_bigint(
+        &self,
+        offset: usize,
+        scalars: &[<G::ScalarField as PrimeField>::BigInt],
+    ) -> G::Group
+    where
+        G::Group: VariableBaseMSM<MulBase = G>;
+}
+
+impl<G: AffineRepr> QuerySource<G> for &[G] {
+    fn len(&self) -> usize {
+        <[G]>::len(self)
+    }
+
+    fn get(&self, index: usize) -> G {
+        self[index]
+    }
+
+    fn as_slice(&self) -> Option<&[G]> {
+        Some(self)
+    }
+
+    fn msm_bigint(
+        &self,
+        offset: usize,
+        scalars: &[<G::ScalarField as PrimeField>::BigInt],
+    ) -> G::Group
+    where
+        G::Group: VariableBaseMSM<MulBase = G>,
+    {
+        G::Group::msm_bigint(&self[offset..], scalars)
+    }
+}
+
+/// The storage backing a proving key.
+///
+/// The prover is generic over this trait, so that proofs can be created both
+/// from a [`ProvingKey`] held in memory and from keys whose query vectors are
+/// stored differently, such as a [`PreparedProvingKey`] or the memory-mapped
+/// `MmapProvingKey` behind the `mmap` feature.
+pub trait ProvingKeyStorage<E: Pairing> {
+    /// The type of the query vectors in `E::G1`.
+    type G1Query<'a>: QuerySource<E::G1Affine>
+    where
+        Self: 'a;
+
+    /// The type of the query vectors in `E::G2`.
+    type G2Query<'a>: QuerySource<E::G2Affine>
+    where
+        Self: 'a;
+
+    /// The underlying verification key.
+    fn vk(&self) -> &VerifyingKey<E>;
+
+    /// The element `beta * G` in `E::G1`.
+    fn beta_g1(&self) -> E::G1Affine;
+
+    /// The element `delta * G` in `E::G1`.
+    fn delta_g1(&self) -> E::G1Affine;
+
+    /// The elements `a_i * G` in `E::G1`.
+    fn a_query(&self) -> Self::G1Query<'_>;
+
+    /// The elements `b_i * G` in `E::G1`.
+    fn b_g1_query(&self) -> Self::G1Query<'_>;
+
+    /// The elements `b_i * H` in `E::G2`.
+    fn b_g2_query(&self) -> Self::G2Query<'_>;
+
+    /// The elements `h_i * G` in `E::G1`.
+    fn h_query(&self) -> Self::G1Query<'_>;
+
+    /// The elements `l_i * G` in `E::G1`.
+    fn l_query(&self) -> Self::G1Query<'_>;
+}
+
+impl<E: Pairing> ProvingKeyStorage<E> for ProvingKey<E> {
+    type G1Query<'a>
+        = &'a [E::G1Affine]
+    where
+        Self: 'a;
+    type G2Query<'a>
+        = &'a [E::G2Affine]
+    where
+        Self: 'a;
+
+    fn vk(&self) -> &VerifyingKey<E> {
+        &self.vk
+    }
+
+    fn beta_g1(&self) -> E::G1Affine {
+        self.beta_g1
+    }
+
+    fn delta_g1(&self) -> E::G1Affine {
+        self.delta_g1
+    }
+
+    fn a_query(&self) -> Self::G1Query<'_> {
+        &self.a_query
+    }
+
+    fn b_g1_query(&self) -> Self::G1Query<'_> {
+        &self.b_g1_query
+    }
+
+    fn b_g2_query(&self) -> Self::G2Query<'_> {
+        &self.b_g2_query
+    }
+
+    fn h_query(&self) -> Self::G1Query<'_> {
+        &self.h_query
+    }
+
+    fn l_query(&self) -> Self::G1Query<'_> {
+        &self.l_query
+    }
+}
@@ -0,0 +1,223 @@
+use super::{ProvingKeyStorage, QuerySource};
+use crate::{ProvingKey, VerifyingKey};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{AdditiveGroup, PrimeField, Zero};
+use ark_std::{cfg_chunks, cfg_iter, vec::Vec};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// A query vector together with precomputed multiples of each of its
+/// elements.
+///
+/// For a window size `c`, every element `P` is stored as
+/// `P, 2^c * P, 2^{2c} * P, ...`, so that a multi-scalar multiplication only
+/// needs a single pass of bucket accumulation over the `c`-bit digits of the
+/// scalars, instead of one pass per window followed by `c` doublings as in a
+/// variable-base MSM. This trades roughly `MODULUS_BIT_SIZE / c` times the
+/// memory of the plain vector for faster repeated proving.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreparedQuery<G: AffineRepr> {
+    /// `table[i * num_windows + j] = 2^{window_size * j} * query[i]`.
+    table: Vec<G>,
+    window_size: usize,
+    num_windows: usize,
+}
+
+impl<G: AffineRepr> PreparedQuery<G> {
+    /// Precomputes the multiples of every element of `query`.
+    pub fn new(query: &[G]) -> Self {
+        let window_size = if query.len() < 32 {
+            3
+        } else {
+            (ark_std::log2(query.len()) as usize + 2).min(16)
+        };
+        let num_windows = (G::ScalarField::MODULUS_BIT_SIZE as usize).div_ceil(window_size);
+
+        let multiples = cfg_iter!(query)
+            .map(|p| {
+                let mut p = p.into_group();
+                (0..num_windows)
+                    .map(|_| {
+                        let multiple = p;
+                        for _ in 0..window_size {
+                            p.double_in_place();
+                        }
+                        multiple
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+            .concat();
+
+        Self {
+            table: G::Group::normalize_batch(&multiples),
+            window_size,
+            num_windows,
+        }
+    }
+}
+
+/// Returns the `width`-bit digit of the little-endian `limbs` starting at bit
+/// `start`.
+fn digit(limbs: &[u64], start: usize, width: usize) -> usize {
+    let (limb, shift) = (start / 64, start % 64);
+    if limb >= limbs.len() {
+        return 0;
+    }
+    let mut bits = limbs[limb] >> shift;
+    if shift + width > 64 && limb + 1 < limbs.len() {
+        bits |= limbs[limb + 1] << (64 - shift);
+    }
+    (bits & ((1 << width) - 1)) as usize
+}
+
+/// Computes `sum_i scalars[i] * P_i` from the multiples of the `P_i` stored in
+/// `table`.
+fn bucket_msm<G: AffineRepr>(
+    table: &[G],
+    scalars: &[<G::ScalarField as PrimeField>::BigInt],
+    window_size: usize,
+    num_windows: usize,
+) -> G::Group {
+    let mut buckets = vec![G::Group::zero(); (1 << window_size) - 1];
+    for (scalar, multiples) in scalars.iter().zip(table.chunks(num_windows)) {
+        let limbs = scalar.as_ref();
+        for (j, multiple) in multiples.iter().enumerate() {
+            let digit = digit(limbs, j * window_size, window_size);
+            if digit != 0 {
+                buckets[digit - 1] += multiple;
+            }
+        }
+    }
+
+    // `sum_k (k + 1) * buckets[k]`, via running sums.
+    let mut running_sum = G::Group::zero();
+    let mut res = G::Group::zero();
+    for bucket in buckets.into_iter().rev() {
+        running_sum += &bucket;
+        res += &running_sum;
+    }
+    res
+}
+
+impl<G: AffineRepr> QuerySource<G> for &PreparedQuery<G> {
+    fn len(&self) -> usize {
+        self.table.len() / self.num_windows
+    }
+
+    fn get(&self, index: usize) -> G {
+        self.table[index * self.num_windows]
+    }
+
+    fn msm_bigint(
+        &self,
+        offset: usize,
+        scalars: &[<G::ScalarField as PrimeField>::BigInt],
+    ) -> G::Group
+    where
+        G::Group: VariableBaseMSM<MulBase = G>,
+    {
+        let num_scalars = scalars.len().min(self.len().saturating_sub(offset));
+        if num_scalars == 0 {
+            return G::Group::zero();
+        }
+        let scalars = &scalars[..num_scalars];
+        let table =
+            &self.table[offset * self.num_windows..(offset + num_scalars) * self.num_windows];
+
+        #[cfg(feature = "parallel")]
+        let num_chunks = rayon::current_num_threads();
+        #[cfg(not(feature = "parallel"))]
+        let num_chunks = 1;
+        let chunk_size = num_scalars.div_ceil(num_chunks);
+
+        cfg_chunks!(scalars, chunk_size)
+            .zip(cfg_chunks!(table, chunk_size * self.num_windows))
+            .map(|(scalars, table)| bucket_msm(table, scalars, self.window_size, self.num_windows))
+            .sum()
+    }
+}
+
+/// A proving key with precomputed tables for all of its query vectors.
+///
+/// This is created by [`ProvingKey::prepare`] and is worthwhile when proving
+/// many times with the same key; see [`PreparedQuery`] for the memory cost.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreparedProvingKey<E: Pairing> {
+    /// The underlying verification key.
+    pub vk: VerifyingKey<E>,
+    /// The element `beta * G` in `E::G1`.
+    pub beta_g1: E::G1Affine,
+    /// The element `delta * G` in `E::G1`.
+    pub delta_g1: E::G1Affine,
+    /// The elements `a_i * G` in `E::G1`.
+    pub a_query: PreparedQuery<E::G1Affine>,
+    /// The elements `b_i * G` in `E::G1`.
+    pub b_g1_query: PreparedQuery<E::G1Affine>,
+    /// The elements `b_i * H` in `E::G2`.
+    pub b_g2_query: PreparedQuery<E::G2Affine>,
+    /// The elements `h_i * G` in `E::G1`.
+    pub h_query: PreparedQuery<E::G1Affine>,
+    /// The elements `l_i * G` in `E::G1`.
+    pub l_query: PreparedQuery<E::G1Affine>,
+}
+
+impl<E: Pairing> ProvingKey<E> {
+    /// Precomputes the tables for every query vector of `self`.
+    pub fn prepare(&self) -> PreparedProvingKey<E> {
+        PreparedProvingKey {
+            vk: self.vk.clone(),
+            beta_g1: self.beta_g1,
+            delta_g1: self.delta_g1,
+            a_query: PreparedQuery::new(&self.a_query),
+            b_g1_query: PreparedQuery::new(&self.b_g1_query),
+            b_g2_query: PreparedQuery::new(&self.b_g2_query),
+            h_query: PreparedQuery::new(&self.h_query),
+            l_query: PreparedQuery::new(&self.l_query),
+        }
+    }
+}
+
+impl<E: Pairing> ProvingKeyStorage<E> for PreparedProvingKey<E> {
+    type G1Query<'a>
+        = &'a PreparedQuery<E::G1Affine>
+    where
+        Self: 'a;
+    type G2Query<'a>
+        = &'a PreparedQuery<E::G2Affine>
+    where
+        Self: 'a;
+
+    fn vk(&self) -> &VerifyingKey<E> {
+        &self.vk
+    }
+
+    fn beta_g1(&self) -> E::G1Affine {
+        self.beta_g1
+    }
+
+    fn delta_g1(&self) -> E::G1Affine {
+        self.delta_g1
+    }
+
+    fn a_query(&self) -> Self::G1Query<'_> {
+        &self.a_query
+    }
+
+    fn b_g1_query(&self) -> Self::G1Query<'_> {
+        &self.b_g1_query
+    }
+
+    fn b_g2_query(&self) -> Self::G2Query<'_> {
+        &self.b_g2_query
+    }
+
+    fn h_query(&self) -> Self::G1Query<'_> {
+        &self.h_query
+    }
+
+    fn l_query(&self) -> Self::G1Query<'_> {
+        &self.l_query
+    }
+}
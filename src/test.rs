@@ -1,14 +1,20 @@
-use crate::{prepare_verifying_key, Groth16};
+use crate::{prepare_verifying_key, Groth16, Groth16Error};
 use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
 use ark_ec::pairing::Pairing;
 use ark_ff::Field;
 use ark_relations::{
-    gr1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+    gr1cs::{
+        ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, OptimizationGoal,
+        SynthesisError, SynthesisMode, R1CS_PREDICATE_LABEL,
+    },
     lc,
 };
+use ark_serialize::CanonicalSerialize;
 use ark_std::{
     rand::{RngCore, SeedableRng},
-    test_rng, UniformRand,
+    test_rng, vec,
+    vec::Vec,
+    UniformRand,
 };
 
 struct MySillyCircuit<F: Field> {
@@ -42,6 +48,164 @@ impl<ConstraintF: Field> ConstraintSynthesizer<ConstraintF> for MySillyCircuit<C
     }
 }
 
+/// Proves `a * b = c` for public inputs `a`, `b`, and `c`, in this order.
+struct MyPublicProductCircuit<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+}
+
+impl<ConstraintF: Field> ConstraintSynthesizer<ConstraintF>
+    for MyPublicProductCircuit<ConstraintF>
+{
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<ConstraintF>,
+    ) -> Result<(), SynthesisError> {
+        let a = cs.new_input_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.new_input_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+        let c = cs.new_input_variable(|| {
+            let a = self.a.ok_or(SynthesisError::AssignmentMissing)?;
+            let b = self.b.ok_or(SynthesisError::AssignmentMissing)?;
+            Ok(a * b)
+        })?;
+        cs.enforce_r1cs_constraint(|| lc!() + a, || lc!() + b, || lc!() + c)?;
+
+        Ok(())
+    }
+}
+
+/// Like [`MySillyCircuit`], but with a configurable number of constraints.
+struct MyLongSillyCircuit<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+    num_constraints: usize,
+}
+
+impl<ConstraintF: Field> ConstraintSynthesizer<ConstraintF> for MyLongSillyCircuit<ConstraintF> {
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<ConstraintF>,
+    ) -> Result<(), SynthesisError> {
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+        let c = cs.new_input_variable(|| {
+            let mut a = self.a.ok_or(SynthesisError::AssignmentMissing)?;
+            let b = self.b.ok_or(SynthesisError::AssignmentMissing)?;
+
+            a *= &b;
+            Ok(a)
+        })?;
+
+        for _ in 0..self.num_constraints {
+            cs.enforce_r1cs_constraint(|| lc!() + a, || lc!() + b, || lc!() + c)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Squares a witness `num_squarings` times, so that every squared variable
+/// appears identically in `A` and `B`.
+struct MySquaringCircuit<F: Field> {
+    x: Option<F>,
+    num_squarings: usize,
+}
+
+impl<ConstraintF: Field> ConstraintSynthesizer<ConstraintF> for MySquaringCircuit<ConstraintF> {
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<ConstraintF>,
+    ) -> Result<(), SynthesisError> {
+        let mut x_val = self.x;
+        let mut x = cs.new_witness_variable(|| x_val.ok_or(SynthesisError::AssignmentMissing))?;
+        for i in 0..self.num_squarings {
+            let y_val = x_val.map(|x| x.square());
+            let y = if i + 1 == self.num_squarings {
+                cs.new_input_variable(|| y_val.ok_or(SynthesisError::AssignmentMissing))?
+            } else {
+                cs.new_witness_variable(|| y_val.ok_or(SynthesisError::AssignmentMissing))?
+            };
+            cs.enforce_r1cs_constraint(|| lc!() + x, || lc!() + x, || lc!() + y)?;
+            x = y;
+            x_val = y_val;
+        }
+
+        Ok(())
+    }
+}
+
+/// Like [`MySillyCircuit`], but with the product `c` as a witness, so that
+/// the circuit has no public inputs.
+struct MyWitnessOnlyCircuit<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+}
+
+impl<ConstraintF: Field> ConstraintSynthesizer<ConstraintF> for MyWitnessOnlyCircuit<ConstraintF> {
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<ConstraintF>,
+    ) -> Result<(), SynthesisError> {
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+        let c = cs.new_witness_variable(|| {
+            let a = self.a.ok_or(SynthesisError::AssignmentMissing)?;
+            let b = self.b.ok_or(SynthesisError::AssignmentMissing)?;
+            Ok(a * b)
+        })?;
+
+        cs.enforce_r1cs_constraint(|| lc!() + a, || lc!() + b, || lc!() + c)?;
+
+        Ok(())
+    }
+}
+
+/// Like [`MySillyCircuit`], but, when `with_predicate` is set, additionally
+/// enforces `a^3 = d` for a witness `d` with a custom polynomial predicate,
+/// which is not an R1CS constraint.
+struct MyPredicateCircuit<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+    with_predicate: bool,
+}
+
+impl<ConstraintF: Field> ConstraintSynthesizer<ConstraintF> for MyPredicateCircuit<ConstraintF> {
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<ConstraintF>,
+    ) -> Result<(), SynthesisError> {
+        use ark_relations::gr1cs::predicate::PredicateConstraintSystem;
+
+        MySillyCircuit {
+            a: self.a,
+            b: self.b,
+        }
+        .generate_constraints(cs.clone())?;
+        if !self.with_predicate {
+            return Ok(());
+        }
+
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let d = cs.new_witness_variable(|| {
+            let a = self.a.ok_or(SynthesisError::AssignmentMissing)?;
+            Ok(a * a * a)
+        })?;
+        cs.register_predicate(
+            "cube",
+            PredicateConstraintSystem::new_polynomial_predicate_cs(
+                2,
+                vec![
+                    (ConstraintF::one(), vec![(0, 3)]),
+                    (-ConstraintF::one(), vec![(1, 1)]),
+                ],
+            ),
+        )?;
+        cs.enforce_constraint("cube", vec![lc!() + a, lc!() + d])?;
+
+        Ok(())
+    }
+}
+
 fn test_prove_and_verify<E>(n_iters: usize)
 where
     E: Pairing,
@@ -117,43 +281,3725 @@ where
     }
 }
 
-mod bls12_377 {
-    use super::{test_prove_and_verify, test_rerandomize};
-    use ark_bls12_377::Bls12_377;
+fn test_proof_rerandomize<E>()
+where
+    E: Pairing,
+{
+    use ark_ff::Zero;
 
-    #[test]
-    fn prove_and_verify() {
-        test_prove_and_verify::<Bls12_377>(100);
-    }
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
 
-    #[test]
-    fn rerandomize() {
-        test_rerandomize::<Bls12_377>();
-    }
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let c = a * b;
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    let r1 = E::ScalarField::rand(&mut rng);
+    let r2 = E::ScalarField::rand(&mut rng);
+    let rerandomized = proof.rerandomize(&vk, r1, r2).unwrap();
+    assert!(Groth16::<E>::verify(&vk, &[c], &rerandomized).unwrap());
+    assert!(!Groth16::<E>::verify(&vk, &[a], &rerandomized).unwrap());
+
+    let mut bytes = Vec::new();
+    proof.serialize_compressed(&mut bytes).unwrap();
+    let mut rerandomized_bytes = Vec::new();
+    rerandomized
+        .serialize_compressed(&mut rerandomized_bytes)
+        .unwrap();
+    assert_ne!(bytes, rerandomized_bytes);
+
+    // `r1 = 1` and `r2 = 0` leave the proof unchanged, while `r1 = 0` is
+    // rejected.
+    let one = E::ScalarField::from(1u64);
+    let zero = E::ScalarField::zero();
+    assert_eq!(proof.rerandomize(&vk, one, zero), Some(proof.clone()));
+    assert!(proof.rerandomize(&vk, zero, r2).is_none());
 }
 
-mod bw6_761 {
-    use super::{test_prove_and_verify, test_rerandomize};
+fn test_proof_field_coordinates<E>()
+where
+    E: Pairing,
+    <E::G1Affine as ark_ec::AffineRepr>::BaseField: Field<BasePrimeField = E::BaseField>,
+    <E::G2Affine as ark_ec::AffineRepr>::BaseField: Field<BasePrimeField = E::BaseField>,
+{
+    use crate::Proof;
+    use ark_ec::AffineRepr;
 
-    use ark_bw6_761::BW6_761;
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
 
-    #[test]
-    fn prove_and_verify() {
-        test_prove_and_verify::<BW6_761>(1);
-    }
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
 
-    #[test]
-    fn rerandomize() {
-        test_rerandomize::<BW6_761>();
-    }
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    let g1_len = 2 * <E::G1Affine as AffineRepr>::BaseField::extension_degree() as usize;
+    let g2_len = 2 * <E::G2Affine as AffineRepr>::BaseField::extension_degree() as usize;
+    let coordinates = proof.to_field_coordinates();
+    assert_eq!(coordinates.len(), 2 * g1_len + g2_len);
+    let (x, y) = proof.a.xy().unwrap();
+    let a_coordinates = x
+        .to_base_prime_field_elements()
+        .chain(y.to_base_prime_field_elements())
+        .collect::<Vec<_>>();
+    assert_eq!(coordinates[..g1_len], a_coordinates);
+
+    let decoded = Proof::<E>::try_from_field_coordinates(&coordinates).unwrap();
+    assert_eq!(decoded, proof);
+    assert!(Groth16::<E>::verify(&vk, &[a * b], &decoded).unwrap());
+
+    // The point at infinity round-trips as `(0, 0)`.
+    let default = Proof::<E>::default();
+    assert_eq!(
+        Proof::<E>::try_from_field_coordinates(&default.to_field_coordinates()).unwrap(),
+        default
+    );
+
+    // Inputs of the wrong length, or with a point off the curve, are rejected.
+    assert!(Proof::<E>::try_from_field_coordinates(&coordinates[1..]).is_err());
+    let mut longer = coordinates.clone();
+    longer.push(E::BaseField::from(0u64));
+    assert!(Proof::<E>::try_from_field_coordinates(&longer).is_err());
+    assert!(Proof::<E>::try_from_field_coordinates(&[]).is_err());
+    let mut off_curve = coordinates;
+    off_curve[0] += E::BaseField::from(1u64);
+    assert!(Proof::<E>::try_from_field_coordinates(&off_curve).is_err());
 }
 
-mod bn_254 {
-    use super::test_prove_and_verify;
-    use ark_bn254::Bn254;
+fn test_prove_deterministic<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
 
-    #[test]
-    fn prove_and_verify() {
-        test_prove_and_verify::<Bn254>(100);
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = || MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+
+    let proof1 = Groth16::<E>::prove_deterministic(&pk, circuit()).unwrap();
+    let proof2 = Groth16::<E>::prove_deterministic(&pk, circuit()).unwrap();
+
+    let mut bytes1 = Vec::new();
+    let mut bytes2 = Vec::new();
+    proof1.serialize_compressed(&mut bytes1).unwrap();
+    proof2.serialize_compressed(&mut bytes2).unwrap();
+    assert_eq!(bytes1, bytes2);
+
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &proof1).unwrap());
+}
+
+fn test_prove_no_zk<E>()
+where
+    E: Pairing,
+{
+    use crate::NoZkSNARK;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = || MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+
+    let proof = Groth16::<E>::prove_no_zk(&pk, circuit()).unwrap();
+    assert!(Groth16::<E>::verify(&vk, &[a * b], &proof).unwrap());
+    assert!(!Groth16::<E>::verify(&vk, &[a], &proof).unwrap());
+
+    // Skipping the blinding terms gives the same proof as blinding with zero.
+    assert_eq!(
+        proof,
+        Groth16::<E>::prove_deterministic(&pk, circuit()).unwrap()
+    );
+    assert_eq!(proof, Groth16::<E>::prove_no_zk(&pk, circuit()).unwrap());
+    assert!(proof != Groth16::<E>::prove(&pk, circuit(), &mut rng).unwrap());
+}
+
+fn test_verifying_key_specialize<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) =
+        Groth16::<E>::setup(MyPublicProductCircuit { a: None, b: None }, &mut rng).unwrap();
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MyPublicProductCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+    assert!(Groth16::<E>::verify(&vk, &[a, b, a * b], &proof).unwrap());
+
+    // Pinning `a` leaves `b` and `c`.
+    let light = vk.specialize(&[(0, a)]).unwrap();
+    assert_eq!(light.gamma_abc_g1.len(), 3);
+    assert!(Groth16::<E>::verify(&light, &[b, a * b], &proof).unwrap());
+    assert!(!Groth16::<E>::verify(&light, &[a, a * b], &proof).unwrap());
+
+    // So does every other pinning, regardless of the order of the pairs.
+    let light = vk.specialize(&[(2, a * b), (0, a)]).unwrap();
+    assert!(Groth16::<E>::verify(&light, &[b], &proof).unwrap());
+    let light = vk.specialize(&[(0, a), (1, b), (2, a * b)]).unwrap();
+    assert!(Groth16::<E>::verify(&light, &[], &proof).unwrap());
+    assert_eq!(vk.specialize(&[]).unwrap(), vk);
+
+    // A key pinned to other values rejects the proof.
+    let light = vk.specialize(&[(0, b)]).unwrap();
+    assert!(!Groth16::<E>::verify(&light, &[b, a * b], &proof).unwrap());
+
+    // Positions must exist and be distinct.
+    assert_eq!(
+        vk.specialize(&[(3, a)]),
+        Err(Groth16Error::InvalidInputIndex { index: 3 })
+    );
+    assert_eq!(
+        vk.specialize(&[(1, b), (1, b)]),
+        Err(Groth16Error::InvalidInputIndex { index: 1 })
+    );
+}
+
+fn test_commitment_round_trip<E>()
+where
+    E: Pairing,
+{
+    use crate::commitment::Opening;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    // Reveal `a` and commit to `b` and `c = a * b`.
+    let (pk, vk) = Groth16::<E>::generate_random_parameters_with_commitment(
+        MyPublicProductCircuit { a: None, b: None },
+        2,
+        &mut rng,
+    )
+    .unwrap();
+    assert_eq!(vk.vk.gamma_abc_g1.len(), 2);
+    assert_eq!(vk.num_committed(), 2);
+    let pvk = prepare_verifying_key(&vk.vk);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let prove = |rng: &mut ark_std::rand::rngs::StdRng| {
+        Groth16::<E>::prove_with_commitment(
+            &pk,
+            MyPublicProductCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            rng,
+        )
+        .unwrap()
+    };
+    let (proof, opening) = prove(&mut rng);
+    assert_eq!(opening.values, vec![b, a * b]);
+    assert!(vk.check_opening(&proof.d, &opening));
+    assert!(Groth16::<E>::verify_with_opening(&vk, &[a], &proof, &opening).unwrap());
+    assert!(Groth16::<E>::verify_committed(&pvk, &[a], &proof).unwrap());
+    assert!(!Groth16::<E>::verify_committed(&pvk, &[b], &proof).unwrap());
+    assert_eq!(
+        Groth16::<E>::verify_committed(&pvk, &[a, b], &proof),
+        Err(Groth16Error::InputLengthMismatch {
+            expected: 1,
+            got: 2
+        })
+    );
+
+    // The commitment is blinded, so proofs of the same statement commit
+    // with different `d`.
+    let (other_proof, other_opening) = prove(&mut rng);
+    assert_ne!(other_proof.d, proof.d);
+    assert!(Groth16::<E>::verify_with_opening(&vk, &[a], &other_proof, &other_opening).unwrap());
+    assert!(!Groth16::<E>::verify_with_opening(&vk, &[a], &proof, &other_opening).unwrap());
+
+    let wrong_value = Opening {
+        values: vec![b + E::ScalarField::from(1u64), a * b],
+        randomness: opening.randomness,
+    };
+    assert!(!Groth16::<E>::verify_with_opening(&vk, &[a], &proof, &wrong_value).unwrap());
+    let wrong_randomness = Opening {
+        randomness: opening.randomness + E::ScalarField::from(1u64),
+        ..opening.clone()
+    };
+    assert!(!Groth16::<E>::verify_with_opening(&vk, &[a], &proof, &wrong_randomness).unwrap());
+
+    // `d` is part of the statement: swapping it invalidates the proof.
+    let mut swapped = proof.clone();
+    swapped.d = other_proof.d;
+    assert!(!Groth16::<E>::verify_committed(&pvk, &[a], &swapped).unwrap());
+
+    let short = Opening {
+        values: vec![b],
+        randomness: opening.randomness,
+    };
+    assert!(matches!(
+        Groth16::<E>::verify_with_opening(&vk, &[a], &proof, &short),
+        Err(Groth16Error::InputLengthMismatch {
+            expected: 2,
+            got: 1
+        })
+    ));
+    assert!(Groth16::<E>::verify_with_opening(&vk, &[], &proof, &opening).is_err());
+
+    // Every public input may be committed, but not more.
+    let (_, vk) = Groth16::<E>::generate_random_parameters_with_commitment(
+        MyPublicProductCircuit { a: None, b: None },
+        3,
+        &mut rng,
+    )
+    .unwrap();
+    assert_eq!(vk.vk.gamma_abc_g1.len(), 1);
+    assert!(Groth16::<E>::generate_random_parameters_with_commitment(
+        MyPublicProductCircuit { a: None, b: None },
+        4,
+        &mut rng,
+    )
+    .is_err());
+}
+
+fn test_verifying_key_summary<E>()
+where
+    E: Pairing,
+{
+    use ark_ec::AffineRepr;
+    use ark_std::string::ToString;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (_, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let summary = vk.summary();
+    assert_eq!(summary.num_public_inputs, 1);
+    assert_eq!(summary.curve, core::any::type_name::<E>());
+
+    // Two points in `G1` for the inputs, one in `G1` and three in `G2` for
+    // the pairing terms, and the length of `gamma_abc_g1`.
+    let g1 = E::G1Affine::generator();
+    let g2 = E::G2Affine::generator();
+    assert_eq!(
+        summary.compressed_size,
+        3 * g1.compressed_size() + 3 * g2.compressed_size() + 8
+    );
+    assert_eq!(
+        summary.uncompressed_size,
+        3 * g1.uncompressed_size() + 3 * g2.uncompressed_size() + 8
+    );
+    assert_eq!(
+        summary.to_string(),
+        ark_std::format!(
+            "verifying key over {} with 1 public inputs, {} bytes compressed, {} bytes uncompressed",
+            summary.curve,
+            summary.compressed_size,
+            summary.uncompressed_size
+        )
+    );
+
+    let (_, vk) =
+        Groth16::<E>::setup(MyPublicProductCircuit { a: None, b: None }, &mut rng).unwrap();
+    assert_eq!(vk.summary().num_public_inputs, 3);
+}
+
+fn test_verifying_key_with_appended_input<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) =
+        Groth16::<E>::setup(MyPublicProductCircuit { a: None, b: None }, &mut rng).unwrap();
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MyPublicProductCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    // A key without the last input, extended by its element, verifies proofs
+    // for the full circuit.
+    let mut prefix = vk.clone();
+    let last = prefix.gamma_abc_g1.pop().unwrap();
+    let extended = prefix.with_appended_input(last);
+    assert_eq!(extended, vk);
+    assert_eq!(prefix.gamma_abc_g1.len() + 1, extended.gamma_abc_g1.len());
+    assert!(Groth16::<E>::verify(&extended, &[a, b, a * b], &proof).unwrap());
+    assert!(!Groth16::<E>::verify(&extended, &[a, b, a + b], &proof).unwrap());
+
+    // Appending a wrong element gives a key that rejects honest proofs.
+    let wrong = prefix.with_appended_input(vk.gamma_abc_g1[1]);
+    assert!(!Groth16::<E>::verify(&wrong, &[a, b, a * b], &proof).unwrap());
+}
+
+fn test_verifying_key_diff<E>()
+where
+    E: Pairing,
+{
+    use crate::VkDiff;
+    use ark_ec::AffineRepr;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (_, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    assert!(vk.diff(&vk).is_empty());
+
+    let mut contributed = vk.clone();
+    contributed.delta_g2 = (contributed.delta_g2 * E::ScalarField::from(2u64)).into();
+    let diff = vk.diff(&contributed);
+    assert!(diff.is_delta_only());
+    assert_eq!(
+        diff,
+        VkDiff {
+            delta_g2: true,
+            ..VkDiff::default()
+        }
+    );
+
+    let mut tampered = vk.clone();
+    tampered.gamma_abc_g1[1] = E::G1Affine::generator();
+    tampered.gamma_abc_g1.push(E::G1Affine::generator());
+    let diff = vk.diff(&tampered);
+    assert!(!diff.is_delta_only());
+    assert_eq!(diff.gamma_abc_g1, vec![1, 2]);
+    assert!(!diff.alpha_g1 && !diff.beta_g2 && !diff.gamma_g2 && !diff.delta_g2);
+    assert_eq!(tampered.diff(&vk), diff);
+}
+
+fn test_groth16_error<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    assert_eq!(
+        Groth16::<E>::verify(&vk, &[a, b], &proof),
+        Err(Groth16Error::InputLengthMismatch {
+            expected: 1,
+            got: 2
+        })
+    );
+    let mut malformed = vk.clone();
+    malformed.gamma_abc_g1.clear();
+    assert_eq!(
+        Groth16::<E>::verify(&malformed, &[], &proof),
+        Err(Groth16Error::MalformedVerifyingKey)
+    );
+
+    // A failed synthesis is wrapped, and unwrapped again by `?` in functions
+    // returning `SynthesisError`.
+    let err = Groth16::<E>::prove(&pk, MySillyCircuit { a: None, b: None }, &mut rng).unwrap_err();
+    assert_eq!(
+        err,
+        Groth16Error::Synthesis(SynthesisError::AssignmentMissing)
+    );
+    assert_eq!(SynthesisError::from(err), SynthesisError::AssignmentMissing);
+    assert_eq!(
+        Groth16Error::from(SynthesisError::MalformedVerifyingKey),
+        Groth16Error::MalformedVerifyingKey
+    );
+}
+
+fn test_prove_mismatched_circuit<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, _) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+
+    // Too many constraints for the QAP domain of `pk`.
+    let too_long = MyLongSillyCircuit {
+        a: Some(a),
+        b: Some(b),
+        num_constraints: 100,
+    };
+    assert!(matches!(
+        Groth16::<E>::prove(&pk, too_long, &mut rng),
+        Err(Groth16Error::Synthesis(SynthesisError::Unsatisfiable))
+    ));
+
+    // Too few witness variables.
+    let too_narrow = MySquaringCircuit {
+        x: Some(a),
+        num_squarings: 1,
+    };
+    assert!(matches!(
+        Groth16::<E>::prove(&pk, too_narrow, &mut rng),
+        Err(Groth16Error::Synthesis(SynthesisError::Unsatisfiable))
+    ));
+    assert!(Groth16::<E>::prove_deterministic(
+        &pk,
+        MySquaringCircuit {
+            x: Some(a),
+            num_squarings: 1,
+        }
+    )
+    .is_err());
+}
+
+fn test_generate_parameters_from_cs<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let cs = ConstraintSystem::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    cs.set_mode(SynthesisMode::Setup);
+    MySillyCircuit::<E::ScalarField> { a: None, b: None }
+        .generate_constraints(cs.clone())
+        .unwrap();
+    cs.finalize();
+
+    let pk = Groth16::<E>::generate_parameters_from_cs(cs, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&pk.vk);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &proof).unwrap());
+
+    assert!(
+        Groth16::<E>::generate_parameters_from_cs(ConstraintSystemRef::None, &mut rng).is_err()
+    );
+}
+
+fn test_prove_with_randomness<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = || MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+
+    let r = E::ScalarField::rand(&mut rng);
+    let s = E::ScalarField::rand(&mut rng);
+    let proof1 = Groth16::<E>::create_proof_with_randomness(&pk, circuit(), r, s).unwrap();
+    let proof2 = Groth16::<E>::create_proof_with_randomness(&pk, circuit(), r, s).unwrap();
+    assert!(proof1 == proof2);
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &proof1).unwrap());
+
+    let zero = E::ScalarField::from(0u64);
+    let proof3 = Groth16::<E>::create_proof_with_randomness(&pk, circuit(), zero, zero).unwrap();
+    let proof4 = Groth16::<E>::prove_deterministic(&pk, circuit()).unwrap();
+    assert!(proof3 == proof4);
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &proof3).unwrap());
+}
+
+fn test_msm_backend<E>()
+where
+    E: Pairing,
+{
+    use crate::msm::{MsmBackend, ScalarBigInt};
+    use crate::storage::QuerySource;
+    use core::cell::Cell;
+
+    /// Forwards to the CPU implementation, counting the calls.
+    #[derive(Default)]
+    struct CountingBackend {
+        g1_calls: Cell<usize>,
+        g2_calls: Cell<usize>,
+    }
+
+    impl<E: Pairing> MsmBackend<E> for CountingBackend {
+        fn msm_g1(
+            &self,
+            bases: &impl QuerySource<E::G1Affine>,
+            offset: usize,
+            scalars: &[ScalarBigInt<E>],
+        ) -> E::G1 {
+            self.g1_calls.set(self.g1_calls.get() + 1);
+            bases.msm_bigint(offset, scalars)
+        }
+
+        fn msm_g2(
+            &self,
+            bases: &impl QuerySource<E::G2Affine>,
+            offset: usize,
+            scalars: &[ScalarBigInt<E>],
+        ) -> E::G2 {
+            assert!(bases.as_slice().is_some());
+            self.g2_calls.set(self.g2_calls.get() + 1);
+            bases.msm_bigint(offset, scalars)
+        }
+    }
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = || MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+
+    let r = E::ScalarField::rand(&mut rng);
+    let s = E::ScalarField::rand(&mut rng);
+    let backend = CountingBackend::default();
+    let proof = Groth16::<E>::create_proof_with_backend(circuit(), &pk, r, s, &backend).unwrap();
+    assert_eq!(
+        proof,
+        Groth16::<E>::create_proof_with_randomness(&pk, circuit(), r, s).unwrap()
+    );
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &proof).unwrap());
+
+    // `h`, `l`, `A`, and `B` in `E::G1`, and `B` in `E::G2`.
+    assert_eq!(backend.g1_calls.get(), 4);
+    assert_eq!(backend.g2_calls.get(), 1);
+
+    let proof =
+        Groth16::<E>::create_random_proof_with_backend(circuit(), &pk, &backend, &mut rng).unwrap();
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &proof).unwrap());
+    assert_eq!(backend.g1_calls.get(), 8);
+}
+
+fn test_prove_and_verify_without_inputs<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) =
+        Groth16::<E>::setup(MyWitnessOnlyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+    assert_eq!(vk.gamma_abc_g1.len(), 1);
+    assert_eq!(
+        Groth16::<E>::prepare_inputs(&pvk, &[]).unwrap(),
+        vk.gamma_abc_g1[0].into()
+    );
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MyWitnessOnlyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    assert!(Groth16::<E>::verify(&vk, &[], &proof).unwrap());
+    assert!(Groth16::<E>::verify_proof(&pvk, &proof, &[]).unwrap());
+    assert!(Groth16::<E>::verify_batch_deterministic(&pvk, &[vec![]], &[proof.clone()]).unwrap());
+
+    let mut corrupted = proof.clone();
+    corrupted.c = proof.a;
+    assert!(!Groth16::<E>::verify(&vk, &[], &corrupted).unwrap());
+    assert!(Groth16::<E>::verify(&vk, &[a], &proof).is_err());
+}
+
+fn test_prove_from_assignment<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    // Synthesize the circuit once to obtain its matrices.
+    let cs = ConstraintSystem::<E::ScalarField>::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    cs.set_mode(SynthesisMode::Setup);
+    MySillyCircuit { a: None, b: None }
+        .generate_constraints(cs.clone())
+        .unwrap();
+    cs.finalize();
+    let matrices = cs.to_matrices().unwrap()[R1CS_PREDICATE_LABEL].clone();
+
+    for _ in 0..5 {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let c = a * b;
+
+        let proof =
+            Groth16::<E>::prove_from_assignment(&pk, &matrices, &[c], &[a, b], &mut rng).unwrap();
+        assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[c], &proof).unwrap());
+    }
+
+    let a = E::ScalarField::rand(&mut rng);
+    assert!(Groth16::<E>::prove_from_assignment(&pk, &matrices, &[], &[a, a], &mut rng).is_err());
+    assert!(Groth16::<E>::prove_from_assignment(&pk, &matrices, &[a], &[a], &mut rng).is_err());
+}
+
+fn test_create_proof_from_montgomery<E, P, const N: usize>()
+where
+    E: Pairing<ScalarField = ark_ff::Fp<P, N>>,
+    P: ark_ff::FpConfig<N>,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    let cs = ConstraintSystem::<E::ScalarField>::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    cs.set_mode(SynthesisMode::Setup);
+    MySillyCircuit { a: None, b: None }
+        .generate_constraints(cs.clone())
+        .unwrap();
+    cs.finalize();
+    let matrices = cs.to_matrices().unwrap()[R1CS_PREDICATE_LABEL].clone();
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let c = a * b;
+
+    // With the same randomness, both paths produce the same proof.
+    let mut rng2 = rng.clone();
+    let expected =
+        Groth16::<E>::prove_from_assignment(&pk, &matrices, &[c], &[a, b], &mut rng).unwrap();
+    let proof =
+        Groth16::<E>::create_proof_from_montgomery(&pk, &matrices, &[c], &[a.0, b.0], &mut rng2)
+            .unwrap();
+    assert_eq!(proof, expected);
+    assert!(Groth16::<E>::verify(&vk, &[c], &proof).unwrap());
+
+    assert!(
+        Groth16::<E>::create_proof_from_montgomery(&pk, &matrices, &[c], &[a.0], &mut rng).is_err()
+    );
+    assert!(Groth16::<E>::create_proof_from_montgomery(
+        &pk,
+        &matrices,
+        &[c],
+        &[a.0, P::MODULUS],
+        &mut rng
+    )
+    .is_err());
+}
+
+fn test_create_proof_with_scratch<E>()
+where
+    E: Pairing,
+{
+    use crate::prover::ProverScratch;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    let mut scratch = ProverScratch::<E>::new();
+    assert_eq!(scratch.reserved_bytes(), 0);
+    let mut reserved = 0;
+    for i in 0..3 {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let circuit = || MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        };
+
+        let mut rng2 = rng.clone();
+        let expected =
+            Groth16::<E>::create_random_proof_with_reduction(circuit(), &pk, &mut rng).unwrap();
+        let proof =
+            Groth16::<E>::create_proof_with_scratch(&pk, circuit(), &mut rng2, &mut scratch)
+                .unwrap();
+        assert_eq!(proof, expected);
+        assert!(Groth16::<E>::verify(&vk, &[a * b], &proof).unwrap());
+
+        // Only the first proof grows the buffers.
+        if i == 0 {
+            reserved = scratch.reserved_bytes();
+            assert!(reserved > 0);
+        }
+        assert_eq!(scratch.reserved_bytes(), reserved);
+    }
+}
+
+fn test_prove_batch<E>(n_proofs: usize)
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let values = (0..n_proofs)
+        .map(|_| {
+            (
+                E::ScalarField::rand(&mut rng),
+                E::ScalarField::rand(&mut rng),
+            )
+        })
+        .collect::<Vec<_>>();
+    let circuits = values.iter().map(|&(a, b)| MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    });
+
+    let proofs = Groth16::<E>::prove_batch(&pk, circuits, &mut rng).unwrap();
+    assert_eq!(proofs.len(), n_proofs);
+    for (proof, (a, b)) in proofs.iter().zip(&values) {
+        assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[*a * b], proof).unwrap());
+    }
+
+    // Proofs of the same statement must still be independently randomized.
+    let (a, b) = values[0];
+    let circuits = (0..2).map(|_| MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    });
+    let proofs = Groth16::<E>::prove_batch(&pk, circuits, &mut rng).unwrap();
+    assert!(proofs[0] != proofs[1]);
+
+    // A prepared key gives proofs that verify in the same way.
+    let circuits = values.iter().map(|&(a, b)| MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    });
+    let proofs =
+        Groth16::<E>::prove_batch_with_prepared_pk(&pk.prepare(), circuits, &mut rng).unwrap();
+    for (proof, (a, b)) in proofs.iter().zip(&values) {
+        assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[*a * b], proof).unwrap());
+    }
+}
+
+fn test_create_proof_with_progress<E>()
+where
+    E: Pairing,
+{
+    use crate::prover::ProvingPhase;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = || MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+
+    let mut events = Vec::new();
+    let mut record = |phase: ProvingPhase, fraction: f64| events.push((phase, fraction));
+    let seed = rng.next_u64();
+    let proof = Groth16::<E>::create_proof_with_progress(
+        &pk,
+        circuit(),
+        &mut ark_std::rand::rngs::StdRng::seed_from_u64(seed),
+        Some(&mut record),
+    )
+    .unwrap();
+    assert!(Groth16::<E>::verify(&vk, &[a * b], &proof).unwrap());
+
+    let phases = events.iter().map(|(phase, _)| *phase).collect::<Vec<_>>();
+    assert_eq!(
+        phases,
+        [
+            ProvingPhase::ConstraintSynthesis,
+            ProvingPhase::WitnessMap,
+            ProvingPhase::HQueryMsm,
+            ProvingPhase::LQueryMsm,
+            ProvingPhase::AQueryMsm,
+            ProvingPhase::BG1QueryMsm,
+            ProvingPhase::BG2QueryMsm,
+        ]
+    );
+    assert!(events.windows(2).all(|pair| pair[0].1 < pair[1].1));
+    assert_eq!(events.last().unwrap().1, 1.0);
+
+    // Without a callback, the proof is the one `prove` creates.
+    let unobserved = Groth16::<E>::create_proof_with_progress(
+        &pk,
+        circuit(),
+        &mut ark_std::rand::rngs::StdRng::seed_from_u64(seed),
+        None,
+    )
+    .unwrap();
+    let expected = Groth16::<E>::prove(
+        &pk,
+        circuit(),
+        &mut ark_std::rand::rngs::StdRng::seed_from_u64(seed),
+    )
+    .unwrap();
+    assert_eq!(proof, expected);
+    assert_eq!(unobserved, expected);
+}
+
+fn test_verify_batch<E>(n_proofs: usize)
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let mut inputs = Vec::new();
+    let mut proofs = Vec::new();
+    for _ in 0..n_proofs {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let proof = Groth16::<E>::prove(
+            &pk,
+            MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &mut rng,
+        )
+        .unwrap();
+        inputs.push(vec![a * b]);
+        proofs.push(proof);
+    }
+
+    assert!(Groth16::<E>::verify_batch(&pvk, &inputs, &proofs, &mut rng).unwrap());
+    assert!(Groth16::<E>::verify_batch_deterministic(&pvk, &inputs, &proofs).unwrap());
+    assert!(Groth16::<E>::verify_batch(&pvk, &[], &[], &mut rng).unwrap());
+
+    // A single corrupted proof must fail the whole batch.
+    let mut corrupted = proofs.clone();
+    corrupted[n_proofs / 2].c = proofs[0].c;
+    assert!(!Groth16::<E>::verify_batch(&pvk, &inputs, &corrupted, &mut rng).unwrap());
+    assert!(!Groth16::<E>::verify_batch_deterministic(&pvk, &inputs, &corrupted).unwrap());
+
+    // So must a single wrong input.
+    let mut wrong_inputs = inputs.clone();
+    wrong_inputs[n_proofs - 1][0] += E::ScalarField::from(1u64);
+    assert!(!Groth16::<E>::verify_batch(&pvk, &wrong_inputs, &proofs, &mut rng).unwrap());
+
+    assert!(Groth16::<E>::verify_batch(&pvk, &inputs[1..], &proofs, &mut rng).is_err());
+}
+
+fn test_verify_returning_ab_pairing<E>()
+where
+    E: Pairing,
+{
+    use ark_ec::pairing::MillerLoopOutput;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    // The result matches `verify_proof`, and the returned Miller loop
+    // output finalizes to `e(A, B)`, whether or not the proof is valid.
+    for c in [a * b, a + b] {
+        let (valid, ab) = Groth16::<E>::verify_returning_ab_pairing(&pvk, &[c], &proof).unwrap();
+        assert_eq!(valid, c == a * b);
+        assert_eq!(
+            valid,
+            Groth16::<E>::verify_proof(&pvk, &proof, &[c]).unwrap()
+        );
+        assert_eq!(
+            E::final_exponentiation(MillerLoopOutput(ab)).unwrap(),
+            E::pairing(proof.a, proof.b)
+        );
+    }
+
+    assert!(Groth16::<E>::verify_returning_ab_pairing(&pvk, &[], &proof).is_err());
+}
+
+fn test_verify_proof_with_vk<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    // Borrowing the unprepared key gives the same results as preparing it.
+    let mut corrupted = proof.clone();
+    corrupted.c = proof.a;
+    for (proof, c) in [(&proof, a * b), (&proof, a + b), (&corrupted, a * b)] {
+        let expected = Groth16::<E>::verify_proof(&pvk, proof, &[c]).unwrap();
+        assert_eq!(expected, c == a * b && proof.c != proof.a);
+        assert_eq!(
+            Groth16::<E>::verify_proof_with_vk(&vk, proof, &[c]).unwrap(),
+            expected
+        );
+        assert_eq!(Groth16::<E>::verify(&vk, &[c], proof).unwrap(), expected);
+        assert_eq!(
+            Groth16::<E>::verify_with_processed_vk(&pvk, &[c], proof).unwrap(),
+            expected
+        );
+    }
+
+    assert!(Groth16::<E>::verify_proof_with_vk(&vk, &proof, &[]).is_err());
+    assert_eq!(
+        Groth16::<E>::verify(&vk, &[a, b], &proof),
+        Err(Groth16Error::InputLengthMismatch {
+            expected: 1,
+            got: 2
+        })
+    );
+    let mut empty = vk.clone();
+    empty.gamma_abc_g1.clear();
+    assert_eq!(
+        Groth16::<E>::verify(&empty, &[], &proof),
+        Err(Groth16Error::MalformedVerifyingKey)
+    );
+}
+
+fn test_verify_batch_in_chunks<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let mut inputs = Vec::new();
+    let mut proofs = Vec::new();
+    for _ in 0..7 {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let proof = Groth16::<E>::prove(
+            &pk,
+            MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &mut rng,
+        )
+        .unwrap();
+        inputs.push(vec![a * b]);
+        proofs.push(proof);
+    }
+    let scalars = (0..proofs.len())
+        .map(|_| E::ScalarField::rand(&mut rng))
+        .collect::<Vec<_>>();
+
+    let mut corrupted = proofs.clone();
+    corrupted[3].c = proofs[0].c;
+
+    // The serial path and any split of the Miller loops, including chunks of
+    // uneven length and more chunks than proofs, must agree.
+    for (proofs, expected) in [(&proofs, true), (&corrupted, false)] {
+        for num_chunks in [1, 2, 3, 7, 16] {
+            let result =
+                Groth16::<E>::verify_batch_in_chunks(&pvk, &inputs, proofs, &scalars, num_chunks)
+                    .unwrap();
+            assert_eq!(result, expected, "{} chunks", num_chunks);
+        }
+    }
+}
+
+fn test_verify_proofs_same_vk<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let mut proofs_and_inputs = Vec::new();
+    for _ in 0..5 {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let proof = Groth16::<E>::prove(
+            &pk,
+            MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &mut rng,
+        )
+        .unwrap();
+        proofs_and_inputs.push((proof, vec![a * b]));
+    }
+
+    assert!(Groth16::<E>::verify_proofs_same_vk(&pvk, &proofs_and_inputs, &mut rng).unwrap());
+    assert!(Groth16::<E>::verify_proofs_same_vk(&pvk, &[], &mut rng).unwrap());
+
+    // One bad proof or one wrong input set fails the whole batch, wherever it
+    // is.
+    for i in [0, 2, 4] {
+        let mut bad_proof = proofs_and_inputs.clone();
+        bad_proof[i].0.a = proofs_and_inputs[(i + 1) % 5].0.a;
+        assert!(!Groth16::<E>::verify_proofs_same_vk(&pvk, &bad_proof, &mut rng).unwrap());
+
+        let mut bad_inputs = proofs_and_inputs.clone();
+        bad_inputs[i].1[0] += E::ScalarField::from(1u64);
+        assert!(!Groth16::<E>::verify_proofs_same_vk(&pvk, &bad_inputs, &mut rng).unwrap());
+    }
+
+    let mut wrong_len = proofs_and_inputs.clone();
+    wrong_len[1].1.push(E::ScalarField::from(1u64));
+    assert!(Groth16::<E>::verify_proofs_same_vk(&pvk, &wrong_len, &mut rng).is_err());
+}
+
+fn test_verify_with_sponge<E>()
+where
+    E: Pairing,
+{
+    use crate::verifier::batch_sponge_config;
+    use ark_crypto_primitives::sponge::{poseidon::PoseidonSponge, CryptographicSponge};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let mut proofs_and_inputs = Vec::new();
+    for _ in 0..4 {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let proof = Groth16::<E>::prove(
+            &pk,
+            MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &mut rng,
+        )
+        .unwrap();
+        assert!(Groth16::<E>::verify_proof(&pvk, &proof, &[a * b]).unwrap());
+        proofs_and_inputs.push((proof, vec![a * b]));
+    }
+
+    let config = batch_sponge_config::<E::ScalarField>();
+    let mut sponge = PoseidonSponge::new(&config);
+    assert!(Groth16::<E>::verify_with_sponge(&pvk, &mut sponge, &proofs_and_inputs).unwrap());
+
+    // The sponge carries the transcript over to the next batch.
+    let mut fresh = PoseidonSponge::new(&config);
+    let next: E::ScalarField = sponge.squeeze_field_elements(1)[0];
+    let unrelated: E::ScalarField = fresh.squeeze_field_elements(1)[0];
+    assert_ne!(next, unrelated);
+    assert!(Groth16::<E>::verify_with_sponge(&pvk, &mut sponge, &proofs_and_inputs[1..]).unwrap());
+
+    let mut wrong_inputs = proofs_and_inputs.clone();
+    wrong_inputs[2].1[0] += E::ScalarField::from(1u64);
+    assert!(!Groth16::<E>::verify_with_sponge(&pvk, &mut fresh, &wrong_inputs).unwrap());
+
+    let mut too_many_inputs = proofs_and_inputs;
+    too_many_inputs[0].1.push(E::ScalarField::from(1u64));
+    assert!(Groth16::<E>::verify_with_sponge(&pvk, &mut fresh, &too_many_inputs).is_err());
+}
+
+fn test_prepare_inputs<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let c = a * b;
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    let prepared_inputs = Groth16::<E>::prepare_inputs(&pvk, &[c]).unwrap();
+    assert_eq!(prepared_inputs, vk.gamma_abc_g1[1] * c + vk.gamma_abc_g1[0]);
+    assert!(
+        Groth16::<E>::verify_proof_with_prepared_inputs(&pvk, &proof, &prepared_inputs).unwrap()
+    );
+
+    assert!(Groth16::<E>::prepare_inputs(&pvk, &[]).is_err());
+    assert!(Groth16::<E>::prepare_inputs(&pvk, &[c, c]).is_err());
+    assert!(Groth16::<E>::verify_proof(&pvk, &proof, &[c, c]).is_err());
+}
+
+fn test_process_vk_cached<E>()
+where
+    E: Pairing,
+{
+    use crate::AlphaBetaCache;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (_, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let (_, other_vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    let mut cache = AlphaBetaCache::<E>::None;
+    let pvk = Groth16::<E>::process_vk_cached(&vk, &mut cache).unwrap();
+    assert_eq!(pvk, prepare_verifying_key(&vk));
+    assert_eq!(cache, Some((vk.fingerprint(), pvk.alpha_g1_beta_g2)));
+
+    // A hit gives the same key; a stale entry is never used for another key.
+    let e = cache.unwrap().1;
+    assert_eq!(
+        Groth16::<E>::process_vk_cached(&vk, &mut cache).unwrap(),
+        pvk
+    );
+    let other_pvk = Groth16::<E>::process_vk_cached(&other_vk, &mut cache).unwrap();
+    assert_eq!(other_pvk, prepare_verifying_key(&other_vk));
+    assert_eq!(cache.unwrap().0, other_vk.fingerprint());
+
+    // The cached pairing is used as is, which shows that a hit skips it.
+    let mut planted = Some((vk.fingerprint(), e * e));
+    let from_planted = Groth16::<E>::process_vk_cached(&vk, &mut planted).unwrap();
+    assert_eq!(from_planted.alpha_g1_beta_g2, e * e);
+}
+
+fn test_process_vk_from_prepared<E>()
+where
+    E: Pairing,
+{
+    use crate::process_vk_from_prepared;
+    use ark_serialize::CanonicalDeserialize;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = Groth16::<E>::process_vk(&vk).unwrap();
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    // Distribute the prepared parts in serialized form.
+    let mut bytes = Vec::new();
+    pvk.gamma_g2_neg_pc
+        .serialize_compressed(&mut bytes)
+        .unwrap();
+    pvk.delta_g2_neg_pc
+        .serialize_compressed(&mut bytes)
+        .unwrap();
+    pvk.alpha_g1_beta_g2
+        .serialize_compressed(&mut bytes)
+        .unwrap();
+    let mut reader = &bytes[..];
+    let gamma_g2_neg_pc = E::G2Prepared::deserialize_compressed(&mut reader).unwrap();
+    let delta_g2_neg_pc = E::G2Prepared::deserialize_compressed(&mut reader).unwrap();
+    let alpha_g1_beta_g2 = E::TargetField::deserialize_compressed(&mut reader).unwrap();
+
+    for validate in [true, false] {
+        let pvk2 = process_vk_from_prepared(
+            &vk,
+            gamma_g2_neg_pc.clone(),
+            delta_g2_neg_pc.clone(),
+            alpha_g1_beta_g2,
+            validate,
+        )
+        .unwrap();
+        assert_eq!(pvk2, pvk);
+        assert!(Groth16::<E>::verify_with_processed_vk(&pvk2, &[a * b], &proof).unwrap());
+    }
+
+    // Swapped parts are rejected when validating, and otherwise make valid
+    // proofs fail.
+    assert_eq!(
+        process_vk_from_prepared(
+            &vk,
+            delta_g2_neg_pc.clone(),
+            gamma_g2_neg_pc.clone(),
+            alpha_g1_beta_g2,
+            true
+        ),
+        Err(SynthesisError::MalformedVerifyingKey)
+    );
+    let swapped = process_vk_from_prepared(
+        &vk,
+        delta_g2_neg_pc,
+        gamma_g2_neg_pc,
+        alpha_g1_beta_g2,
+        false,
+    )
+    .unwrap();
+    assert!(!Groth16::<E>::verify_with_processed_vk(&swapped, &[a * b], &proof).unwrap());
+}
+
+fn test_prepared_verifying_key_serialization<E>()
+where
+    E: Pairing,
+{
+    use crate::PreparedVerifyingKey;
+    use ark_serialize::{CanonicalDeserialize, Compress, Validate};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let (_, other_vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = Groth16::<E>::process_vk(&vk).unwrap();
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    for compress in [Compress::Yes, Compress::No] {
+        let mut bytes = Vec::new();
+        pvk.serialize_with_mode(&mut bytes, compress).unwrap();
+        assert_eq!(bytes.len(), pvk.serialized_size(compress));
+
+        let pvk2 =
+            PreparedVerifyingKey::<E>::deserialize_with_mode(&bytes[..], compress, Validate::Yes)
+                .unwrap();
+        assert_eq!(pvk2, Groth16::<E>::process_vk(&vk).unwrap());
+        assert!(Groth16::<E>::verify_with_processed_vk(&pvk2, &[a * b], &proof).unwrap());
+
+        // The stored `G2` elements must match the stored verification key.
+        let mut other_gamma_g2_neg = Vec::new();
+        (-other_vk.gamma_g2)
+            .serialize_with_mode(&mut other_gamma_g2_neg, compress)
+            .unwrap();
+        let offset = bytes.len() - 2 * other_gamma_g2_neg.len();
+        bytes[offset..offset + other_gamma_g2_neg.len()].copy_from_slice(&other_gamma_g2_neg);
+        assert!(PreparedVerifyingKey::<E>::deserialize_with_mode(
+            &bytes[..],
+            compress,
+            Validate::Yes
+        )
+        .is_err());
+    }
+}
+
+fn test_verify_proof_with_inputs<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    // Inputs produced lazily, without an intermediate collection.
+    let inputs = || core::iter::once(a).map(|a| a * b);
+    assert!(Groth16::<E>::verify_proof_with_inputs(&pvk, &proof, inputs()).unwrap());
+    assert!(
+        !Groth16::<E>::verify_proof_with_inputs(&pvk, &proof, inputs().map(|c| c + a)).unwrap()
+    );
+
+    assert!(Groth16::<E>::verify_proof_with_inputs(&pvk, &proof, core::iter::empty()).is_err());
+    assert!(
+        Groth16::<E>::verify_proof_with_inputs(&pvk, &proof, inputs().chain(inputs())).is_err()
+    );
+}
+
+fn test_verify_proof_with_group_inputs<E>()
+where
+    E: Pairing,
+{
+    use crate::{GroupInput, Proof};
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_ff::{One, Zero};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let c = a * b;
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    let base = vk.input_base(0).unwrap();
+    assert!(vk.input_base(1).is_none());
+    let group_input = GroupInput::<E>::new(base, c, &mut rng);
+    assert!(group_input.check(&base));
+    let wrong_group_input = GroupInput::<E>::new(base, a, &mut rng);
+
+    assert!(Groth16::<E>::verify_proof_with_group_inputs(
+        &pvk,
+        &proof,
+        &[],
+        &[group_input.clone()]
+    )
+    .unwrap());
+    assert!(
+        !Groth16::<E>::verify_proof_with_group_inputs(&pvk, &proof, &[], &[wrong_group_input])
+            .unwrap()
+    );
+    assert!(Groth16::<E>::verify_proof_with_group_inputs(&pvk, &proof, &[c], &[]).unwrap());
+
+    // A point chosen to cancel `gamma_abc_g1[0]` makes `(alpha_g1, beta_g2,
+    // 0)` satisfy the pairing equation, but no proof of knowledge for it can
+    // be made.
+    let forged_proof = Proof::<E> {
+        a: vk.alpha_g1,
+        b: vk.beta_g2,
+        c: E::G1Affine::zero(),
+    };
+    let forged_point = (-vk.gamma_abc_g1[0].into_group()).into_affine();
+    let forged_input = GroupInput::<E> {
+        point: forged_point,
+        pok_commitment: base,
+        pok_response: E::ScalarField::one(),
+    };
+    assert!(!forged_input.check(&base));
+    assert!(!Groth16::<E>::verify_proof_with_group_inputs(
+        &pvk,
+        &forged_proof,
+        &[],
+        &[forged_input]
+    )
+    .unwrap());
+    // Proofs of knowledge are bound to their points.
+    let moved_input = GroupInput::<E> {
+        point: forged_point,
+        ..group_input.clone()
+    };
+    assert!(!Groth16::<E>::verify_proof_with_group_inputs(
+        &pvk,
+        &forged_proof,
+        &[],
+        &[moved_input]
+    )
+    .unwrap());
+
+    assert!(Groth16::<E>::verify_proof_with_group_inputs(&pvk, &proof, &[], &[]).is_err());
+    assert!(
+        Groth16::<E>::verify_proof_with_group_inputs(&pvk, &proof, &[c], &[group_input]).is_err()
+    );
+}
+
+fn test_verify_fixed<E>()
+where
+    E: Pairing,
+{
+    use crate::FixedPvk;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    assert!(FixedPvk::<E, 0>::new(pvk.clone()).is_err());
+    assert!(FixedPvk::<E, 2>::new(pvk.clone()).is_err());
+    let fixed_pvk = FixedPvk::<E, 1>::new(pvk.clone()).unwrap();
+    assert_eq!(fixed_pvk.pvk(), &pvk);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    assert!(Groth16::<E>::verify_fixed(&fixed_pvk, &[a * b], &proof));
+    assert!(!Groth16::<E>::verify_fixed(&fixed_pvk, &[a], &proof));
+    assert_eq!(fixed_pvk.into_inner(), pvk);
+}
+
+fn test_verify_constant_time<E>()
+where
+    E: Pairing,
+{
+    use crate::verifier::ct_eq;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let c = a * b;
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+    let mut tampered = proof.clone();
+    tampered.a = tampered.c;
+
+    // Valid and invalid proofs go through the same computation and only
+    // differ in the result.
+    for (proof, inputs, valid) in [
+        (&proof, [c], true),
+        (&proof, [a], false),
+        (&tampered, [c], false),
+    ] {
+        assert_eq!(
+            Groth16::<E>::verify_constant_time(&pvk, proof, &inputs).unwrap(),
+            valid
+        );
+        assert_eq!(
+            Groth16::<E>::verify_proof(&pvk, proof, &inputs).unwrap(),
+            valid
+        );
+    }
+    assert!(Groth16::<E>::verify_constant_time(&pvk, &proof, &[]).is_err());
+
+    assert!(ct_eq(&pvk.alpha_g1_beta_g2, &pvk.alpha_g1_beta_g2.clone()));
+    assert!(!ct_eq(
+        &pvk.alpha_g1_beta_g2,
+        &pvk.alpha_g1_beta_g2.square()
+    ));
+}
+
+fn test_verify_with_cached_inputs<E>()
+where
+    E: Pairing,
+{
+    use crate::PreparedInputs;
+    use ark_ec::CurveGroup;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    assert!(PreparedInputs::<E>::new(&pvk, &[]).is_err());
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let c = a * b;
+    let prepared_inputs = PreparedInputs::<E>::new(&pvk, &[c]).unwrap();
+    let wrong_inputs = PreparedInputs::<E>::new(&pvk, &[a]).unwrap();
+    assert_eq!(
+        prepared_inputs.point(),
+        Groth16::<E>::prepare_inputs(&pvk, &[c])
+            .unwrap()
+            .into_affine()
+    );
+
+    // Several proofs of the same instance are checked against one cache.
+    for _ in 0..3 {
+        let mut proof = Groth16::<E>::prove(
+            &pk,
+            MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &mut rng,
+        )
+        .unwrap();
+        for _ in 0..2 {
+            assert_eq!(
+                Groth16::<E>::verify_proof_with_cached_inputs(&pvk, &prepared_inputs, &proof)
+                    .unwrap(),
+                Groth16::<E>::verify_proof(&pvk, &proof, &[c]).unwrap()
+            );
+            assert_eq!(
+                Groth16::<E>::verify_proof_with_cached_inputs(&pvk, &wrong_inputs, &proof).unwrap(),
+                Groth16::<E>::verify_proof(&pvk, &proof, &[a]).unwrap()
+            );
+            // Corrupt the proof for the second round.
+            proof.c = proof.a;
+        }
+    }
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+    assert!(Groth16::<E>::verify_proof_with_cached_inputs(&pvk, &prepared_inputs, &proof).unwrap());
+    assert!(!Groth16::<E>::verify_proof_with_cached_inputs(&pvk, &wrong_inputs, &proof).unwrap());
+}
+
+fn test_miller_loop_for_verify<E>()
+where
+    E: Pairing,
+{
+    use ark_ec::pairing::MillerLoopOutput;
+    use ark_ff::One;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let mut instances = Vec::new();
+    for _ in 0..2 {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let proof = Groth16::<E>::prove(
+            &pk,
+            MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &mut rng,
+        )
+        .unwrap();
+        instances.push((a, a * b, proof));
+    }
+    let is_one = |f: E::TargetField| {
+        E::final_exponentiation(MillerLoopOutput(f)).unwrap().0 == E::TargetField::one()
+    };
+
+    let mut product = E::TargetField::one();
+    for (a, c, proof) in &instances {
+        let valid = Groth16::<E>::miller_loop_for_verify(&pvk, &[*c], proof).unwrap();
+        let invalid = Groth16::<E>::miller_loop_for_verify(&pvk, &[*a], proof).unwrap();
+        assert!(is_one(valid));
+        assert!(!is_one(invalid));
+        product *= valid;
+    }
+    // One final exponentiation checks both proofs.
+    assert!(is_one(product));
+
+    let (a, c, proof) = &instances[0];
+    let invalid = Groth16::<E>::miller_loop_for_verify(&pvk, &[*a], proof).unwrap();
+    let valid = Groth16::<E>::miller_loop_for_verify(&pvk, &[*c], &instances[1].2).unwrap();
+    assert!(!is_one(invalid * valid));
+    assert!(Groth16::<E>::miller_loop_for_verify(&pvk, &[], proof).is_err());
+}
+
+fn test_vk_fingerprint<E>()
+where
+    E: Pairing,
+{
+    use crate::VerifyingKey;
+    use ark_serialize::CanonicalDeserialize;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (_, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let (_, other_vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    // A key that went through serialization has a different memory layout,
+    // but the same fingerprint.
+    let mut bytes = Vec::new();
+    vk.serialize_uncompressed(&mut bytes).unwrap();
+    let mut deserialized = VerifyingKey::<E>::deserialize_uncompressed(&bytes[..]).unwrap();
+    deserialized.gamma_abc_g1.reserve(100);
+    assert_eq!(deserialized.fingerprint(), vk.fingerprint());
+    assert_eq!(vk.clone().fingerprint(), vk.fingerprint());
+
+    assert_ne!(other_vk.fingerprint(), vk.fingerprint());
+    let mut modified = vk.clone();
+    modified.gamma_abc_g1.pop();
+    assert_ne!(modified.fingerprint(), vk.fingerprint());
+}
+
+fn test_verify_detailed<E>(off_subgroup_g1: E::G1Affine)
+where
+    E: Pairing,
+{
+    use crate::VerificationError;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let c = a * b;
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    assert_eq!(Groth16::<E>::verify_detailed(&pvk, &proof, &[c]), Ok(()));
+    assert_eq!(
+        Groth16::<E>::verify_detailed(&pvk, &proof, &[a]),
+        Err(VerificationError::PairingCheckFailed)
+    );
+    assert_eq!(
+        Groth16::<E>::verify_detailed(&pvk, &proof, &[c, c]),
+        Err(VerificationError::InputLengthMismatch {
+            expected: 1,
+            got: 2
+        })
+    );
+
+    let mut bad_pvk = pvk.clone();
+    bad_pvk.vk.gamma_abc_g1.clear();
+    assert_eq!(
+        Groth16::<E>::verify_detailed(&bad_pvk, &proof, &[c]),
+        Err(VerificationError::MalformedVk)
+    );
+
+    let mut bad_proof = proof.clone();
+    bad_proof.a = off_subgroup_g1;
+    assert_eq!(
+        Groth16::<E>::verify_detailed(&pvk, &bad_proof, &[c]),
+        Err(VerificationError::MalformedProof)
+    );
+
+    // The bool-returning API is built on the detailed one.
+    assert!(Groth16::<E>::verify_proof(&pvk, &proof, &[c]).unwrap());
+    assert!(!Groth16::<E>::verify_proof(&pvk, &proof, &[a]).unwrap());
+    assert!(Groth16::<E>::verify_proof(&pvk, &proof, &[c, c]).is_err());
+}
+
+fn test_mixed_radix_reduction<E>()
+where
+    E: Pairing,
+{
+    use crate::r1cs_to_qap::{LibsnarkReduction, MixedRadixReduction};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    // 10 constraints and 2 instance variables need a domain of size 12, which
+    // a mixed-radix domain provides exactly instead of padding to 16.
+    let num_constraints = 10;
+    assert!(MixedRadixReduction::uses_mixed_radix::<E::ScalarField>(
+        num_constraints + 2
+    ));
+    let circuit = |a, b| MyLongSillyCircuit {
+        a,
+        b,
+        num_constraints,
+    };
+
+    let (pk, vk) = Groth16::<E, MixedRadixReduction>::setup(circuit(None, None), &mut rng).unwrap();
+    let (radix2_pk, radix2_vk) =
+        Groth16::<E, LibsnarkReduction>::setup(circuit(None, None), &mut rng).unwrap();
+    assert!(pk.h_query.len() < radix2_pk.h_query.len());
+
+    for _ in 0..5 {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let c = a * b;
+
+        let proof =
+            Groth16::<E, MixedRadixReduction>::prove(&pk, circuit(Some(a), Some(b)), &mut rng)
+                .unwrap();
+        assert!(Groth16::<E, MixedRadixReduction>::verify(&vk, &[c], &proof).unwrap());
+        assert!(!Groth16::<E, MixedRadixReduction>::verify(&vk, &[a], &proof).unwrap());
+
+        let proof =
+            Groth16::<E, LibsnarkReduction>::prove(&radix2_pk, circuit(Some(a), Some(b)), &mut rng)
+                .unwrap();
+        assert!(Groth16::<E, LibsnarkReduction>::verify(&radix2_vk, &[c], &proof).unwrap());
+    }
+}
+
+fn test_groth_paper_reduction<E>()
+where
+    E: Pairing,
+{
+    use crate::r1cs_to_qap::{GrothPaperReduction, LibsnarkReduction};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    // 16 constraints fill a domain of size 16 exactly, while the two extra
+    // input constraints of `LibsnarkReduction` push it to 32.
+    let circuit = |a, b| MyLongSillyCircuit {
+        a,
+        b,
+        num_constraints: 16,
+    };
+
+    let (pk, vk) = Groth16::<E, GrothPaperReduction>::setup(circuit(None, None), &mut rng).unwrap();
+    let (libsnark_pk, libsnark_vk) =
+        Groth16::<E, LibsnarkReduction>::setup(circuit(None, None), &mut rng).unwrap();
+    assert_eq!(pk.h_query.len(), 15);
+    assert_eq!(libsnark_pk.h_query.len(), 31);
+
+    for _ in 0..5 {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let c = a * b;
+
+        let proof =
+            Groth16::<E, GrothPaperReduction>::prove(&pk, circuit(Some(a), Some(b)), &mut rng)
+                .unwrap();
+        assert!(Groth16::<E, GrothPaperReduction>::verify(&vk, &[c], &proof).unwrap());
+        assert!(!Groth16::<E, GrothPaperReduction>::verify(&vk, &[a], &proof).unwrap());
+        assert!(!Groth16::<E, LibsnarkReduction>::verify(&libsnark_vk, &[c], &proof).unwrap());
+
+        let libsnark_proof = Groth16::<E, LibsnarkReduction>::prove(
+            &libsnark_pk,
+            circuit(Some(a), Some(b)),
+            &mut rng,
+        )
+        .unwrap();
+        assert!(
+            Groth16::<E, LibsnarkReduction>::verify(&libsnark_vk, &[c], &libsnark_proof).unwrap()
+        );
+        assert!(!Groth16::<E, GrothPaperReduction>::verify(&vk, &[c], &libsnark_proof).unwrap());
+    }
+}
+
+fn test_unsupported_predicate<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let circuit = |a, b, with_predicate| MyPredicateCircuit {
+        a,
+        b,
+        with_predicate,
+    };
+    let unsupported = Some(Groth16Error::UnsupportedPredicate {
+        label: "cube".into(),
+    });
+
+    assert_eq!(
+        Groth16::<E>::setup(circuit(None, None, true), &mut rng).err(),
+        unsupported
+    );
+
+    let (pk, vk) = Groth16::<E>::setup(circuit(None, None, false), &mut rng).unwrap();
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    assert_eq!(
+        Groth16::<E>::prove(&pk, circuit(Some(a), Some(b), true), &mut rng).err(),
+        unsupported
+    );
+    assert!(matches!(
+        Groth16::<E>::create_random_proof_with_reduction(
+            circuit(Some(a), Some(b), true),
+            &pk,
+            &mut rng
+        ),
+        Err(SynthesisError::Unsatisfiable)
+    ));
+
+    let proof = Groth16::<E>::prove(&pk, circuit(Some(a), Some(b), false), &mut rng).unwrap();
+    assert!(Groth16::<E>::verify(&vk, &[a * b], &proof).unwrap());
+}
+
+fn test_coset_reduction<E>()
+where
+    E: Pairing,
+{
+    use crate::r1cs_to_qap::{CosetOffset, CosetReduction, LibsnarkReduction};
+    use ark_ff::PrimeField;
+
+    struct Seven;
+
+    impl CosetOffset for Seven {
+        fn offset<F: PrimeField>() -> F {
+            F::from(7u64)
+        }
+    }
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let circuit = |a, b| MyLongSillyCircuit {
+        a,
+        b,
+        num_constraints: 10,
+    };
+
+    let (pk, vk) =
+        Groth16::<E, CosetReduction<Seven>>::setup(circuit(None, None), &mut rng).unwrap();
+
+    for _ in 0..5 {
+        let a = E::ScalarField::rand(&mut rng);
+        let b = E::ScalarField::rand(&mut rng);
+        let c = a * b;
+
+        let proof =
+            Groth16::<E, CosetReduction<Seven>>::prove(&pk, circuit(Some(a), Some(b)), &mut rng)
+                .unwrap();
+        assert!(Groth16::<E, CosetReduction<Seven>>::verify(&vk, &[c], &proof).unwrap());
+        assert!(!Groth16::<E, CosetReduction<Seven>>::verify(&vk, &[a], &proof).unwrap());
+
+        // A prover over the subgroup itself does not agree with the key.
+        let proof =
+            Groth16::<E, LibsnarkReduction>::prove(&pk, circuit(Some(a), Some(b)), &mut rng)
+                .unwrap();
+        assert!(!Groth16::<E, LibsnarkReduction>::verify(&vk, &[c], &proof).unwrap());
+    }
+}
+
+#[cfg(feature = "serde")]
+fn test_serde_round_trip<E>()
+where
+    E: Pairing,
+{
+    use crate::{PreparedVerifyingKey, Proof, ProvingKey, VerifyingKey};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let pvk = prepare_verifying_key::<E>(&vk);
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    let json = serde_json::to_string(&proof).unwrap();
+    let proof2: Proof<E> = serde_json::from_str(&json).unwrap();
+    assert_eq!(proof, proof2);
+    assert!(Groth16::<E>::verify_with_processed_vk(&pvk, &[a * b], &proof2).unwrap());
+
+    let vk2: VerifyingKey<E> = serde_json::from_str(&serde_json::to_string(&vk).unwrap()).unwrap();
+    assert_eq!(vk, vk2);
+
+    let pvk2: PreparedVerifyingKey<E> =
+        serde_json::from_str(&serde_json::to_string(&pvk).unwrap()).unwrap();
+    assert_eq!(pvk, pvk2);
+
+    let pk2: ProvingKey<E> = serde_json::from_str(&serde_json::to_string(&pk).unwrap()).unwrap();
+    assert_eq!(pk, pk2);
+
+    // Malformed encodings are rejected rather than panicking.
+    assert!(serde_json::from_str::<Proof<E>>("\"0x123\"").is_err());
+    assert!(serde_json::from_str::<Proof<E>>("\"00\"").is_err());
+}
+
+#[cfg(any(feature = "bn254", feature = "bls12-381"))]
+fn test_dyn_verify<E>()
+where
+    E: Pairing,
+    crate::dynamic::DynProof: From<crate::Proof<E>>,
+    crate::dynamic::DynVerifyingKey: From<crate::VerifyingKey<E>>,
+    crate::dynamic::DynInputs: From<Vec<E::ScalarField>>,
+{
+    use crate::dynamic::{dyn_verify, DynInputs, DynProof, DynVerifyingKey};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    let vk = DynVerifyingKey::from(vk);
+    let proof = DynProof::from(proof);
+    let inputs = DynInputs::from(vec![a * b]);
+    assert_eq!(vk.curve(), proof.curve());
+    assert_eq!(vk.curve(), inputs.curve());
+
+    let vk2 = DynVerifyingKey::from_bytes(&vk.to_bytes()).unwrap();
+    let proof2 = DynProof::from_bytes(&proof.to_bytes()).unwrap();
+    let inputs2 = DynInputs::from_bytes(&inputs.to_bytes()).unwrap();
+    assert_eq!(vk, vk2);
+    assert_eq!(proof, proof2);
+    assert_eq!(inputs, inputs2);
+    assert!(dyn_verify(&vk2, &inputs2, &proof2).unwrap());
+    assert!(!dyn_verify(&vk2, &DynInputs::from(vec![a]), &proof2).unwrap());
+
+    // An unknown curve identifier is rejected.
+    let mut bytes = proof.to_bytes();
+    bytes[0] = 0;
+    assert!(DynProof::from_bytes(&bytes).is_err());
+    assert!(DynProof::from_bytes(&[]).is_err());
+
+    // So are trailing bytes.
+    let mut bytes = proof.to_bytes();
+    bytes.push(0);
+    assert!(DynProof::from_bytes(&bytes).is_err());
+}
+
+#[cfg(feature = "zeroize")]
+fn test_zeroize_toxic_waste<E>()
+where
+    E: Pairing,
+{
+    use crate::generator::wipe;
+    use ark_ff::Zero;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let mut tau = E::ScalarField::rand(&mut rng);
+    let mut powers = (0..8).map(|_| tau).collect::<Vec<_>>();
+    wipe(&mut tau);
+    wipe(&mut powers);
+    assert!(tau.is_zero());
+    assert!(powers.is_empty());
+
+    // Wiping the trapdoor does not affect the parameters that were derived
+    // from it.
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+    assert!(Groth16::<E>::verify(&vk, &[a * b], &proof).unwrap());
+}
+
+fn test_verifying_key_builder<E>(off_subgroup_g1: E::G1Affine)
+where
+    E: Pairing,
+{
+    use crate::VerifyingKey;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let (_, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    let builder = VerifyingKey::<E>::builder()
+        .alpha_g1(vk.alpha_g1)
+        .beta_g2(vk.beta_g2)
+        .gamma_g2(vk.gamma_g2)
+        .delta_g2(vk.delta_g2);
+    assert_eq!(
+        builder.clone().build(),
+        Err(Groth16Error::MalformedVerifyingKey)
+    );
+    assert_eq!(
+        builder.clone().gamma_abc_g1(Vec::new()).build(),
+        Err(Groth16Error::MalformedVerifyingKey)
+    );
+    assert_eq!(
+        builder
+            .clone()
+            .gamma_abc_g1(vk.gamma_abc_g1.clone())
+            .build(),
+        Ok(vk.clone())
+    );
+
+    let mut gamma_abc_g1 = vk.gamma_abc_g1.clone();
+    gamma_abc_g1[1] = off_subgroup_g1;
+    assert_eq!(
+        builder.clone().gamma_abc_g1(gamma_abc_g1).build(),
+        Err(Groth16Error::MalformedVerifyingKey)
+    );
+    assert_eq!(
+        builder
+            .alpha_g1(off_subgroup_g1)
+            .gamma_abc_g1(vk.gamma_abc_g1)
+            .build(),
+        Err(Groth16Error::MalformedVerifyingKey)
+    );
+}
+
+fn test_deserialize_strict<E>()
+where
+    E: Pairing,
+{
+    use crate::Proof;
+    use ark_ec::AffineRepr;
+    use ark_ff::{BigInteger, PrimeField};
+    use ark_serialize::{CanonicalDeserialize, Compress};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, _) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(E::ScalarField::rand(&mut rng)),
+            b: Some(E::ScalarField::rand(&mut rng)),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    for compress in [Compress::Yes, Compress::No] {
+        let mut bytes = Vec::new();
+        proof.serialize_with_mode(&mut bytes, compress).unwrap();
+        assert_eq!(
+            Proof::<E>::deserialize_strict(&bytes, compress).unwrap(),
+            proof
+        );
+
+        // Trailing bytes are only rejected by the strict variant.
+        let mut padded = bytes.clone();
+        padded.push(0);
+        assert!(Proof::<E>::deserialize_strict(&padded, compress).is_err());
+        assert_eq!(
+            Proof::<E>::deserialize_checked(&padded[..], compress).unwrap(),
+            proof
+        );
+    }
+
+    // Adding the modulus to the `x`-coordinate of `a` leaves the flag bits
+    // alone, as the modulus of the base fields under test is narrower than
+    // its encoding.
+    let mut bytes = Vec::new();
+    proof.serialize_compressed(&mut bytes).unwrap();
+    let modulus = E::BaseField::MODULUS.to_bytes_le();
+    let mut carry = 0u16;
+    for (byte, m) in bytes.iter_mut().zip(&modulus) {
+        let sum = u16::from(*byte) + u16::from(*m) + carry;
+        *byte = sum as u8;
+        carry = sum >> 8;
+    }
+    assert_eq!(carry, 0);
+    assert!(Proof::<E>::deserialize_strict(&bytes, Compress::Yes).is_err());
+    assert!(Proof::<E>::deserialize_checked(&bytes[..], Compress::Yes).is_err());
+
+    // The point at infinity has a single encoding too.
+    let with_identity = Proof::<E> {
+        a: E::G1Affine::zero(),
+        ..proof
+    };
+    let mut bytes = Vec::new();
+    with_identity.serialize_compressed(&mut bytes).unwrap();
+    assert_eq!(
+        Proof::<E>::deserialize_strict(&bytes, Compress::Yes).unwrap(),
+        with_identity
+    );
+    bytes[0] ^= 1;
+    assert!(Proof::<E>::deserialize_strict(&bytes, Compress::Yes).is_err());
+}
+
+fn test_deserialize_checked<E>(off_subgroup_g2: E::G2Affine)
+where
+    E: Pairing,
+{
+    use crate::Proof;
+    use ark_serialize::{CanonicalDeserialize, Compress};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, _) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(E::ScalarField::rand(&mut rng)),
+            b: Some(E::ScalarField::rand(&mut rng)),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    for compress in [Compress::Yes, Compress::No] {
+        let mut bytes = Vec::new();
+        proof.serialize_with_mode(&mut bytes, compress).unwrap();
+        assert_eq!(
+            Proof::<E>::deserialize_checked(&bytes[..], compress).unwrap(),
+            proof
+        );
+
+        let malformed = Proof::<E> {
+            b: off_subgroup_g2,
+            ..proof.clone()
+        };
+        let mut bytes = Vec::new();
+        malformed.serialize_with_mode(&mut bytes, compress).unwrap();
+        assert!(Proof::<E>::deserialize_checked(&bytes[..], compress).is_err());
+        assert_eq!(
+            Proof::<E>::deserialize_with_mode(&bytes[..], compress, ark_serialize::Validate::No)
+                .unwrap(),
+            malformed
+        );
+    }
+}
+
+fn test_mpc_contribution<E>()
+where
+    E: Pairing,
+{
+    use crate::mpc::{contribute, verify_contribution};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    let mut pk1 = pk.clone();
+    let mut vk1 = vk.clone();
+    let contribution1 = contribute(&mut pk1, &mut vk1, &mut rng);
+    assert_ne!(pk1.vk.delta_g2, pk.vk.delta_g2);
+    assert_eq!(vk1, pk1.vk);
+    assert!(verify_contribution(&pk, &pk1, &contribution1));
+
+    let mut pk2 = pk1.clone();
+    let mut vk2 = vk1.clone();
+    let contribution2 = contribute(&mut pk2, &mut vk2, &mut rng);
+    assert!(verify_contribution(&pk1, &pk2, &contribution2));
+
+    // Contributions do not verify against the wrong transcript.
+    assert!(!verify_contribution(&pk, &pk2, &contribution2));
+    assert!(!verify_contribution(&pk1, &pk2, &contribution1));
+
+    // Tampering with a `delta`-dependent element is detected.
+    let mut tampered = pk2.clone();
+    tampered.l_query[0] = pk1.l_query[0];
+    assert!(!verify_contribution(&pk1, &tampered, &contribution2));
+
+    // The final parameters still prove and verify the circuit.
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk2,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+    assert!(Groth16::<E>::verify(&vk2, &[a * b], &proof).unwrap());
+    assert!(!Groth16::<E>::verify(&vk, &[a * b], &proof).unwrap());
+}
+
+#[cfg(feature = "seeded-setup")]
+fn test_generate_parameters_with_seed<E>()
+where
+    E: Pairing,
+{
+    let circuit = || MySillyCircuit::<E::ScalarField> { a: None, b: None };
+
+    let pk1 = Groth16::<E>::generate_parameters_with_seed(circuit(), [7u8; 32]).unwrap();
+    let pk2 = Groth16::<E>::generate_parameters_with_seed(circuit(), [7u8; 32]).unwrap();
+    assert_eq!(pk1, pk2);
+
+    let mut bytes1 = Vec::new();
+    let mut bytes2 = Vec::new();
+    pk1.serialize_compressed(&mut bytes1).unwrap();
+    pk2.serialize_compressed(&mut bytes2).unwrap();
+    assert_eq!(bytes1, bytes2);
+
+    let pk3 = Groth16::<E>::generate_parameters_with_seed(circuit(), [8u8; 32]).unwrap();
+    assert_ne!(pk1.vk, pk3.vk);
+}
+
+fn test_serialize_compact<E>()
+where
+    E: Pairing,
+{
+    use crate::ProvingKey;
+    use ark_serialize::Validate;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let num_squarings = 16;
+    let (pk, _) = Groth16::<E>::setup(
+        MySquaringCircuit {
+            x: None,
+            num_squarings,
+        },
+        &mut rng,
+    )
+    .unwrap();
+    let (silly_pk, _) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    for pk in [pk.clone(), silly_pk] {
+        let mut compact = Vec::new();
+        pk.serialize_compact(&mut compact).unwrap();
+        for validate in [Validate::Yes, Validate::No] {
+            let pk2 = ProvingKey::<E>::deserialize_compact(&compact[..], validate).unwrap();
+            assert_eq!(pk, pk2);
+        }
+        assert!(
+            ProvingKey::<E>::deserialize_compact(&compact[..compact.len() - 1], Validate::Yes)
+                .is_err()
+        );
+    }
+
+    // Every squared variable appears identically in `A` and `B`, so each one
+    // saves a compressed `G1` element at the cost of one bit in the bitmap.
+    let mut compact = Vec::new();
+    pk.serialize_compact(&mut compact).unwrap();
+    let g1_size = pk.delta_g1.compressed_size();
+    let bitmap_size = pk.b_g1_query.len().div_ceil(8);
+    assert_eq!(
+        compact.len(),
+        pk.compressed_size() - num_squarings * g1_size + bitmap_size
+    );
+}
+
+fn test_size_in_memory<E>()
+where
+    E: Pairing,
+{
+    use crate::VerifyingKey;
+    use core::mem::size_of;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    assert!(vk.size_in_memory() >= vk.gamma_abc_g1.len() * size_of::<E::G1Affine>());
+    let g1_len = pk.a_query.len() + pk.b_g1_query.len() + pk.h_query.len() + pk.l_query.len();
+    assert!(
+        pk.size_in_memory()
+            >= vk.size_in_memory()
+                + g1_len * size_of::<E::G1Affine>()
+                + pk.b_g2_query.len() * size_of::<E::G2Affine>()
+    );
+
+    let mut shrunk = pk.clone();
+    shrunk.h_query.clear();
+    shrunk.h_query.shrink_to_fit();
+    assert_eq!(
+        pk.size_in_memory() - shrunk.size_in_memory(),
+        pk.h_query.capacity() * size_of::<E::G1Affine>()
+    );
+    assert_eq!(VerifyingKey::<E>::default().size_in_memory(), 0);
+}
+
+fn test_parallel_generator<E>()
+where
+    E: Pairing,
+{
+    use crate::generator::fixed_base_mul;
+    use ark_ec::scalar_mul::BatchMulPreprocessing;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    // Enough scalars to be split into several chunks.
+    let scalars = (0..3000)
+        .map(|_| E::ScalarField::rand(&mut rng))
+        .collect::<Vec<_>>();
+    let table = BatchMulPreprocessing::new(E::G1::rand(&mut rng), scalars.len());
+    assert_eq!(fixed_base_mul(&table, &scalars), table.batch_mul(&scalars));
+
+    // Keys generated on a single thread match those generated on all of them.
+    #[cfg(feature = "parallel")]
+    {
+        let seed = rng.next_u64();
+        let setup = || {
+            let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(seed);
+            let circuit = MyLongSillyCircuit::<E::ScalarField> {
+                a: None,
+                b: None,
+                num_constraints: 100,
+            };
+            Groth16::<E>::setup(circuit, &mut rng).unwrap()
+        };
+        let serial = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap()
+            .install(setup);
+        assert_eq!(serial, setup());
+    }
+}
+
+fn test_collaborative_proof<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+    let circuit = |x| MySquaringCircuit::<E::ScalarField> {
+        x,
+        num_squarings: 8,
+    };
+    let (pk, vk) = Groth16::<E>::setup(circuit(None), &mut rng).unwrap();
+
+    let cs = ConstraintSystem::<E::ScalarField>::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    cs.set_mode(SynthesisMode::Setup);
+    circuit(None).generate_constraints(cs.clone()).unwrap();
+    cs.finalize();
+    let matrices = cs.to_matrices().unwrap()[R1CS_PREDICATE_LABEL].clone();
+
+    let x = E::ScalarField::rand(&mut rng);
+    let mut witness = vec![x];
+    for _ in 1..8 {
+        witness.push(witness.last().unwrap().square());
+    }
+    let y = witness.last().unwrap().square();
+
+    // Two parties contribute disjoint halves of the witness, in any order.
+    let mut state = Groth16::<E>::begin_proof(&pk, &matrices, &[y]).unwrap();
+    Groth16::<E>::contribute_witness(&mut state, &[4, 5, 6, 7], &witness[4..]).unwrap();
+    assert_eq!(state.missing_witness().collect::<Vec<_>>(), [0, 1, 2, 3]);
+    assert!(Groth16::<E>::contribute_witness(&mut state, &[3, 4], &witness[3..5]).is_err());
+    assert!(Groth16::<E>::contribute_witness(&mut state, &[2, 2], &[x, x]).is_err());
+    assert!(Groth16::<E>::contribute_witness(&mut state, &[8], &[x]).is_err());
+    assert!(Groth16::<E>::contribute_witness(&mut state, &[0, 1], &[x]).is_err());
+    Groth16::<E>::contribute_witness(&mut state, &[0, 1, 2, 3], &witness[..4]).unwrap();
+
+    let seed = rng.next_u64();
+    let proof =
+        Groth16::<E>::finalize_proof(state, &mut ark_std::rand::rngs::StdRng::seed_from_u64(seed))
+            .unwrap();
+    let monolithic = Groth16::<E>::prove(
+        &pk,
+        circuit(Some(x)),
+        &mut ark_std::rand::rngs::StdRng::seed_from_u64(seed),
+    )
+    .unwrap();
+    assert_eq!(proof, monolithic);
+    assert!(Groth16::<E>::verify(&vk, &[y], &proof).unwrap());
+
+    let mut state = Groth16::<E>::begin_proof(&pk, &matrices, &[y]).unwrap();
+    Groth16::<E>::contribute_witness(&mut state, &[0], &[x]).unwrap();
+    assert_eq!(
+        Groth16::<E>::finalize_proof(state, &mut rng).unwrap_err(),
+        SynthesisError::AssignmentMissing
+    );
+    assert!(Groth16::<E>::begin_proof(&pk, &matrices, &[]).is_err());
+}
+
+fn test_verify_against_commitment<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let (_, other_vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let proof = Groth16::<E>::prove(
+        &pk,
+        MySillyCircuit {
+            a: Some(a),
+            b: Some(b),
+        },
+        &mut rng,
+    )
+    .unwrap();
+
+    let commitment = vk.fingerprint();
+    assert_eq!(
+        Groth16::<E>::verify_against_commitment(&vk, &commitment, &[a * b], &proof),
+        Ok(true)
+    );
+    assert_eq!(
+        Groth16::<E>::verify_against_commitment(&vk, &commitment, &[a], &proof),
+        Ok(false)
+    );
+    assert_eq!(
+        Groth16::<E>::verify_against_commitment(&vk, &commitment, &[], &proof),
+        Err(Groth16Error::InputLengthMismatch {
+            expected: 1,
+            got: 0
+        })
+    );
+    assert_eq!(
+        Groth16::<E>::verify_against_commitment(&other_vk, &commitment, &[a * b], &proof),
+        Err(Groth16Error::VkCommitmentMismatch)
+    );
+}
+
+fn test_generator_config<E>()
+where
+    E: Pairing,
+{
+    use crate::generator::GeneratorConfig;
+
+    let seed = test_rng().next_u64();
+    let setup = |window_size| {
+        let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(seed);
+        let circuit = MyLongSillyCircuit::<E::ScalarField> {
+            a: None,
+            b: None,
+            num_constraints: 100,
+        };
+        let config = GeneratorConfig { window_size };
+        Groth16::<E>::generate_random_parameters_with_config(circuit, &config, &mut rng).unwrap()
+    };
+
+    // The window size only changes how the parameters are computed.
+    let pk = setup(None);
+    for window_size in [0, 1, 4, 12] {
+        assert_eq!(pk, setup(Some(window_size)));
+    }
+
+    // Out-of-range window sizes are clamped, so huge values cannot make the
+    // generator allocate exponentially large tables.
+    let clamped = |window_size| {
+        GeneratorConfig {
+            window_size: Some(window_size),
+        }
+        .clamped_window_size::<E::ScalarField>()
+    };
+    assert_eq!(clamped(0), Some(1));
+    assert_eq!(clamped(12), Some(12));
+    assert_eq!(clamped(1000), Some(GeneratorConfig::MAX_WINDOW_SIZE));
+    assert_eq!(clamped(usize::MAX), Some(GeneratorConfig::MAX_WINDOW_SIZE));
+    assert_eq!(
+        GeneratorConfig::default().clamped_window_size::<E::ScalarField>(),
+        None
+    );
+
+    let mut rng = test_rng();
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = MyLongSillyCircuit {
+        a: Some(a),
+        b: Some(b),
+        num_constraints: 100,
+    };
+    let proof = Groth16::<E>::prove(&setup(Some(3)), circuit, &mut rng).unwrap();
+    assert!(Groth16::<E>::verify(&pk.vk, &[a * b], &proof).unwrap());
+}
+
+fn test_proving_key_self_check<E>()
+where
+    E: Pairing,
+{
+    use ark_ec::{AffineRepr, CurveGroup};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let (other_pk, other_vk) =
+        Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    assert_eq!(pk.self_check(&vk), Ok(()));
+    assert_eq!(other_pk.self_check(&other_vk), Ok(()));
+    assert_eq!(
+        pk.self_check(&other_vk),
+        Err(Groth16Error::MalformedProvingKey)
+    );
+
+    let tampered = |tamper: &dyn Fn(&mut crate::ProvingKey<E>)| {
+        let mut pk = pk.clone();
+        tamper(&mut pk);
+        let vk = pk.vk.clone();
+        pk.self_check(&vk)
+    };
+    let double = |p: &mut E::G1Affine| *p = (*p + *p).into_affine();
+
+    assert!(tampered(&|pk| double(&mut pk.beta_g1)).is_err());
+    assert!(tampered(&|pk| double(&mut pk.delta_g1)).is_err());
+    assert!(tampered(
+        &|pk| pk.vk.beta_g2 = (pk.vk.beta_g2 * E::ScalarField::from(2u64)).into_affine()
+    )
+    .is_err());
+    assert!(tampered(&|pk| pk.delta_g1 = E::G1Affine::zero()).is_err());
+    assert!(tampered(&|pk| {
+        pk.b_g2_query.pop();
+    })
+    .is_err());
+    assert!(tampered(&|pk| {
+        pk.l_query.pop();
+    })
+    .is_err());
+    // Corrupt a nonzero element of `b_g1_query`.
+    assert!(tampered(&|pk| {
+        let i = pk.b_g1_query.iter().position(|p| !p.is_zero()).unwrap();
+        double(&mut pk.b_g1_query[i]);
+    })
+    .is_err());
+    assert!(tampered(&|pk| {
+        let i = pk.b_g2_query.iter().position(|p| !p.is_zero()).unwrap();
+        pk.b_g2_query[i] = pk.vk.beta_g2;
+    })
+    .is_err());
+
+    // A degenerate key for which the trivial proof verifies is rejected:
+    // with `gamma_g2 = beta_g2` and `gamma_abc_g1[0] = -alpha_g1`, the
+    // equation for `(0, 0, 0)` and the all-zero instance holds.
+    assert_eq!(
+        tampered(&|pk| pk.vk.gamma_g2 = E::G2Affine::zero()),
+        Err(Groth16Error::MalformedProvingKey)
+    );
+    assert_eq!(
+        tampered(&|pk| {
+            pk.vk.gamma_g2 = pk.vk.beta_g2;
+            pk.vk.gamma_abc_g1[0] = (-pk.vk.alpha_g1.into_group()).into_affine();
+        }),
+        Err(Groth16Error::MalformedVerifyingKey)
+    );
+
+    // Corruption of the queries that only a proof uses is caught once a
+    // proof is created and verified.
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = || MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+    assert_eq!(
+        pk.self_check_with_circuit(&vk, circuit(), &[a * b], &mut rng),
+        Ok(())
+    );
+    let corruptions: [&dyn Fn(&mut crate::ProvingKey<E>); 3] = [
+        &|pk| {
+            let i = pk.a_query.iter().position(|p| !p.is_zero()).unwrap();
+            double(&mut pk.a_query[i]);
+        },
+        &|pk| double(&mut pk.h_query[0]),
+        &|pk| double(&mut pk.l_query[0]),
+    ];
+    for corrupt in corruptions {
+        let mut pk = pk.clone();
+        corrupt(&mut pk);
+        assert_eq!(pk.self_check(&vk), Ok(()));
+        assert_eq!(
+            pk.self_check_with_circuit(&vk, circuit(), &[a * b], &mut rng),
+            Err(Groth16Error::MalformedProvingKey)
+        );
+    }
+}
+
+fn test_operation_counts<E>()
+where
+    E: Pairing,
+{
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    // Six constraints and one input give a QAP domain of size 8; the four
+    // variables are one, `c`, `a`, and `b`.
+    let (pk, _) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+    let cost = pk.operation_counts();
+    assert_eq!(cost.g1_scalar_mults, 4 + 4 + 7 + 2 + 5);
+    assert_eq!(cost.g2_scalar_mults, 4 + 1);
+    assert_eq!(cost.fft_size, 8);
+    assert_eq!(cost.num_ffts, 7);
+
+    let long = MyLongSillyCircuit {
+        a: None,
+        b: None,
+        num_constraints: 100,
+    };
+    let (pk, _) = Groth16::<E>::setup(long, &mut rng).unwrap();
+    let long_cost = pk.operation_counts();
+    assert_eq!(long_cost.g1_scalar_mults, 4 + 4 + 127 + 2 + 5);
+    assert_eq!(long_cost.g2_scalar_mults, cost.g2_scalar_mults);
+    assert_eq!(long_cost.fft_size, 128);
+}
+
+#[cfg(feature = "mmap")]
+#[allow(unsafe_code)]
+fn test_mmap_proving_key<E>()
+where
+    E: Pairing,
+{
+    use crate::ProvingKey;
+    use ark_ec::AffineRepr;
+    use ark_ff::Zero;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let (pk, vk) = Groth16::<E>::setup(MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+
+    let dir = std::env::temp_dir();
+    let path = dir.join(format!("groth16-mmap-{}.pk", rng.next_u64()));
+    let mut bytes = Vec::new();
+    pk.serialize_uncompressed(&mut bytes).unwrap();
+    std::fs::write(&path, &bytes).unwrap();
+    // SAFETY: the file is private to this test and is only rewritten after
+    // the mapping is dropped.
+    let mapped = unsafe { ProvingKey::<E>::deserialize_mmap(&path) }.unwrap();
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = || MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    };
+
+    // The mapped key produces exactly the same proofs as the in-memory one.
+    let r = E::ScalarField::rand(&mut rng);
+    let s = E::ScalarField::rand(&mut rng);
+    assert_eq!(
+        Groth16::<E>::create_proof_with_randomness(&mapped, circuit(), r, s).unwrap(),
+        Groth16::<E>::create_proof_with_randomness(&pk, circuit(), r, s).unwrap()
+    );
+    let zero = E::ScalarField::zero();
+    assert_eq!(
+        Groth16::<E>::create_proof_with_randomness(&mapped, circuit(), zero, zero).unwrap(),
+        Groth16::<E>::prove_deterministic(&pk, circuit()).unwrap()
+    );
+
+    let proof =
+        Groth16::<E>::create_random_proof_with_reduction(circuit(), &mapped, &mut rng).unwrap();
+    assert!(Groth16::<E>::verify(&vk, &[a * b], &proof).unwrap());
+    drop(mapped);
+
+    // Truncated files, and files with an invalid query element, are rejected
+    // when they are mapped.
+    std::fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+    assert!(unsafe { ProvingKey::<E>::deserialize_mmap(&path) }.is_err());
+    let g1_size = E::G1Affine::zero().uncompressed_size();
+    let mut corrupted = bytes.clone();
+    let len = corrupted.len();
+    corrupted[len - g1_size..].fill(0xff);
+    std::fs::write(&path, &corrupted).unwrap();
+    assert!(unsafe { ProvingKey::<E>::deserialize_mmap(&path) }.is_err());
+    std::fs::remove_file(&path).unwrap();
+}
+
+fn test_prepared_proving_key<E>()
+where
+    E: Pairing,
+{
+    use crate::storage::{PreparedQuery, QuerySource};
+    use ark_ec::{CurveGroup, VariableBaseMSM};
+    use ark_ff::PrimeField;
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    // The tables agree with a variable-base MSM, for several window sizes.
+    for len in [0, 1, 31, 100] {
+        let bases = (0..len)
+            .map(|_| E::G1::rand(&mut rng).into_affine())
+            .collect::<Vec<_>>();
+        let scalars = (0..len)
+            .map(|_| E::ScalarField::rand(&mut rng).into_bigint())
+            .collect::<Vec<_>>();
+        let prepared = &PreparedQuery::new(&bases);
+        assert_eq!(prepared.len(), len);
+        assert_eq!(
+            prepared.msm_bigint(0, &scalars),
+            E::G1::msm_bigint(&bases, &scalars)
+        );
+        if len > 1 {
+            assert_eq!(prepared.get(1), bases[1]);
+            assert_eq!(
+                prepared.msm_bigint(1, &scalars),
+                E::G1::msm_bigint(&bases[1..], &scalars)
+            );
+        }
+    }
+
+    let (pk, vk) = Groth16::<E>::setup(
+        MyLongSillyCircuit {
+            a: None,
+            b: None,
+            num_constraints: 40,
+        },
+        &mut rng,
+    )
+    .unwrap();
+    let prepared_pk = pk.prepare();
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let circuit = || MyLongSillyCircuit {
+        a: Some(a),
+        b: Some(b),
+        num_constraints: 40,
+    };
+
+    let r = E::ScalarField::rand(&mut rng);
+    let s = E::ScalarField::rand(&mut rng);
+    assert_eq!(
+        Groth16::<E>::create_proof_with_randomness(&prepared_pk, circuit(), r, s).unwrap(),
+        Groth16::<E>::create_proof_with_randomness(&pk, circuit(), r, s).unwrap()
+    );
+
+    let proof =
+        Groth16::<E>::create_proof_with_prepared_pk(&prepared_pk, circuit(), &mut rng).unwrap();
+    assert!(Groth16::<E>::verify(&vk, &[a * b], &proof).unwrap());
+}
+
+fn test_witness_map<E>()
+where
+    E: Pairing,
+{
+    use crate::r1cs_to_qap::{evaluate_constraint, LibsnarkReduction, R1CSToQAP};
+    use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let cs = ConstraintSystem::<E::ScalarField>::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    MySillyCircuit {
+        a: Some(E::ScalarField::rand(&mut rng)),
+        b: Some(E::ScalarField::rand(&mut rng)),
+    }
+    .generate_constraints(cs.clone())
+    .unwrap();
+    cs.finalize();
+
+    let matrices = &cs.to_matrices().unwrap()[R1CS_PREDICATE_LABEL];
+    let num_inputs = cs.num_instance_variables();
+    let num_constraints = cs.num_constraints();
+    let full_assignment = {
+        let cs = cs.borrow().unwrap();
+        [
+            cs.instance_assignment().unwrap(),
+            cs.witness_assignment().unwrap(),
+        ]
+        .concat()
+    };
+
+    type D<F> = GeneralEvaluationDomain<F>;
+    let h = LibsnarkReduction::witness_map_from_matrices::<E::ScalarField, D<E::ScalarField>>(
+        matrices,
+        num_inputs,
+        num_constraints,
+        &full_assignment,
+    )
+    .unwrap();
+
+    // Compare against a direct serial evaluation of the QAP relation
+    // `a(t) * b(t) - c(t) = h(t) * z(t)` at a random point `t`.
+    let domain = D::<E::ScalarField>::new(num_constraints + num_inputs).unwrap();
+    let t = domain.sample_element_outside_domain(&mut rng);
+    let lagrange = domain.evaluate_all_lagrange_coefficients(t);
+    let (mut a_t, mut b_t, mut c_t) = (
+        E::ScalarField::from(0u64),
+        E::ScalarField::from(0u64),
+        E::ScalarField::from(0u64),
+    );
+    for (i, l_i) in lagrange.iter().enumerate().take(num_constraints) {
+        let a_i: E::ScalarField = evaluate_constraint(&matrices[0][i], &full_assignment);
+        let b_i: E::ScalarField = evaluate_constraint(&matrices[1][i], &full_assignment);
+        let c_i: E::ScalarField = evaluate_constraint(&matrices[2][i], &full_assignment);
+        a_t += *l_i * a_i;
+        b_t += *l_i * b_i;
+        c_t += *l_i * c_i;
+    }
+    for (l_i, x_i) in lagrange[num_constraints..]
+        .iter()
+        .zip(&full_assignment[..num_inputs])
+    {
+        a_t += *l_i * x_i;
+    }
+    let h_t = h
+        .iter()
+        .rev()
+        .fold(E::ScalarField::from(0u64), |acc, h_i| acc * t + h_i);
+
+    assert_eq!(
+        a_t * b_t - c_t,
+        h_t * domain.evaluate_vanishing_polynomial(t)
+    );
+
+    // `h` computed on a single thread matches `h` computed on all of them,
+    // for a circuit large enough for the parallel FFTs and evaluations to
+    // split their work.
+    #[cfg(feature = "parallel")]
+    {
+        let (a, b) = (
+            E::ScalarField::rand(&mut rng),
+            E::ScalarField::rand(&mut rng),
+        );
+        let witness_map = || {
+            let cs = ConstraintSystem::<E::ScalarField>::new_ref();
+            cs.set_optimization_goal(OptimizationGoal::Constraints);
+            MyLongSillyCircuit {
+                a: Some(a),
+                b: Some(b),
+                num_constraints: 1 << 12,
+            }
+            .generate_constraints(cs.clone())
+            .unwrap();
+            cs.finalize();
+            LibsnarkReduction::witness_map::<E::ScalarField, D<E::ScalarField>>(cs).unwrap()
+        };
+        let serial = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap()
+            .install(witness_map);
+        assert_eq!(serial, witness_map());
+    }
+}
+
+#[cfg(feature = "debug-internals")]
+fn test_debug_qap_witness<E>()
+where
+    E: Pairing,
+{
+    use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+
+    let mut rng = ark_std::rand::rngs::StdRng::seed_from_u64(test_rng().next_u64());
+
+    let a = E::ScalarField::rand(&mut rng);
+    let b = E::ScalarField::rand(&mut rng);
+    let (h, qap_a, qap_b, qap_c) = Groth16::<E>::debug_qap_witness(MySillyCircuit {
+        a: Some(a),
+        b: Some(b),
+    })
+    .unwrap();
+
+    // Check `a(t) * b(t) - c(t) = h(t) * z(t)` at a random point `t`.
+    let domain = GeneralEvaluationDomain::<E::ScalarField>::new(qap_a.len()).unwrap();
+    assert_eq!(domain.size(), qap_a.len());
+    let t = domain.sample_element_outside_domain(&mut rng);
+    let lagrange = domain.evaluate_all_lagrange_coefficients(t);
+    let eval = |evals: &[E::ScalarField]| -> E::ScalarField {
+        lagrange.iter().zip(evals).map(|(l, e)| *l * e).sum()
+    };
+    let h_t = h
+        .iter()
+        .rev()
+        .fold(E::ScalarField::from(0u64), |acc, h_i| acc * t + h_i);
+
+    assert_eq!(
+        eval(&qap_a) * eval(&qap_b) - eval(&qap_c),
+        h_t * domain.evaluate_vanishing_polynomial(t)
+    );
+}
+
+mod bls12_377 {
+    use super::{
+        test_collaborative_proof, test_commitment_round_trip, test_coset_reduction,
+        test_create_proof_from_montgomery, test_create_proof_with_progress,
+        test_create_proof_with_scratch, test_deserialize_checked, test_deserialize_strict,
+        test_generate_parameters_from_cs, test_generator_config, test_groth16_error,
+        test_groth_paper_reduction, test_miller_loop_for_verify, test_mpc_contribution,
+        test_msm_backend, test_operation_counts, test_parallel_generator, test_prepare_inputs,
+        test_prepared_proving_key, test_prepared_verifying_key_serialization,
+        test_process_vk_cached, test_process_vk_from_prepared, test_proof_field_coordinates,
+        test_proof_rerandomize, test_prove_and_verify, test_prove_and_verify_without_inputs,
+        test_prove_batch, test_prove_deterministic, test_prove_from_assignment,
+        test_prove_mismatched_circuit, test_prove_no_zk, test_prove_with_randomness,
+        test_proving_key_self_check, test_rerandomize, test_serialize_compact, test_size_in_memory,
+        test_unsupported_predicate, test_verify_against_commitment, test_verify_batch,
+        test_verify_batch_in_chunks, test_verify_constant_time, test_verify_detailed,
+        test_verify_fixed, test_verify_proof_with_group_inputs, test_verify_proof_with_inputs,
+        test_verify_proof_with_vk, test_verify_proofs_same_vk, test_verify_returning_ab_pairing,
+        test_verify_with_cached_inputs, test_verify_with_sponge, test_verifying_key_builder,
+        test_verifying_key_diff, test_verifying_key_specialize, test_verifying_key_summary,
+        test_verifying_key_with_appended_input, test_vk_fingerprint, test_witness_map,
+    };
+    use ark_bls12_377::Bls12_377;
+
+    #[test]
+    fn prove_and_verify() {
+        test_prove_and_verify::<Bls12_377>(100);
+    }
+
+    #[test]
+    fn rerandomize() {
+        test_rerandomize::<Bls12_377>();
+    }
+
+    #[test]
+    fn proof_rerandomize() {
+        test_proof_rerandomize::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_deterministic() {
+        test_prove_deterministic::<Bls12_377>();
+    }
+
+    #[test]
+    fn generate_parameters_from_cs() {
+        test_generate_parameters_from_cs::<Bls12_377>();
+    }
+
+    #[test]
+    fn groth_paper_reduction() {
+        test_groth_paper_reduction::<Bls12_377>();
+    }
+
+    #[test]
+    fn operation_counts() {
+        test_operation_counts::<Bls12_377>();
+    }
+
+    #[test]
+    fn parallel_generator() {
+        test_parallel_generator::<Bls12_377>();
+    }
+
+    #[test]
+    fn verify_proof_with_group_inputs() {
+        test_verify_proof_with_group_inputs::<Bls12_377>();
+    }
+
+    #[test]
+    fn verify_fixed() {
+        test_verify_fixed::<Bls12_377>();
+    }
+
+    #[test]
+    fn msm_backend() {
+        test_msm_backend::<Bls12_377>();
+    }
+
+    #[test]
+    fn verify_with_sponge() {
+        test_verify_with_sponge::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_and_verify_without_inputs() {
+        test_prove_and_verify_without_inputs::<Bls12_377>();
+    }
+
+    #[test]
+    fn proof_field_coordinates() {
+        test_proof_field_coordinates::<Bls12_377>();
+    }
+
+    #[test]
+    fn verify_with_cached_inputs() {
+        test_verify_with_cached_inputs::<Bls12_377>();
+    }
+
+    #[test]
+    fn miller_loop_for_verify() {
+        test_miller_loop_for_verify::<Bls12_377>();
+    }
+
+    #[test]
+    fn vk_fingerprint() {
+        test_vk_fingerprint::<Bls12_377>();
+    }
+
+    #[test]
+    fn groth16_error() {
+        test_groth16_error::<Bls12_377>();
+    }
+
+    #[test]
+    fn proving_key_self_check() {
+        test_proving_key_self_check::<Bls12_377>();
+    }
+
+    #[test]
+    fn generator_config() {
+        test_generator_config::<Bls12_377>();
+    }
+
+    #[test]
+    fn collaborative_proof() {
+        test_collaborative_proof::<Bls12_377>();
+    }
+
+    #[test]
+    fn verify_constant_time() {
+        test_verify_constant_time::<Bls12_377>();
+    }
+
+    #[test]
+    fn verify_against_commitment() {
+        test_verify_against_commitment::<Bls12_377>();
+    }
+
+    #[test]
+    fn create_proof_with_progress() {
+        test_create_proof_with_progress::<Bls12_377>();
+    }
+
+    #[test]
+    fn deserialize_strict() {
+        test_deserialize_strict::<Bls12_377>();
+    }
+
+    #[test]
+    fn coset_reduction() {
+        test_coset_reduction::<Bls12_377>();
+    }
+
+    #[test]
+    fn unsupported_predicate() {
+        test_unsupported_predicate::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_no_zk() {
+        test_prove_no_zk::<Bls12_377>();
+    }
+
+    #[test]
+    fn verifying_key_diff() {
+        test_verifying_key_diff::<Bls12_377>();
+    }
+
+    #[test]
+    fn process_vk_from_prepared() {
+        test_process_vk_from_prepared::<Bls12_377>();
+    }
+
+    #[test]
+    fn create_proof_from_montgomery() {
+        test_create_proof_from_montgomery::<Bls12_377, _, 4>();
+    }
+
+    #[test]
+    fn create_proof_with_scratch() {
+        test_create_proof_with_scratch::<Bls12_377>();
+    }
+
+    #[test]
+    fn verify_batch_in_chunks() {
+        test_verify_batch_in_chunks::<Bls12_377>();
+    }
+
+    #[test]
+    fn verifying_key_specialize() {
+        test_verifying_key_specialize::<Bls12_377>();
+    }
+
+    #[test]
+    fn verify_returning_ab_pairing() {
+        test_verify_returning_ab_pairing::<Bls12_377>();
+    }
+
+    #[test]
+    fn commitment_round_trip() {
+        test_commitment_round_trip::<Bls12_377>();
+    }
+
+    #[test]
+    fn verify_proof_with_vk() {
+        test_verify_proof_with_vk::<Bls12_377>();
+    }
+
+    #[test]
+    fn verify_proofs_same_vk() {
+        test_verify_proofs_same_vk::<Bls12_377>();
+    }
+
+    #[test]
+    fn verifying_key_summary() {
+        test_verifying_key_summary::<Bls12_377>();
+    }
+
+    #[test]
+    fn verifying_key_with_appended_input() {
+        test_verifying_key_with_appended_input::<Bls12_377>();
+    }
+
+    #[test]
+    fn process_vk_cached() {
+        test_process_vk_cached::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_mismatched_circuit() {
+        test_prove_mismatched_circuit::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_with_randomness() {
+        test_prove_with_randomness::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_from_assignment() {
+        test_prove_from_assignment::<Bls12_377>();
+    }
+
+    #[test]
+    fn prove_batch() {
+        test_prove_batch::<Bls12_377>(5);
+        // Spans more than one chunk of blinding tables.
+        test_prove_batch::<Bls12_377>(70);
+    }
+
+    #[test]
+    fn prepare_inputs() {
+        test_prepare_inputs::<Bls12_377>();
+    }
+
+    #[test]
+    fn verify_batch() {
+        test_verify_batch::<Bls12_377>(8);
+    }
+
+    #[test]
+    fn verify_detailed() {
+        use ark_bls12_377::{Fq, G1Affine};
+
+        // A point on the curve but outside the prime-order subgroup.
+        let off_subgroup_g1 = (0u64..)
+            .filter_map(|x| G1Affine::get_point_from_x_unchecked(Fq::from(x), false))
+            .find(|p| !p.is_in_correct_subgroup_assuming_on_curve())
+            .unwrap();
+        test_verify_detailed::<Bls12_377>(off_subgroup_g1);
+    }
+
+    #[test]
+    fn verifying_key_builder() {
+        use ark_bls12_377::{Fq, G1Affine};
+
+        // A point on the curve but outside the prime-order subgroup.
+        let off_subgroup_g1 = (0u64..)
+            .filter_map(|x| G1Affine::get_point_from_x_unchecked(Fq::from(x), false))
+            .find(|p| !p.is_in_correct_subgroup_assuming_on_curve())
+            .unwrap();
+        test_verifying_key_builder::<Bls12_377>(off_subgroup_g1);
+    }
+
+    #[test]
+    fn deserialize_checked() {
+        use ark_bls12_377::{Fq, Fq2, G2Affine};
+        use ark_ff::Zero;
+
+        // A point on the curve but outside the prime-order subgroup.
+        let off_subgroup_g2 = (0u64..)
+            .filter_map(|x| {
+                G2Affine::get_point_from_x_unchecked(Fq2::new(Fq::from(x), Fq::zero()), false)
+            })
+            .find(|p| !p.is_in_correct_subgroup_assuming_on_curve())
+            .unwrap();
+        test_deserialize_checked::<Bls12_377>(off_subgroup_g2);
+    }
+
+    #[test]
+    fn prepared_proving_key() {
+        test_prepared_proving_key::<Bls12_377>();
+    }
+
+    #[test]
+    fn prepared_verifying_key_serialization() {
+        test_prepared_verifying_key_serialization::<Bls12_377>();
+    }
+
+    #[test]
+    fn verify_proof_with_inputs() {
+        test_verify_proof_with_inputs::<Bls12_377>();
+    }
+
+    #[test]
+    fn mpc_contribution() {
+        test_mpc_contribution::<Bls12_377>();
+    }
+
+    #[test]
+    fn serialize_compact() {
+        test_serialize_compact::<Bls12_377>();
+    }
+
+    #[test]
+    fn size_in_memory() {
+        test_size_in_memory::<Bls12_377>();
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn mmap_proving_key() {
+        super::test_mmap_proving_key::<Bls12_377>();
+    }
+
+    #[test]
+    fn witness_map() {
+        test_witness_map::<Bls12_377>();
+    }
+
+    #[cfg(feature = "debug-internals")]
+    #[test]
+    fn debug_qap_witness() {
+        super::test_debug_qap_witness::<Bls12_377>();
+    }
+}
+
+mod bls12_381 {
+    use super::test_mixed_radix_reduction;
+    use ark_bls12_381::Bls12_381;
+
+    #[test]
+    fn mixed_radix_reduction() {
+        test_mixed_radix_reduction::<Bls12_381>();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        super::test_serde_round_trip::<Bls12_381>();
+    }
+
+    #[cfg(feature = "seeded-setup")]
+    #[test]
+    fn generate_parameters_with_seed() {
+        super::test_generate_parameters_with_seed::<Bls12_381>();
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn zeroize_toxic_waste() {
+        super::test_zeroize_toxic_waste::<Bls12_381>();
+    }
+
+    #[cfg(feature = "bls12-381")]
+    #[test]
+    fn dyn_verify() {
+        super::test_dyn_verify::<Bls12_381>();
+    }
+
+    #[cfg(feature = "bls12-381")]
+    #[test]
+    fn gnark_encoding() {
+        use crate::{interop::gnark, Groth16, Proof};
+        use ark_bls12_381::{Fr, G1Affine, G2Affine};
+        use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+        use ark_ec::{AffineRepr, CurveGroup};
+        use ark_std::{test_rng, vec::Vec, UniformRand};
+
+        fn hex(s: &str) -> Vec<u8> {
+            (0..s.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+                .collect()
+        }
+
+        // The Zcash encodings of the generators, which gnark also uses on
+        // BLS12-381: both have the smaller `y`, so they are flagged with
+        // `0b100`, and their negations with `0b101`.
+        let g1 = hex(
+            "97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac58\
+             6c55e83ff97a1aeffb3af00adb22c6bb",
+        );
+        let g2 = hex(
+            "93e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049\
+             334cf11213945d57e5ac7d055d042b7e024aa2b2f08f0a91260805272dc51051\
+             c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8",
+        );
+        let mut neg_g1 = g1.clone();
+        neg_g1[0] |= 0x20;
+        let mut infinity = vec![0u8; 48];
+        infinity[0] = 0xc0;
+
+        let proof = Proof::<Bls12_381> {
+            a: G1Affine::generator(),
+            b: G2Affine::generator(),
+            c: (-G1Affine::generator().into_group()).into_affine(),
+        };
+        let bytes = gnark::proof_to_bytes(&proof);
+        assert_eq!(bytes.len(), 48 + 96 + 48 + 4 + 48);
+        assert_eq!(bytes[..48], g1[..]);
+        assert_eq!(bytes[48..144], g2[..]);
+        assert_eq!(bytes[144..192], neg_g1[..]);
+        assert_eq!(bytes[196..], infinity[..]);
+        assert_eq!(gnark::proof_from_bytes::<Bls12_381>(&bytes).unwrap(), proof);
+
+        // BN254's flag for the larger root, `0b11` in the top two bits, reads
+        // as the infinity flag `0b110` here, which is rejected for a nonzero
+        // `x`.
+        let mut bn254_flags = bytes.clone();
+        bn254_flags[144] = (bn254_flags[144] & 0x1f) | 0xc0;
+        assert!(gnark::proof_from_bytes::<Bls12_381>(&bn254_flags).is_err());
+
+        let mut rng = test_rng();
+        let (pk, vk) =
+            Groth16::<Bls12_381>::setup(super::MySillyCircuit { a: None, b: None }, &mut rng)
+                .unwrap();
+        let a = Fr::rand(&mut rng);
+        let b = Fr::rand(&mut rng);
+        let proof = Groth16::<Bls12_381>::prove(
+            &pk,
+            super::MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &mut rng,
+        )
+        .unwrap();
+
+        let proof2 = gnark::proof_from_bytes::<Bls12_381>(&gnark::proof_to_bytes(&proof)).unwrap();
+        let vk2 = gnark::vk_from_bytes::<Bls12_381>(&gnark::vk_to_bytes(&pk)).unwrap();
+        assert_eq!(proof, proof2);
+        assert_eq!(vk, vk2);
+        assert!(Groth16::<Bls12_381>::verify(&vk2, &[a * b], &proof2).unwrap());
+    }
+}
+
+mod bw6_761 {
+    use super::{test_prove_and_verify, test_rerandomize};
+
+    use ark_bw6_761::BW6_761;
+
+    #[test]
+    fn prove_and_verify() {
+        test_prove_and_verify::<BW6_761>(1);
+    }
+
+    #[test]
+    fn rerandomize() {
+        test_rerandomize::<BW6_761>();
+    }
+}
+
+mod bn_254 {
+    use super::test_prove_and_verify;
+    use ark_bn254::Bn254;
+
+    #[test]
+    fn prove_and_verify() {
+        test_prove_and_verify::<Bn254>(100);
+    }
+
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn dyn_verify() {
+        super::test_dyn_verify::<Bn254>();
+    }
+
+    #[cfg(all(feature = "bn254", feature = "bls12-381"))]
+    #[test]
+    fn dyn_verify_curve_mismatch() {
+        use crate::{
+            dynamic::{dyn_verify, DynInputs, DynProof, DynVerifyingKey},
+            Groth16, Groth16Error,
+        };
+        use ark_bls12_381::Bls12_381;
+        use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+        use ark_std::test_rng;
+
+        let mut rng = test_rng();
+        let circuit = || super::MySillyCircuit { a: None, b: None };
+        let (_, vk) = Groth16::<Bn254>::setup(circuit(), &mut rng).unwrap();
+        let (pk, _) = Groth16::<Bls12_381>::setup(circuit(), &mut rng).unwrap();
+        let one = ark_bls12_381::Fr::from(1u64);
+        let proof = Groth16::<Bls12_381>::prove(
+            &pk,
+            super::MySillyCircuit {
+                a: Some(one),
+                b: Some(one),
+            },
+            &mut rng,
+        )
+        .unwrap();
+
+        assert_eq!(
+            dyn_verify(
+                &DynVerifyingKey::from(vk),
+                &DynInputs::from(vec![one]),
+                &DynProof::from(proof)
+            ),
+            Err(Groth16Error::CurveMismatch)
+        );
+    }
+
+    #[test]
+    fn ethereum_encoding() {
+        use crate::{Groth16, Proof, VerifyingKey};
+        use ark_bn254::{Fq, G1Affine, G2Affine};
+        use ark_crypto_primitives::snark::CircuitSpecificSetupSNARK;
+        use ark_ec::{AffineRepr, CurveGroup};
+        use ark_ff::{BigInteger, PrimeField};
+        use ark_std::{test_rng, vec::Vec};
+        use core::str::FromStr;
+
+        fn word(decimal: &str) -> Vec<u8> {
+            Fq::from_str(decimal).unwrap().into_bigint().to_bytes_be()
+        }
+
+        // The generators as written in Solidity verifiers, e.g.
+        // `Pairing.P2()`: G2 coordinates are given as `[c1, c0]`.
+        let g1 = G1Affine::generator();
+        let g2 = G2Affine::generator();
+        let expected_g2 = [
+            word("11559732032986387107991004021392285783925812861821192530917403151452391805634"),
+            word("10857046999023057135944570762232829481370756359578518086990519993285655852781"),
+            word("4082367875863433681332203403145435568316851327593401208105741076214120093531"),
+            word("8495653923123431417604973247489272438418190587263600148770280649306958101930"),
+        ]
+        .concat();
+        let expected_neg_g1 = [
+            word("1"),
+            word("21888242871839275222246405745257275088696311157297823662689037894645226208581"),
+        ]
+        .concat();
+
+        let proof = Proof::<Bn254> {
+            a: g1,
+            b: g2,
+            c: (-g1.into_group()).into_affine(),
+        };
+        let bytes = proof.to_ethereum_bytes();
+        assert_eq!(bytes.len(), 256);
+        assert_eq!(&bytes[..64], &[word("1"), word("2")].concat()[..]);
+        assert_eq!(&bytes[64..192], &expected_g2[..]);
+        assert_eq!(&bytes[192..], &expected_neg_g1[..]);
+        assert_eq!(Proof::<Bn254>::from_ethereum_bytes(&bytes).unwrap(), proof);
+
+        // The point at infinity is encoded as zeros.
+        let zero_proof = Proof::<Bn254>::default();
+        let bytes = zero_proof.to_ethereum_bytes();
+        assert!(bytes.iter().all(|b| *b == 0));
+        assert_eq!(
+            Proof::<Bn254>::from_ethereum_bytes(&bytes).unwrap(),
+            zero_proof
+        );
+
+        // Swapping the G2 coordinate order yields a point off the curve.
+        let mut swapped = proof.to_ethereum_bytes();
+        swapped[64..96].copy_from_slice(&expected_g2[32..64]);
+        swapped[96..128].copy_from_slice(&expected_g2[..32]);
+        assert!(Proof::<Bn254>::from_ethereum_bytes(&swapped).is_err());
+        assert!(Proof::<Bn254>::from_ethereum_bytes(&bytes[1..]).is_err());
+
+        let (_, vk) =
+            Groth16::<Bn254>::setup(super::MySillyCircuit { a: None, b: None }, &mut test_rng())
+                .unwrap();
+        let calldata = vk.to_ethereum_calldata();
+        assert_eq!(calldata.len(), 64 + 3 * 128 + 64 * vk.gamma_abc_g1.len());
+        assert_eq!(
+            VerifyingKey::<Bn254>::from_ethereum_calldata(&calldata).unwrap(),
+            vk
+        );
+        assert!(VerifyingKey::<Bn254>::from_ethereum_calldata(&calldata[..448]).is_err());
+    }
+
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn gnark_encoding() {
+        use crate::{interop::gnark, Groth16, Proof};
+        use ark_bn254::{Fr, G1Affine, G2Affine};
+        use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+        use ark_ec::{AffineRepr, CurveGroup};
+        use ark_std::{test_rng, UniformRand};
+
+        // The generator `(1, 2)` has the smaller `y`, so it is flagged with
+        // `0b10`; its negation `(1, p - 2)` is flagged with `0b11`.
+        let mut g1 = [0u8; 32];
+        g1[0] = 0x80;
+        g1[31] = 1;
+        let mut neg_g1 = g1;
+        neg_g1[0] = 0xc0;
+
+        let proof = Proof::<Bn254> {
+            a: G1Affine::generator(),
+            b: G2Affine::generator(),
+            c: (-G1Affine::generator().into_group()).into_affine(),
+        };
+        let bytes = gnark::proof_to_bytes(&proof);
+        assert_eq!(bytes.len(), 32 + 64 + 32 + 4 + 32);
+        assert_eq!(bytes[..32], g1);
+        assert_eq!(bytes[96..128], neg_g1);
+        assert_eq!(gnark::proof_from_bytes::<Bn254>(&bytes).unwrap(), proof);
+        // Older gnark versions do not write the commitment fields.
+        assert_eq!(
+            gnark::proof_from_bytes::<Bn254>(&bytes[..128]).unwrap(),
+            proof
+        );
+
+        // Trailing data and non-empty commitments are rejected.
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(gnark::proof_from_bytes::<Bn254>(&trailing).is_err());
+        let mut committed = bytes.clone();
+        committed[131] = 1;
+        assert!(gnark::proof_from_bytes::<Bn254>(&committed).is_err());
+        let mut wrong_sign = bytes.clone();
+        wrong_sign[0] ^= 0x40;
+        assert_ne!(
+            gnark::proof_from_bytes::<Bn254>(&wrong_sign).ok(),
+            Some(proof)
+        );
+
+        // Round-trip a real proof and verifying key.
+        let mut rng = test_rng();
+        let (pk, vk) =
+            Groth16::<Bn254>::setup(super::MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+        let a = Fr::rand(&mut rng);
+        let b = Fr::rand(&mut rng);
+        let proof = Groth16::<Bn254>::prove(
+            &pk,
+            super::MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &mut rng,
+        )
+        .unwrap();
+
+        let proof2 = gnark::proof_from_bytes::<Bn254>(&gnark::proof_to_bytes(&proof)).unwrap();
+        let vk_bytes = gnark::vk_to_bytes(&pk);
+        let vk2 = gnark::vk_from_bytes::<Bn254>(&vk_bytes).unwrap();
+        assert_eq!(proof, proof2);
+        assert_eq!(vk, vk2);
+        assert!(Groth16::<Bn254>::verify(&vk2, &[a * b], &proof2).unwrap());
+        assert!(gnark::vk_from_bytes::<Bn254>(&vk_bytes[..vk_bytes.len() - 1]).is_err());
+    }
+
+    #[cfg(feature = "snarkjs")]
+    #[test]
+    fn snarkjs_json() {
+        use crate::{interop::snarkjs, Groth16, Proof};
+        use ark_bn254::{Fr, G1Affine, G2Affine};
+        use ark_crypto_primitives::snark::{CircuitSpecificSetupSNARK, SNARK};
+        use ark_ec::{AffineRepr, CurveGroup};
+        use ark_std::{test_rng, UniformRand};
+
+        // A `proof.json` in the layout snarkjs emits, holding the generators.
+        const PROOF_JSON: &str = r#"{
+ "pi_a": [
+  "1",
+  "2",
+  "1"
+ ],
+ "pi_b": [
+  [
+   "10857046999023057135944570762232829481370756359578518086990519993285655852781",
+   "11559732032986387107991004021392285783925812861821192530917403151452391805634"
+  ],
+  [
+   "8495653923123431417604973247489272438418190587263600148770280649306958101930",
+   "4082367875863433681332203403145435568316851327593401208105741076214120093531"
+  ],
+  [
+   "1",
+   "0"
+  ]
+ ],
+ "pi_c": [
+  "1",
+  "21888242871839275222246405745257275088696311157297823662689037894645226208581",
+  "1"
+ ],
+ "protocol": "groth16",
+ "curve": "bn128"
+}"#;
+        let expected = Proof::<Bn254> {
+            a: G1Affine::generator(),
+            b: G2Affine::generator(),
+            c: (-G1Affine::generator().into_group()).into_affine(),
+        };
+        let proof = snarkjs::proof_from_json::<Bn254>(PROOF_JSON).unwrap();
+        assert_eq!(proof, expected);
+        let json = snarkjs::proof_to_json(&proof, "bn128");
+        assert_eq!(snarkjs::proof_from_json::<Bn254>(&json).unwrap(), proof);
+
+        // Swapping the `G2` coordinates must be rejected.
+        let mut swapped: serde_json::Value = serde_json::from_str(PROOF_JSON).unwrap();
+        swapped["pi_b"][0].as_array_mut().unwrap().swap(0, 1);
+        assert!(snarkjs::proof_from_json::<Bn254>(&swapped.to_string()).is_err());
+        let plonk = PROOF_JSON.replace("groth16", "plonk");
+        assert!(snarkjs::proof_from_json::<Bn254>(&plonk).is_err());
+
+        // Coordinates must be canonical: `p + 1` and `01` both reduce to `1`,
+        // but are rejected.
+        let p_plus_one =
+            "21888242871839275222246405745257275088696311157297823662689037894645226208584";
+        for x in [p_plus_one, "01", "+1", ""] {
+            let non_canonical = PROOF_JSON.replacen(r#""1","#, &ark_std::format!(r#""{}","#, x), 1);
+            assert_ne!(non_canonical, PROOF_JSON);
+            assert!(snarkjs::proof_from_json::<Bn254>(&non_canonical).is_err());
+        }
+
+        // Round-trip a real proof and verifying key.
+        let mut rng = test_rng();
+        let (pk, vk) =
+            Groth16::<Bn254>::setup(super::MySillyCircuit { a: None, b: None }, &mut rng).unwrap();
+        let a = Fr::rand(&mut rng);
+        let b = Fr::rand(&mut rng);
+        let proof = Groth16::<Bn254>::prove(
+            &pk,
+            super::MySillyCircuit {
+                a: Some(a),
+                b: Some(b),
+            },
+            &mut rng,
+        )
+        .unwrap();
+
+        let proof2 =
+            snarkjs::proof_from_json::<Bn254>(&snarkjs::proof_to_json(&proof, "bn128")).unwrap();
+        let vk2 = snarkjs::vk_from_json::<Bn254>(&snarkjs::vk_to_json(&vk, "bn128")).unwrap();
+        assert_eq!(proof, proof2);
+        assert_eq!(vk, vk2);
+        assert!(Groth16::<Bn254>::verify(&vk2, &[a * b], &proof2).unwrap());
     }
 }
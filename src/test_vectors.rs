@@ -0,0 +1,186 @@
+//! The bytes are the compressed [`CanonicalSerialize`] encodings for BN254,
+//! over the standard BN254 generators, of the keys of [`TestVectorCircuit`]
+//! and of a [`PROOF`] that `3 * 5 = 15`.
+//!
+//! The tests check that the vectors round-trip through serialization, that
+//! [`PROOF`] verifies, and that a fresh proof from [`PROVING_KEY`] verifies as
+//! well. The keys are not regenerated, so the tests do not pin the output of
+//! the generator.
+//!
+//! [`CanonicalSerialize`]: ark_serialize::CanonicalSerialize
+
+use ark_ff::PrimeField;
+use ark_relations::{
+    gr1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+    lc,
+};
+
+/// The circuit the test vectors were generated for: a single constraint
+/// `a * b = c` with witnesses `a`, `b` and the public input `c`.
+#[derive(Clone, Copy, Debug)]
+pub struct TestVectorCircuit<F: PrimeField> {
+    /// The first factor.
+    pub a: Option<F>,
+    /// The second factor.
+    pub b: Option<F>,
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for TestVectorCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let c = cs.new_input_variable(|| {
+            let a = self.a.ok_or(SynthesisError::AssignmentMissing)?;
+            let b = self.b.ok_or(SynthesisError::AssignmentMissing)?;
+            Ok(a * b)
+        })?;
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+        cs.enforce_r1cs_constraint(|| lc!() + a, || lc!() + b, || lc!() + c)
+    }
+}
+
+/// The public input of [`PROOF`].
+pub const PUBLIC_INPUT: u64 = 15;
+
+/// The compressed serialization of the verifying key of [`TestVectorCircuit`].
+pub const VERIFYING_KEY: &[u8] = &[
+    0xef, 0x49, 0x3d, 0xfc, 0x80, 0x1f, 0xb7, 0xa8, 0x2c, 0xc1, 0x2a, 0xe9, 0x7f, 0x47, 0x82, 0x24,
+    0xb8, 0xcd, 0xee, 0x51, 0x86, 0x80, 0xa8, 0x9d, 0x18, 0x09, 0xb0, 0x37, 0x55, 0x70, 0x14, 0xaa,
+    0xf7, 0x05, 0xa9, 0x72, 0x15, 0x15, 0xcd, 0x64, 0x27, 0xfb, 0xe5, 0x1d, 0xb9, 0x44, 0x62, 0x31,
+    0x24, 0xd1, 0xeb, 0xfa, 0x35, 0x96, 0x04, 0x75, 0xdc, 0xa9, 0xcc, 0xa7, 0xf3, 0x66, 0xad, 0x23,
+    0xd1, 0x97, 0x90, 0x52, 0xae, 0x0a, 0x83, 0xab, 0x2e, 0x6d, 0xbb, 0x27, 0xd4, 0x37, 0x3c, 0xee,
+    0x4c, 0x09, 0xcc, 0x8a, 0x58, 0x39, 0x11, 0xf5, 0x56, 0x8c, 0x8a, 0x69, 0xf0, 0xda, 0x9e, 0x80,
+    0x98, 0x80, 0x1f, 0xb4, 0xc1, 0x99, 0xe2, 0x16, 0xff, 0x49, 0x8f, 0x8d, 0x55, 0xb5, 0x02, 0x80,
+    0xb7, 0xcb, 0xdc, 0x9d, 0x73, 0x29, 0x7b, 0x67, 0x19, 0x31, 0x1d, 0xb6, 0x67, 0xa3, 0x51, 0x0c,
+    0x2c, 0x9c, 0x49, 0xae, 0x4e, 0x16, 0xa4, 0xf1, 0xbf, 0x33, 0xb9, 0x59, 0x32, 0x7a, 0x1d, 0x70,
+    0xbc, 0x15, 0xb2, 0xb5, 0x4b, 0x50, 0x8b, 0xed, 0x47, 0x3b, 0xff, 0xa5, 0xbb, 0x71, 0x70, 0x22,
+    0x4d, 0x40, 0x75, 0xbf, 0xf6, 0x89, 0x3d, 0xc7, 0xc4, 0x40, 0xb0, 0x19, 0x9c, 0x7a, 0x10, 0x73,
+    0x0c, 0xcb, 0x15, 0x2a, 0x13, 0xad, 0xa4, 0x0b, 0x3d, 0xc7, 0x3b, 0xb7, 0x9a, 0xba, 0xcb, 0x15,
+    0x4e, 0xc5, 0xe9, 0xa5, 0x4f, 0xca, 0x6b, 0xc0, 0x48, 0x24, 0x76, 0x03, 0xf0, 0x6f, 0xe6, 0xc0,
+    0x66, 0x14, 0x31, 0x41, 0x48, 0x37, 0x74, 0x41, 0x59, 0xc6, 0x18, 0x5f, 0xe3, 0x7b, 0x40, 0xa5,
+    0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xd3, 0x5f, 0x9a, 0x7f, 0xd4, 0x71, 0xed, 0xbc,
+    0x9e, 0x48, 0xf2, 0xf4, 0x59, 0x8f, 0xb6, 0xb3, 0xd8, 0xa7, 0xde, 0xcc, 0x68, 0x1c, 0x3c, 0x03,
+    0xfb, 0xb3, 0x5f, 0xf9, 0x9f, 0x53, 0xdc, 0x21, 0x8e, 0x50, 0x07, 0x13, 0x5f, 0x32, 0x23, 0xad,
+    0x93, 0x5b, 0xbf, 0x38, 0xe2, 0xac, 0xad, 0x76, 0xd3, 0x5b, 0x08, 0x23, 0xfe, 0x12, 0x65, 0x53,
+    0x00, 0x15, 0x4b, 0x49, 0x6c, 0x8f, 0xdf, 0x23,
+];
+
+/// The compressed serialization of the proving key of [`TestVectorCircuit`],
+/// which contains [`VERIFYING_KEY`].
+pub const PROVING_KEY: &[u8] = &[
+    0xef, 0x49, 0x3d, 0xfc, 0x80, 0x1f, 0xb7, 0xa8, 0x2c, 0xc1, 0x2a, 0xe9, 0x7f, 0x47, 0x82, 0x24,
+    0xb8, 0xcd, 0xee, 0x51, 0x86, 0x80, 0xa8, 0x9d, 0x18, 0x09, 0xb0, 0x37, 0x55, 0x70, 0x14, 0xaa,
+    0xf7, 0x05, 0xa9, 0x72, 0x15, 0x15, 0xcd, 0x64, 0x27, 0xfb, 0xe5, 0x1d, 0xb9, 0x44, 0x62, 0x31,
+    0x24, 0xd1, 0xeb, 0xfa, 0x35, 0x96, 0x04, 0x75, 0xdc, 0xa9, 0xcc, 0xa7, 0xf3, 0x66, 0xad, 0x23,
+    0xd1, 0x97, 0x90, 0x52, 0xae, 0x0a, 0x83, 0xab, 0x2e, 0x6d, 0xbb, 0x27, 0xd4, 0x37, 0x3c, 0xee,
+    0x4c, 0x09, 0xcc, 0x8a, 0x58, 0x39, 0x11, 0xf5, 0x56, 0x8c, 0x8a, 0x69, 0xf0, 0xda, 0x9e, 0x80,
+    0x98, 0x80, 0x1f, 0xb4, 0xc1, 0x99, 0xe2, 0x16, 0xff, 0x49, 0x8f, 0x8d, 0x55, 0xb5, 0x02, 0x80,
+    0xb7, 0xcb, 0xdc, 0x9d, 0x73, 0x29, 0x7b, 0x67, 0x19, 0x31, 0x1d, 0xb6, 0x67, 0xa3, 0x51, 0x0c,
+    0x2c, 0x9c, 0x49, 0xae, 0x4e, 0x16, 0xa4, 0xf1, 0xbf, 0x33, 0xb9, 0x59, 0x32, 0x7a, 0x1d, 0x70,
+    0xbc, 0x15, 0xb2, 0xb5, 0x4b, 0x50, 0x8b, 0xed, 0x47, 0x3b, 0xff, 0xa5, 0xbb, 0x71, 0x70, 0x22,
+    0x4d, 0x40, 0x75, 0xbf, 0xf6, 0x89, 0x3d, 0xc7, 0xc4, 0x40, 0xb0, 0x19, 0x9c, 0x7a, 0x10, 0x73,
+    0x0c, 0xcb, 0x15, 0x2a, 0x13, 0xad, 0xa4, 0x0b, 0x3d, 0xc7, 0x3b, 0xb7, 0x9a, 0xba, 0xcb, 0x15,
+    0x4e, 0xc5, 0xe9, 0xa5, 0x4f, 0xca, 0x6b, 0xc0, 0x48, 0x24, 0x76, 0x03, 0xf0, 0x6f, 0xe6, 0xc0,
+    0x66, 0x14, 0x31, 0x41, 0x48, 0x37, 0x74, 0x41, 0x59, 0xc6, 0x18, 0x5f, 0xe3, 0x7b, 0x40, 0xa5,
+    0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xd3, 0x5f, 0x9a, 0x7f, 0xd4, 0x71, 0xed, 0xbc,
+    0x9e, 0x48, 0xf2, 0xf4, 0x59, 0x8f, 0xb6, 0xb3, 0xd8, 0xa7, 0xde, 0xcc, 0x68, 0x1c, 0x3c, 0x03,
+    0xfb, 0xb3, 0x5f, 0xf9, 0x9f, 0x53, 0xdc, 0x21, 0x8e, 0x50, 0x07, 0x13, 0x5f, 0x32, 0x23, 0xad,
+    0x93, 0x5b, 0xbf, 0x38, 0xe2, 0xac, 0xad, 0x76, 0xd3, 0x5b, 0x08, 0x23, 0xfe, 0x12, 0x65, 0x53,
+    0x00, 0x15, 0x4b, 0x49, 0x6c, 0x8f, 0xdf, 0x23, 0x3f, 0x2f, 0xf5, 0x30, 0xe0, 0x29, 0xb5, 0x08,
+    0xe4, 0x69, 0x14, 0xb4, 0xfd, 0xc3, 0xb8, 0xf8, 0x17, 0x9f, 0x67, 0x65, 0x34, 0x09, 0x56, 0x0f,
+    0xf1, 0xa4, 0xa7, 0xc8, 0x8c, 0x6f, 0xe8, 0x85, 0x8d, 0xc2, 0xed, 0x71, 0x4c, 0x4f, 0xd4, 0x57,
+    0xe8, 0x3a, 0xd6, 0xed, 0x01, 0xc4, 0x3b, 0x74, 0x66, 0x98, 0xd5, 0xbe, 0x22, 0xbc, 0xb2, 0xf7,
+    0x8e, 0x15, 0x36, 0xa1, 0xe6, 0x4d, 0x51, 0x15, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x38, 0xab, 0x3c, 0x7c, 0xba, 0x0b, 0xf6, 0xdc, 0x2d, 0xa6, 0xb7, 0x91, 0x99, 0x97, 0x8c, 0xca,
+    0xe8, 0xaf, 0x8d, 0x33, 0x8c, 0x38, 0xd8, 0x56, 0xf6, 0xe2, 0x59, 0x46, 0x8b, 0x08, 0x94, 0x12,
+    0xab, 0x13, 0x7b, 0x57, 0x6a, 0xc0, 0x09, 0xbf, 0x62, 0x8a, 0x1e, 0x15, 0xdc, 0x5c, 0x3b, 0xbe,
+    0xa3, 0xd6, 0x7f, 0xf9, 0x28, 0x71, 0xb5, 0x30, 0xf1, 0xdf, 0xda, 0xb7, 0x9c, 0x67, 0x5a, 0x95,
+    0x63, 0xe2, 0xad, 0x9e, 0xae, 0x8e, 0x5b, 0x63, 0xbf, 0x3c, 0xbe, 0x8e, 0xa0, 0xfe, 0xa9, 0x57,
+    0xb1, 0x06, 0x01, 0xfa, 0x1b, 0x91, 0x37, 0x71, 0xa0, 0x6b, 0x5d, 0x3a, 0x32, 0x47, 0xb3, 0x86,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+    0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40, 0x63, 0xe2, 0xad, 0x9e, 0xae, 0x8e, 0x5b, 0x63,
+    0xbf, 0x3c, 0xbe, 0x8e, 0xa0, 0xfe, 0xa9, 0x57, 0xb1, 0x06, 0x01, 0xfa, 0x1b, 0x91, 0x37, 0x71,
+    0xa0, 0x6b, 0x5d, 0x3a, 0x32, 0x47, 0xb3, 0x86, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x40,
+    0xce, 0xd3, 0x46, 0x56, 0xb0, 0x7f, 0x72, 0xb1, 0xb2, 0x7a, 0xa9, 0xa7, 0x8a, 0x9c, 0x42, 0x77,
+    0x7c, 0x40, 0xeb, 0x82, 0x37, 0x31, 0xac, 0xb7, 0x45, 0x26, 0x9c, 0x19, 0x75, 0xf0, 0xc1, 0x17,
+    0x9e, 0xc4, 0xf3, 0x9c, 0xbc, 0x0a, 0xc0, 0x4b, 0xa1, 0x0f, 0x67, 0x60, 0x20, 0x7f, 0x74, 0x25,
+    0x95, 0x1e, 0x16, 0x17, 0x8d, 0xc8, 0x96, 0x33, 0xf1, 0x14, 0xad, 0x70, 0x64, 0x6a, 0x44, 0xa3,
+    0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x74, 0x7d, 0x36, 0xc4, 0x7a, 0xd7, 0x7b, 0x61,
+    0xac, 0x9b, 0xc0, 0xce, 0x60, 0x43, 0xee, 0x83, 0x80, 0x7d, 0x3c, 0xf5, 0xec, 0xb6, 0x83, 0x54,
+    0x9f, 0xc5, 0xee, 0xb5, 0x2f, 0xdf, 0xfa, 0x2d, 0x70, 0x8e, 0x71, 0xac, 0x88, 0xf4, 0x87, 0xc4,
+    0xb6, 0xa5, 0x2f, 0xb6, 0xe0, 0x40, 0xcb, 0x62, 0xa2, 0x5c, 0xb1, 0x12, 0xab, 0x82, 0x97, 0x44,
+    0x52, 0x05, 0xcd, 0xf5, 0x1c, 0xe9, 0x30, 0xa6, 0xc4, 0xf7, 0xd1, 0xce, 0x50, 0x2b, 0xbf, 0x7e,
+    0x3f, 0xa7, 0x6a, 0xbc, 0x65, 0xa8, 0x75, 0x2c, 0xaf, 0xe5, 0x72, 0xcb, 0xe8, 0x3f, 0x4d, 0x8d,
+    0x7f, 0x02, 0x0b, 0xf7, 0xaf, 0x37, 0x64, 0x81, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x9e, 0x1f, 0x8f, 0x74, 0xf8, 0xb1, 0x8e, 0x72, 0x73, 0xbf, 0x4a, 0x3e, 0x82, 0xaa, 0x10, 0xb0,
+    0x9b, 0x2e, 0xc5, 0xac, 0x7d, 0xe4, 0x92, 0xfc, 0x33, 0x91, 0xd1, 0xf9, 0xc5, 0x63, 0x97, 0x12,
+    0xf3, 0xbb, 0xf1, 0x0f, 0xb8, 0x50, 0x9c, 0x2e, 0xe0, 0xe4, 0x17, 0xfc, 0x3c, 0x36, 0x82, 0xe4,
+    0x86, 0xac, 0xc2, 0xed, 0x70, 0x36, 0x68, 0xa1, 0xd8, 0x34, 0xd2, 0xd1, 0x59, 0x58, 0x7f, 0x8f,
+];
+
+/// The compressed serialization of a proof for the instance [`PUBLIC_INPUT`].
+pub const PROOF: &[u8] = &[
+    0x2c, 0x72, 0xd2, 0xf2, 0xa1, 0xa8, 0xcb, 0xf5, 0xc5, 0xba, 0x6e, 0x83, 0x42, 0x49, 0xfa, 0xfc,
+    0x45, 0xfa, 0x6f, 0x51, 0xfb, 0xc1, 0xde, 0xef, 0xaf, 0x80, 0xd8, 0x27, 0x3e, 0x22, 0x0e, 0x25,
+    0x0b, 0xb9, 0x9d, 0x3d, 0x9f, 0x8b, 0x72, 0xcc, 0x51, 0x54, 0x38, 0x5a, 0x6c, 0x9b, 0x1e, 0x38,
+    0xaf, 0x71, 0x1e, 0x0e, 0xe1, 0xd2, 0xd9, 0xe3, 0x13, 0xb4, 0x1a, 0xe7, 0xc2, 0x69, 0x77, 0x19,
+    0x68, 0x9e, 0xaf, 0x22, 0x7c, 0x36, 0xd1, 0x78, 0xe5, 0xb8, 0xd5, 0xd7, 0xc4, 0x1e, 0x3d, 0xed,
+    0x94, 0x47, 0x19, 0x83, 0xfa, 0x5c, 0xf1, 0xcf, 0x0c, 0x55, 0xe8, 0x79, 0x4d, 0x0d, 0xdb, 0x05,
+    0x3b, 0x1c, 0x96, 0x51, 0x59, 0x03, 0xbd, 0x3e, 0xbd, 0xe9, 0x47, 0xdf, 0xc2, 0x31, 0x30, 0x2d,
+    0xc8, 0x16, 0xb9, 0x43, 0xbc, 0x35, 0x84, 0x68, 0x18, 0xbc, 0x9b, 0x7a, 0xfc, 0x23, 0xdc, 0x0e,
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Groth16, Proof, ProvingKey, VerifyingKey};
+    use ark_bn254::{Bn254, Fr};
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use ark_snark::SNARK;
+    use ark_std::{test_rng, vec::Vec};
+
+    fn round_trip<T: CanonicalSerialize + CanonicalDeserialize>(bytes: &[u8]) -> T {
+        let value = T::deserialize_compressed(bytes).unwrap();
+        let mut serialized = Vec::new();
+        value.serialize_compressed(&mut serialized).unwrap();
+        assert_eq!(serialized, bytes);
+        value
+    }
+
+    #[test]
+    fn test_vectors_verify() {
+        let vk: VerifyingKey<Bn254> = round_trip(VERIFYING_KEY);
+        let pk: ProvingKey<Bn254> = round_trip(PROVING_KEY);
+        let proof: Proof<Bn254> = round_trip(PROOF);
+        assert_eq!(pk.vk, vk);
+
+        let public_input = [Fr::from(PUBLIC_INPUT)];
+        assert!(Groth16::<Bn254>::verify(&vk, &public_input, &proof).unwrap());
+        assert!(!Groth16::<Bn254>::verify(&vk, &[Fr::from(PUBLIC_INPUT + 1)], &proof).unwrap());
+
+        let circuit = TestVectorCircuit {
+            a: Some(Fr::from(3u64)),
+            b: Some(Fr::from(5u64)),
+        };
+        let fresh = Groth16::<Bn254>::prove(&pk, circuit, &mut test_rng()).unwrap();
+        assert!(Groth16::<Bn254>::verify(&vk, &public_input, &fresh).unwrap());
+    }
+}
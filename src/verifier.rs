@@ -1,33 +1,200 @@
-use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
-use ark_ff::PrimeField;
+use ark_crypto_primitives::sponge::{
+    poseidon::{find_poseidon_ark_and_mds, PoseidonConfig, PoseidonSponge},
+    CryptographicSponge,
+};
+use ark_ec::{
+    pairing::{MillerLoopOutput, Pairing},
+    AffineRepr, CurveGroup, VariableBaseMSM,
+};
+use ark_ff::{Field, One, PrimeField, UniformRand, Zero};
+use ark_serialize::{CanonicalSerialize, Valid};
+use ark_snark::SNARK;
+use ark_std::{cfg_chunks, rand::Rng, vec::Vec};
 
-use crate::{r1cs_to_qap::R1CSToQAP, Groth16};
+use crate::{r1cs_to_qap::R1CSToQAP, Groth16, Groth16Error};
 
-use super::{PreparedVerifyingKey, Proof, VerifyingKey};
+use super::{FixedPvk, GroupInput, PreparedInputs, PreparedVerifyingKey, Proof, VerifyingKey};
 
-use ark_relations::gr1cs::Result as R1CSResult;
+use ark_relations::gr1cs::{Result as R1CSResult, SynthesisError};
 
-use core::ops::{AddAssign, Neg};
+use core::{
+    borrow::Borrow,
+    ops::{AddAssign, Neg},
+};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Parameters for the Poseidon sponge used to derive batching challenges.
+pub(crate) fn batch_sponge_config<F: PrimeField>() -> PoseidonConfig<F> {
+    let (full_rounds, partial_rounds, alpha, rate) = (8, 31, 17, 2);
+    let (ark, mds) = find_poseidon_ark_and_mds::<F>(
+        F::MODULUS_BIT_SIZE as u64,
+        rate,
+        full_rounds as u64,
+        partial_rounds as u64,
+        0,
+    );
+    PoseidonConfig::new(full_rounds, partial_rounds, alpha, mds, ark, rate, 1)
+}
 
 /// Prepare the verifying key `vk` for use in proof verification.
+///
+/// This is the single place where the caches of [`PreparedVerifyingKey`] are
+/// built, apart from the pairing that [`prepare_verifying_key_cached`] may
+/// reuse; [`Groth16::process_vk`](ark_snark::SNARK::process_vk) delegates to it.
 pub fn prepare_verifying_key<E: Pairing>(vk: &VerifyingKey<E>) -> PreparedVerifyingKey<E> {
+    prepare_with_alpha_beta(vk, E::pairing(vk.alpha_g1, vk.beta_g2).0)
+}
+
+/// Prepares `vk`, given `alpha_g1_beta_g2 = e(alpha_g1, beta_g2)`.
+fn prepare_with_alpha_beta<E: Pairing>(
+    vk: &VerifyingKey<E>,
+    alpha_g1_beta_g2: E::TargetField,
+) -> PreparedVerifyingKey<E> {
     PreparedVerifyingKey {
         vk: vk.clone(),
-        alpha_g1_beta_g2: E::pairing(vk.alpha_g1, vk.beta_g2).0,
+        alpha_g1_beta_g2,
         gamma_g2_neg_pc: vk.gamma_g2.into_group().neg().into_affine().into(),
         delta_g2_neg_pc: vk.delta_g2.into_group().neg().into_affine().into(),
     }
 }
 
+/// A cache for `e(alpha_g1, beta_g2)` of the last key prepared with
+/// [`prepare_verifying_key_cached`], holding the key's
+/// [`VerifyingKey::fingerprint`] and the pairing.
+pub type AlphaBetaCache<E> = Option<([u8; 32], <E as Pairing>::TargetField)>;
+
+/// Like [`prepare_verifying_key`], but takes `e(alpha_g1, beta_g2)` from
+/// `cache` if it was computed for a key with the same fingerprint as `vk`.
+///
+/// Otherwise, the pairing is computed and stored in `cache`, replacing its
+/// previous entry, so preparing the same key repeatedly, e.g. each time it
+/// is reloaded, costs one pairing in total. The `G2` preparations of
+/// `gamma_g2` and `delta_g2` are not cached. The result is the same as that
+/// of [`prepare_verifying_key`].
+pub fn prepare_verifying_key_cached<E: Pairing>(
+    vk: &VerifyingKey<E>,
+    cache: &mut AlphaBetaCache<E>,
+) -> PreparedVerifyingKey<E> {
+    let fingerprint = vk.fingerprint();
+    let alpha_g1_beta_g2 = match cache {
+        Some((cached, alpha_g1_beta_g2)) if *cached == fingerprint => *alpha_g1_beta_g2,
+        _ => {
+            let alpha_g1_beta_g2 = E::pairing(vk.alpha_g1, vk.beta_g2).0;
+            *cache = Some((fingerprint, alpha_g1_beta_g2));
+            alpha_g1_beta_g2
+        },
+    };
+    prepare_with_alpha_beta(vk, alpha_g1_beta_g2)
+}
+
+/// Builds a [`PreparedVerifyingKey`] for `vk` from prepared parts that were
+/// computed elsewhere, e.g. once and then distributed to many verifiers, so
+/// that the pairing and the `G2` preparations of [`prepare_verifying_key`]
+/// are skipped.
+///
+/// The parts must be `-gamma_g2` and `-delta_g2` in prepared form and
+/// `e(alpha_g1, beta_g2)`. If `validate` is set, they are recomputed from
+/// `vk` and compared, which costs as much as [`prepare_verifying_key`] and
+/// returns an error if they do not match. Otherwise they are trusted as
+/// given, and wrong parts make every proof fail to verify.
+pub fn process_vk_from_prepared<E: Pairing>(
+    vk: &VerifyingKey<E>,
+    gamma_g2_neg_pc: E::G2Prepared,
+    delta_g2_neg_pc: E::G2Prepared,
+    alpha_g1_beta_g2: E::TargetField,
+    validate: bool,
+) -> R1CSResult<PreparedVerifyingKey<E>> {
+    let pvk = PreparedVerifyingKey {
+        vk: vk.clone(),
+        alpha_g1_beta_g2,
+        gamma_g2_neg_pc,
+        delta_g2_neg_pc,
+    };
+    if validate && pvk != prepare_verifying_key(vk) {
+        return Err(SynthesisError::MalformedVerifyingKey);
+    }
+    Ok(pvk)
+}
+
+/// The number of bytes packed into each public input by
+/// [`pack_bytes_to_inputs`], which is the largest number that always fits
+/// below the modulus of `F`.
+pub(crate) fn input_chunk_len<F: PrimeField>() -> usize {
+    (F::MODULUS_BIT_SIZE as usize - 1) / 8
+}
+
+/// Packs `bytes` into public inputs, e.g. for a circuit that receives a hash
+/// preimage as its public input.
+///
+/// The bytes are split into chunks of `(F::MODULUS_BIT_SIZE - 1) / 8` bytes,
+/// the last one possibly shorter, and each chunk is read as a little-endian
+/// integer. This is the convention of `ToConstraintField<F>` for `[u8]`, and
+/// the one the verifier gadget uses for `BytesInputVar`.
+pub fn pack_bytes_to_inputs<F: PrimeField>(bytes: &[u8]) -> Vec<F> {
+    bytes
+        .chunks(input_chunk_len::<F>())
+        .map(F::from_le_bytes_mod_order)
+        .collect()
+}
+
+/// Returns whether `a` and `b` are equal, comparing their encodings without
+/// branching on their contents.
+pub(crate) fn ct_eq<T: CanonicalSerialize>(a: &T, b: &T) -> bool {
+    let mut a_bytes = Vec::new();
+    let mut b_bytes = Vec::new();
+    a.serialize_uncompressed(&mut a_bytes).unwrap();
+    b.serialize_uncompressed(&mut b_bytes).unwrap();
+    // The encodings of a fixed-size type have a public length.
+    if a_bytes.len() != b_bytes.len() {
+        return false;
+    }
+    let diff = a_bytes
+        .iter()
+        .zip(&b_bytes)
+        .fold(0u8, |diff, (a, b)| diff | (a ^ b));
+    // Keep the compiler from turning the fold into an early-exit comparison.
+    core::hint::black_box(diff) == 0
+}
+
 impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
+    /// Like [`SNARK::process_vk`], but reuses `e(alpha_g1, beta_g2)` from
+    /// `cache` when `vk` was prepared with it before, see
+    /// [`prepare_verifying_key_cached`].
+    pub fn process_vk_cached(
+        vk: &VerifyingKey<E>,
+        cache: &mut AlphaBetaCache<E>,
+    ) -> Result<PreparedVerifyingKey<E>, Groth16Error> {
+        Ok(prepare_verifying_key_cached(vk, cache))
+    }
+
     /// Prepare proof inputs for use with [`verify_proof_with_prepared_inputs`], wrt the prepared
     /// verification key `pvk` and instance public inputs.
+    ///
+    /// The result is the point `gamma_abc_g1[0] + sum_i public_inputs[i] * gamma_abc_g1[i + 1]`,
+    /// which can be reused across verifications of the same instance. Returns
+    /// [`SynthesisError::Unsatisfiable`] if the number of inputs does not match `pvk`.
+    ///
+    /// [`verify_proof_with_prepared_inputs`]: Self::verify_proof_with_prepared_inputs
     pub fn prepare_inputs(
         pvk: &PreparedVerifyingKey<E>,
         public_inputs: &[E::ScalarField],
     ) -> R1CSResult<E::G1> {
-        let mut g_ic = pvk.vk.gamma_abc_g1[0].into_group();
-        for (i, b) in public_inputs.iter().zip(pvk.vk.gamma_abc_g1.iter().skip(1)) {
+        Self::accumulate_inputs(&pvk.vk.gamma_abc_g1, public_inputs)
+    }
+
+    /// Computes `gamma_abc_g1[0] + sum_i public_inputs[i] * gamma_abc_g1[i + 1]`.
+    fn accumulate_inputs(
+        gamma_abc_g1: &[E::G1Affine],
+        public_inputs: &[E::ScalarField],
+    ) -> R1CSResult<E::G1> {
+        if public_inputs.len() + 1 != gamma_abc_g1.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        let mut g_ic = gamma_abc_g1[0].into_group();
+        for (i, b) in public_inputs.iter().zip(gamma_abc_g1.iter().skip(1)) {
             g_ic.add_assign(&b.mul_bigint(i.into_bigint()));
         }
 
@@ -42,22 +209,406 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         proof: &Proof<E>,
         prepared_inputs: &E::G1,
     ) -> R1CSResult<bool> {
+        Self::check_prepared_pairing(pvk, proof, prepared_inputs.into_affine().into())
+    }
+
+    /// Verify a Groth16 proof `proof` against the prepared verification key
+    /// `pvk` and public inputs cached in `prepared_inputs`.
+    ///
+    /// This gives the same result as [`Self::verify_proof`] on the inputs
+    /// `prepared_inputs` was built from, but skips the MSM over
+    /// `gamma_abc_g1` and the preparation of its result, so it should be
+    /// preferred when many proofs of the same instance are verified.
+    pub fn verify_proof_with_cached_inputs(
+        pvk: &PreparedVerifyingKey<E>,
+        prepared_inputs: &PreparedInputs<E>,
+        proof: &Proof<E>,
+    ) -> R1CSResult<bool> {
+        Self::check_prepared_pairing(pvk, proof, prepared_inputs.g_ic_prepared.clone())
+    }
+
+    /// Returns the Miller loop output of the Groth16 pairing equation for
+    /// `proof` and the instance `public_inputs`, without the final
+    /// exponentiation.
+    ///
+    /// The returned value is the Miller loop of
+    ///
+    /// `e(A, B) * e(g_ic, -gamma_g2) * e(C, -delta_g2) * e(-alpha_g1, beta_g2)`,
+    ///
+    /// where `g_ic` is the result of [`Self::prepare_inputs`]. `proof` is
+    /// valid if and only if applying `E::final_exponentiation` to it yields
+    /// one. Since every term is folded in, several outputs can be multiplied
+    /// together and checked with a single final exponentiation. Note that a
+    /// plain product of outputs for independent proofs lets errors cancel
+    /// out; a sound accumulation scheme must randomize the terms, e.g. by
+    /// scaling each `proof.a` and `g_ic` by the same random scalar before
+    /// the Miller loop.
+    ///
+    /// Returns [`SynthesisError::Unsatisfiable`] if the number of inputs does
+    /// not match `pvk`.
+    pub fn miller_loop_for_verify(
+        pvk: &PreparedVerifyingKey<E>,
+        public_inputs: &[E::ScalarField],
+        proof: &Proof<E>,
+    ) -> R1CSResult<E::TargetField> {
+        let prepared_inputs = Self::prepare_inputs(pvk, public_inputs)?;
         let qap = E::multi_miller_loop(
             [
                 <E::G1Affine as Into<E::G1Prepared>>::into(proof.a),
                 prepared_inputs.into_affine().into(),
                 proof.c.into(),
+                (-pvk.vk.alpha_g1.into_group()).into_affine().into(),
             ],
             [
                 proof.b.into(),
                 pvk.gamma_g2_neg_pc.clone(),
                 pvk.delta_g2_neg_pc.clone(),
+                pvk.vk.beta_g2.into(),
             ],
         );
 
-        let test = E::final_exponentiation(qap).unwrap();
+        Ok(qap.0)
+    }
+
+    /// Verify a Groth16 proof `proof` against the prepared verification key
+    /// `pvk`, with respect to the instance `public_inputs`, and also return
+    /// the Miller loop of `e(A, B)` for `proof`.
+    ///
+    /// The returned value is taken **before** the final exponentiation, which
+    /// is left to callers that need `e(A, B)` itself:
+    /// `E::final_exponentiation(MillerLoopOutput(f))` turns it into
+    /// `E::pairing(proof.a, proof.b)`. Miller loop outputs can also be
+    /// multiplied with each other before a single final exponentiation, so
+    /// binding checks that combine `e(A, B)` with other pairings should do
+    /// that instead.
+    ///
+    /// The `(A, B)` loop is computed on its own and multiplied into the loop
+    /// of the remaining terms, so verification still takes a single final
+    /// exponentiation, and no pairing is spent on `e(A, B)` beyond the ones
+    /// of [`Self::verify_proof`].
+    ///
+    /// Returns [`SynthesisError::Unsatisfiable`] if the number of inputs does
+    /// not match `pvk`.
+    pub fn verify_returning_ab_pairing(
+        pvk: &PreparedVerifyingKey<E>,
+        public_inputs: &[E::ScalarField],
+        proof: &Proof<E>,
+    ) -> R1CSResult<(bool, E::TargetField)> {
+        let prepared_inputs = Self::prepare_inputs(pvk, public_inputs)?;
+        let ab = E::miller_loop(proof.a, proof.b);
+        let rest = E::multi_miller_loop(
+            [
+                <E::G1Affine as Into<E::G1Prepared>>::into(prepared_inputs.into_affine()),
+                proof.c.into(),
+            ],
+            [pvk.gamma_g2_neg_pc.clone(), pvk.delta_g2_neg_pc.clone()],
+        );
+        let test = E::final_exponentiation(MillerLoopOutput(ab.0 * rest.0))
+            .ok_or(SynthesisError::UnexpectedIdentity)?;
+
+        Ok((test.0 == pvk.alpha_g1_beta_g2, ab.0))
+    }
+
+    /// Checks the Groth16 pairing equation for `proof`, given the prepared
+    /// input accumulator.
+    fn check_prepared_pairing(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        prepared_inputs: E::G1Prepared,
+    ) -> R1CSResult<bool> {
+        Ok(Self::prepared_pairing(pvk, proof, prepared_inputs)? == pvk.alpha_g1_beta_g2)
+    }
+
+    /// Verify a Groth16 proof `proof` against the prepared verification key
+    /// `pvk`, with respect to the instance `public_inputs`, in time that does
+    /// not depend on whether `proof` is valid.
+    ///
+    /// This addresses settings where an observer who can time the verifier,
+    /// but not see its result, must not learn whether a proof was accepted,
+    /// e.g. when the result is only revealed later or is used to select a
+    /// secret. The full multi-pairing is always computed, with no early
+    /// exits, and its result is compared to `e(alpha_g1, beta_g2)` without
+    /// branching on their contents.
+    ///
+    /// This does not hide the proof, the instance, or `pvk`, which are
+    /// public to the verifier anyway: the underlying field and curve
+    /// arithmetic is not guaranteed to run in constant time in its inputs.
+    /// The number of public inputs is public too, so a length mismatch
+    /// returns [`SynthesisError::Unsatisfiable`] immediately.
+    pub fn verify_constant_time(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        public_inputs: &[E::ScalarField],
+    ) -> R1CSResult<bool> {
+        let prepared_inputs = Self::prepare_inputs(pvk, public_inputs)?;
+        let test = Self::prepared_pairing(pvk, proof, prepared_inputs.into_affine().into())?;
+        Ok(ct_eq(&test, &pvk.alpha_g1_beta_g2))
+    }
+
+    /// Returns `e(A, B) * e(g_ic, -gamma_g2) * e(C, -delta_g2)` for `proof`,
+    /// given the prepared input accumulator `g_ic`.
+    fn prepared_pairing(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        prepared_inputs: E::G1Prepared,
+    ) -> R1CSResult<E::TargetField> {
+        // The `gamma` and `delta` terms reuse the line coefficients cached in
+        // `pvk`, so only `A`, `B`, `C`, and the inputs are prepared here.
+        let qap = E::multi_miller_loop(
+            [
+                <E::G1Affine as Into<E::G1Prepared>>::into(proof.a),
+                prepared_inputs,
+                proof.c.into(),
+            ],
+            [
+                proof.b.into(),
+                pvk.gamma_g2_neg_pc.clone(),
+                pvk.delta_g2_neg_pc.clone(),
+            ],
+        );
+
+        Ok(E::final_exponentiation(qap)
+            .ok_or(SynthesisError::UnexpectedIdentity)?
+            .0)
+    }
+
+    /// Verify a batch of Groth16 proofs against the prepared verification key
+    /// `pvk`, where `proofs[i]` is checked against the instance `inputs[i]`.
+    ///
+    /// The proofs are combined with a random linear combination sampled from
+    /// `rng`, so the whole batch costs a single multi-pairing and a few MSMs.
+    /// The result is `true` only if every proof is valid, except with
+    /// probability negligible in the size of the scalar field.
+    pub fn verify_batch(
+        pvk: &PreparedVerifyingKey<E>,
+        inputs: &[Vec<E::ScalarField>],
+        proofs: &[Proof<E>],
+        rng: &mut impl Rng,
+    ) -> R1CSResult<bool> {
+        let scalars = Self::batch_challenges(proofs.len(), rng);
+        Self::verify_batch_with_scalars(pvk, inputs, proofs, &scalars)
+    }
+
+    /// Samples `n` nonzero batching challenges from `rng`.
+    fn batch_challenges(n: usize, rng: &mut impl Rng) -> Vec<E::ScalarField> {
+        (0..n)
+            .map(|_| loop {
+                let rho = E::ScalarField::rand(rng);
+                if !rho.is_zero() {
+                    break rho;
+                }
+            })
+            .collect()
+    }
+
+    /// Like [`Self::verify_batch`], but derives the random linear combination
+    /// deterministically by hashing `pvk`, `inputs`, and `proofs`, for callers
+    /// without access to a source of randomness.
+    pub fn verify_batch_deterministic(
+        pvk: &PreparedVerifyingKey<E>,
+        inputs: &[Vec<E::ScalarField>],
+        proofs: &[Proof<E>],
+    ) -> R1CSResult<bool> {
+        let mut bytes = Vec::new();
+        pvk.vk.serialize_compressed(&mut bytes).unwrap();
+        inputs.serialize_compressed(&mut bytes).unwrap();
+        proofs.serialize_compressed(&mut bytes).unwrap();
+
+        let mut sponge = PoseidonSponge::new(&batch_sponge_config::<E::ScalarField>());
+        sponge.absorb(&bytes);
+        let scalars = sponge
+            .squeeze_field_elements::<E::ScalarField>(proofs.len())
+            .into_iter()
+            .map(|rho| {
+                if rho.is_zero() {
+                    E::ScalarField::one()
+                } else {
+                    rho
+                }
+            })
+            .collect::<Vec<_>>();
+        Self::verify_batch_with_scalars(pvk, inputs, proofs, &scalars)
+    }
+
+    /// Verify a sequence of Groth16 proofs against the prepared verification
+    /// key `pvk`, where each proof is checked against the instance paired
+    /// with it, deriving the batching challenges from `sponge`.
+    ///
+    /// `pvk` is absorbed once, followed by every proof and its inputs in
+    /// order; only then are the challenges squeezed, so that each challenge
+    /// depends on the whole batch. `sponge` is left in the resulting state,
+    /// so a caller can keep using it as a Fiat-Shamir transcript, e.g. to
+    /// derive the inputs of the next batch. As with
+    /// [`Self::verify_batch_deterministic`], the result is `true` only if
+    /// every proof is valid, except with negligible probability.
+    pub fn verify_with_sponge<S: CryptographicSponge>(
+        pvk: &PreparedVerifyingKey<E>,
+        sponge: &mut S,
+        proofs_and_inputs: &[(Proof<E>, Vec<E::ScalarField>)],
+    ) -> R1CSResult<bool> {
+        let mut bytes = Vec::new();
+        pvk.vk.serialize_compressed(&mut bytes).unwrap();
+        sponge.absorb(&bytes);
+        for (proof, inputs) in proofs_and_inputs {
+            let mut bytes = Vec::new();
+            proof.serialize_compressed(&mut bytes).unwrap();
+            inputs.serialize_compressed(&mut bytes).unwrap();
+            sponge.absorb(&bytes);
+        }
+
+        let scalars = sponge
+            .squeeze_field_elements::<E::ScalarField>(proofs_and_inputs.len())
+            .into_iter()
+            .map(|rho| {
+                if rho.is_zero() {
+                    E::ScalarField::one()
+                } else {
+                    rho
+                }
+            })
+            .collect::<Vec<_>>();
+        let (proofs, inputs): (Vec<_>, Vec<_>) = proofs_and_inputs
+            .iter()
+            .map(|(proof, inputs)| (proof, inputs.as_slice()))
+            .unzip();
+        Self::verify_batch_with_scalars(pvk, &inputs, &proofs, &scalars)
+    }
+
+    /// Verify Groth16 proofs of many instances of the same circuit against its
+    /// prepared verification key `pvk`, where each proof is checked against
+    /// the inputs paired with it, e.g. the proofs of a rollup block.
+    ///
+    /// This is [`Self::verify_batch`] for a sequence of pairs, in the layout
+    /// of [`Self::verify_with_sponge`]; the pairs are only borrowed. The terms
+    /// that only depend on the key, i.e. `e(alpha_g1, beta_g2)` and the
+    /// prepared `-gamma_g2` and `-delta_g2`, are taken from `pvk` rather than
+    /// recomputed, so the key costs the batch a single exponentiation of
+    /// `e(alpha_g1, beta_g2)` by the sum of the challenges. The rest is one
+    /// multi-pairing with one term per proof plus two, and a few MSMs. The
+    /// result is `true` only if every proof is valid, except with
+    /// probability negligible in the size of the scalar field.
+    pub fn verify_proofs_same_vk(
+        pvk: &PreparedVerifyingKey<E>,
+        proofs_and_inputs: &[(Proof<E>, Vec<E::ScalarField>)],
+        rng: &mut impl Rng,
+    ) -> R1CSResult<bool> {
+        let scalars = Self::batch_challenges(proofs_and_inputs.len(), rng);
+        let (proofs, inputs): (Vec<_>, Vec<_>) = proofs_and_inputs
+            .iter()
+            .map(|(proof, inputs)| (proof, inputs.as_slice()))
+            .unzip();
+        Self::verify_batch_with_scalars(pvk, &inputs, &proofs, &scalars)
+    }
+
+    /// Checks `prod_i e(rho_i * A_i, B_i) = e(alpha, beta)^(sum_i rho_i) *
+    /// e(sum_i rho_i * IC_i, gamma) * e(sum_i rho_i * C_i, delta)`, where
+    /// `IC_i` is the prepared input of the `i`-th instance.
+    ///
+    /// With the `parallel` feature, the Miller loops of the proofs are split
+    /// across the threads of the current `rayon` pool. The instances and
+    /// proofs are only borrowed, so callers holding them in other layouts
+    /// can pass slices of references.
+    fn verify_batch_with_scalars<I, P>(
+        pvk: &PreparedVerifyingKey<E>,
+        inputs: &[I],
+        proofs: &[P],
+        scalars: &[E::ScalarField],
+    ) -> R1CSResult<bool>
+    where
+        I: AsRef<[E::ScalarField]>,
+        P: Borrow<Proof<E>>,
+    {
+        #[cfg(feature = "parallel")]
+        let num_chunks = rayon::current_num_threads();
+        #[cfg(not(feature = "parallel"))]
+        let num_chunks = 1;
+        Self::verify_batch_in_chunks(pvk, inputs, proofs, scalars, num_chunks)
+    }
+
+    /// Like [`Self::verify_batch_with_scalars`], but splits the Miller loops
+    /// of `e(rho_i * A_i, B_i)` into `num_chunks` chunks, which are computed
+    /// in parallel if the `parallel` feature is enabled. The outputs of the
+    /// chunks are multiplied before a single final exponentiation, so the
+    /// result does not depend on `num_chunks`. With at most one chunk, all
+    /// pairs go through one multi-Miller loop.
+    pub(crate) fn verify_batch_in_chunks<I, P>(
+        pvk: &PreparedVerifyingKey<E>,
+        inputs: &[I],
+        proofs: &[P],
+        scalars: &[E::ScalarField],
+        num_chunks: usize,
+    ) -> R1CSResult<bool>
+    where
+        I: AsRef<[E::ScalarField]>,
+        P: Borrow<Proof<E>>,
+    {
+        let num_inputs = pvk.vk.gamma_abc_g1.len();
+        if inputs.len() != proofs.len() || inputs.iter().any(|x| x.as_ref().len() + 1 != num_inputs)
+        {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        if proofs.is_empty() {
+            return Ok(true);
+        }
+        let proofs = proofs
+            .iter()
+            .map(Borrow::borrow)
+            .collect::<Vec<&Proof<E>>>();
+
+        // The coefficient of `gamma_abc_g1[j]` in `sum_i rho_i * IC_i`.
+        let mut input_scalars = Vec::with_capacity(num_inputs);
+        input_scalars.push(scalars.iter().sum::<E::ScalarField>());
+        for j in 0..(num_inputs - 1) {
+            input_scalars.push(
+                inputs
+                    .iter()
+                    .zip(scalars)
+                    .map(|(x, rho)| x.as_ref()[j] * rho)
+                    .sum(),
+            );
+        }
+        let ic_acc = E::G1::msm(&pvk.vk.gamma_abc_g1, &input_scalars).unwrap();
+
+        let c = proofs.iter().map(|proof| proof.c).collect::<Vec<_>>();
+        let c_acc = E::G1::msm(&c, scalars).unwrap();
+
+        let scaled_a = E::G1::normalize_batch(
+            &proofs
+                .iter()
+                .zip(scalars)
+                .map(|(proof, rho)| proof.a * rho)
+                .collect::<Vec<_>>(),
+        );
+
+        let qap = if num_chunks <= 1 {
+            let g1 = scaled_a
+                .into_iter()
+                .chain([ic_acc.into_affine(), c_acc.into_affine()])
+                .map(E::G1Prepared::from);
+            let g2 = proofs
+                .iter()
+                .map(|proof| E::G2Prepared::from(proof.b))
+                .chain([pvk.gamma_g2_neg_pc.clone(), pvk.delta_g2_neg_pc.clone()]);
+            E::multi_miller_loop(g1, g2)
+        } else {
+            let fixed = E::multi_miller_loop(
+                [ic_acc.into_affine(), c_acc.into_affine()].map(E::G1Prepared::from),
+                [pvk.gamma_g2_neg_pc.clone(), pvk.delta_g2_neg_pc.clone()],
+            );
+            let chunk_size = proofs.len().div_ceil(num_chunks);
+            let chunks = cfg_chunks!(scaled_a, chunk_size)
+                .zip(cfg_chunks!(proofs, chunk_size))
+                .map(|(a, proofs)| {
+                    let g1 = a.iter().map(|a| E::G1Prepared::from(*a));
+                    let g2 = proofs.iter().map(|proof| E::G2Prepared::from(proof.b));
+                    E::multi_miller_loop(g1, g2).0
+                })
+                .collect::<Vec<_>>();
+            MillerLoopOutput(chunks.into_iter().fold(fixed.0, |acc, f| acc * f))
+        };
+        let test = E::final_exponentiation(qap).ok_or(SynthesisError::UnexpectedIdentity)?;
 
-        Ok(test.0 == pvk.alpha_g1_beta_g2)
+        Ok(test.0 == pvk.alpha_g1_beta_g2.pow(input_scalars[0].into_bigint()))
     }
 
     /// Verify a Groth16 proof `proof` against the prepared verification key `pvk`,
@@ -67,7 +618,253 @@ impl<E: Pairing, QAP: R1CSToQAP> Groth16<E, QAP> {
         proof: &Proof<E>,
         public_inputs: &[E::ScalarField],
     ) -> R1CSResult<bool> {
-        let prepared_inputs = Self::prepare_inputs(pvk, public_inputs)?;
-        Self::verify_proof_with_prepared_inputs(pvk, proof, &prepared_inputs)
+        match Self::check_pairing(pvk, proof, public_inputs) {
+            Ok(()) => Ok(true),
+            Err(VerificationError::PairingCheckFailed) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Verify a Groth16 proof `proof` against the unprepared verification key
+    /// `vk`, with respect to the instance `public_inputs`.
+    ///
+    /// This borrows `vk` instead of building a [`PreparedVerifyingKey`], which
+    /// would copy all of `vk`, including its `gamma_abc_g1` vector. Since
+    /// nothing is cached for later verifications anyway, `e(alpha_g1,
+    /// beta_g2)` is folded into the multi-Miller loop rather than computed as
+    /// a pairing of its own, so a one-off verification costs a single
+    /// four-term multi-Miller loop and final exponentiation. [`SNARK::verify`]
+    /// delegates to this. Returns [`SynthesisError::Unsatisfiable`] if the
+    /// number of inputs does not match `vk`.
+    pub fn verify_proof_with_vk(
+        vk: &VerifyingKey<E>,
+        proof: &Proof<E>,
+        public_inputs: &[E::ScalarField],
+    ) -> R1CSResult<bool> {
+        let prepared_inputs = Self::accumulate_inputs(&vk.gamma_abc_g1, public_inputs)?;
+        let qap = E::multi_miller_loop(
+            [
+                <E::G1Affine as Into<E::G1Prepared>>::into(proof.a),
+                prepared_inputs.into_affine().into(),
+                proof.c.into(),
+                (-vk.alpha_g1.into_group()).into_affine().into(),
+            ],
+            [
+                proof.b.into(),
+                (-vk.gamma_g2.into_group()).into_affine().into(),
+                (-vk.delta_g2.into_group()).into_affine().into(),
+                vk.beta_g2.into(),
+            ],
+        );
+
+        Ok(E::final_exponentiation(qap)
+            .ok_or(SynthesisError::UnexpectedIdentity)?
+            .0
+            .is_one())
+    }
+
+    /// Verify a Groth16 proof `proof` against the verification key `vk`, with
+    /// respect to the instance `public_inputs`, after checking that `vk`
+    /// matches `vk_commitment`.
+    ///
+    /// This is meant for verifiers that only store the commitment
+    /// [`VerifyingKey::fingerprint`] of a key and receive the key itself with
+    /// each proof. Returns [`Groth16Error::VkCommitmentMismatch`] if the
+    /// fingerprint of `vk` differs from `vk_commitment`, in which case the
+    /// proof is not looked at, and otherwise behaves like
+    /// [`SNARK::verify`].
+    pub fn verify_against_commitment(
+        vk: &VerifyingKey<E>,
+        vk_commitment: &[u8; 32],
+        public_inputs: &[E::ScalarField],
+        proof: &Proof<E>,
+    ) -> Result<bool, Groth16Error> {
+        if vk.fingerprint() != *vk_commitment {
+            return Err(Groth16Error::VkCommitmentMismatch);
+        }
+        <Self as SNARK<E::ScalarField>>::verify(vk, public_inputs, proof)
+    }
+
+    /// Verify a Groth16 proof `proof` against the prepared verification key `pvk`
+    /// of a circuit with exactly `N` public inputs, with respect to the
+    /// instance `public_inputs`.
+    ///
+    /// The input count was checked when `pvk` was built, so unlike
+    /// [`Self::verify_proof`] this cannot fail on a length mismatch; a proof
+    /// for which the pairing check cannot be completed is rejected.
+    pub fn verify_fixed<const N: usize>(
+        pvk: &FixedPvk<E, N>,
+        public_inputs: &[E::ScalarField; N],
+        proof: &Proof<E>,
+    ) -> bool {
+        // `FixedPvk::new` checked that `pvk` expects `N` inputs, so preparing
+        // them cannot fail.
+        let prepared_inputs = Self::prepare_inputs(pvk.pvk(), public_inputs).unwrap();
+        // The pairing check only fails when the final exponentiation meets
+        // the identity, which no valid proof produces, so that rejects.
+        Self::verify_proof_with_prepared_inputs(pvk.pvk(), proof, &prepared_inputs).unwrap_or(false)
+    }
+
+    /// Verify a Groth16 proof `proof` against the prepared verification key `pvk`,
+    /// with respect to the instance yielded by `public_inputs`.
+    ///
+    /// The inputs are folded into the input accumulator as they are produced,
+    /// so streamed inputs never need to be collected. Returns
+    /// [`SynthesisError::Unsatisfiable`] if `public_inputs` yields a different
+    /// number of elements than `pvk` expects.
+    pub fn verify_proof_with_inputs<I>(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        public_inputs: I,
+    ) -> R1CSResult<bool>
+    where
+        I: IntoIterator<Item = E::ScalarField>,
+    {
+        let mut bases = pvk.vk.gamma_abc_g1.iter();
+        let mut g_ic = bases
+            .next()
+            .ok_or(SynthesisError::Unsatisfiable)?
+            .into_group();
+        for input in public_inputs {
+            let base = bases.next().ok_or(SynthesisError::Unsatisfiable)?;
+            g_ic.add_assign(&base.mul_bigint(input.into_bigint()));
+        }
+        if bases.next().is_some() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        Self::verify_proof_with_prepared_inputs(pvk, proof, &g_ic)
+    }
+
+    /// Verify a Groth16 proof `proof` against the prepared verification key `pvk`,
+    /// with respect to an instance whose last `group_inputs.len()` public
+    /// inputs are given as elements of `E::G1` instead of scalars.
+    ///
+    /// The group input for the public input `x_i` at position `i` is its term
+    /// `x_i * vk.input_base(i)` of the input accumulator, see
+    /// [`VerifyingKey::input_base`], with a proof of knowledge of `x_i`, see
+    /// [`GroupInput`]. This lets a party that knows `x_i` publish it as a
+    /// point, e.g. as a commitment, without the verifier learning or hashing
+    /// `x_i`. The bases of the group inputs are the `gamma_abc_g1` elements
+    /// of their positions, so their points are only sound as inputs because
+    /// each proof of knowledge is checked against its base; this returns
+    /// `false` if any of them fails.
+    ///
+    /// Returns [`SynthesisError::Unsatisfiable`] if `public_inputs` and
+    /// `group_inputs` together do not have as many elements as `pvk` expects.
+    pub fn verify_proof_with_group_inputs(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        public_inputs: &[E::ScalarField],
+        group_inputs: &[GroupInput<E>],
+    ) -> R1CSResult<bool> {
+        if public_inputs.len() + group_inputs.len() + 1 != pvk.vk.gamma_abc_g1.len() {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        // The scalar inputs come first, so they use a prefix of the bases.
+        let scalar_bases = &pvk.vk.gamma_abc_g1[..=public_inputs.len()];
+        let mut g_ic = Self::accumulate_inputs(scalar_bases, public_inputs)?;
+        let group_bases = &pvk.vk.gamma_abc_g1[public_inputs.len() + 1..];
+        for (input, base) in group_inputs.iter().zip(group_bases) {
+            if !input.check(base) {
+                return Ok(false);
+            }
+            g_ic.add_assign(&input.point);
+        }
+
+        Self::verify_proof_with_prepared_inputs(pvk, proof, &g_ic)
+    }
+
+    /// Verify a Groth16 proof `proof` against the prepared verification key `pvk`,
+    /// with respect to the instance `public_inputs`, reporting why verification
+    /// failed.
+    ///
+    /// Unlike [`Self::verify_proof`], this also checks that `proof` and `pvk`
+    /// consist of valid points in the correct subgroups.
+    pub fn verify_detailed(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        public_inputs: &[E::ScalarField],
+    ) -> Result<(), VerificationError> {
+        pvk.vk.check().map_err(|_| VerificationError::MalformedVk)?;
+        proof
+            .check()
+            .map_err(|_| VerificationError::MalformedProof)?;
+        Self::check_pairing(pvk, proof, public_inputs)
+    }
+
+    fn check_pairing(
+        pvk: &PreparedVerifyingKey<E>,
+        proof: &Proof<E>,
+        public_inputs: &[E::ScalarField],
+    ) -> Result<(), VerificationError> {
+        let expected = pvk
+            .vk
+            .gamma_abc_g1
+            .len()
+            .checked_sub(1)
+            .ok_or(VerificationError::MalformedVk)?;
+        if public_inputs.len() != expected {
+            return Err(VerificationError::InputLengthMismatch {
+                expected,
+                got: public_inputs.len(),
+            });
+        }
+
+        let prepared_inputs =
+            Self::prepare_inputs(pvk, public_inputs).map_err(|_| VerificationError::MalformedVk)?;
+        match Self::verify_proof_with_prepared_inputs(pvk, proof, &prepared_inputs) {
+            Ok(true) => Ok(()),
+            _ => Err(VerificationError::PairingCheckFailed),
+        }
+    }
+}
+
+/// The reasons why [`Groth16::verify_detailed`] can reject a proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerificationError {
+    /// The proof contains a point that is not on the curve or not in the
+    /// correct subgroup.
+    MalformedProof,
+    /// The verification key contains a point that is not on the curve or not
+    /// in the correct subgroup, or has no `gamma_abc_g1` elements.
+    MalformedVk,
+    /// The number of public inputs does not match the verification key.
+    InputLengthMismatch {
+        /// The number of public inputs expected by the verification key.
+        expected: usize,
+        /// The number of public inputs provided.
+        got: usize,
+    },
+    /// The proof is well-formed but does not verify for the given inputs.
+    PairingCheckFailed,
+}
+
+impl core::fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            VerificationError::MalformedProof => write!(f, "malformed proof"),
+            VerificationError::MalformedVk => write!(f, "malformed verifying key"),
+            VerificationError::InputLengthMismatch { expected, got } => write!(
+                f,
+                "expected {} public inputs, but {} were provided",
+                expected, got
+            ),
+            VerificationError::PairingCheckFailed => write!(f, "pairing check failed"),
+        }
+    }
+}
+
+impl ark_std::error::Error for VerificationError {}
+
+impl From<VerificationError> for SynthesisError {
+    fn from(e: VerificationError) -> Self {
+        match e {
+            VerificationError::MalformedVk => SynthesisError::MalformedVerifyingKey,
+            VerificationError::MalformedProof
+            | VerificationError::InputLengthMismatch { .. }
+            | VerificationError::PairingCheckFailed => SynthesisError::Unsatisfiable,
+        }
     }
 }